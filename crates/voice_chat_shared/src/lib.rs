@@ -0,0 +1,694 @@
+//! Error codes, events, the storage PDA layout and the storage backend interface shared by
+//! the voice chat programs.
+//!
+//! Each program's errors live in their own numeric range so a client can tell from the
+//! code alone which program failed and map it without knowing the program id:
+//!
+//! - voice_chat_manager: 6000-6999 (Anchor's default offset, so existing codes are unchanged)
+//! - voicechat: 7000-7999
+//! - storage_manager: 8000-8999
+//!
+//! Variants are only ever appended, so a code never changes meaning.
+
+use anchor_lang::prelude::*;
+
+pub const MANAGER_ERROR_OFFSET: u32 = 6000;
+pub const VOICECHAT_ERROR_OFFSET: u32 = 7000;
+pub const STORAGE_ERROR_OFFSET: u32 = 8000;
+
+#[error_code(offset = 6000)]
+pub enum ManagerError {
+    #[msg("Voice room is not active")]
+    RoomNotActive,
+    #[msg("Voice room is full")]
+    RoomFull,
+    #[msg("Voice data exceeds maximum size")]
+    VoiceDataTooLarge,
+    #[msg("Invalid storage PDA index")]
+    InvalidStoragePDA,
+    #[msg("Room ID too long")]
+    RoomIdTooLong,
+    #[msg("Too many target PDAs for broadcast")]
+    TooManyTargetPDAs,
+    #[msg("Voice batch contains no frames")]
+    EmptyBatch,
+    #[msg("Too many frames in voice batch")]
+    TooManyFrames,
+    #[msg("Frame headers do not match the batch payload")]
+    FrameLengthMismatch,
+    #[msg("Batch frames must be numbered consecutively from the batch sequence number")]
+    BatchSequenceMismatch,
+    #[msg("Voice clip payload is empty")]
+    EmptyClip,
+    #[msg("Storage PDAs do not match the number of clip chunks")]
+    ClipChunkMismatch,
+    #[msg("Storage PDA passed more than once")]
+    DuplicateStoragePDA,
+    #[msg("Compressed message log is full")]
+    MessageLogFull,
+    #[msg("Cleanup accounts must be passed as (account, rent recipient) pairs")]
+    InvalidCleanupAccounts,
+    #[msg("Token-gated room requires a gate token account")]
+    GateTokenAccountMissing,
+    #[msg("Gate token account has the wrong mint or owner")]
+    InvalidGateTokenAccount,
+    #[msg("Insufficient gate token balance to join this room")]
+    InsufficientGateBalance,
+    #[msg("Collection-gated room requires an NFT token account and metadata")]
+    NftAccountsMissing,
+    #[msg("NFT token account or metadata is invalid")]
+    InvalidNftAccount,
+    #[msg("NFT is not a verified member of the gate collection")]
+    NftNotInCollection,
+    #[msg("Paid room requires escrow and fee payment accounts")]
+    EscrowAccountsMissing,
+    #[msg("Fee mint does not match the room's entry fee mint")]
+    InvalidFeeMint,
+    #[msg("Escrow can only be withdrawn after the session has ended")]
+    EscrowLocked,
+    #[msg("Event start time must be in the future")]
+    InvalidSchedule,
+    #[msg("Ticket capacity exceeds room capacity or tickets already sold")]
+    InvalidTicketCapacity,
+    #[msg("Tickets cannot be sold for rooms with token-denominated entry fees")]
+    TokenFeeTicketsUnsupported,
+    #[msg("Room is not a scheduled event")]
+    NotAScheduledEvent,
+    #[msg("Event is sold out")]
+    EventSoldOut,
+    #[msg("A ticket is required to join this event")]
+    TicketRequired,
+    #[msg("Event has not started yet")]
+    EventNotStarted,
+    #[msg("Ticket has already been used")]
+    TicketAlreadyUsed,
+    #[msg("Tip amount must be greater than zero")]
+    InvalidTipAmount,
+    #[msg("Cannot tip yourself")]
+    CannotTipSelf,
+    #[msg("Token tips require tipper and speaker token accounts, mint and token program")]
+    TipTokenAccountsMissing,
+    #[msg("Tip token accounts do not match the tip mint or speaker")]
+    InvalidTipTokenAccount,
+    #[msg("Revenue split must have 1-8 distinct recipients totalling 10000 bps")]
+    InvalidRevenueSplit,
+    #[msg("Room has a revenue split; use settle_room")]
+    RevenueSplitConfigured,
+    #[msg("Settlement accounts do not match the revenue split")]
+    InvalidSettlementAccounts,
+    #[msg("Event has been cancelled")]
+    EventCancelled,
+    #[msg("Event was not started within the grace period")]
+    EventGracePeriodElapsed,
+    #[msg("Event has already started")]
+    EventAlreadyStarted,
+    #[msg("Refunds are only available for cancelled or unstarted events")]
+    RefundNotAvailable,
+    #[msg("Streaming payments are not enabled for this room")]
+    StreamingDisabled,
+    #[msg("Stream deposit must be greater than zero")]
+    InvalidStreamDeposit,
+    #[msg("Payment stream has been stopped")]
+    StreamStopped,
+    #[msg("Payment stream has not been stopped")]
+    StreamNotStopped,
+    #[msg("Participant account is required to settle a running stream")]
+    StreamParticipantMissing,
+    #[msg("This room requires a locked sender stake")]
+    SenderStakeRequired,
+    #[msg("Sender stake is below the room's requirement")]
+    InsufficientSenderStake,
+    #[msg("Stake is locked until the cooldown after leaving has passed")]
+    StakeLocked,
+    #[msg("Participant is muted")]
+    ParticipantMuted,
+    #[msg("Slash basis points cannot exceed 10000")]
+    InvalidSlashBps,
+    #[msg("Report-and-slash moderation is not enabled for this room")]
+    ModerationDisabled,
+    #[msg("Cannot report yourself")]
+    CannotReportSelf,
+    #[msg("Sender's reputation is below the room's threshold")]
+    InsufficientReputation,
+    #[msg("Protocol fee exceeds the maximum")]
+    ProtocolFeeTooHigh,
+    #[msg("Protocol treasury accounts are required for this payment")]
+    ProtocolAccountsMissing,
+    #[msg("Token account is not owned by the protocol treasury")]
+    InvalidTreasuryVault,
+    #[msg("Program is paused")]
+    ProgramPaused,
+    #[msg("Signer is not the pending protocol admin")]
+    NotPendingAdmin,
+    #[msg("Cannot follow yourself")]
+    CannotFollowSelf,
+    #[msg("Cannot block yourself")]
+    CannotBlockSelf,
+    #[msg("Cannot call yourself")]
+    CannotCallSelf,
+    #[msg("The recipient has blocked you")]
+    BlockedByTarget,
+    #[msg("Call is not ringing")]
+    CallNotRinging,
+    #[msg("Call is not active")]
+    CallNotActive,
+    #[msg("Signer is not a party to this call")]
+    NotCallParty,
+    #[msg("Call has not rung long enough to expire")]
+    CallStillRinging,
+    #[msg("Participant timeout cannot be negative")]
+    InvalidParticipantTimeout,
+    #[msg("Participant's heartbeat is still fresh")]
+    ParticipantNotStale,
+    #[msg("Text message too long")]
+    TextMessageTooLong,
+    #[msg("Polls need between 2 and 8 options of up to 32 bytes")]
+    InvalidPollOptions,
+    #[msg("Poll duration must be positive")]
+    InvalidPollDuration,
+    #[msg("Poll is closed")]
+    PollClosed,
+    #[msg("Invalid poll option")]
+    InvalidPollOption,
+    // No longer returned; kept so later error codes don't shift
+    #[msg("Session has already ended")]
+    SessionAlreadyEnded,
+    #[msg("Session has not ended")]
+    SessionNotEnded,
+    #[msg("Attendee joined too late or did not stay long enough")]
+    AttendanceRequirementsNotMet,
+    #[msg("No session is running")]
+    SessionNotRunning,
+    #[msg("A session is already running")]
+    SessionAlreadyRunning,
+    #[msg("Room is already being recorded")]
+    AlreadyRecording,
+    #[msg("Room is not being recorded")]
+    NotRecording,
+    #[msg("Every current participant must consent before recording starts")]
+    RecordingConsentMissing,
+    #[msg("Content id too long")]
+    ContentIdTooLong,
+    #[msg("Clip range must be non-empty and within the recording")]
+    InvalidClipRange,
+    #[msg("Revenue split has more recipients than NFT royalties support")]
+    TooManyRoyaltyRecipients,
+    #[msg("Recording does not belong to this room")]
+    InvalidRecording,
+    #[msg("Signer is not the registered transcription oracle")]
+    NotTranscriptionOracle,
+    #[msg("Provider name too long")]
+    ProviderNameTooLong,
+    #[msg("SDP offer has already been answered")]
+    SdpAlreadyAnswered,
+    #[msg("ICE candidate blob too large")]
+    IceCandidatesTooLarge,
+    #[msg("Relay region too long")]
+    RelayRegionTooLong,
+    #[msg("Relay stake below the minimum")]
+    InsufficientRelayStake,
+    #[msg("Relay is not live")]
+    RelayNotActive,
+    #[msg("Room is not pinned to this relay")]
+    RelayNotPinned,
+    #[msg("First sequence number is after the last")]
+    InvalidSequenceRange,
+    #[msg("Session key expiry must be in the future and within the maximum duration")]
+    InvalidSessionKeyExpiry,
+    #[msg("Signer is neither the sender nor a session key with the required scope")]
+    InvalidSessionKey,
+    #[msg("Session key has expired")]
+    SessionKeyExpired,
+    #[msg("Missing or invalid Ed25519 signature from the sender")]
+    InvalidSenderSignature,
+    #[msg("Idempotency key was already used for a recent send")]
+    DuplicateSend,
+    #[msg("Account is not an older version of the expected type")]
+    InvalidAccountVersion,
+    #[msg("Participants can only move between a room and its breakouts")]
+    InvalidBreakoutMove,
+    #[msg("Schedule must start in the future with an interval at least as long as its duration")]
+    InvalidRecurringSchedule,
+    #[msg("Next occurrence is not due yet")]
+    OccurrenceNotDue,
+    #[msg("Schedule lacks the lamports to fund another occurrence")]
+    ScheduleUnderfunded,
+    #[msg("Unknown leaderboard metric or empty window")]
+    InvalidLeaderboard,
+    #[msg("Source account does not match the leaderboard's metric")]
+    InvalidLeaderboardSource,
+    #[msg("Storage lane is reserved for the stage")]
+    StageLaneReserved,
+    #[msg("Sending faster than the room allows")]
+    SendRateLimited,
+    #[msg("Room journals its storage writes; pass its write journal")]
+    WriteJournalMissing,
+    #[msg("Frame codec is not accepted in this room")]
+    CodecNotAccepted,
+    #[msg("None of the joiner's codecs are accepted in this room")]
+    NoCommonCodec,
+    #[msg("Frame is longer or denser than the room's quality settings allow")]
+    FrameQualityExceeded,
+    #[msg("Capture timestamp is too far from the chain clock")]
+    InvalidCaptureTimestamp,
+    #[msg("USD-priced fees need the price feed config and a SOL/USD price update")]
+    PriceFeedMissing,
+    #[msg("Invalid price feed")]
+    InvalidPriceFeed,
+    #[msg("SOL/USD price is stale or too uncertain")]
+    UnreliablePrice,
+    #[msg("Binding a domain needs its name record and the room domain account")]
+    DomainAccountsMissing,
+    #[msg("Name record is not a .sol domain owned by the host")]
+    InvalidDomainRecord,
+    #[msg("Room already has a membership mint")]
+    MembershipMintExists,
+    #[msg("Membership token account does not belong to the joiner or the room")]
+    InvalidMembershipAccount,
+    #[msg("Wallet already holds the room's membership")]
+    AlreadyMember,
+    #[msg("Wallet does not hold the room's membership")]
+    NotAMember,
+    #[msg("Compressed ticket needs the ticket tree, its merkle tree and the compression program")]
+    CompressedTicketAccountsMissing,
+    #[msg("Compressed ticket is not from this event's tree")]
+    InvalidCompressedTicket,
+    #[msg("Cross-chain gate needs an attestor or a Wormhole emitter")]
+    InvalidCrossChainGate,
+    #[msg("Attestation is invalid, expired or not from the gate's attestor or emitter")]
+    InvalidAttestation,
+    #[msg("Room requires an unexpired cross-chain pass")]
+    CrossChainPassRequired,
+    #[msg("Voice room is already active")]
+    RoomAlreadyActive,
+    #[msg("New capacity must exceed the current one")]
+    InvalidRoomCapacity,
+    #[msg("Signer is not a host of this room")]
+    NotRoomHost,
+    #[msg("Key is already a host, or not one when removing")]
+    InvalidHost,
+    #[msg("Room's host set is full")]
+    TooManyHosts,
+    #[msg("The last host can't be removed")]
+    LastHost,
+    #[msg("Ban must end in the future")]
+    InvalidBanDuration,
+    #[msg("Hosts can't be banned")]
+    CannotBanHost,
+    #[msg("Wallet is banned from this room")]
+    BannedFromRoom,
+    #[msg("Room is flagged for review")]
+    RoomFlagged,
+    #[msg("Room is not flagged")]
+    RoomNotFlagged,
+    #[msg("Relay does not serve the room's region")]
+    RelayRegionMismatch,
+    #[msg("Bandwidth price must be positive")]
+    InvalidBandwidthPrice,
+    #[msg("Bandwidth deposit must be positive")]
+    InvalidBandwidthDeposit,
+    #[msg("Room meters bandwidth but its allowance was not provided")]
+    BandwidthAllowanceMissing,
+    #[msg("Room's bandwidth allowance is exhausted")]
+    BandwidthExhausted,
+    #[msg("Invalid hand queue weighting")]
+    InvalidHandWeighting,
+    #[msg("Account the hand queue weighs by was not provided")]
+    HandWeightAccountMissing,
+    #[msg("Participant is already on stage")]
+    AlreadyOnStage,
+    #[msg("Hand is already raised")]
+    HandAlreadyRaised,
+    #[msg("Hand queue is full")]
+    HandQueueFull,
+    #[msg("No raised hand for this participant")]
+    HandNotRaised,
+    #[msg("Participant is not next in the hand queue")]
+    NotNextInQueue,
+    #[msg("Stage invitation has expired")]
+    StageInviteExpired,
+    #[msg("Only a host or the designated recorder can do this")]
+    NotRecorder,
+    #[msg("Archive accounts must be (voice_message, sender) pairs from the session")]
+    InvalidArchiveAccounts,
+    #[msg("Messages must be archived oldest first")]
+    ArchiveOutOfOrder,
+    #[msg("Session archive is full")]
+    ArchiveFull,
+    #[msg("Manifest accounts must be the session's messages, batches or clips, oldest first")]
+    InvalidManifestAccounts,
+    #[msg("Every storage lane is allocated")]
+    NoFreeWriteSlot,
+    #[msg("Storage lane is not allocated")]
+    WriteSlotNotAllocated,
+    #[msg("Lane holds a frame listeners haven't consumed; pass force to overwrite it")]
+    UnreadFrameInLane,
+    #[msg("Listener cursor can't move backwards")]
+    CursorRegression,
+    #[msg("Cannot subscribe to your own rooms")]
+    CannotSubscribeToSelf,
+    #[msg("Unknown notification preference bits")]
+    InvalidNotificationPreferences,
+    #[msg("Storage PDA was written under a different storage layout version")]
+    StorageLayoutMismatch,
+    #[msg("Storage writes over CPI need the storage_writer account")]
+    StorageWriterMissing,
+    #[msg("This build writes storage PDAs in place; rebuild with storage-cpi to write over CPI")]
+    StorageCpiDisabled,
+    #[msg("Storage program is not the protocol's storage backend")]
+    InvalidStorageBackend,
+    #[msg("Storage no longer matches the voice message's content hash")]
+    IntegrityCheckFailed,
+    #[msg("The participant's payment stream must be passed")]
+    PaymentStreamRequired,
+    #[msg("Offender is neither in that session nor within their stake's cooldown")]
+    OffenderNotReportable,
+    #[msg("Offender stake and room treasury are required in rooms with a sender stake")]
+    SlashAccountsRequired,
+    #[msg("Signer is not the program's upgrade authority")]
+    NotUpgradeAuthority,
+    #[msg("Relay has not reached the report quorum")]
+    RelayReportsBelowQuorum,
+    #[msg("Breakout rooms are joined through move_participant")]
+    BreakoutJoinNotAllowed,
+    #[msg("Occurrence has not reached the end of its duration")]
+    OccurrenceStillRunning,
+    #[msg("Start a new sequence epoch before sending again after rejoining")]
+    SequenceEpochBehind,
+    #[msg("Rooms hold at most 16 participants, the width of the slot bitmaps")]
+    RoomCapacityCeiling,
+    #[msg("Storage PDA's authority hasn't delegated writes to this program")]
+    StorageNotDelegated,
+    #[msg("Storage lane is allocated to another participant")]
+    WriteSlotHeldByOther,
+}
+
+#[error_code(offset = 7000)]
+pub enum VoicechatError {
+    #[msg("Invalid PDA index. Must be below the authority's PDA limit (10 without a registry).")]
+    InvalidPDAIndex,
+    #[msg("Data size exceeds maximum allowed size of 1MB.")]
+    DataTooLarge,
+    #[msg("No reallocation needed - account is already at or above target size.")]
+    NoReallocNeeded,
+    #[msg("Target size exceeds maximum allowed size of 1MB.")]
+    TargetSizeTooLarge,
+    #[msg("Program is paused.")]
+    ProgramPaused,
+    #[msg("Account is not a pre-version PDA account.")]
+    InvalidAccountVersion,
+    #[msg("PDA limit must be between 1 and 1024.")]
+    InvalidPDALimit,
+    #[msg("PDA account's prepaid balance doesn't cover the rent for this reallocation.")]
+    InsufficientPrepaidBalance,
+}
+
+#[error_code(offset = 8000)]
+pub enum StorageError {
+    #[msg("Invalid PDA index. Must be 0-9.")]
+    InvalidPDAIndex,
+    #[msg("Data too large for storage PDA.")]
+    DataTooLarge,
+    #[msg("Program is paused.")]
+    ProgramPaused,
+    #[msg("Account is not an older version of a storage PDA.")]
+    InvalidAccountVersion,
+    #[msg("Caller speaks a different storage interface version.")]
+    InterfaceVersionMismatch,
+    #[msg("Writer is neither the storage PDA's authority nor voice_chat_manager.")]
+    UnauthorizedWriter,
+    #[msg("Batch accounts don't match the requested storage PDA indices.")]
+    InvalidBatchAccounts,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CallStatus {
+    Ringing,
+    Active,
+    Rejected,
+    Ended,
+    Missed,
+}
+
+#[event]
+pub struct TipReceived {
+    pub room: Pubkey,
+    pub tipper: Pubkey,
+    pub speaker: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SenderSlashed {
+    pub room: Pubkey,
+    pub offender: Pubkey,
+    pub report_count: u16,
+    pub slashed_lamports: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FollowChanged {
+    pub follower: Pubkey,
+    pub target: Pubkey,
+    pub following: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DirectCallUpdated {
+    pub caller: Pubkey,
+    pub callee: Pubkey,
+    pub status: CallStatus,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SpeakingStateChanged {
+    pub room: Pubkey,
+    pub participant: Pubkey,
+    pub speaking: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TextMessageSent {
+    pub room: Pubkey,
+    pub sender: Pubkey,
+    pub content: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct Reaction {
+    pub room: Pubkey,
+    pub participant: Pubkey,
+    pub emoji_code: u16,
+    pub target_sequence: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PollClosed {
+    pub room: Pubkey,
+    pub poll_id: u32,
+    pub votes: Vec<u32>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RecordingStateChanged {
+    pub room: Pubkey,
+    pub recording: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SdpPosted {
+    pub offerer: Pubkey,
+    pub answerer: Pubkey,
+    pub is_answer: bool,
+    pub blob_hash: [u8; 32],
+    pub storage_slot: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct IceCandidatesPosted {
+    pub sender: Pubkey,
+    pub target: Pubkey,
+    pub candidates: Vec<u8>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RelaySlashed {
+    pub relay: Pubkey,
+    pub operator: Pubkey,
+    pub report_count: u16,
+    pub slashed_lamports: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DeliveryAttested {
+    pub session: Pubkey,
+    pub relay: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub frames_delivered: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RoomOpened {
+    pub room: Pubkey,
+    pub host: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RoomSubscriptionChanged {
+    pub subscriber: Pubkey,
+    pub host: Pubkey,
+    pub subscribed: bool,
+    pub timestamp: i64,
+}
+
+/// StoragePDA byte layout. storage_manager owns it; voice_chat_manager reads storage PDAs
+/// through these offsets when it touches them in place. Bump LAYOUT_VERSION whenever an
+/// offset moves: storage_manager stamps it on every StoragePDA and voice_chat_manager
+/// refuses an account stamped for a different version.
+pub mod storage_layout {
+    use anchor_lang::prelude::*;
+
+    pub const STORAGE_MANAGER_PROGRAM_ID: Pubkey =
+        anchor_lang::solana_program::pubkey!("SU6CRGJXz5ksvXPyUuWXYfW2qmba6ZgHa3sxdr9aYMz");
+    pub const VOICE_CHAT_MANAGER_PROGRAM_ID: Pubkey =
+        anchor_lang::solana_program::pubkey!("AGpoLxtMeNW17NZL7nWLFLmmhKPW5nbxfdY8BVaMxDNy");
+
+    pub const LAYOUT_VERSION: u8 = 3;
+    pub const CHUNK_SIZE: usize = 30 * 1024;
+    pub const MAX_STORAGE_PDAS: u8 = 10;
+
+    // discriminator + index + authority + created_at + data_length + is_active + data + version + bump + manager_writes
+    pub const INDEX_OFFSET: usize = 8;
+    pub const AUTHORITY_OFFSET: usize = INDEX_OFFSET + 1;
+    pub const DATA_LENGTH_OFFSET: usize = AUTHORITY_OFFSET + 32 + 8;
+    pub const DATA_OFFSET: usize = DATA_LENGTH_OFFSET + 4 + 1;
+    pub const VERSION_OFFSET: usize = DATA_OFFSET + CHUNK_SIZE;
+    pub const BUMP_OFFSET: usize = VERSION_OFFSET + 1;
+    pub const MANAGER_WRITES_OFFSET: usize = BUMP_OFFSET + 1;
+    pub const SPACE: usize = MANAGER_WRITES_OFFSET + 1;
+
+    /// Seed of the voice_chat_manager PDA that signs its write_chunk CPIs
+    pub const WRITER_SEED: &[u8] = b"storage_writer";
+
+    /// Layout version stamped on a storage PDA, 0 when the account predates versioning
+    pub fn layout_version(data: &[u8]) -> u8 {
+        data.get(VERSION_OFFSET).copied().unwrap_or(0)
+    }
+
+    /// Whether the storage PDA's authority has delegated writes to the storage_writer PDA
+    pub fn manager_writes(data: &[u8]) -> bool {
+        data.get(MANAGER_WRITES_OFFSET) == Some(&1)
+    }
+}
+
+/// Instructions a storage backend implements so voice_chat_manager can keep audio in it.
+/// As with SPL's interfaces, each instruction is tagged with the first 8 bytes of
+/// sha256("voice-storage-interface:<name>") rather than an Anchor sighash, so a backend can
+/// be written with any framework; arguments follow Borsh-encoded. Backends must accept
+/// write_chunk from voice_chat_manager's storage_writer PDA (storage_layout::WRITER_SEED)
+/// for exactly the chunks whose authority delegated to it.
+///
+/// - write_chunk(interface_version: u8, data: Vec<u8>): [chunk (w), writer (s), protocol_config]
+///   replaces the chunk's contents with `data`
+/// - read_chunk(offset: u32, length: u32): [chunk], returns at most MAX_READ_LENGTH bytes as
+///   return data
+/// - alloc(index: u8): [chunk (w), authority (w, s), system_program, protocol_config]
+/// - free(): [chunk (w), authority (w, s), protocol_config], rent goes back to the authority
+/// - delegate(enabled: bool): [chunk (w), authority (s), protocol_config] lets the storage_writer
+///   PDA write the chunk, or stops it
+pub mod storage_interface {
+    use anchor_lang::prelude::*;
+    use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+
+    pub const INTERFACE_VERSION: u8 = 1;
+    pub const MAX_READ_LENGTH: u32 = 1024; // Return data limit
+
+    pub const WRITE_CHUNK: [u8; 8] = [91, 105, 201, 111, 185, 238, 19, 199];
+    pub const READ_CHUNK: [u8; 8] = [90, 26, 206, 72, 86, 48, 126, 83];
+    pub const ALLOC: [u8; 8] = [193, 40, 223, 236, 126, 190, 115, 179];
+    pub const FREE: [u8; 8] = [136, 182, 88, 54, 34, 207, 0, 158];
+    pub const DELEGATE: [u8; 8] = [142, 156, 199, 158, 255, 143, 187, 164];
+
+    pub fn write_chunk(program_id: Pubkey, chunk: Pubkey, writer: Pubkey, protocol_config: Pubkey, data: &[u8]) -> Instruction {
+        let mut ix_data = WRITE_CHUNK.to_vec();
+        ix_data.push(INTERFACE_VERSION);
+        ix_data.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        ix_data.extend_from_slice(data);
+        Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(chunk, false),
+                AccountMeta::new_readonly(writer, true),
+                AccountMeta::new_readonly(protocol_config, false),
+            ],
+            data: ix_data,
+        }
+    }
+
+    pub fn read_chunk(program_id: Pubkey, chunk: Pubkey, offset: u32, length: u32) -> Instruction {
+        let mut ix_data = READ_CHUNK.to_vec();
+        ix_data.extend_from_slice(&offset.to_le_bytes());
+        ix_data.extend_from_slice(&length.to_le_bytes());
+        Instruction {
+            program_id,
+            accounts: vec![AccountMeta::new_readonly(chunk, false)],
+            data: ix_data,
+        }
+    }
+
+    pub fn alloc(program_id: Pubkey, chunk: Pubkey, authority: Pubkey, protocol_config: Pubkey, index: u8) -> Instruction {
+        let mut ix_data = ALLOC.to_vec();
+        ix_data.push(index);
+        Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(chunk, false),
+                AccountMeta::new(authority, true),
+                AccountMeta::new_readonly(anchor_lang::system_program::ID, false),
+                AccountMeta::new_readonly(protocol_config, false),
+            ],
+            data: ix_data,
+        }
+    }
+
+    pub fn free(program_id: Pubkey, chunk: Pubkey, authority: Pubkey, protocol_config: Pubkey) -> Instruction {
+        Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(chunk, false),
+                AccountMeta::new(authority, true),
+                AccountMeta::new_readonly(protocol_config, false),
+            ],
+            data: FREE.to_vec(),
+        }
+    }
+
+    pub fn delegate(program_id: Pubkey, chunk: Pubkey, authority: Pubkey, protocol_config: Pubkey, enabled: bool) -> Instruction {
+        let mut ix_data = DELEGATE.to_vec();
+        ix_data.push(enabled as u8);
+        Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(chunk, false),
+                AccountMeta::new_readonly(authority, true),
+                AccountMeta::new_readonly(protocol_config, false),
+            ],
+            data: ix_data,
+        }
+    }
+}
@@ -1,9 +1,24 @@
 use anchor_lang::prelude::*;
+pub use voice_chat_shared::{storage_interface, storage_layout};
+pub use voice_chat_shared::StorageError;
 
 declare_id!("SU6CRGJXz5ksvXPyUuWXYfW2qmba6ZgHa3sxdr9aYMz");
 
-const CHUNK_SIZE: usize = 30 * 1024; // 30KB per PDA
-const MAX_STORAGE_PDAS: u8 = 10; // 10 PDAs total
+// The StoragePDA layout is published in voice_chat_shared::storage_layout so other programs
+// never hard-code it; bump storage_layout::LAYOUT_VERSION when appending StoragePDA fields
+const CHUNK_SIZE: usize = storage_layout::CHUNK_SIZE; // 30KB per PDA
+const MAX_STORAGE_PDAS: u8 = storage_layout::MAX_STORAGE_PDAS; // 10 PDAs total
+const STORAGE_PDA_VERSION: u8 = storage_layout::LAYOUT_VERSION;
+const STORAGE_PDA_SPACE: usize = 8 + 1 + 32 + 8 + 4 + 1 + CHUNK_SIZE + 1 + 1 + 1; // discriminator + index + authority + created_at + data_length + is_active + 30KB data + version + bump + manager_writes
+const _: () = assert!(STORAGE_PDA_SPACE == storage_layout::SPACE);
+const STORAGE_PDA_AUTHORITY_OFFSET: usize = storage_layout::AUTHORITY_OFFSET;
+const STORAGE_PDA_VERSION_OFFSET: usize = storage_layout::VERSION_OFFSET;
+const STORAGE_PDA_BUMP_OFFSET: usize = storage_layout::BUMP_OFFSET;
+const STORAGE_SNAPSHOT_DATA_OFFSET: usize = 8 + 32 + 32 + 32 + 1 + 4 + 8 + 1; // discriminator + source + authority + payer + index + data_length + taken_at + bump; raw data follows
+
+// Program-wide pause switch owned by voice_chat_manager
+const VOICE_CHAT_MANAGER_PROGRAM_ID: Pubkey = storage_layout::VOICE_CHAT_MANAGER_PROGRAM_ID;
+const PROTOCOL_CONFIG_PAUSED_OFFSET: usize = 8 + 32 + 2; // discriminator + admin + fee_bps
 
 #[program]
 pub mod storage_manager {
@@ -11,6 +26,7 @@ pub mod storage_manager {
 
     /// Initialize the storage system
     pub fn initialize_storage(ctx: Context<InitializeStorage>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
         let storage_config = &mut ctx.accounts.storage_config;
         storage_config.authority = ctx.accounts.authority.key();
         storage_config.total_pdas = 0;
@@ -25,47 +41,145 @@ pub mod storage_manager {
         ctx: Context<CreateStoragePDA>, 
         pda_index: u8
     ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
         require!(pda_index < MAX_STORAGE_PDAS, StorageError::InvalidPDAIndex);
         
-        let storage_pda = &mut ctx.accounts.storage_pda;
-        storage_pda.index = pda_index;
-        storage_pda.authority = ctx.accounts.authority.key();
-        storage_pda.created_at = Clock::get()?.unix_timestamp;
-        storage_pda.data_length = 0;
-        storage_pda.is_active = true;
-        
         // Initialize 30KB data space with zeros
-        storage_pda.data = [0u8; CHUNK_SIZE];
+        let authority = ctx.accounts.authority.key();
+        ctx.accounts.storage_pda.init(pda_index, authority, ctx.bumps.storage_pda)?;
         
         msg!("Created storage PDA {} with 30KB capacity", pda_index);
         Ok(())
     }
 
+    /// storage_interface::alloc - create_storage_pda under the interface discriminator
+    #[instruction(discriminator = storage_interface::ALLOC)]
+    pub fn alloc(ctx: Context<CreateStoragePDA>, pda_index: u8) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(pda_index < MAX_STORAGE_PDAS, StorageError::InvalidPDAIndex);
+        
+        let authority = ctx.accounts.authority.key();
+        ctx.accounts.storage_pda.init(pda_index, authority, ctx.bumps.storage_pda)?;
+        
+        msg!("Allocated storage PDA {}", pda_index);
+        Ok(())
+    }
+
+    /// storage_interface::free - close a storage PDA, returning its rent to the authority
+    #[instruction(discriminator = storage_interface::FREE)]
+    pub fn free(ctx: Context<FreeStoragePDA>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        msg!("Freed storage PDA {}", ctx.accounts.storage_pda.index);
+        Ok(())
+    }
+
+    /// storage_interface::delegate - let voice_chat_manager's storage_writer PDA write this
+    /// storage PDA through write_chunk, or stop it
+    #[instruction(discriminator = storage_interface::DELEGATE)]
+    pub fn delegate(ctx: Context<DelegateStoragePDA>, enabled: bool) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let storage_pda = &mut ctx.accounts.storage_pda;
+        storage_pda.manager_writes = enabled;
+        
+        msg!("Storage PDA {} manager writes: {}", storage_pda.index, enabled);
+        Ok(())
+    }
+
     /// Create all 10 storage PDAs - batch creation helper
     pub fn create_all_storage_pdas(
         ctx: Context<CreateAllStoragePDAs>,
         pda_index: u8
     ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
         require!(pda_index < MAX_STORAGE_PDAS, StorageError::InvalidPDAIndex);
         
-        let storage_pda = &mut ctx.accounts.storage_pda;
-        storage_pda.index = pda_index;
-        storage_pda.authority = ctx.accounts.authority.key();
-        storage_pda.created_at = Clock::get()?.unix_timestamp;
-        storage_pda.data_length = 0;
-        storage_pda.is_active = true;
-        storage_pda.data = [0u8; CHUNK_SIZE];
+        let authority = ctx.accounts.authority.key();
+        ctx.accounts.storage_pda.init(pda_index, authority, ctx.bumps.storage_pda)?;
         
         msg!("Batch created storage PDA {} (30KB)", pda_index);
         Ok(())
     }
 
+    /// Create several storage PDAs in one transaction. The uninitialized PDAs are passed as
+    /// writable remaining_accounts in the same order as `pda_indices`; each is created here
+    /// with its PDA signer seeds, so provisioning a room's storage isn't one transaction per PDA.
+    pub fn create_storage_pdas<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateStoragePDAs<'info>>,
+        pda_indices: Vec<u8>,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(
+            !pda_indices.is_empty() && pda_indices.len() == ctx.remaining_accounts.len(),
+            StorageError::InvalidBatchAccounts
+        );
+        
+        let authority = ctx.accounts.authority.to_account_info();
+        let rent_exempt_minimum = Rent::get()?.minimum_balance(STORAGE_PDA_SPACE);
+        let created_at = Clock::get()?.unix_timestamp;
+        for (&pda_index, storage_info) in pda_indices.iter().zip(ctx.remaining_accounts) {
+            require!(pda_index < MAX_STORAGE_PDAS, StorageError::InvalidPDAIndex);
+            let (address, bump) = Pubkey::find_program_address(
+                &[b"storage", authority.key.as_ref(), &[pda_index]],
+                &crate::ID,
+            );
+            require_keys_eq!(address, storage_info.key(), StorageError::InvalidBatchAccounts);
+            require!(storage_info.data_is_empty(), StorageError::InvalidBatchAccounts);
+            
+            // Same steps as Anchor's init: top up whatever the address already holds, then
+            // allocate and assign it under the PDA's signature
+            let signer_seeds: &[&[u8]] = &[b"storage", authority.key.as_ref(), &[pda_index], &[bump]];
+            let shortfall = rent_exempt_minimum.saturating_sub(storage_info.lamports());
+            if shortfall > 0 {
+                anchor_lang::system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        anchor_lang::system_program::Transfer {
+                            from: authority.clone(),
+                            to: storage_info.clone(),
+                        },
+                    ),
+                    shortfall,
+                )?;
+            }
+            anchor_lang::system_program::allocate(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Allocate { account_to_allocate: storage_info.clone() },
+                    &[signer_seeds],
+                ),
+                STORAGE_PDA_SPACE as u64,
+            )?;
+            anchor_lang::system_program::assign(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Assign { account_to_assign: storage_info.clone() },
+                    &[signer_seeds],
+                ),
+                &crate::ID,
+            )?;
+            
+            // Fresh accounts are zeroed, so only the non-zero header fields need writing
+            let mut data = storage_info.try_borrow_mut_data()?;
+            data[..8].copy_from_slice(StoragePDA::DISCRIMINATOR);
+            data[storage_layout::INDEX_OFFSET] = pda_index;
+            data[STORAGE_PDA_AUTHORITY_OFFSET..STORAGE_PDA_AUTHORITY_OFFSET + 32].copy_from_slice(authority.key.as_ref());
+            data[STORAGE_PDA_AUTHORITY_OFFSET + 32..STORAGE_PDA_AUTHORITY_OFFSET + 40].copy_from_slice(&created_at.to_le_bytes());
+            data[storage_layout::DATA_LENGTH_OFFSET + 4] = 1; // is_active
+            data[STORAGE_PDA_VERSION_OFFSET] = STORAGE_PDA_VERSION;
+            data[STORAGE_PDA_BUMP_OFFSET] = bump;
+        }
+        
+        msg!("Batch created {} storage PDAs (30KB each)", pda_indices.len());
+        Ok(())
+    }
+
     /// Update storage PDA data (used by voice chat contract)
     pub fn update_storage_data(
         ctx: Context<UpdateStorageData>,
         new_data: Vec<u8>,
         offset: u32,
     ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
         require!(new_data.len() <= CHUNK_SIZE, StorageError::DataTooLarge);
         require!((offset as usize + new_data.len()) <= CHUNK_SIZE, StorageError::DataTooLarge);
         
@@ -85,6 +199,52 @@ pub mod storage_manager {
         Ok(())
     }
 
+    /// storage_interface::write_chunk - replace a storage PDA's contents with `data`, written
+    /// from the start. Besides the PDA's authority, voice_chat_manager may write by signing
+    /// with its storage_writer PDA once the authority has delegated to it.
+    #[instruction(discriminator = storage_interface::WRITE_CHUNK)]
+    pub fn write_chunk(ctx: Context<WriteChunk>, interface_version: u8, data: Vec<u8>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(
+            interface_version == storage_interface::INTERFACE_VERSION,
+            StorageError::InterfaceVersionMismatch
+        );
+        require!(data.len() <= CHUNK_SIZE, StorageError::DataTooLarge);
+        let writer = ctx.accounts.writer.key();
+        let storage_pda = &mut ctx.accounts.storage_pda;
+        require!(
+            writer == storage_pda.authority
+                || (storage_pda.manager_writes && writer == manager_storage_writer()),
+            StorageError::UnauthorizedWriter
+        );
+        
+        storage_pda.data[..data.len()].copy_from_slice(&data);
+        storage_pda.data_length = data.len() as u32;
+        
+        msg!("Wrote {} bytes to storage PDA {}", data.len(), storage_pda.index);
+        Ok(())
+    }
+
+    /// storage_interface::read_chunk - return up to MAX_READ_LENGTH bytes of a storage PDA's
+    /// data as return data
+    #[instruction(discriminator = storage_interface::READ_CHUNK)]
+    pub fn read_chunk(ctx: Context<ReadChunk>, offset: u32, length: u32) -> Result<()> {
+        let storage_pda = &ctx.accounts.storage_pda;
+        let start = offset.min(storage_pda.data_length) as usize;
+        let end = offset
+            .saturating_add(length.min(storage_interface::MAX_READ_LENGTH))
+            .min(storage_pda.data_length) as usize;
+        anchor_lang::solana_program::program::set_return_data(&storage_pda.data[start..end]);
+        Ok(())
+    }
+
+    /// Report the storage layout version this build speaks, as return data, so callers can
+    /// check compatibility before they write
+    pub fn get_layout_version(_ctx: Context<GetLayoutVersion>) -> Result<()> {
+        anchor_lang::solana_program::program::set_return_data(&[STORAGE_PDA_VERSION]);
+        Ok(())
+    }
+
     /// Get storage info
     pub fn get_storage_info(ctx: Context<GetStorageInfo>) -> Result<()> {
         let storage_pda = &ctx.accounts.storage_pda;
@@ -96,6 +256,7 @@ pub mod storage_manager {
 
     /// Clear storage PDA data
     pub fn clear_storage_data(ctx: Context<ClearStorageData>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
         let storage_pda = &mut ctx.accounts.storage_pda;
         storage_pda.data = [0u8; CHUNK_SIZE];
         storage_pda.data_length = 0;
@@ -103,6 +264,90 @@ pub mod storage_manager {
         msg!("Cleared storage PDA {}", storage_pda.index);
         Ok(())
     }
+
+    /// Copy a storage PDA's current contents into a read-only snapshot account owned by the
+    /// payer, so an archival process can capture the chunk while live writes carry on. One
+    /// snapshot per chunk and payer; close it to take the next one.
+    pub fn snapshot_storage(ctx: Context<SnapshotStorage>, pda_index: u8) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let storage_pda = &ctx.accounts.storage_pda;
+        let data_length = storage_pda.data_length as usize;
+        
+        let snapshot = &mut ctx.accounts.snapshot;
+        snapshot.source = storage_pda.key();
+        snapshot.authority = storage_pda.authority;
+        snapshot.payer = ctx.accounts.payer.key();
+        snapshot.index = pda_index;
+        snapshot.data_length = storage_pda.data_length;
+        snapshot.taken_at = Clock::get()?.unix_timestamp;
+        snapshot.bump = ctx.bumps.snapshot;
+        
+        // The copied bytes live after the header, like a PDAAccount's data
+        let snapshot_info = snapshot.to_account_info();
+        snapshot_info.try_borrow_mut_data()?[STORAGE_SNAPSHOT_DATA_OFFSET..STORAGE_SNAPSHOT_DATA_OFFSET + data_length]
+            .copy_from_slice(&storage_pda.data[..data_length]);
+        
+        msg!("Snapshot of storage PDA {} taken: {} bytes", pda_index, data_length);
+        Ok(())
+    }
+
+    /// Close a storage snapshot and return its rent to the payer who took it
+    pub fn close_storage_snapshot(ctx: Context<CloseStorageSnapshot>) -> Result<()> {
+        msg!("Closed snapshot of storage PDA {}", ctx.accounts.snapshot.index);
+        Ok(())
+    }
+
+    /// Upgrade a storage PDA created under an older layout by growing it to the current
+    /// size and stamping the current version. Existing data stays where it is; version 3's
+    /// manager_writes flag starts off.
+    pub fn migrate_storage_pda(ctx: Context<MigrateStoragePDA>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let storage_info = ctx.accounts.storage_pda.to_account_info();
+        let bump = {
+            let data = storage_info.try_borrow_data()?;
+            require!(data.starts_with(StoragePDA::DISCRIMINATOR), StorageError::InvalidAccountVersion);
+            require!(
+                data.get(STORAGE_PDA_AUTHORITY_OFFSET..STORAGE_PDA_AUTHORITY_OFFSET + 32)
+                    == Some(ctx.accounts.authority.key().as_ref()),
+                StorageError::InvalidAccountVersion
+            );
+            let version = data.get(STORAGE_PDA_VERSION_OFFSET).copied().unwrap_or(0);
+            require!(version < STORAGE_PDA_VERSION, StorageError::InvalidAccountVersion);
+            
+            // Version 2 stores the canonical bump; derive it once here
+            let index = data.get(8).copied().unwrap_or_default();
+            let (address, bump) = Pubkey::find_program_address(
+                &[b"storage", ctx.accounts.authority.key().as_ref(), &[index]],
+                &crate::ID,
+            );
+            require_keys_eq!(address, storage_info.key(), StorageError::InvalidAccountVersion);
+            bump
+        };
+        
+        let rent_exempt_minimum = Rent::get()?.minimum_balance(STORAGE_PDA_SPACE);
+        let shortfall = rent_exempt_minimum.saturating_sub(storage_info.lamports());
+        if shortfall > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to: storage_info.clone(),
+                    },
+                ),
+                shortfall,
+            )?;
+        }
+        if storage_info.data_len() < STORAGE_PDA_SPACE {
+            storage_info.resize(STORAGE_PDA_SPACE)?;
+        }
+        let mut data = storage_info.try_borrow_mut_data()?;
+        data[STORAGE_PDA_VERSION_OFFSET] = STORAGE_PDA_VERSION;
+        data[STORAGE_PDA_BUMP_OFFSET] = bump;
+        
+        msg!("Migrated storage PDA {} to version {}", storage_info.key(), STORAGE_PDA_VERSION);
+        Ok(())
+    }
 }
 
 #[derive(Accounts)]
@@ -120,6 +365,14 @@ pub struct InitializeStorage<'info> {
     pub authority: Signer<'info>,
     
     pub system_program: Program<'info, System>,
+    
+    /// CHECK: voice_chat_manager's ProtocolConfig, read by require_not_paused
+    #[account(
+        seeds = [b"protocol_config"],
+        bump,
+        seeds::program = VOICE_CHAT_MANAGER_PROGRAM_ID
+    )]
+    pub protocol_config: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
@@ -128,7 +381,7 @@ pub struct CreateStoragePDA<'info> {
     #[account(
         init,
         payer = authority,
-        space = 8 + 1 + 32 + 8 + 4 + 1 + CHUNK_SIZE, // discriminator + index + authority + created_at + data_length + is_active + 30KB data
+        space = STORAGE_PDA_SPACE,
         seeds = [b"storage", authority.key().as_ref(), &[pda_index]],
         bump
     )]
@@ -138,6 +391,14 @@ pub struct CreateStoragePDA<'info> {
     pub authority: Signer<'info>,
     
     pub system_program: Program<'info, System>,
+    
+    /// CHECK: voice_chat_manager's ProtocolConfig, read by require_not_paused
+    #[account(
+        seeds = [b"protocol_config"],
+        bump,
+        seeds::program = VOICE_CHAT_MANAGER_PROGRAM_ID
+    )]
+    pub protocol_config: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
@@ -146,7 +407,7 @@ pub struct CreateAllStoragePDAs<'info> {
     #[account(
         init,
         payer = authority,
-        space = 8 + 1 + 32 + 8 + 4 + 1 + CHUNK_SIZE,
+        space = STORAGE_PDA_SPACE,
         seeds = [b"storage", authority.key().as_ref(), &[pda_index]],
         bump
     )]
@@ -156,6 +417,32 @@ pub struct CreateAllStoragePDAs<'info> {
     pub authority: Signer<'info>,
     
     pub system_program: Program<'info, System>,
+    
+    /// CHECK: voice_chat_manager's ProtocolConfig, read by require_not_paused
+    #[account(
+        seeds = [b"protocol_config"],
+        bump,
+        seeds::program = VOICE_CHAT_MANAGER_PROGRAM_ID
+    )]
+    pub protocol_config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateStoragePDAs<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    /// CHECK: voice_chat_manager's ProtocolConfig, read by require_not_paused
+    #[account(
+        seeds = [b"protocol_config"],
+        bump,
+        seeds::program = VOICE_CHAT_MANAGER_PROGRAM_ID
+    )]
+    pub protocol_config: UncheckedAccount<'info>,
+    
+    // remaining_accounts: the uninitialized storage PDAs (writable), in pda_indices order
 }
 
 #[derive(Accounts)]
@@ -163,19 +450,103 @@ pub struct UpdateStorageData<'info> {
     #[account(
         mut,
         seeds = [b"storage", authority.key().as_ref(), &[storage_pda.index]],
+        bump = storage_pda.bump,
+        has_one = authority
+    )]
+    pub storage_pda: Account<'info, StoragePDA>,
+    
+    pub authority: Signer<'info>,
+    
+    /// CHECK: voice_chat_manager's ProtocolConfig, read by require_not_paused
+    #[account(
+        seeds = [b"protocol_config"],
+        bump,
+        seeds::program = VOICE_CHAT_MANAGER_PROGRAM_ID
+    )]
+    pub protocol_config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WriteChunk<'info> {
+    #[account(
+        mut,
+        seeds = [b"storage", storage_pda.authority.as_ref(), &[storage_pda.index]],
+        bump = storage_pda.bump
+    )]
+    pub storage_pda: Account<'info, StoragePDA>,
+    
+    pub writer: Signer<'info>,
+    
+    /// CHECK: voice_chat_manager's ProtocolConfig, read by require_not_paused
+    #[account(
+        seeds = [b"protocol_config"],
+        bump,
+        seeds::program = VOICE_CHAT_MANAGER_PROGRAM_ID
+    )]
+    pub protocol_config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReadChunk<'info> {
+    #[account(
+        seeds = [b"storage", storage_pda.authority.as_ref(), &[storage_pda.index]],
+        bump = storage_pda.bump
+    )]
+    pub storage_pda: Account<'info, StoragePDA>,
+}
+
+#[derive(Accounts)]
+pub struct FreeStoragePDA<'info> {
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"storage", authority.key().as_ref(), &[storage_pda.index]],
+        bump = storage_pda.bump,
+        has_one = authority
+    )]
+    pub storage_pda: Account<'info, StoragePDA>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: voice_chat_manager's ProtocolConfig, read by require_not_paused
+    #[account(
+        seeds = [b"protocol_config"],
         bump,
+        seeds::program = VOICE_CHAT_MANAGER_PROGRAM_ID
+    )]
+    pub protocol_config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DelegateStoragePDA<'info> {
+    #[account(
+        mut,
+        seeds = [b"storage", authority.key().as_ref(), &[storage_pda.index]],
+        bump = storage_pda.bump,
         has_one = authority
     )]
     pub storage_pda: Account<'info, StoragePDA>,
     
     pub authority: Signer<'info>,
+    
+    /// CHECK: voice_chat_manager's ProtocolConfig, read by require_not_paused
+    #[account(
+        seeds = [b"protocol_config"],
+        bump,
+        seeds::program = VOICE_CHAT_MANAGER_PROGRAM_ID
+    )]
+    pub protocol_config: UncheckedAccount<'info>,
 }
 
+#[derive(Accounts)]
+pub struct GetLayoutVersion {}
+
 #[derive(Accounts)]
 pub struct GetStorageInfo<'info> {
     #[account(
         seeds = [b"storage", authority.key().as_ref(), &[storage_pda.index]],
-        bump
+        bump = storage_pda.bump
     )]
     pub storage_pda: Account<'info, StoragePDA>,
     
@@ -187,12 +558,91 @@ pub struct ClearStorageData<'info> {
     #[account(
         mut,
         seeds = [b"storage", authority.key().as_ref(), &[storage_pda.index]],
+        bump = storage_pda.bump,
+        has_one = authority
+    )]
+    pub storage_pda: Account<'info, StoragePDA>,
+    
+    pub authority: Signer<'info>,
+    
+    /// CHECK: voice_chat_manager's ProtocolConfig, read by require_not_paused
+    #[account(
+        seeds = [b"protocol_config"],
         bump,
+        seeds::program = VOICE_CHAT_MANAGER_PROGRAM_ID
+    )]
+    pub protocol_config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(pda_index: u8)]
+pub struct SnapshotStorage<'info> {
+    #[account(
+        seeds = [b"storage", authority.key().as_ref(), &[pda_index]],
+        bump = storage_pda.bump,
         has_one = authority
     )]
     pub storage_pda: Account<'info, StoragePDA>,
     
+    #[account(
+        init,
+        payer = payer,
+        space = STORAGE_SNAPSHOT_DATA_OFFSET + storage_pda.data_length as usize,
+        seeds = [b"storage_snapshot", storage_pda.key().as_ref(), payer.key().as_ref()],
+        bump
+    )]
+    pub snapshot: Account<'info, StorageSnapshot>,
+    
+    /// CHECK: Only used for the storage PDA seeds
+    pub authority: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    /// CHECK: voice_chat_manager's ProtocolConfig, read by require_not_paused
+    #[account(
+        seeds = [b"protocol_config"],
+        bump,
+        seeds::program = VOICE_CHAT_MANAGER_PROGRAM_ID
+    )]
+    pub protocol_config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseStorageSnapshot<'info> {
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"storage_snapshot", snapshot.source.as_ref(), payer.key().as_ref()],
+        bump = snapshot.bump,
+        has_one = payer
+    )]
+    pub snapshot: Account<'info, StorageSnapshot>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateStoragePDA<'info> {
+    /// CHECK: Possibly too small to deserialize; the handler checks discriminator and authority
+    #[account(mut, owner = crate::ID)]
+    pub storage_pda: UncheckedAccount<'info>,
+    
+    #[account(mut)]
     pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    /// CHECK: voice_chat_manager's ProtocolConfig, read by require_not_paused
+    #[account(
+        seeds = [b"protocol_config"],
+        bump,
+        seeds::program = VOICE_CHAT_MANAGER_PROGRAM_ID
+    )]
+    pub protocol_config: UncheckedAccount<'info>,
 }
 
 #[account]
@@ -210,12 +660,51 @@ pub struct StoragePDA {
     pub data_length: u32,
     pub is_active: bool,
     pub data: [u8; CHUNK_SIZE], // 30KB storage
+    pub version: u8,
+    pub bump: u8,
+    pub manager_writes: bool, // Set through delegate; lets voice_chat_manager's storage_writer write
+}
+
+impl StoragePDA {
+    pub fn init(&mut self, index: u8, authority: Pubkey, bump: u8) -> Result<()> {
+        self.index = index;
+        self.authority = authority;
+        self.created_at = Clock::get()?.unix_timestamp;
+        self.data_length = 0;
+        self.is_active = true;
+        self.data = [0u8; CHUNK_SIZE];
+        self.version = STORAGE_PDA_VERSION;
+        self.bump = bump;
+        self.manager_writes = false;
+        Ok(())
+    }
+}
+
+/// Read-only copy of a storage PDA's data at `taken_at`. The copied bytes follow the header
+/// as raw data (data_length of them), like voicechat's PDAAccount.
+#[account]
+pub struct StorageSnapshot {
+    pub source: Pubkey,
+    pub authority: Pubkey,
+    pub payer: Pubkey,
+    pub index: u8,
+    pub data_length: u32,
+    pub taken_at: i64,
+    pub bump: u8,
+}
+
+/// Guard for state-mutating instructions. The pause switch lives in voice_chat_manager's
+/// ProtocolConfig; until that config exists the program is treated as unpaused.
+fn require_not_paused(protocol_config: &AccountInfo) -> Result<()> {
+    if protocol_config.owner != &VOICE_CHAT_MANAGER_PROGRAM_ID {
+        return Ok(());
+    }
+    let data = protocol_config.try_borrow_data()?;
+    require!(data.get(PROTOCOL_CONFIG_PAUSED_OFFSET) != Some(&1), StorageError::ProgramPaused);
+    Ok(())
 }
 
-#[error_code]
-pub enum StorageError {
-    #[msg("Invalid PDA index. Must be 0-9.")]
-    InvalidPDAIndex,
-    #[msg("Data too large for storage PDA.")]
-    DataTooLarge,
+/// The voice_chat_manager PDA allowed to write delegated storage PDAs through write_chunk
+fn manager_storage_writer() -> Pubkey {
+    Pubkey::find_program_address(&[storage_layout::WRITER_SEED], &VOICE_CHAT_MANAGER_PROGRAM_ID).0
 }
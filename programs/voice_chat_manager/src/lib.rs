@@ -1,10 +1,123 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::metadata::{
+    create_master_edition_v3, create_metadata_accounts_v3,
+    mpl_token_metadata::types::{Creator, DataV2},
+    CreateMasterEditionV3, CreateMetadataAccountsV3, Metadata, MetadataAccount,
+};
+use anchor_spl::token_2022::{spl_token_2022, Token2022};
+use anchor_spl::token_interface::{self, Burn, Mint, MintTo, TokenAccount, TokenInterface};
+pub use voice_chat_shared::{ManagerError as VoiceChatError, *};
+#[cfg(feature = "light")]
+use light_sdk::{
+    account::LightAccount,
+    address::v1::derive_address,
+    cpi::{CpiAccounts, CpiInputs, CpiSigner},
+    derive_light_cpi_signer,
+    instruction::{PackedAddressTreeInfo, ValidityProof},
+    LightDiscriminator, LightHasher,
+};
 
 declare_id!("AGpoLxtMeNW17NZL7nWLFLmmhKPW5nbxfdY8BVaMxDNy");
 
+#[cfg(feature = "light")]
+pub const LIGHT_CPI_SIGNER: CpiSigner = derive_light_cpi_signer!("AGpoLxtMeNW17NZL7nWLFLmmhKPW5nbxfdY8BVaMxDNy");
+
 const MAX_VOICE_DATA_SIZE: usize = 29 * 1024; // Leave 1KB for metadata
-const MAX_PARTICIPANTS: u8 = 10;
+const MAX_PARTICIPANTS: u8 = 10; // Default room capacity
+const MAX_ROOM_CAPACITY: u8 = 16; // Width of the per-slot bitmaps
+const MAX_CO_HOSTS: usize = 4; // Hosts besides voice_room.host
+const ROOM_FLAG_THRESHOLD: u16 = 5; // Upheld reports before a room is flagged for admin review
 const MAX_ROOM_ID_LENGTH: usize = 32;
+const MAX_BATCH_FRAMES: usize = 32;
+const MAX_CLIP_CHUNKS: usize = STORAGE_LANE_COUNT; // One lane per chunk
+const MAX_SPLIT_RECIPIENTS: usize = 8;
+const MAX_PROTOCOL_FEE_BPS: u16 = 1_000; // Protocol fee is capped at 10%
+const MAX_MISSED_CALLS: usize = 16; // Call log keeps the most recent entries
+const MAX_TEXT_MESSAGE_LENGTH: usize = 256;
+const MAX_CHAT_LOG_ENTRIES: usize = 20; // Chat log keeps the most recent messages
+const MAX_POLL_OPTIONS: usize = 8;
+const MAX_POLL_OPTION_LENGTH: usize = 32;
+const MAX_CONTENT_ID_LENGTH: usize = 64; // Fits Arweave tx ids and IPFS CIDv1
+const MAX_METADATA_CREATORS: usize = 5; // Token Metadata's creator limit
+const MAX_TRANSCRIPT_URI_LENGTH: usize = 128;
+const MAX_TICKET_URI_LENGTH: usize = 200; // Bubblegum's metadata uri limit
+const MAX_TRANSCRIPT_PROVIDER_LENGTH: usize = 32;
+const MAX_ICE_CANDIDATES_LENGTH: usize = 512; // A handful of candidate lines per post
+const MAX_ROOM_TAGS: usize = 4;
+const MAX_SCHEDULE_LABEL_LENGTH: usize = 20; // Leaves room for the occurrence number in the room id
+const IDEMPOTENCY_WINDOW: usize = 16; // Recent idempotency keys remembered per sender
+const CAPTURE_TIMESTAMP_TOLERANCE_MS: i64 = 30_000; // Allowed drift between client capture time and chain time
+const MESSAGE_INDEX_PAGE_CAPACITY: usize = 64; // Frames per MessageIndexPage
+const WRITE_JOURNAL_CAPACITY: usize = 32; // Most recent storage writes kept per room
+const SESSION_ARCHIVE_DEPTH: usize = 20; // Merkle depth of a SessionArchive, up to 2^20 messages
+const EXPORT_MANIFEST_PAGE_SIZE: usize = 14; // Manifest entries that fit in 1 KB of return data
+const VOICE_ROOM_VERSION: u8 = 28; // Bump when changing the VoiceRoom layout
+
+// Fixed VoiceRoom offsets for getProgramAccounts memcmp filters
+pub const VOICE_ROOM_HOST_OFFSET: usize = 8;
+pub const VOICE_ROOM_IS_ACTIVE_OFFSET: usize = VOICE_ROOM_HOST_OFFSET + 32;
+pub const VOICE_ROOM_CATEGORY_OFFSET: usize = VOICE_ROOM_IS_ACTIVE_OFFSET + 1;
+pub const VOICE_ROOM_TAGS_OFFSET: usize = VOICE_ROOM_CATEGORY_OFFSET + 2;
+pub const VOICE_ROOM_ROOM_ID_OFFSET: usize = VOICE_ROOM_TAGS_OFFSET + 2 * MAX_ROOM_TAGS;
+
+// Fixed RelayNode offset for getProgramAccounts memcmp filters
+pub const RELAY_NODE_REGION_OFFSET: usize = 8 + 32 + 32;
+
+// Sizes of the string-prefixed layouts before version 5, used to recognise rooms to migrate.
+// Version 4 added category and tags in front of room_id; older rooms are at most V3 sized.
+const LEGACY_VOICE_ROOM_V4_SPACE: usize = 8 + 2 + 2 * MAX_ROOM_TAGS + 4 + MAX_ROOM_ID_LENGTH + 32 + 1 + 1 + 8 + 8 + 1 + 32 + 8 + 1 + 32 + 8 + 1 + 32 + 8 + 8 + 2 + 2 + 1 + 1 + 8 + 8 + 1 + 2 + 8 + 8 + 4 + 8 + 8 + 8 + 4 + 1 + 8 + 8 + 2 + 2 + 1 + 1 + 32 + 1 + 4 + 1 + 32;
+const LEGACY_VOICE_ROOM_V3_SPACE: usize = LEGACY_VOICE_ROOM_V4_SPACE - 2 - 2 * MAX_ROOM_TAGS;
+const _: () = assert!(VoiceRoom::SPACE != LEGACY_VOICE_ROOM_V4_SPACE && VoiceRoom::SPACE > LEGACY_VOICE_ROOM_V3_SPACE);
+const EVENT_GRACE_PERIOD_SECONDS: i64 = 30 * 60; // Host must start a scheduled event within this window
+const STAKE_COOLDOWN_SECONDS: i64 = 24 * 60 * 60; // Stake stays slashable this long after leaving
+const REPUTATION_SESSION_WEIGHT: i64 = 10; // Per completed session
+const REPUTATION_TIP_WEIGHT: i64 = 5; // Per tip received
+const REPUTATION_REPORT_PENALTY: i64 = 50; // Per upheld report
+const REPUTATION_SLASH_PENALTY: i64 = 200; // Per stake slash
+const REPUTATION_MIN_SESSION_SECONDS: i64 = 10 * 60; // Shorter sessions don't count as completed
+const REPUTATION_MIN_TIP_LAMPORTS: u64 = 10_000_000; // Smaller tips (and token tips) don't count
+const STORAGE_CHUNK_SIZE: usize = storage_layout::CHUNK_SIZE;
+const STORAGE_LANE_COUNT: usize = storage_layout::MAX_STORAGE_PDAS as usize;
+
+// Cleanup crank parameters
+const ROOM_IDLE_TIMEOUT_SECONDS: i64 = 24 * 60 * 60;
+const PARTICIPANT_TIMEOUT_SECONDS: i64 = 2 * 60; // Default; rooms can configure their own
+const MESSAGE_RETENTION_SECONDS: i64 = 60 * 60;
+const CRANK_REWARD_LAMPORTS: u64 = 5_000; // Paid to the cranker from each reclaimed account's rent
+const VOICE_MESSAGE_TOMBSTONE_SPACE: usize = 8; // Zeroed stub left at a retired VoiceMessage's address
+const CALL_RING_TIMEOUT_SECONDS: i64 = 60; // Unanswered calls can be expired after this long
+const MIN_RELAY_STAKE_LAMPORTS: u64 = 1_000_000_000;
+const RELAY_HEARTBEAT_TIMEOUT_SECONDS: i64 = 5 * 60; // Relays without a recent heartbeat can't be pinned
+const MAX_PRICE_CONFIDENCE_BPS: u64 = 200; // Oracle prices with a wider confidence interval are rejected
+const RELAY_REPORT_QUORUM: u16 = 3; // Distinct reports before the admin can rule on a relay
+const RELAY_SLASH_BPS: u16 = 5_000;
+const MAX_SESSION_KEY_SECONDS: i64 = 24 * 60 * 60;
+const STAGE_INVITE_SECONDS: i64 = 10 * 60; // Unaccepted stage invitations lapse after this long
+
+// Session key scopes
+const SESSION_SCOPE_SEND_VOICE: u8 = 1 << 0;
+
+// Leaderboards
+const LEADERBOARD_SIZE: usize = 10;
+const LEADERBOARD_METRIC_TALK_TIME: u8 = 0; // Folds AttendanceRecords
+const LEADERBOARD_METRIC_TIPS: u8 = 1; // Folds SOL TipTallies
+
+// Raise-hand queue ordering
+const HAND_WEIGHT_NONE: u8 = 0; // First come, first served
+const HAND_WEIGHT_STAKE: u8 = 1; // Sender stake at hand-raise time
+const HAND_WEIGHT_TOKEN: u8 = 2; // Gate mint balance at hand-raise time
+
+// Notification preferences
+const NOTIFY_CALLS: u8 = 1 << 0; // Direct calls
+const NOTIFY_MENTIONS: u8 = 1 << 1;
+const NOTIFY_FOLLOWED_ROOMS: u8 = 1 << 2; // Rooms opened by subscribed hosts
+const NOTIFY_ALL: u8 = NOTIFY_CALLS | NOTIFY_MENTIONS | NOTIFY_FOLLOWED_ROOMS;
+
+// StoragePDA offsets, published by storage_manager through voice_chat_shared::storage_layout
+const STORAGE_DATA_LENGTH_OFFSET: usize = storage_layout::DATA_LENGTH_OFFSET;
+const STORAGE_DATA_OFFSET: usize = storage_layout::DATA_OFFSET;
 
 #[program]
 pub mod voice_chat_manager {
@@ -14,100 +127,1009 @@ pub mod voice_chat_manager {
     pub fn initialize_voice_room(
         ctx: Context<InitializeVoiceRoom>,
         room_id: String,
+        sns_domain: Option<Pubkey>,
     ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
         require!(room_id.len() <= MAX_ROOM_ID_LENGTH, VoiceChatError::RoomIdTooLong);
         
         let voice_room = &mut ctx.accounts.voice_room;
-        voice_room.room_id = room_id.clone();
-        voice_room.host = ctx.accounts.host.key();
-        voice_room.participant_count = 1; // Host is first participant
-        voice_room.is_active = true;
-        voice_room.created_at = Clock::get()?.unix_timestamp;
-        voice_room.last_activity = Clock::get()?.unix_timestamp;
+        voice_room.init(&room_id, ctx.accounts.host.key(), Clock::get()?.unix_timestamp, ctx.bumps.voice_room);
+        
+        // Binding a .sol domain requires the host to own its name record; the RoomDomain PDA
+        // lets clients resolve the domain to the room and keeps each domain on one room
+        if let Some(sns_domain) = sns_domain {
+            let (Some(domain_record), Some(room_domain)) = (
+                ctx.accounts.domain_record.as_ref(),
+                ctx.accounts.room_domain.as_mut(),
+            ) else {
+                return err!(VoiceChatError::DomainAccountsMissing);
+            };
+            require_keys_eq!(domain_record.key(), sns_domain, VoiceChatError::InvalidDomainRecord);
+            sns::verify_sol_domain_owner(&domain_record.to_account_info(), &voice_room.host)?;
+            
+            room_domain.domain = sns_domain;
+            room_domain.room = voice_room.key();
+            room_domain.bump = ctx.bumps.room_domain.ok_or(VoiceChatError::DomainAccountsMissing)?;
+            voice_room.sns_domain = Some(sns_domain);
+        } else {
+            require!(ctx.accounts.room_domain.is_none(), VoiceChatError::InvalidDomainRecord);
+        }
+        voice_room.add_participant(); // Host is first participant
+        let host_slot = voice_room.claim_slot()?;
+        voice_room.assign_channel(host_slot);
+        
+        let host_participant = &mut ctx.accounts.host_participant;
+        host_participant.room = voice_room.key();
+        host_participant.user = voice_room.host;
+        host_participant.joined_at = voice_room.created_at;
+        host_participant.last_seen = voice_room.created_at;
+        host_participant.muted = false;
+        host_participant.slot = host_slot;
+        host_participant.supported_codecs = 0;
+        host_participant.sequence_epoch = 0;
+        host_participant.highest_contiguous_sequence = 0;
+        host_participant.bump = ctx.bumps.host_participant;
+        host_participant.has_stream = false;
+        
+        emit!(RoomOpened {
+            room: voice_room.key(),
+            host: voice_room.host,
+            timestamp: voice_room.created_at,
+        });
         
-        msg!("Voice room '{}' created by {}", room_id, voice_room.host);
+        msg!("Voice room '{}' created by {} (domain {:?})", room_id, voice_room.host, voice_room.sns_domain);
         Ok(())
     }
 
     /// Join voice chat room
-    pub fn join_voice_room(ctx: Context<JoinVoiceRoom>) -> Result<()> {
+    pub fn join_voice_room<'info>(
+        ctx: Context<'_, '_, '_, 'info, JoinVoiceRoom<'info>>,
+        supported_codecs: u16,
+        compressed_ticket: Option<CompressedTicketProof>,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
         let voice_room = &mut ctx.accounts.voice_room;
-        require!(voice_room.is_active, VoiceChatError::RoomNotActive);
-        require!(voice_room.participant_count < MAX_PARTICIPANTS, VoiceChatError::RoomFull);
+        // Breakouts skip the fees and bans checked on the parent, so only the host joins them
+        // directly; everyone else is moved in from the parent with move_participant
+        require!(
+            voice_room.parent_room.is_none() || voice_room.is_host(&ctx.accounts.participant.key()),
+            VoiceChatError::BreakoutJoinNotAllowed
+        );
+        // The host can rejoin an inactive room, which reopens it
+        if !voice_room.is_active {
+            require!(voice_room.is_host(&ctx.accounts.participant.key()), VoiceChatError::RoomNotActive);
+            voice_room.is_active = true;
+            emit!(RoomOpened {
+                room: voice_room.key(),
+                host: voice_room.host,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        }
+        require!(voice_room.participant_count < voice_room.max_participants(), VoiceChatError::RoomFull);
+        require_not_banned(&ctx.accounts.room_ban, Clock::get()?.unix_timestamp)?;
+        // Joiners start without recording consent, so nobody can walk into a running recording
+        require!(!voice_room.is_recording, VoiceChatError::AlreadyRecording);
+        
+        // Joiners must be able to decode at least one of the room's codecs
+        if voice_room.accepted_codecs != 0 {
+            require!(supported_codecs & voice_room.accepted_codecs != 0, VoiceChatError::NoCommonCodec);
+        }
+        
+        // Holders of the room's membership token skip the gates, tickets and entry fees
+        let is_member = match (voice_room.membership_mint, ctx.accounts.membership_token_account.as_ref()) {
+            (Some(membership_mint), Some(membership_token_account)) => {
+                require_keys_eq!(membership_token_account.mint, membership_mint, VoiceChatError::InvalidMembershipAccount);
+                require_keys_eq!(membership_token_account.owner, ctx.accounts.participant.key(), VoiceChatError::InvalidMembershipAccount);
+                membership_token_account.amount >= 1
+            }
+            _ => false,
+        };
+        
+        // Token-gated rooms require the joiner to hold enough of the gate mint
+        if let Some(gate_mint) = voice_room.gate_mint.filter(|_| !is_member) {
+            let gate_token_account = ctx.accounts.gate_token_account.as_ref()
+                .ok_or(VoiceChatError::GateTokenAccountMissing)?;
+            require_keys_eq!(gate_token_account.mint, gate_mint, VoiceChatError::InvalidGateTokenAccount);
+            require_keys_eq!(gate_token_account.owner, ctx.accounts.participant.key(), VoiceChatError::InvalidGateTokenAccount);
+            require!(gate_token_account.amount >= voice_room.gate_min_balance, VoiceChatError::InsufficientGateBalance);
+        }
+        
+        // Collection-gated rooms require the joiner to hold an NFT from the verified collection
+        if let Some(gate_collection) = voice_room.gate_collection.filter(|_| !is_member) {
+            let nft_token_account = ctx.accounts.nft_token_account.as_ref()
+                .ok_or(VoiceChatError::NftAccountsMissing)?;
+            let nft_metadata = ctx.accounts.nft_metadata.as_ref()
+                .ok_or(VoiceChatError::NftAccountsMissing)?;
+            require_keys_eq!(nft_token_account.owner, ctx.accounts.participant.key(), VoiceChatError::InvalidNftAccount);
+            require!(nft_token_account.amount >= 1, VoiceChatError::InvalidNftAccount);
+            // Metadata is owned by mpl-token-metadata, so its mint field ties it to the held token
+            require_keys_eq!(nft_metadata.mint, nft_token_account.mint, VoiceChatError::InvalidNftAccount);
+            
+            let collection = nft_metadata.collection.as_ref()
+                .ok_or(VoiceChatError::NftNotInCollection)?;
+            require!(collection.verified, VoiceChatError::NftNotInCollection);
+            require_keys_eq!(collection.key, gate_collection, VoiceChatError::NftNotInCollection);
+        }
+        
+        // Cross-chain gated rooms admit holders with an unexpired pass from attest_holdings
+        if voice_room.cross_chain_gated && !is_member {
+            let cross_chain_pass = ctx.accounts.cross_chain_pass.as_ref()
+                .ok_or(VoiceChatError::CrossChainPassRequired)?;
+            require!(
+                cross_chain_pass.expires_at > Clock::get()?.unix_timestamp,
+                VoiceChatError::CrossChainPassRequired
+            );
+        }
+        
+        // Scheduled events admit ticket holders once the host has started the event; the fee was paid at purchase.
+        // Compressed tickets are proven against the event's Bubblegum tree, with the proof path in remaining_accounts.
+        let is_scheduled = voice_room.scheduled_start > 0;
+        if is_scheduled {
+            require!(voice_room.event_started, VoiceChatError::EventNotStarted);
+            if !is_member {
+                if let Some(proof) = compressed_ticket {
+                    let (Some(ticket_tree), Some(ticket_merkle_tree), Some(compression_program)) = (
+                        ctx.accounts.ticket_tree.as_mut(),
+                        ctx.accounts.ticket_merkle_tree.as_ref(),
+                        ctx.accounts.compression_program.as_ref(),
+                    ) else {
+                        return err!(VoiceChatError::CompressedTicketAccountsMissing);
+                    };
+                    require_keys_eq!(ticket_merkle_tree.key(), ticket_tree.merkle_tree, VoiceChatError::InvalidCompressedTicket);
+                    let leaf = bubblegum::leaf_hash(&ticket_tree.merkle_tree, &ctx.accounts.participant.key(), &proof);
+                    account_compression::verify_leaf(
+                        compression_program,
+                        ticket_merkle_tree,
+                        proof.root,
+                        leaf,
+                        proof.nonce as u32,
+                        ctx.remaining_accounts,
+                    )?;
+                    ticket_tree.redeem(proof.nonce)?;
+                } else {
+                    let ticket = ctx.accounts.ticket.as_mut()
+                        .ok_or(VoiceChatError::TicketRequired)?;
+                    require!(!ticket.consumed, VoiceChatError::TicketAlreadyUsed);
+                    ticket.consumed = true;
+                }
+            }
+        }
+        
+        // Paid rooms collect the entry fee into the room escrow
+        if !is_scheduled && !is_member && (voice_room.entry_fee_lamports > 0 || voice_room.entry_fee_usd_cents > 0) {
+            let (entry_fee_lamports, min_protocol_fee) = entry_fee_lamports(
+                voice_room,
+                ctx.accounts.price_feed.as_ref(),
+                ctx.accounts.sol_usd_price.as_ref(),
+            )?;
+            let escrow = ctx.accounts.escrow.as_mut()
+                .ok_or(VoiceChatError::EscrowAccountsMissing)?;
+            let protocol_fee = collect_protocol_fee_lamports(
+                &ctx.accounts.protocol_config,
+                ctx.accounts.protocol_treasury.as_mut(),
+                &ctx.accounts.participant.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                entry_fee_lamports,
+                min_protocol_fee,
+            )?;
+            let room_share = entry_fee_lamports - protocol_fee;
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.participant.to_account_info(),
+                        to: escrow.to_account_info(),
+                    },
+                ),
+                room_share,
+            )?;
+            escrow.lamports_collected += room_share;
+        }
+        
+        if let Some(entry_fee_mint) = voice_room.entry_fee_mint.filter(|_| !is_scheduled && !is_member) {
+            let (Some(escrow), Some(fee_payer_token_account), Some(escrow_vault), Some(fee_mint), Some(token_program)) = (
+                ctx.accounts.escrow.as_mut(),
+                ctx.accounts.fee_payer_token_account.as_ref(),
+                ctx.accounts.escrow_vault.as_mut(),
+                ctx.accounts.fee_mint.as_ref(),
+                ctx.accounts.token_program.as_ref(),
+            ) else {
+                return err!(VoiceChatError::EscrowAccountsMissing);
+            };
+            require_keys_eq!(fee_mint.key(), entry_fee_mint, VoiceChatError::InvalidFeeMint);
+            
+            // remaining_accounts carry any Token-2022 transfer hook accounts
+            let protocol_fee = collect_protocol_fee_tokens(
+                &ctx.accounts.protocol_config,
+                ctx.accounts.protocol_treasury.as_ref(),
+                ctx.accounts.protocol_treasury_vault.as_mut(),
+                &token_program.to_account_info(),
+                fee_payer_token_account,
+                fee_mint,
+                &ctx.accounts.participant.to_account_info(),
+                ctx.remaining_accounts,
+                voice_room.entry_fee_amount,
+            )?;
+            let received = transfer_tokens_checked(
+                &token_program.to_account_info(),
+                fee_payer_token_account,
+                fee_mint,
+                escrow_vault,
+                &ctx.accounts.participant.to_account_info(),
+                ctx.remaining_accounts,
+                voice_room.entry_fee_amount - protocol_fee,
+                &[],
+            )?;
+            escrow.tokens_collected += received;
+        }
         
-        voice_room.participant_count += 1;
+        let slot = voice_room.claim_slot()?;
+        voice_room.add_participant();
         voice_room.last_activity = Clock::get()?.unix_timestamp;
         
+        let participant_account = &mut ctx.accounts.participant_account;
+        participant_account.room = voice_room.key();
+        participant_account.user = ctx.accounts.participant.key();
+        participant_account.joined_at = voice_room.last_activity;
+        participant_account.last_seen = voice_room.last_activity;
+        // Broadcast rooms mute joiners until the host brings them on stage
+        participant_account.muted = voice_room.mute_on_join && !voice_room.is_host(&participant_account.user);
+        participant_account.slot = slot;
+        if !participant_account.muted {
+            voice_room.assign_channel(slot);
+        }
+        participant_account.supported_codecs = supported_codecs;
+        participant_account.sequence_epoch = 0;
+        participant_account.highest_contiguous_sequence = 0;
+        participant_account.bump = ctx.bumps.participant_account;
+        participant_account.has_stream = false;
+        
+        if let Some(participant_stats) = ctx.accounts.participant_stats.as_mut() {
+            participant_stats.rooms_joined += 1;
+        }
+        
         msg!("User {} joined room '{}'. Participants: {}", 
              ctx.accounts.participant.key(), 
-             voice_room.room_id, 
+             voice_room.room_id(), 
              voice_room.participant_count);
         Ok(())
     }
 
-    /// Send voice data to storage PDA
+    /// Send voice data to storage PDA. A lane whose last frame no listener has acked yet is
+    /// only overwritten with `force`.
     pub fn send_voice_data(
         ctx: Context<SendVoiceData>,
         voice_data: Vec<u8>,
         target_pda_index: u8,
         sequence_number: u32,
+        idempotency_key: u64,
+        capture_timestamp_ms: i64,
+        duration_ms: u16,
+        force: bool,
     ) -> Result<()> {
-        require!(voice_data.len() <= MAX_VOICE_DATA_SIZE, VoiceChatError::VoiceDataTooLarge);
-        require!(target_pda_index < 10, VoiceChatError::InvalidStoragePDA);
-        
-        // Get storage PDA account info (from storage_manager contract)
-        let storage_account_info = &ctx.accounts.storage_pda;
-        let mut storage_data = storage_account_info.try_borrow_mut_data()?;
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        check_capture_timestamp(capture_timestamp_ms)?;
+        require!(voice_data.len() <= ctx.accounts.voice_room.max_frame_bytes(), VoiceChatError::VoiceDataTooLarge);
+        check_codec(&ctx.accounts.voice_room, &voice_data)?;
+        check_frame_quality(&ctx.accounts.voice_room, voice_data.len(), duration_ms)?;
+        authorize_sender(
+            &ctx.accounts.sender,
+            &ctx.accounts.authority,
+            ctx.accounts.session_key.as_ref(),
+            SESSION_SCOPE_SEND_VOICE,
+        )?;
         
-        // Calculate where to write in the 30KB storage
-        // StoragePDA struct: discriminator(8) + index(1) + authority(32) + created_at(8) + data_length(4) + is_active(1) + data(30720)
-        let metadata_size = 8 + 1 + 32 + 8 + 4 + 1;
-        let data_start = metadata_size;
+        // A retried submit fails as a whole instead of creating a second message record
+        let recent_sends = &mut ctx.accounts.recent_sends;
+        recent_sends.room = ctx.accounts.voice_room.key();
+        recent_sends.sender = ctx.accounts.sender.key();
+        recent_sends.bump = ctx.bumps.recent_sends;
+        recent_sends.track_epoch(ctx.accounts.sender_participant.sequence_epoch)?;
+        recent_sends.record(idempotency_key)?;
+        check_sender(
+            &ctx.accounts.voice_room,
+            &ctx.accounts.sender_participant,
+            ctx.accounts.sender_stake.as_ref(),
+            ctx.accounts.sender_reputation.as_ref(),
+        )?;
+        require!((target_pda_index as usize) < STORAGE_LANE_COUNT, VoiceChatError::InvalidStoragePDA);
+        check_room_lane(
+            &ctx.accounts.voice_room,
+            &ctx.accounts.storage_pda,
+            target_pda_index,
+            &ctx.accounts.protocol_config,
+        )?;
+        check_stage_lane(
+            &ctx.accounts.voice_room,
+            &ctx.accounts.sender_participant,
+            &mut ctx.accounts.recent_sends,
+            &[target_pda_index],
+        )?;
+        check_lanes_consumed(&ctx.accounts.voice_room, &[target_pda_index], force)?;
+        check_lane_allocations(&ctx.accounts.voice_room, &ctx.accounts.sender_participant, &[target_pda_index])?;
         
-        // Write voice data to storage PDA
+        // Write voice data to storage PDA (from storage_manager contract)
         let copy_len = std::cmp::min(voice_data.len(), MAX_VOICE_DATA_SIZE);
-        storage_data[data_start..data_start + copy_len].copy_from_slice(&voice_data[..copy_len]);
-        
-        // Update data_length field in storage PDA
-        let data_length_offset = 8 + 1 + 32 + 8; // offset to data_length field
-        let new_length = copy_len as u32;
-        storage_data[data_length_offset..data_length_offset + 4].copy_from_slice(&new_length.to_le_bytes());
+        meter_storage_write(
+            &ctx.accounts.voice_room,
+            ctx.accounts.bandwidth_allowance.as_mut(),
+            copy_len,
+        )?;
+        let storage_writer = StorageWriter {
+            program: &ctx.accounts.storage_program,
+            writer: &ctx.accounts.storage_writer,
+            writer_bump: ctx.bumps.storage_writer,
+            protocol_config: &ctx.accounts.protocol_config,
+        };
+        write_storage_chunk(&ctx.accounts.storage_pda, &storage_writer, &voice_data[..copy_len])?;
+        journal_storage_write(
+            &ctx.accounts.voice_room,
+            ctx.accounts.write_journal.as_deref_mut(),
+            &ctx.accounts.storage_pda,
+            &voice_data[..copy_len],
+        )?;
         
         // Create voice message record
         let voice_message = &mut ctx.accounts.voice_message;
         voice_message.sender = ctx.accounts.sender.key();
-        voice_message.room_id = ctx.accounts.voice_room.room_id.clone();
+        voice_message.room_id = ctx.accounts.voice_room.room_id().to_string();
         voice_message.storage_pda_index = target_pda_index;
         voice_message.sequence_number = sequence_number;
         voice_message.data_length = voice_data.len() as u32;
         voice_message.timestamp = Clock::get()?.unix_timestamp;
+        voice_message.capture_timestamp_ms = capture_timestamp_ms;
+        voice_message.sequence_epoch = ctx.accounts.sender_participant.sequence_epoch;
+        voice_message.storage_pda = ctx.accounts.storage_pda.key();
+        voice_message.content_hash = anchor_lang::solana_program::keccak::hash(&voice_data[..copy_len]).to_bytes();
+        ctx.accounts.sender_participant.extend_contiguous(sequence_number, sequence_number);
         
         // Update room activity
         let voice_room = &mut ctx.accounts.voice_room;
         voice_room.last_activity = Clock::get()?.unix_timestamp;
+        voice_room.record_lane_write(target_pda_index, ctx.accounts.sender_participant.slot, sequence_number);
+        voice_room.record_traffic(1, voice_data.len());
+        voice_room.message_count += 1;
+        ctx.accounts.message_index_page.append(voice_room, voice_message, ctx.bumps.message_index_page);
+        if let Some(sender_stats) = ctx.accounts.sender_stats.as_mut() {
+            sender_stats.record_message(voice_data.len());
+        }
         
         msg!("Voice data sent: {} bytes to PDA {}, sequence {}", 
              voice_data.len(), target_pda_index, sequence_number);
         Ok(())
     }
 
-    /// Retrieve voice data from storage PDA
+    /// Send several small frames (e.g. 20ms Opus packets) in one instruction.
+    /// Frames are written back-to-back into the storage PDA and recorded as a single batch message,
+    /// which takes the room's next message number like a VoiceMessage does.
+    pub fn send_voice_batch(
+        ctx: Context<SendVoiceBatch>,
+        frames: Vec<FrameHeader>,
+        payload: Vec<u8>,
+        target_pda_index: u8,
+        sequence_number: u32,
+        force: bool,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(!frames.is_empty(), VoiceChatError::EmptyBatch);
+        require!(frames.len() <= MAX_BATCH_FRAMES, VoiceChatError::TooManyFrames);
+        require!(payload.len() <= MAX_VOICE_DATA_SIZE, VoiceChatError::VoiceDataTooLarge);
+        let max_frame_bytes = ctx.accounts.voice_room.max_frame_bytes();
+        require!(
+            frames.iter().all(|frame| frame.length as usize <= max_frame_bytes),
+            VoiceChatError::VoiceDataTooLarge
+        );
+        check_sender(
+            &ctx.accounts.voice_room,
+            &ctx.accounts.sender_participant,
+            ctx.accounts.sender_stake.as_ref(),
+            ctx.accounts.sender_reputation.as_ref(),
+        )?;
+        require!((target_pda_index as usize) < STORAGE_LANE_COUNT, VoiceChatError::InvalidStoragePDA);
+        check_room_lane(
+            &ctx.accounts.voice_room,
+            &ctx.accounts.storage_pda,
+            target_pda_index,
+            &ctx.accounts.protocol_config,
+        )?;
+        // Frames carry consecutive sequence numbers starting at `sequence_number`
+        require!(
+            frames.iter().enumerate().all(|(i, frame)| {
+                sequence_number.checked_add(i as u32) == Some(frame.sequence_number)
+            }),
+            VoiceChatError::BatchSequenceMismatch
+        );
+        let recent_sends = &mut ctx.accounts.recent_sends;
+        recent_sends.room = ctx.accounts.voice_room.key();
+        recent_sends.sender = ctx.accounts.sender.key();
+        recent_sends.bump = ctx.bumps.recent_sends;
+        recent_sends.track_epoch(ctx.accounts.sender_participant.sequence_epoch)?;
+        check_stage_lane(
+            &ctx.accounts.voice_room,
+            &ctx.accounts.sender_participant,
+            &mut ctx.accounts.recent_sends,
+            &[target_pda_index],
+        )?;
+        check_lanes_consumed(&ctx.accounts.voice_room, &[target_pda_index], force)?;
+        check_lane_allocations(&ctx.accounts.voice_room, &ctx.accounts.sender_participant, &[target_pda_index])?;
+        
+        // Every payload byte must belong to exactly one frame
+        let total_frame_bytes: usize = frames.iter().map(|frame| frame.length as usize).sum();
+        require!(total_frame_bytes == payload.len(), VoiceChatError::FrameLengthMismatch);
+        let mut frame_start = 0;
+        let mut frame_hashes = Vec::with_capacity(frames.len());
+        for frame in &frames {
+            let frame_end = frame_start + frame.length as usize;
+            check_codec(&ctx.accounts.voice_room, &payload[frame_start..frame_end])?;
+            check_frame_quality(&ctx.accounts.voice_room, frame.length as usize, frame.duration_ms)?;
+            frame_hashes.push(anchor_lang::solana_program::keccak::hash(&payload[frame_start..frame_end]).to_bytes());
+            frame_start = frame_end;
+        }
+        
+        // Frames are laid out consecutively, so offsets follow from the lengths
+        meter_storage_write(
+            &ctx.accounts.voice_room,
+            ctx.accounts.bandwidth_allowance.as_mut(),
+            payload.len(),
+        )?;
+        let storage_writer = StorageWriter {
+            program: &ctx.accounts.storage_program,
+            writer: &ctx.accounts.storage_writer,
+            writer_bump: ctx.bumps.storage_writer,
+            protocol_config: &ctx.accounts.protocol_config,
+        };
+        write_storage_chunk(&ctx.accounts.storage_pda, &storage_writer, &payload)?;
+        journal_storage_write(
+            &ctx.accounts.voice_room,
+            ctx.accounts.write_journal.as_deref_mut(),
+            &ctx.accounts.storage_pda,
+            &payload,
+        )?;
+        
+        let last_sequence = frames[frames.len() - 1].sequence_number;
+        ctx.accounts.sender_participant.extend_contiguous(sequence_number, last_sequence);
+        
+        let batch_message = &mut ctx.accounts.batch_message;
+        batch_message.sender = ctx.accounts.sender.key();
+        batch_message.room_id = ctx.accounts.voice_room.room_id().to_string();
+        batch_message.storage_pda_index = target_pda_index;
+        batch_message.sequence_number = sequence_number;
+        batch_message.data_length = payload.len() as u32;
+        batch_message.frames = frames;
+        batch_message.timestamp = Clock::get()?.unix_timestamp;
+        batch_message.sequence_epoch = ctx.accounts.sender_participant.sequence_epoch;
+        batch_message.storage_pda = ctx.accounts.storage_pda.key();
+        batch_message.content_hash = anchor_lang::solana_program::keccak::hash(&payload).to_bytes();
+        batch_message.frame_hashes = frame_hashes;
+        
+        let voice_room = &mut ctx.accounts.voice_room;
+        voice_room.last_activity = Clock::get()?.unix_timestamp;
+        // The lane is read once listeners have acked the batch's last frame
+        voice_room.record_lane_write(target_pda_index, ctx.accounts.sender_participant.slot, last_sequence);
+        voice_room.record_traffic(batch_message.frames.len(), payload.len());
+        voice_room.message_count += 1;
+        ctx.accounts.message_index_page.append_batch(voice_room, batch_message, ctx.bumps.message_index_page);
+        if let Some(sender_stats) = ctx.accounts.sender_stats.as_mut() {
+            sender_stats.record_message(payload.len());
+        }
+        
+        msg!("Voice batch sent: {} frames, {} bytes to PDA {}, first sequence {}", 
+             batch_message.frames.len(), payload.len(), target_pda_index, sequence_number);
+        Ok(())
+    }
+
+    /// Write a clip larger than a single chunk across several storage PDAs.
+    /// Storage PDAs are passed via remaining_accounts in chunk order, each the host's lane for
+    /// the matching `target_pdas` index; every target is validated before any byte is written
+    /// so the clip is never left torn. The clip takes the room's next message number.
+    pub fn send_voice_clip<'info>(
+        ctx: Context<'_, '_, '_, 'info, SendVoiceClip<'info>>,
+        payload: Vec<u8>,
+        target_pdas: Vec<u8>,
+        sequence_number: u32,
+        duration_ms: u16,
+        force: bool,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(!payload.is_empty(), VoiceChatError::EmptyClip);
+        check_codec(&ctx.accounts.voice_room, &payload)?;
+        check_bitrate(&ctx.accounts.voice_room, payload.len(), duration_ms)?;
+        check_sender(
+            &ctx.accounts.voice_room,
+            &ctx.accounts.sender_participant,
+            ctx.accounts.sender_stake.as_ref(),
+            ctx.accounts.sender_reputation.as_ref(),
+        )?;
+        require!(target_pdas.len() <= MAX_CLIP_CHUNKS, VoiceChatError::TooManyTargetPDAs);
+        require!(target_pdas.iter().all(|index| (*index as usize) < STORAGE_LANE_COUNT), VoiceChatError::InvalidStoragePDA);
+        require!(ctx.remaining_accounts.len() == target_pdas.len(), VoiceChatError::ClipChunkMismatch);
+        for (storage_account_info, index) in ctx.remaining_accounts.iter().zip(&target_pdas) {
+            check_room_lane(&ctx.accounts.voice_room, storage_account_info, *index, &ctx.accounts.protocol_config)?;
+        }
+        let recent_sends = &mut ctx.accounts.recent_sends;
+        recent_sends.room = ctx.accounts.voice_room.key();
+        recent_sends.sender = ctx.accounts.sender.key();
+        recent_sends.bump = ctx.bumps.recent_sends;
+        recent_sends.track_epoch(ctx.accounts.sender_participant.sequence_epoch)?;
+        check_stage_lane(
+            &ctx.accounts.voice_room,
+            &ctx.accounts.sender_participant,
+            &mut ctx.accounts.recent_sends,
+            &target_pdas,
+        )?;
+        check_lanes_consumed(&ctx.accounts.voice_room, &target_pdas, force)?;
+        check_lane_allocations(&ctx.accounts.voice_room, &ctx.accounts.sender_participant, &target_pdas)?;
+        
+        let chunk_count = payload.len().div_ceil(STORAGE_CHUNK_SIZE);
+        require!(target_pdas.len() == chunk_count, VoiceChatError::ClipChunkMismatch);
+        
+        // Validate every target up front - nothing is written unless all chunks fit
+        for (i, storage_account_info) in ctx.remaining_accounts.iter().enumerate() {
+            require!(storage_account_info.is_writable, VoiceChatError::InvalidStoragePDA);
+            require!(
+                ctx.remaining_accounts[..i].iter().all(|other| other.key != storage_account_info.key),
+                VoiceChatError::DuplicateStoragePDA
+            );
+            
+            // Chunks written through a storage backend are validated by the backend itself
+            if ctx.accounts.storage_program.is_none() {
+                let chunk_len = std::cmp::min(STORAGE_CHUNK_SIZE, payload.len() - i * STORAGE_CHUNK_SIZE);
+                require!(
+                    storage_account_info.data_len() >= STORAGE_DATA_OFFSET + chunk_len,
+                    VoiceChatError::InvalidStoragePDA
+                );
+                check_storage_layout(storage_account_info)?;
+            }
+        }
+        
+        meter_storage_write(
+            &ctx.accounts.voice_room,
+            ctx.accounts.bandwidth_allowance.as_mut(),
+            payload.len(),
+        )?;
+        let storage_writer = StorageWriter {
+            program: &ctx.accounts.storage_program,
+            writer: &ctx.accounts.storage_writer,
+            writer_bump: ctx.bumps.storage_writer,
+            protocol_config: &ctx.accounts.protocol_config,
+        };
+        for (storage_account_info, chunk) in ctx.remaining_accounts.iter().zip(payload.chunks(STORAGE_CHUNK_SIZE)) {
+            write_storage_chunk(storage_account_info, &storage_writer, chunk)?;
+            journal_storage_write(
+                &ctx.accounts.voice_room,
+                ctx.accounts.write_journal.as_deref_mut(),
+                storage_account_info,
+                chunk,
+            )?;
+        }
+        
+        let clip_message = &mut ctx.accounts.clip_message;
+        clip_message.sender = ctx.accounts.sender.key();
+        clip_message.room_id = ctx.accounts.voice_room.room_id().to_string();
+        clip_message.target_pdas = target_pdas;
+        clip_message.sequence_number = sequence_number;
+        clip_message.data_length = payload.len() as u32;
+        clip_message.timestamp = Clock::get()?.unix_timestamp;
+        clip_message.sequence_epoch = ctx.accounts.sender_participant.sequence_epoch;
+        clip_message.content_hash = anchor_lang::solana_program::keccak::hash(&payload).to_bytes();
+        clip_message.storage_pdas = ctx.remaining_accounts.iter().map(|info| info.key()).collect();
+        clip_message.chunk_hashes = payload.chunks(STORAGE_CHUNK_SIZE)
+            .map(|chunk| anchor_lang::solana_program::keccak::hash(chunk).to_bytes())
+            .collect();
+        
+        let voice_room = &mut ctx.accounts.voice_room;
+        voice_room.last_activity = Clock::get()?.unix_timestamp;
+        for lane in &clip_message.target_pdas {
+            voice_room.record_lane_write(*lane, ctx.accounts.sender_participant.slot, sequence_number);
+        }
+        voice_room.record_traffic(1, payload.len());
+        voice_room.message_count += 1;
+        ctx.accounts.message_index_page.append_clip(voice_room, clip_message, ctx.bumps.message_index_page);
+        
+        msg!("Voice clip sent: {} bytes across {} PDAs, sequence {}", 
+             payload.len(), chunk_count, sequence_number);
+        Ok(())
+    }
+
+    /// Create a compressed message log for a room backed by a concurrent merkle tree.
+    /// The tree account must already be allocated and owned by spl-account-compression;
+    /// the log PDA becomes its authority.
+    pub fn initialize_message_log(
+        ctx: Context<InitializeMessageLog>,
+        max_depth: u32,
+        max_buffer_size: u32,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room_key = ctx.accounts.voice_room.key();
+        let bump = ctx.bumps.message_log;
+        let signer_seeds: &[&[u8]] = &[b"message_log", voice_room_key.as_ref(), &[bump]];
+        
+        account_compression::init_empty_merkle_tree(
+            &ctx.accounts.compression_program,
+            &ctx.accounts.merkle_tree,
+            &ctx.accounts.message_log.to_account_info(),
+            &ctx.accounts.noop_program,
+            max_depth,
+            max_buffer_size,
+            &[signer_seeds],
+        )?;
+        
+        let message_log = &mut ctx.accounts.message_log;
+        message_log.room = voice_room_key;
+        message_log.merkle_tree = ctx.accounts.merkle_tree.key();
+        message_log.max_depth = max_depth;
+        message_log.max_buffer_size = max_buffer_size;
+        message_log.leaf_count = 0;
+        message_log.bump = bump;
+        
+        msg!("Compressed message log created for room '{}' (depth {}, buffer {})", 
+             ctx.accounts.voice_room.room_id(), max_depth, max_buffer_size);
+        Ok(())
+    }
+
+    /// Send voice data and record the message as a merkle tree leaf instead of a rent-exempt PDA.
+    /// The full leaf is logged through the noop program so indexers can rebuild the message history.
+    pub fn send_voice_data_compressed(
+        ctx: Context<SendVoiceDataCompressed>,
+        voice_data: Vec<u8>,
+        target_pda_index: u8,
+        sequence_number: u32,
+        duration_ms: u16,
+        force: bool,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(voice_data.len() <= ctx.accounts.voice_room.max_frame_bytes(), VoiceChatError::VoiceDataTooLarge);
+        check_codec(&ctx.accounts.voice_room, &voice_data)?;
+        check_frame_quality(&ctx.accounts.voice_room, voice_data.len(), duration_ms)?;
+        check_sender(
+            &ctx.accounts.voice_room,
+            &ctx.accounts.sender_participant,
+            ctx.accounts.sender_stake.as_ref(),
+            ctx.accounts.sender_reputation.as_ref(),
+        )?;
+        require!((target_pda_index as usize) < STORAGE_LANE_COUNT, VoiceChatError::InvalidStoragePDA);
+        check_room_lane(
+            &ctx.accounts.voice_room,
+            &ctx.accounts.storage_pda,
+            target_pda_index,
+            &ctx.accounts.protocol_config,
+        )?;
+        let recent_sends = &mut ctx.accounts.recent_sends;
+        recent_sends.room = ctx.accounts.voice_room.key();
+        recent_sends.sender = ctx.accounts.sender.key();
+        recent_sends.bump = ctx.bumps.recent_sends;
+        recent_sends.track_epoch(ctx.accounts.sender_participant.sequence_epoch)?;
+        check_stage_lane(
+            &ctx.accounts.voice_room,
+            &ctx.accounts.sender_participant,
+            &mut ctx.accounts.recent_sends,
+            &[target_pda_index],
+        )?;
+        check_lanes_consumed(&ctx.accounts.voice_room, &[target_pda_index], force)?;
+        check_lane_allocations(&ctx.accounts.voice_room, &ctx.accounts.sender_participant, &[target_pda_index])?;
+        
+        let message_log = &ctx.accounts.message_log;
+        require!(message_log.leaf_count < 1u64 << message_log.max_depth, VoiceChatError::MessageLogFull);
+        
+        meter_storage_write(
+            &ctx.accounts.voice_room,
+            ctx.accounts.bandwidth_allowance.as_mut(),
+            voice_data.len(),
+        )?;
+        let storage_writer = StorageWriter {
+            program: &ctx.accounts.storage_program,
+            writer: &ctx.accounts.storage_writer,
+            writer_bump: ctx.bumps.storage_writer,
+            protocol_config: &ctx.accounts.protocol_config,
+        };
+        write_storage_chunk(&ctx.accounts.storage_pda, &storage_writer, &voice_data)?;
+        journal_storage_write(
+            &ctx.accounts.voice_room,
+            ctx.accounts.write_journal.as_deref_mut(),
+            &ctx.accounts.storage_pda,
+            &voice_data,
+        )?;
+        
+        let leaf = CompressedVoiceMessage {
+            room: ctx.accounts.voice_room.key(),
+            sender: ctx.accounts.sender.key(),
+            storage_pda_index: target_pda_index,
+            sequence_number,
+            data_length: voice_data.len() as u32,
+            timestamp: Clock::get()?.unix_timestamp,
+            leaf_index: message_log.leaf_count,
+        };
+        let mut leaf_data = Vec::new();
+        leaf.serialize(&mut leaf_data)
+            .map_err(|_| anchor_lang::error::ErrorCode::AccountDidNotSerialize)?;
+        
+        let voice_room_key = ctx.accounts.voice_room.key();
+        let signer_seeds: &[&[u8]] = &[b"message_log", voice_room_key.as_ref(), &[message_log.bump]];
+        
+        account_compression::wrap_application_data(&ctx.accounts.noop_program, leaf_data.clone())?;
+        account_compression::append(
+            &ctx.accounts.compression_program,
+            &ctx.accounts.merkle_tree,
+            &ctx.accounts.message_log.to_account_info(),
+            &ctx.accounts.noop_program,
+            anchor_lang::solana_program::keccak::hash(&leaf_data).to_bytes(),
+            &[signer_seeds],
+        )?;
+        
+        let message_log = &mut ctx.accounts.message_log;
+        message_log.leaf_count += 1;
+        
+        let voice_room = &mut ctx.accounts.voice_room;
+        voice_room.last_activity = leaf.timestamp;
+        voice_room.record_lane_write(target_pda_index, ctx.accounts.sender_participant.slot, sequence_number);
+        voice_room.record_traffic(1, voice_data.len());
+        
+        msg!("Compressed voice data sent: {} bytes to PDA {}, sequence {}, leaf {}", 
+             voice_data.len(), target_pda_index, sequence_number, leaf.leaf_index);
+        Ok(())
+    }
+
+    /// Send voice data and record the message as a ZK-compressed (Light Protocol) account.
+    /// Only available with the `light` feature; light system accounts are passed via remaining_accounts.
+    #[cfg(feature = "light")]
+    pub fn send_voice_data_light<'info>(
+        ctx: Context<'_, '_, '_, 'info, SendVoiceDataLight<'info>>,
+        voice_data: Vec<u8>,
+        target_pda_index: u8,
+        sequence_number: u32,
+        duration_ms: u16,
+        force: bool,
+        proof: ValidityProof,
+        address_tree_info: PackedAddressTreeInfo,
+        output_tree_index: u8,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(voice_data.len() <= ctx.accounts.voice_room.max_frame_bytes(), VoiceChatError::VoiceDataTooLarge);
+        check_codec(&ctx.accounts.voice_room, &voice_data)?;
+        check_frame_quality(&ctx.accounts.voice_room, voice_data.len(), duration_ms)?;
+        check_sender(
+            &ctx.accounts.voice_room,
+            &ctx.accounts.sender_participant,
+            ctx.accounts.sender_stake.as_ref(),
+            ctx.accounts.sender_reputation.as_ref(),
+        )?;
+        require!((target_pda_index as usize) < STORAGE_LANE_COUNT, VoiceChatError::InvalidStoragePDA);
+        check_room_lane(
+            &ctx.accounts.voice_room,
+            &ctx.accounts.storage_pda,
+            target_pda_index,
+            &ctx.accounts.protocol_config,
+        )?;
+        let recent_sends = &mut ctx.accounts.recent_sends;
+        recent_sends.room = ctx.accounts.voice_room.key();
+        recent_sends.sender = ctx.accounts.sender.key();
+        recent_sends.bump = ctx.bumps.recent_sends;
+        recent_sends.track_epoch(ctx.accounts.sender_participant.sequence_epoch)?;
+        check_stage_lane(
+            &ctx.accounts.voice_room,
+            &ctx.accounts.sender_participant,
+            &mut ctx.accounts.recent_sends,
+            &[target_pda_index],
+        )?;
+        check_lanes_consumed(&ctx.accounts.voice_room, &[target_pda_index], force)?;
+        check_lane_allocations(&ctx.accounts.voice_room, &ctx.accounts.sender_participant, &[target_pda_index])?;
+        
+        meter_storage_write(
+            &ctx.accounts.voice_room,
+            ctx.accounts.bandwidth_allowance.as_mut(),
+            voice_data.len(),
+        )?;
+        let storage_writer = StorageWriter {
+            program: &ctx.accounts.storage_program,
+            writer: &ctx.accounts.storage_writer,
+            writer_bump: ctx.bumps.storage_writer,
+            protocol_config: &ctx.accounts.protocol_config,
+        };
+        write_storage_chunk(&ctx.accounts.storage_pda, &storage_writer, &voice_data)?;
+        journal_storage_write(
+            &ctx.accounts.voice_room,
+            ctx.accounts.write_journal.as_deref_mut(),
+            &ctx.accounts.storage_pda,
+            &voice_data,
+        )?;
+        
+        let light_cpi_accounts = CpiAccounts::new(
+            ctx.accounts.sender.as_ref(),
+            ctx.remaining_accounts,
+            LIGHT_CPI_SIGNER,
+        );
+        let address_tree = address_tree_info
+            .get_tree_pubkey(&light_cpi_accounts)
+            .map_err(|_| ErrorCode::AccountNotEnoughKeys)?;
+        let (address, address_seed) = derive_address(
+            &[b"voice_message", ctx.accounts.sender.key().as_ref(), &sequence_number.to_le_bytes()],
+            &address_tree,
+            &crate::ID,
+        );
+        
+        let mut voice_message = LightAccount::<'_, LightVoiceMessage>::new_init(
+            &crate::ID,
+            Some(address),
+            output_tree_index,
+        );
+        voice_message.sender = ctx.accounts.sender.key();
+        voice_message.room = ctx.accounts.voice_room.key();
+        voice_message.storage_pda_index = target_pda_index;
+        voice_message.sequence_number = sequence_number;
+        voice_message.data_length = voice_data.len() as u32;
+        voice_message.timestamp = Clock::get()?.unix_timestamp;
+        
+        let cpi = CpiInputs::new_with_address(
+            proof,
+            vec![voice_message.to_account_info().map_err(ProgramError::from)?],
+            vec![address_tree_info.into_new_address_params_packed(address_seed)],
+        );
+        cpi.invoke_light_system_program(light_cpi_accounts).map_err(ProgramError::from)?;
+        
+        let voice_room = &mut ctx.accounts.voice_room;
+        voice_room.last_activity = Clock::get()?.unix_timestamp;
+        voice_room.record_lane_write(target_pda_index, ctx.accounts.sender_participant.slot, sequence_number);
+        voice_room.record_traffic(1, voice_data.len());
+        
+        msg!("Voice data sent (light): {} bytes to PDA {}, sequence {}", 
+             voice_data.len(), target_pda_index, sequence_number);
+        Ok(())
+    }
+
+    /// Broadcast record stored as a ZK-compressed (Light Protocol) account.
+    /// Only available with the `light` feature; light system accounts are passed via remaining_accounts.
+    #[cfg(feature = "light")]
+    pub fn broadcast_voice_data_light<'info>(
+        ctx: Context<'_, '_, '_, 'info, BroadcastVoiceDataLight<'info>>,
+        voice_data: Vec<u8>,
+        target_pdas: Vec<u8>,
+        sequence_number: u32,
+        duration_ms: u16,
+        proof: ValidityProof,
+        address_tree_info: PackedAddressTreeInfo,
+        output_tree_index: u8,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(voice_data.len() <= ctx.accounts.voice_room.max_frame_bytes(), VoiceChatError::VoiceDataTooLarge);
+        check_codec(&ctx.accounts.voice_room, &voice_data)?;
+        check_frame_quality(&ctx.accounts.voice_room, voice_data.len(), duration_ms)?;
+        check_sender(
+            &ctx.accounts.voice_room,
+            &ctx.accounts.sender_participant,
+            ctx.accounts.sender_stake.as_ref(),
+            ctx.accounts.sender_reputation.as_ref(),
+        )?;
+        require!(target_pdas.len() <= STORAGE_LANE_COUNT, VoiceChatError::TooManyTargetPDAs);
+        require!(target_pdas.iter().all(|index| (*index as usize) < STORAGE_LANE_COUNT), VoiceChatError::InvalidStoragePDA);
+        
+        let light_cpi_accounts = CpiAccounts::new(
+            ctx.accounts.sender.as_ref(),
+            ctx.remaining_accounts,
+            LIGHT_CPI_SIGNER,
+        );
+        let address_tree = address_tree_info
+            .get_tree_pubkey(&light_cpi_accounts)
+            .map_err(|_| ErrorCode::AccountNotEnoughKeys)?;
+        let (address, address_seed) = derive_address(
+            &[b"broadcast_message", ctx.accounts.sender.key().as_ref(), &sequence_number.to_le_bytes()],
+            &address_tree,
+            &crate::ID,
+        );
+        
+        let mut broadcast_message = LightAccount::<'_, LightBroadcastMessage>::new_init(
+            &crate::ID,
+            Some(address),
+            output_tree_index,
+        );
+        broadcast_message.sender = ctx.accounts.sender.key();
+        broadcast_message.room = ctx.accounts.voice_room.key();
+        // Target PDAs are packed into a bitmask to keep the compressed account hashable
+        broadcast_message.target_pda_mask = target_pdas.iter().fold(0u16, |mask, index| mask | (1 << index));
+        broadcast_message.sequence_number = sequence_number;
+        broadcast_message.data_length = voice_data.len() as u32;
+        broadcast_message.timestamp = Clock::get()?.unix_timestamp;
+        
+        let cpi = CpiInputs::new_with_address(
+            proof,
+            vec![broadcast_message.to_account_info().map_err(ProgramError::from)?],
+            vec![address_tree_info.into_new_address_params_packed(address_seed)],
+        );
+        cpi.invoke_light_system_program(light_cpi_accounts).map_err(ProgramError::from)?;
+        
+        ctx.accounts.voice_room.record_traffic(1, voice_data.len());
+        
+        msg!("Voice data broadcasted (light): {} bytes to {} PDAs, sequence {}", 
+             voice_data.len(), target_pdas.len(), sequence_number);
+        Ok(())
+    }
+
+    /// Retrieve voice data from storage PDA. When the VoiceMessage that recorded the frame is
+    /// passed, its content_hash is recomputed over the frame's storage range and the read fails
+    /// with IntegrityCheckFailed if the slot has been overwritten since.
     pub fn get_voice_data(
         ctx: Context<GetVoiceData>,
         pda_index: u8,
     ) -> Result<()> {
-        require!(pda_index < 10, VoiceChatError::InvalidStoragePDA);
+        require!((pda_index as usize) < STORAGE_LANE_COUNT, VoiceChatError::InvalidStoragePDA);
+        
+        // The frame this read is checked against: written at offset 0 of this storage PDA
+        let expected = match ctx.accounts.voice_message.as_ref() {
+            Some(voice_message) => {
+                require_keys_eq!(voice_message.storage_pda, ctx.accounts.storage_pda.key(), VoiceChatError::InvalidStoragePDA);
+                require!(voice_message.room_id == ctx.accounts.voice_room.room_id(), VoiceChatError::InvalidStoragePDA);
+                // Only the first MAX_VOICE_DATA_SIZE bytes of a frame were written and hashed
+                let length = (voice_message.data_length as usize).min(MAX_VOICE_DATA_SIZE);
+                Some((length, voice_message.content_hash))
+            }
+            None => None,
+        };
+        
+        // Through a storage backend, the chunk's leading bytes are passed back as return data
+        if let Some(storage_program) = ctx.accounts.storage_program.as_ref() {
+            require_keys_eq!(
+                storage_program.key(),
+                ctx.accounts.protocol_config.storage_backend(),
+                VoiceChatError::InvalidStorageBackend
+            );
+            // Verification needs the whole frame, read MAX_READ_LENGTH bytes at a time
+            let read_length = expected.map_or(storage_interface::MAX_READ_LENGTH as usize, |(length, _)| length);
+            let mut voice_data = Vec::with_capacity(read_length);
+            while voice_data.len() < read_length {
+                let ix = storage_interface::read_chunk(
+                    storage_program.key(),
+                    ctx.accounts.storage_pda.key(),
+                    voice_data.len() as u32,
+                    (read_length - voice_data.len()).min(storage_interface::MAX_READ_LENGTH as usize) as u32,
+                );
+                anchor_lang::solana_program::program::invoke(
+                    &ix,
+                    &[ctx.accounts.storage_pda.to_account_info(), storage_program.to_account_info()],
+                )?;
+                let chunk = match anchor_lang::solana_program::program::get_return_data() {
+                    Some((program_id, chunk)) if program_id == storage_program.key() => chunk,
+                    _ => Vec::new(),
+                };
+                if chunk.is_empty() {
+                    break;
+                }
+                voice_data.extend_from_slice(&chunk);
+            }
+            if let Some((_, content_hash)) = expected {
+                require!(
+                    anchor_lang::solana_program::keccak::hash(&voice_data).to_bytes() == content_hash,
+                    VoiceChatError::IntegrityCheckFailed
+                );
+            }
+            voice_data.truncate(storage_interface::MAX_READ_LENGTH as usize);
+            anchor_lang::solana_program::program::set_return_data(&voice_data);
+            
+            msg!("Read {} bytes of voice data from PDA {} through {}{}", 
+                 voice_data.len(), pda_index, storage_program.key(),
+                 if expected.is_some() { " (hash verified)" } else { "" });
+            return Ok(());
+        }
         
         let storage_account_info = &ctx.accounts.storage_pda;
+        check_storage_layout(storage_account_info)?;
         let storage_data = storage_account_info.try_borrow_data()?;
         
+        if let Some((length, content_hash)) = expected {
+            let frame = storage_data
+                .get(STORAGE_DATA_OFFSET..STORAGE_DATA_OFFSET + length)
+                .ok_or(VoiceChatError::IntegrityCheckFailed)?;
+            require!(
+                anchor_lang::solana_program::keccak::hash(frame).to_bytes() == content_hash,
+                VoiceChatError::IntegrityCheckFailed
+            );
+            msg!("Voice data of PDA {} matches the recorded content hash", pda_index);
+        }
+        
         // Read metadata to get data length
-        let data_length_offset = 8 + 1 + 32 + 8; // offset to data_length field
         let data_length = u32::from_le_bytes([
-            storage_data[data_length_offset],
-            storage_data[data_length_offset + 1],
-            storage_data[data_length_offset + 2],
-            storage_data[data_length_offset + 3],
+            storage_data[STORAGE_DATA_LENGTH_OFFSET],
+            storage_data[STORAGE_DATA_LENGTH_OFFSET + 1],
+            storage_data[STORAGE_DATA_LENGTH_OFFSET + 2],
+            storage_data[STORAGE_DATA_LENGTH_OFFSET + 3],
         ]);
         
         msg!("Retrieved voice data from PDA {}: {} bytes", pda_index, data_length);
@@ -116,13 +1138,44 @@ pub mod voice_chat_manager {
 
     /// Leave voice room
     pub fn leave_voice_room(ctx: Context<LeaveVoiceRoom>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
         let voice_room = &mut ctx.accounts.voice_room;
         if voice_room.participant_count > 0 {
             voice_room.participant_count -= 1;
         }
+        voice_room.release_slot(ctx.accounts.participant_account.slot);
         
         voice_room.last_activity = Clock::get()?.unix_timestamp;
         
+        // Leaving starts the sender stake's withdrawal cooldown
+        if let Some(sender_stake) = ctx.accounts.sender_stake.as_mut() {
+            sender_stake.left_at = voice_room.last_activity;
+            sender_stake.session_joined_at = ctx.accounts.participant_account.joined_at;
+            sender_stake.session_left_at = voice_room.last_activity;
+        }
+        
+        // Leaving stops the listener's payment stream; it is settled up to this moment
+        require!(
+            !ctx.accounts.participant_account.has_stream || ctx.accounts.payment_stream.is_some(),
+            VoiceChatError::PaymentStreamRequired
+        );
+        if let Some(payment_stream) = ctx.accounts.payment_stream.as_mut() {
+            payment_stream.stopped_at = voice_room.last_activity;
+        }
+        
+        // A session completes when the participant stayed long enough and leaves without having been muted
+        if let Some(reputation) = ctx.accounts.reputation.as_mut() {
+            let session_seconds = voice_room.last_activity - ctx.accounts.participant_account.joined_at;
+            if !ctx.accounts.participant_account.muted && session_seconds >= REPUTATION_MIN_SESSION_SECONDS {
+                reputation.sessions_completed += 1;
+            }
+        }
+        
+        if let Some(participant_stats) = ctx.accounts.participant_stats.as_mut() {
+            let time_in_room = voice_room.last_activity - ctx.accounts.participant_account.joined_at;
+            participant_stats.talk_time_seconds += time_in_room.max(0) as u64;
+        }
+        
         // If no participants left, deactivate room
         if voice_room.participant_count == 0 {
             voice_room.is_active = false;
@@ -130,19 +1183,42 @@ pub mod voice_chat_manager {
         
         msg!("User {} left room '{}'. Participants: {}", 
              ctx.accounts.participant.key(), 
-             voice_room.room_id, 
+             voice_room.room_id(), 
              voice_room.participant_count);
         Ok(())
     }
 
+    /// Host reopens a room that went inactive so it can be reused for another session
+    pub fn reactivate_room(ctx: Context<UpdateRoomConfig>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        require!(!voice_room.is_active, VoiceChatError::RoomAlreadyActive);
+        voice_room.is_active = true;
+        voice_room.last_activity = Clock::get()?.unix_timestamp;
+        
+        emit!(RoomOpened {
+            room: voice_room.key(),
+            host: voice_room.host,
+            timestamp: voice_room.last_activity,
+        });
+        
+        msg!("Room '{}' reactivated by host", voice_room.room_id());
+        Ok(())
+    }
+
     /// Get room info
     pub fn get_room_info(ctx: Context<GetRoomInfo>) -> Result<()> {
         let voice_room = &ctx.accounts.voice_room;
         msg!("Room '{}': {} participants, active: {}, host: {}", 
-             voice_room.room_id,
+             voice_room.room_id(),
              voice_room.participant_count,
              voice_room.is_active,
              voice_room.host);
+        msg!("Room '{}' lifetime: peak {} participants, {} messages, {} bytes", 
+             voice_room.room_id(),
+             voice_room.peak_participants,
+             voice_room.total_messages,
+             voice_room.total_bytes);
         Ok(())
     }
 
@@ -152,103 +1228,4762 @@ pub mod voice_chat_manager {
         voice_data: Vec<u8>,
         target_pdas: Vec<u8>,
         sequence_number: u32,
+        duration_ms: u16,
     ) -> Result<()> {
-        require!(voice_data.len() <= MAX_VOICE_DATA_SIZE, VoiceChatError::VoiceDataTooLarge);
-        require!(target_pdas.len() <= 10, VoiceChatError::TooManyTargetPDAs);
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(voice_data.len() <= ctx.accounts.voice_room.max_frame_bytes(), VoiceChatError::VoiceDataTooLarge);
+        check_codec(&ctx.accounts.voice_room, &voice_data)?;
+        check_frame_quality(&ctx.accounts.voice_room, voice_data.len(), duration_ms)?;
+        check_sender(
+            &ctx.accounts.voice_room,
+            &ctx.accounts.sender_participant,
+            ctx.accounts.sender_stake.as_ref(),
+            ctx.accounts.sender_reputation.as_ref(),
+        )?;
+        require!(target_pdas.len() <= STORAGE_LANE_COUNT, VoiceChatError::TooManyTargetPDAs);
         
         // Create broadcast message record
         let broadcast_message = &mut ctx.accounts.broadcast_message;
         broadcast_message.sender = ctx.accounts.sender.key();
-        broadcast_message.room_id = ctx.accounts.voice_room.room_id.clone();
+        broadcast_message.room_id = ctx.accounts.voice_room.room_id().to_string();
         broadcast_message.target_pdas = target_pdas.clone();
         broadcast_message.sequence_number = sequence_number;
         broadcast_message.data_length = voice_data.len() as u32;
         broadcast_message.timestamp = Clock::get()?.unix_timestamp;
         
+        ctx.accounts.voice_room.record_traffic(1, voice_data.len());
+        
         msg!("Voice data broadcasted: {} bytes to {} PDAs, sequence {}", 
              voice_data.len(), target_pdas.len(), sequence_number);
         Ok(())
     }
-}
-
-#[derive(Accounts)]
-#[instruction(room_id: String)]
-pub struct InitializeVoiceRoom<'info> {
-    #[account(
-        init,
-        payer = host,
-        space = 8 + 4 + MAX_ROOM_ID_LENGTH + 32 + 1 + 1 + 8 + 8, // discriminator + room_id_len + room_id + host + participant_count + is_active + created_at + last_activity
-        seeds = [b"voice_room", room_id.as_bytes()],
-        bump
-    )]
-    pub voice_room: Account<'info, VoiceRoom>,
-    
-    #[account(mut)]
-    pub host: Signer<'info>,
-    
-    pub system_program: Program<'info, System>,
-}
 
-#[derive(Accounts)]
-pub struct JoinVoiceRoom<'info> {
-    #[account(mut)]
-    pub voice_room: Account<'info, VoiceRoom>,
-    
-    pub participant: Signer<'info>,
-}
+    /// Refresh a participant's liveness so cleanup cranks don't evict them
+    pub fn heartbeat(ctx: Context<Heartbeat>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let participant_account = &mut ctx.accounts.participant_account;
+        participant_account.last_seen = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
 
-#[derive(Accounts)]
-#[instruction(voice_data: Vec<u8>, target_pda_index: u8, sequence_number: u32)]
-pub struct SendVoiceData<'info> {
-    #[account(mut)]
-    pub voice_room: Account<'info, VoiceRoom>,
-    
-    /// CHECK: This is the storage PDA from storage_manager contract
-    #[account(mut)]
-    pub storage_pda: AccountInfo<'info>,
-    
-    #[account(
-        init,
-        payer = sender,
-        space = 8 + 32 + 4 + MAX_ROOM_ID_LENGTH + 1 + 4 + 4 + 8, // discriminator + sender + room_id_len + room_id + storage_pda_index + sequence_number + data_length + timestamp
-        seeds = [b"voice_message", sender.key().as_ref(), &sequence_number.to_le_bytes()],
-        bump
-    )]
-    pub voice_message: Account<'info, VoiceMessage>,
-    
-    #[account(mut)]
-    pub sender: Signer<'info>,
-    
-    pub system_program: Program<'info, System>,
-}
+    /// Start a new sequence epoch after the sender's sequence numbers restart (reconnect or
+    /// overflow), so listeners can tell a restarted stream from a wrapped one
+    pub fn start_sequence_epoch(ctx: Context<StartSequenceEpoch>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let recent_sends = &mut ctx.accounts.recent_sends;
+        recent_sends.room = ctx.accounts.voice_room.key();
+        recent_sends.sender = ctx.accounts.participant.key();
+        recent_sends.bump = ctx.bumps.recent_sends;
+        
+        // Continue past every epoch used in the room, including ones from before a rejoin
+        let participant_account = &mut ctx.accounts.participant_account;
+        let sequence_epoch = participant_account.sequence_epoch.max(recent_sends.sequence_epoch).wrapping_add(1);
+        participant_account.sequence_epoch = sequence_epoch;
+        participant_account.highest_contiguous_sequence = 0;
+        recent_sends.sequence_epoch = sequence_epoch;
+        
+        // Sponsored sends restart their sequence check; older signatures name the previous epoch
+        recent_sends.last_sponsored_sequence = 0;
+        
+        msg!("{} started sequence epoch {} in room '{}'", 
+             participant_account.user, participant_account.sequence_epoch, ctx.accounts.voice_room.room_id());
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct GetVoiceData<'info> {
-    pub voice_room: Account<'info, VoiceRoom>,
-    
-    /// CHECK: This is the storage PDA from storage_manager contract
-    pub storage_pda: AccountInfo<'info>,
-    
-    pub requester: Signer<'info>,
-}
+    /// Sender reports frames `first..=last` as delivered, e.g. ones sent through clip or
+    /// compressed paths or recovered through FEC, advancing their contiguity watermark
+    pub fn ack_range(ctx: Context<AckRange>, first: u32, last: u32) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(first <= last, VoiceChatError::InvalidSequenceRange);
+        let participant_account = &mut ctx.accounts.participant_account;
+        participant_account.extend_contiguous(first, last);
+        
+        msg!("{} acked {}..={}; contiguous through {}", 
+             participant_account.user, first, last, participant_account.highest_contiguous_sequence);
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct LeaveVoiceRoom<'info> {
-    #[account(mut)]
-    pub voice_room: Account<'info, VoiceRoom>,
-    
-    pub participant: Signer<'info>,
-}
+    /// Permissionless cleanup crank, safe to call repeatedly from an automation thread.
+    /// Expires an idle room, evicts heartbeat-dead participants, tombstones expired voice
+    /// messages, batches and clips (their addresses stay taken so the seeds can't be reused) and closes
+    /// expired frame accounts. remaining_accounts are (account, rent_recipient) pairs: a Participant
+    /// paired with its user, or a VoiceMessage, VoiceBatchMessage, VoiceClipMessage or EphemeralFrame
+    /// paired with its sender. Accounts
+    /// that are already gone or not yet eligible are skipped so overlapping cranks never fail
+    /// each other. Participants with a payment stream are left to evict_stale, which stops it.
+    pub fn tick_room<'info>(ctx: Context<'_, '_, 'info, 'info, TickRoom<'info>>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let now = Clock::get()?.unix_timestamp;
+        let room_key = ctx.accounts.voice_room.key();
+        let cranker = ctx.accounts.cranker.to_account_info();
+        require!(ctx.remaining_accounts.len() % 2 == 0, VoiceChatError::InvalidCleanupAccounts);
+        
+        let mut evicted = 0u32;
+        let mut collected = 0u32;
+        for pair in ctx.remaining_accounts.chunks(2) {
+            let (target, rent_recipient) = (&pair[0], &pair[1]);
+            if target.owner != &crate::ID || target.lamports() == 0 {
+                continue;
+            }
+            
+            if let Ok(participant_account) = Account::<Participant>::try_from(target) {
+                if participant_account.room != room_key
+                    || participant_account.user != rent_recipient.key()
+                    || now - participant_account.last_seen <= ctx.accounts.voice_room.participant_timeout()
+                    || participant_account.has_stream
+                {
+                    continue;
+                }
+                pay_crank_reward(target, &cranker)?;
+                participant_account.close(rent_recipient.clone())?;
+                
+                let voice_room = &mut ctx.accounts.voice_room;
+                voice_room.participant_count = voice_room.participant_count.saturating_sub(1);
+                voice_room.release_slot(participant_account.slot);
+                if voice_room.participant_count == 0 {
+                    voice_room.is_active = false;
+                }
+                evicted += 1;
+            } else if let Ok(voice_message) = Account::<VoiceMessage>::try_from(target) {
+                if voice_message.room_id != ctx.accounts.voice_room.room_id()
+                    || voice_message.sender != rent_recipient.key()
+                    || now - voice_message.timestamp <= MESSAGE_RETENTION_SECONDS
+                {
+                    continue;
+                }
+                pay_crank_reward(target, &cranker)?;
+                tombstone_voice_message(target, rent_recipient)?;
+                collected += 1;
+            } else if let Ok(batch_message) = Account::<VoiceBatchMessage>::try_from(target) {
+                if batch_message.room_id != ctx.accounts.voice_room.room_id()
+                    || batch_message.sender != rent_recipient.key()
+                    || now - batch_message.timestamp <= MESSAGE_RETENTION_SECONDS
+                {
+                    continue;
+                }
+                pay_crank_reward(target, &cranker)?;
+                tombstone_voice_message(target, rent_recipient)?;
+                collected += 1;
+            } else if let Ok(clip_message) = Account::<VoiceClipMessage>::try_from(target) {
+                if clip_message.room_id != ctx.accounts.voice_room.room_id()
+                    || clip_message.sender != rent_recipient.key()
+                    || now - clip_message.timestamp <= MESSAGE_RETENTION_SECONDS
+                {
+                    continue;
+                }
+                pay_crank_reward(target, &cranker)?;
+                tombstone_voice_message(target, rent_recipient)?;
+                collected += 1;
+            } else if let Ok(frame) = Account::<EphemeralFrame>::try_from(target) {
+                if frame.room != room_key
+                    || frame.sender != rent_recipient.key()
+                    || now - frame.timestamp <= MESSAGE_RETENTION_SECONDS
+                {
+                    continue;
+                }
+                pay_crank_reward(target, &cranker)?;
+                frame.close(rent_recipient.clone())?;
+                collected += 1;
+            }
+        }
+        
+        let voice_room = &mut ctx.accounts.voice_room;
+        if voice_room.is_active && now - voice_room.last_activity > ROOM_IDLE_TIMEOUT_SECONDS {
+            voice_room.is_active = false;
+            msg!("Room '{}' expired after inactivity", voice_room.room_id());
+        }
+        
+        msg!("Tick room '{}': evicted {} participants, collected {} messages", 
+             voice_room.room_id(), evicted, collected);
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct GetRoomInfo<'info> {
-    pub voice_room: Account<'info, VoiceRoom>,
-    
-    pub requester: Signer<'info>,
-}
+    /// Configure (or clear with `None`) the token gate for room entry
+    pub fn set_token_gate(
+        ctx: Context<SetTokenGate>,
+        gate_mint: Option<Pubkey>,
+        gate_min_balance: u64,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        voice_room.gate_mint = gate_mint;
+        voice_room.gate_min_balance = gate_min_balance;
+        
+        match gate_mint {
+            Some(mint) => msg!("Room '{}' gated by mint {} (min balance {})", 
+                               voice_room.room_id(), mint, gate_min_balance),
+            None => msg!("Room '{}' token gate removed", voice_room.room_id()),
+        }
+        Ok(())
+    }
 
-#[derive(Accounts)]
-#[instruction(voice_data: Vec<u8>, target_pdas: Vec<u8>, sequence_number: u32)]
+    /// Configure (or clear with `None`) the verified Metaplex collection required for room entry
+    pub fn set_collection_gate(
+        ctx: Context<SetTokenGate>,
+        gate_collection: Option<Pubkey>,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        voice_room.gate_collection = gate_collection;
+        
+        match gate_collection {
+            Some(collection) => msg!("Room '{}' gated by collection {}", voice_room.room_id(), collection),
+            None => msg!("Room '{}' collection gate removed", voice_room.room_id()),
+        }
+        Ok(())
+    }
+
+    /// Configure the room's entry fee and create its escrow if needed.
+    /// Fees can be charged in lamports, in tokens of `entry_fee_mint`, or both. A non-zero
+    /// `entry_fee_usd_cents` replaces the lamport fee and is converted at the SOL/USD price
+    /// when each joiner or ticket buyer pays.
+    pub fn set_entry_fee(
+        ctx: Context<SetEntryFee>,
+        entry_fee_lamports: u64,
+        entry_fee_mint: Option<Pubkey>,
+        entry_fee_amount: u64,
+        entry_fee_usd_cents: u64,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        voice_room.entry_fee_lamports = entry_fee_lamports;
+        voice_room.entry_fee_usd_cents = entry_fee_usd_cents;
+        voice_room.entry_fee_mint = entry_fee_mint;
+        voice_room.entry_fee_amount = entry_fee_amount;
+        
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.room = voice_room.key();
+        escrow.bump = ctx.bumps.escrow;
+        
+        msg!("Room '{}' entry fee set: {} lamports, {} USD cents, {} tokens of {:?}", 
+             voice_room.room_id(), entry_fee_lamports, entry_fee_usd_cents, entry_fee_amount, entry_fee_mint);
+        Ok(())
+    }
+
+    /// Create the escrow token vault for a room whose entry fee is token-denominated
+    pub fn initialize_escrow_vault(ctx: Context<InitializeEscrowVault>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        msg!("Escrow vault {} created for room '{}'", 
+             ctx.accounts.escrow_vault.key(), ctx.accounts.voice_room.room_id());
+        Ok(())
+    }
+
+    /// Withdraw collected entry fees to the host once the session has ended
+    pub fn withdraw_escrow<'info>(ctx: Context<'_, '_, '_, 'info, WithdrawEscrow<'info>>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &ctx.accounts.voice_room;
+        require!(voice_room.escrow_unlocked(), VoiceChatError::EscrowLocked);
+        require!(!ctx.accounts.escrow.split_configured, VoiceChatError::RevenueSplitConfigured);
+        
+        // Lamports above the escrow's rent-exempt minimum belong to the host
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        let rent_exempt_minimum = Rent::get()?.minimum_balance(escrow_info.data_len());
+        let withdrawable = escrow_info.lamports().saturating_sub(rent_exempt_minimum);
+        if withdrawable > 0 {
+            **escrow_info.try_borrow_mut_lamports()? -= withdrawable;
+            **ctx.accounts.host.to_account_info().try_borrow_mut_lamports()? += withdrawable;
+        }
+        
+        let mut withdrawn_tokens = 0;
+        if let (Some(escrow_vault), Some(host_token_account), Some(fee_mint), Some(token_program)) = (
+            ctx.accounts.escrow_vault.as_ref(),
+            ctx.accounts.host_token_account.as_mut(),
+            ctx.accounts.fee_mint.as_ref(),
+            ctx.accounts.token_program.as_ref(),
+        ) {
+            let voice_room_key = voice_room.key();
+            let signer_seeds: &[&[u8]] = &[b"escrow", voice_room_key.as_ref(), &[ctx.accounts.escrow.bump]];
+            withdrawn_tokens = transfer_tokens_checked(
+                &token_program.to_account_info(),
+                escrow_vault,
+                fee_mint,
+                host_token_account,
+                &escrow_info,
+                ctx.remaining_accounts,
+                escrow_vault.amount,
+                &[signer_seeds],
+            )?;
+        }
+        
+        msg!("Host withdrew {} lamports and {} tokens from room '{}' escrow", 
+             withdrawable, withdrawn_tokens, voice_room.room_id());
+        Ok(())
+    }
+
+    /// Turn the room into a scheduled event with a fixed number of tickets
+    pub fn schedule_event(
+        ctx: Context<ScheduleEvent>,
+        scheduled_start: i64,
+        ticket_capacity: u16,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        require!(scheduled_start > Clock::get()?.unix_timestamp, VoiceChatError::InvalidSchedule);
+        // Every ticket holder must fit in the room alongside the host
+        require!(ticket_capacity < voice_room.max_participants() as u16, VoiceChatError::InvalidTicketCapacity);
+        require!(ticket_capacity >= voice_room.tickets_sold, VoiceChatError::InvalidTicketCapacity);
+        // Tickets are paid in lamports; token-denominated fees are only collected at join
+        require!(voice_room.entry_fee_mint.is_none(), VoiceChatError::TokenFeeTicketsUnsupported);
+        
+        voice_room.scheduled_start = scheduled_start;
+        voice_room.ticket_capacity = ticket_capacity;
+        
+        msg!("Room '{}' scheduled for {} with {} tickets", 
+             voice_room.room_id(), scheduled_start, ticket_capacity);
+        Ok(())
+    }
+
+    /// Buy a ticket for a scheduled event, paying the lamport entry fee into the room escrow.
+    /// USD-priced events are converted at purchase time and the ticket records the lamports paid.
+    /// The room account is written on every purchase, so concurrent buyers can't oversubscribe it.
+    pub fn buy_ticket(ctx: Context<BuyTicket>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        require!(voice_room.scheduled_start > 0, VoiceChatError::NotAScheduledEvent);
+        require!(voice_room.tickets_sold < voice_room.ticket_capacity, VoiceChatError::EventSoldOut);
+        
+        let (price, min_protocol_fee) = entry_fee_lamports(
+            voice_room,
+            ctx.accounts.price_feed.as_ref(),
+            ctx.accounts.sol_usd_price.as_ref(),
+        )?;
+        let mut room_share = 0;
+        if price > 0 {
+            let escrow = ctx.accounts.escrow.as_mut()
+                .ok_or(VoiceChatError::EscrowAccountsMissing)?;
+            let protocol_fee = collect_protocol_fee_lamports(
+                &ctx.accounts.protocol_config,
+                ctx.accounts.protocol_treasury.as_mut(),
+                &ctx.accounts.buyer.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                price,
+                min_protocol_fee,
+            )?;
+            room_share = price - protocol_fee;
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.buyer.to_account_info(),
+                        to: escrow.to_account_info(),
+                    },
+                ),
+                room_share,
+            )?;
+            escrow.lamports_collected += room_share;
+        }
+        voice_room.tickets_sold += 1;
+        
+        let ticket = &mut ctx.accounts.ticket;
+        ticket.room = voice_room.key();
+        ticket.holder = ctx.accounts.buyer.key();
+        ticket.purchased_at = Clock::get()?.unix_timestamp;
+        // Only the escrowed share is refundable; the protocol fee is kept
+        ticket.price_paid = room_share;
+        ticket.consumed = false;
+        
+        msg!("Ticket {}/{} for room '{}' bought by {}", 
+             voice_room.tickets_sold, voice_room.ticket_capacity, voice_room.room_id(), ticket.holder);
+        Ok(())
+    }
+
+    /// Tip a participant in SOL (`mint = None`) or in SPL/Token-2022 tokens of `mint`.
+    /// Tips are tallied per (room, speaker, mint) and announced with a TipReceived event.
+    pub fn tip_speaker<'info>(
+        ctx: Context<'_, '_, '_, 'info, TipSpeaker<'info>>,
+        amount: u64,
+        mint: Option<Pubkey>,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(amount > 0, VoiceChatError::InvalidTipAmount);
+        require_keys_neq!(ctx.accounts.tipper.key(), ctx.accounts.speaker.key(), VoiceChatError::CannotTipSelf);
+        
+        let received = match mint {
+            None => {
+                let protocol_fee = collect_protocol_fee_lamports(
+                    &ctx.accounts.protocol_config,
+                    ctx.accounts.protocol_treasury.as_mut(),
+                    &ctx.accounts.tipper.to_account_info(),
+                    &ctx.accounts.system_program.to_account_info(),
+                    amount,
+                    0,
+                )?;
+                system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: ctx.accounts.tipper.to_account_info(),
+                            to: ctx.accounts.speaker.to_account_info(),
+                        },
+                    ),
+                    amount - protocol_fee,
+                )?;
+                amount - protocol_fee
+            }
+            Some(mint) => {
+                let (Some(tipper_token_account), Some(speaker_token_account), Some(tip_mint), Some(token_program)) = (
+                    ctx.accounts.tipper_token_account.as_ref(),
+                    ctx.accounts.speaker_token_account.as_mut(),
+                    ctx.accounts.tip_mint.as_ref(),
+                    ctx.accounts.token_program.as_ref(),
+                ) else {
+                    return err!(VoiceChatError::TipTokenAccountsMissing);
+                };
+                require_keys_eq!(tip_mint.key(), mint, VoiceChatError::InvalidTipTokenAccount);
+                require_keys_eq!(speaker_token_account.owner, ctx.accounts.speaker.key(), VoiceChatError::InvalidTipTokenAccount);
+                
+                // remaining_accounts carry any Token-2022 transfer hook accounts
+                let protocol_fee = collect_protocol_fee_tokens(
+                    &ctx.accounts.protocol_config,
+                    ctx.accounts.protocol_treasury.as_ref(),
+                    ctx.accounts.protocol_treasury_vault.as_mut(),
+                    &token_program.to_account_info(),
+                    tipper_token_account,
+                    tip_mint,
+                    &ctx.accounts.tipper.to_account_info(),
+                    ctx.remaining_accounts,
+                    amount,
+                )?;
+                transfer_tokens_checked(
+                    &token_program.to_account_info(),
+                    tipper_token_account,
+                    tip_mint,
+                    speaker_token_account,
+                    &ctx.accounts.tipper.to_account_info(),
+                    ctx.remaining_accounts,
+                    amount - protocol_fee,
+                    &[],
+                )?
+            }
+        };
+        
+        let timestamp = Clock::get()?.unix_timestamp;
+        let tip_tally = &mut ctx.accounts.tip_tally;
+        tip_tally.room = ctx.accounts.voice_room.key();
+        tip_tally.speaker = ctx.accounts.speaker.key();
+        tip_tally.mint = mint;
+        if tip_tally.tip_count == 0 {
+            tip_tally.first_tip_at = timestamp;
+        }
+        tip_tally.total_amount += received;
+        tip_tally.tip_count += 1;
+        tip_tally.last_tip_at = timestamp;
+        
+        // Any mint can be created for free, so only SOL tips above the floor build reputation
+        if let Some(speaker_reputation) = ctx.accounts.speaker_reputation.as_mut() {
+            if mint.is_none() && received >= REPUTATION_MIN_TIP_LAMPORTS {
+                speaker_reputation.tips_received += 1;
+            }
+        }
+        if let Some(speaker_stats) = ctx.accounts.speaker_stats.as_mut() {
+            speaker_stats.tips_received += 1;
+            if mint.is_none() {
+                speaker_stats.tip_lamports_received += received;
+            }
+        }
+        
+        emit!(TipReceived {
+            room: tip_tally.room,
+            tipper: ctx.accounts.tipper.key(),
+            speaker: tip_tally.speaker,
+            mint,
+            amount: received,
+            timestamp,
+        });
+        
+        msg!("Speaker {} tipped {} by {} in room '{}'", 
+             tip_tally.speaker, received, ctx.accounts.tipper.key(), ctx.accounts.voice_room.room_id());
+        Ok(())
+    }
+
+    /// Configure how escrowed entry fees and room tips are shared between hosts and co-hosts.
+    /// Once set, escrow funds can only be paid out through settle_room.
+    pub fn set_revenue_split(
+        ctx: Context<SetRevenueSplit>,
+        shares: Vec<SplitShare>,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(!shares.is_empty() && shares.len() <= MAX_SPLIT_RECIPIENTS, VoiceChatError::InvalidRevenueSplit);
+        let total_bps: u32 = shares.iter().map(|share| share.bps as u32).sum();
+        require!(total_bps == 10_000, VoiceChatError::InvalidRevenueSplit);
+        for (i, share) in shares.iter().enumerate() {
+            require!(
+                shares[..i].iter().all(|other| other.recipient != share.recipient),
+                VoiceChatError::InvalidRevenueSplit
+            );
+        }
+        
+        let revenue_split = &mut ctx.accounts.revenue_split;
+        revenue_split.room = ctx.accounts.voice_room.key();
+        revenue_split.shares = shares;
+        revenue_split.bump = ctx.bumps.revenue_split;
+        ctx.accounts.escrow.split_configured = true;
+        
+        msg!("Room '{}' revenue split set across {} recipients", 
+             ctx.accounts.voice_room.room_id(), revenue_split.shares.len());
+        Ok(())
+    }
+
+    /// Tip the room itself; the tip is held in escrow and shared through the revenue split
+    pub fn tip_room<'info>(
+        ctx: Context<'_, '_, '_, 'info, TipRoom<'info>>,
+        amount: u64,
+        mint: Option<Pubkey>,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(amount > 0, VoiceChatError::InvalidTipAmount);
+        
+        let escrow = &mut ctx.accounts.escrow;
+        let received = match mint {
+            None => {
+                let protocol_fee = collect_protocol_fee_lamports(
+                    &ctx.accounts.protocol_config,
+                    ctx.accounts.protocol_treasury.as_mut(),
+                    &ctx.accounts.tipper.to_account_info(),
+                    &ctx.accounts.system_program.to_account_info(),
+                    amount,
+                    0,
+                )?;
+                let room_share = amount - protocol_fee;
+                system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: ctx.accounts.tipper.to_account_info(),
+                            to: escrow.to_account_info(),
+                        },
+                    ),
+                    room_share,
+                )?;
+                escrow.tip_lamports_collected += room_share;
+                room_share
+            }
+            Some(mint) => {
+                // Token tips share the escrow vault, so they must be in the room's fee mint
+                require!(ctx.accounts.voice_room.entry_fee_mint == Some(mint), VoiceChatError::InvalidFeeMint);
+                let (Some(tipper_token_account), Some(escrow_vault), Some(tip_mint), Some(token_program)) = (
+                    ctx.accounts.tipper_token_account.as_ref(),
+                    ctx.accounts.escrow_vault.as_mut(),
+                    ctx.accounts.tip_mint.as_ref(),
+                    ctx.accounts.token_program.as_ref(),
+                ) else {
+                    return err!(VoiceChatError::TipTokenAccountsMissing);
+                };
+                require_keys_eq!(tip_mint.key(), mint, VoiceChatError::InvalidTipTokenAccount);
+                
+                let protocol_fee = collect_protocol_fee_tokens(
+                    &ctx.accounts.protocol_config,
+                    ctx.accounts.protocol_treasury.as_ref(),
+                    ctx.accounts.protocol_treasury_vault.as_mut(),
+                    &token_program.to_account_info(),
+                    tipper_token_account,
+                    tip_mint,
+                    &ctx.accounts.tipper.to_account_info(),
+                    ctx.remaining_accounts,
+                    amount,
+                )?;
+                let received = transfer_tokens_checked(
+                    &token_program.to_account_info(),
+                    tipper_token_account,
+                    tip_mint,
+                    escrow_vault,
+                    &ctx.accounts.tipper.to_account_info(),
+                    ctx.remaining_accounts,
+                    amount - protocol_fee,
+                    &[],
+                )?;
+                escrow.tip_tokens_collected += received;
+                received
+            }
+        };
+        
+        emit!(TipReceived {
+            room: ctx.accounts.voice_room.key(),
+            tipper: ctx.accounts.tipper.key(),
+            speaker: ctx.accounts.voice_room.host,
+            mint,
+            amount: received,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Room '{}' tipped {} by {}", 
+             ctx.accounts.voice_room.room_id(), received, ctx.accounts.tipper.key());
+        Ok(())
+    }
+
+    /// Pay out the escrow (entry fees and room tips) according to the revenue split.
+    /// remaining_accounts: one wallet per share (in split order), then - if the room has a
+    /// token vault - one token account per share, then any Token-2022 transfer hook accounts.
+    pub fn settle_room<'info>(ctx: Context<'_, '_, 'info, 'info, SettleRoom<'info>>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &ctx.accounts.voice_room;
+        require!(voice_room.escrow_unlocked(), VoiceChatError::EscrowLocked);
+        
+        let shares = &ctx.accounts.revenue_split.shares;
+        let share_count = shares.len();
+        let has_token_vault = ctx.accounts.escrow_vault.is_some();
+        let required_accounts = if has_token_vault { share_count * 2 } else { share_count };
+        require!(ctx.remaining_accounts.len() >= required_accounts, VoiceChatError::InvalidSettlementAccounts);
+        let (wallets, rest) = ctx.remaining_accounts.split_at(share_count);
+        
+        // Lamports above the escrow's rent-exempt minimum are split by basis points;
+        // rounding dust goes to the first recipient
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        let rent_exempt_minimum = Rent::get()?.minimum_balance(escrow_info.data_len());
+        let distributable = escrow_info.lamports().saturating_sub(rent_exempt_minimum);
+        let lamport_payouts = split_by_bps(distributable, shares);
+        for ((share, wallet), payout) in shares.iter().zip(wallets).zip(&lamport_payouts) {
+            require_keys_eq!(wallet.key(), share.recipient, VoiceChatError::InvalidSettlementAccounts);
+            **escrow_info.try_borrow_mut_lamports()? -= payout;
+            **wallet.try_borrow_mut_lamports()? += payout;
+        }
+        
+        let mut distributed_tokens = 0;
+        if let (Some(escrow_vault), Some(fee_mint), Some(token_program)) = (
+            ctx.accounts.escrow_vault.as_ref(),
+            ctx.accounts.fee_mint.as_ref(),
+            ctx.accounts.token_program.as_ref(),
+        ) {
+            let (token_accounts, hook_accounts) = rest.split_at(share_count);
+            let token_payouts = split_by_bps(escrow_vault.amount, shares);
+            let voice_room_key = voice_room.key();
+            let signer_seeds: &[&[u8]] = &[b"escrow", voice_room_key.as_ref(), &[ctx.accounts.escrow.bump]];
+            
+            for ((share, token_account_info), payout) in shares.iter().zip(token_accounts).zip(token_payouts) {
+                let mut recipient_token_account = InterfaceAccount::<TokenAccount>::try_from(token_account_info)?;
+                require_keys_eq!(recipient_token_account.owner, share.recipient, VoiceChatError::InvalidSettlementAccounts);
+                if payout == 0 {
+                    continue;
+                }
+                transfer_tokens_checked(
+                    &token_program.to_account_info(),
+                    escrow_vault,
+                    fee_mint,
+                    &mut recipient_token_account,
+                    &escrow_info,
+                    hook_accounts,
+                    payout,
+                    &[signer_seeds],
+                )?;
+                distributed_tokens += payout;
+            }
+        }
+        
+        msg!("Room '{}' settled: {} lamports and {} tokens across {} recipients", 
+             voice_room.room_id(), distributable, distributed_tokens, share_count);
+        Ok(())
+    }
+
+    /// Host starts a scheduled event, opening it to ticket holders and releasing the escrow lock
+    pub fn start_event(ctx: Context<ScheduleEvent>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        let now = Clock::get()?.unix_timestamp;
+        require!(voice_room.scheduled_start > 0, VoiceChatError::NotAScheduledEvent);
+        require!(!voice_room.event_cancelled, VoiceChatError::EventCancelled);
+        require!(now >= voice_room.scheduled_start, VoiceChatError::EventNotStarted);
+        // Past the grace period ticket holders may already be claiming refunds
+        require!(now <= voice_room.scheduled_start + EVENT_GRACE_PERIOD_SECONDS, VoiceChatError::EventGracePeriodElapsed);
+        
+        voice_room.event_started = true;
+        voice_room.last_activity = now;
+        
+        msg!("Event '{}' started", voice_room.room_id());
+        Ok(())
+    }
+
+    /// Host cancels a scheduled event that hasn't started; ticket holders can then claim refunds
+    pub fn cancel_event(ctx: Context<ScheduleEvent>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        require!(voice_room.scheduled_start > 0, VoiceChatError::NotAScheduledEvent);
+        require!(!voice_room.event_started, VoiceChatError::EventAlreadyStarted);
+        
+        voice_room.event_cancelled = true;
+        
+        msg!("Event '{}' cancelled", voice_room.room_id());
+        Ok(())
+    }
+
+    /// Refund an unused ticket when the event was cancelled or the host didn't start it
+    /// within the grace period. Closes the ticket and returns its rent to the holder.
+    pub fn claim_refund(ctx: Context<ClaimRefund>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        let now = Clock::get()?.unix_timestamp;
+        require!(voice_room.scheduled_start > 0, VoiceChatError::NotAScheduledEvent);
+        
+        let host_no_show = !voice_room.event_started
+            && now > voice_room.scheduled_start + EVENT_GRACE_PERIOD_SECONDS;
+        require!(voice_room.event_cancelled || host_no_show, VoiceChatError::RefundNotAvailable);
+        require!(!ctx.accounts.ticket.consumed, VoiceChatError::TicketAlreadyUsed);
+        // A no-show cancels the event so the host can no longer start it and keep the fees
+        voice_room.event_cancelled = true;
+        
+        let refund = ctx.accounts.ticket.price_paid;
+        if refund > 0 {
+            let escrow = ctx.accounts.escrow.as_mut()
+                .ok_or(VoiceChatError::EscrowAccountsMissing)?;
+            **escrow.to_account_info().try_borrow_mut_lamports()? -= refund;
+            **ctx.accounts.holder.to_account_info().try_borrow_mut_lamports()? += refund;
+            escrow.lamports_collected = escrow.lamports_collected.saturating_sub(refund);
+        }
+        voice_room.tickets_sold = voice_room.tickets_sold.saturating_sub(1);
+        
+        msg!("Refunded {} lamports to {} for event '{}'", 
+             refund, ctx.accounts.holder.key(), voice_room.room_id());
+        Ok(())
+    }
+
+    /// Enable per-minute streaming payments for listeners (0 disables)
+    pub fn set_stream_rate(ctx: Context<UpdateRoomConfig>, rate_lamports_per_minute: u64) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        voice_room.stream_rate_lamports_per_minute = rate_lamports_per_minute;
+        
+        msg!("Room '{}' stream rate set to {} lamports/minute", 
+             voice_room.room_id(), rate_lamports_per_minute);
+        Ok(())
+    }
+
+    /// Open a payment stream for a participant, locking `deposit` lamports at the room's current rate
+    pub fn open_stream(ctx: Context<OpenStream>, deposit: u64) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &ctx.accounts.voice_room;
+        require!(voice_room.stream_rate_lamports_per_minute > 0, VoiceChatError::StreamingDisabled);
+        require!(deposit > 0, VoiceChatError::InvalidStreamDeposit);
+        
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.listener.to_account_info(),
+                    to: ctx.accounts.payment_stream.to_account_info(),
+                },
+            ),
+            deposit,
+        )?;
+        
+        let now = Clock::get()?.unix_timestamp;
+        let payment_stream = &mut ctx.accounts.payment_stream;
+        payment_stream.room = voice_room.key();
+        payment_stream.listener = ctx.accounts.listener.key();
+        payment_stream.rate_lamports_per_minute = voice_room.stream_rate_lamports_per_minute;
+        payment_stream.deposited = deposit;
+        payment_stream.paid_out = 0;
+        payment_stream.last_settled_at = std::cmp::max(now, ctx.accounts.participant_account.joined_at);
+        payment_stream.stopped_at = 0;
+        payment_stream.bump = ctx.bumps.payment_stream;
+        ctx.accounts.participant_account.has_stream = true;
+        
+        msg!("Payment stream opened in room '{}' by {} with {} lamports", 
+             voice_room.room_id(), payment_stream.listener, deposit);
+        Ok(())
+    }
+
+    /// Add lamports to an open payment stream
+    pub fn top_up_stream(ctx: Context<TopUpStream>, amount: u64) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(ctx.accounts.payment_stream.stopped_at == 0, VoiceChatError::StreamStopped);
+        
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.listener.to_account_info(),
+                    to: ctx.accounts.payment_stream.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+        ctx.accounts.payment_stream.deposited += amount;
+        Ok(())
+    }
+
+    /// Move lamports owed for elapsed listening time to the host. Callable by the host or a crank.
+    /// Listening time runs until the listener's last join/heartbeat, or until they left.
+    pub fn settle_stream(ctx: Context<SettleStream>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let payment_stream = &mut ctx.accounts.payment_stream;
+        let until = if payment_stream.stopped_at > 0 {
+            payment_stream.stopped_at
+        } else {
+            ctx.accounts.participant_account.as_ref()
+                .ok_or(VoiceChatError::StreamParticipantMissing)?
+                .last_seen
+        };
+        
+        let payment = settle_payment_stream(payment_stream, &ctx.accounts.host, until)?;
+        
+        msg!("Settled {} lamports from {}'s stream in room '{}'", 
+             payment, payment_stream.listener, ctx.accounts.voice_room.room_id());
+        Ok(())
+    }
+
+    /// Close a stopped stream: settle the final listening time and refund the rest to the listener
+    pub fn close_stream(ctx: Context<CloseStream>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let payment_stream = &mut ctx.accounts.payment_stream;
+        // A listener removed by the host (ban or move) never stopped the stream; with the
+        // participant record gone it stops at its last settlement
+        if payment_stream.stopped_at == 0 && ctx.accounts.participant_account.data_is_empty() {
+            payment_stream.stopped_at = payment_stream.last_settled_at;
+        }
+        require!(payment_stream.stopped_at > 0, VoiceChatError::StreamNotStopped);
+        
+        let until = payment_stream.stopped_at;
+        let payment = settle_payment_stream(payment_stream, &ctx.accounts.host, until)?;
+        
+        msg!("Closed {}'s stream in room '{}' (final payment {} lamports)", 
+             payment_stream.listener, ctx.accounts.voice_room.room_id(), payment);
+        Ok(())
+    }
+
+    /// Require senders to lock a stake before sending frames (0 disables)
+    pub fn set_sender_stake(ctx: Context<UpdateRoomConfig>, sender_stake_lamports: u64) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        voice_room.sender_stake_lamports = sender_stake_lamports;
+        
+        msg!("Room '{}' sender stake set to {} lamports", voice_room.room_id(), sender_stake_lamports);
+        Ok(())
+    }
+
+    /// Lock (or top up) a sender stake. Also re-locks a stake left over from a previous session.
+    pub fn deposit_stake(ctx: Context<DepositStake>, amount: u64) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        if amount > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.sender.to_account_info(),
+                        to: ctx.accounts.sender_stake.to_account_info(),
+                    },
+                ),
+                amount,
+            )?;
+        }
+        
+        let sender_stake = &mut ctx.accounts.sender_stake;
+        sender_stake.room = ctx.accounts.voice_room.key();
+        sender_stake.owner = ctx.accounts.sender.key();
+        sender_stake.amount += amount;
+        sender_stake.left_at = 0;
+        sender_stake.bump = ctx.bumps.sender_stake;
+        
+        msg!("Sender {} staked {} lamports in room '{}' (total {})", 
+             sender_stake.owner, amount, ctx.accounts.voice_room.room_id(), sender_stake.amount);
+        Ok(())
+    }
+
+    /// Withdraw a sender stake once the cooldown after leaving has passed
+    pub fn withdraw_stake(ctx: Context<WithdrawStake>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let sender_stake = &ctx.accounts.sender_stake;
+        require!(sender_stake.left_at > 0, VoiceChatError::StakeLocked);
+        require!(
+            Clock::get()?.unix_timestamp >= sender_stake.left_at + STAKE_COOLDOWN_SECONDS,
+            VoiceChatError::StakeLocked
+        );
+        
+        msg!("Sender {} withdrew {} lamports of stake from room '{}'", 
+             sender_stake.owner, sender_stake.amount, ctx.accounts.voice_room.room_id());
+        Ok(())
+    }
+
+    /// Configure report-and-slash moderation: distinct reports needed and share of stake slashed
+    pub fn set_moderation_policy(
+        ctx: Context<SetModerationPolicy>,
+        report_quorum: u8,
+        slash_bps: u16,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(slash_bps <= 10_000, VoiceChatError::InvalidSlashBps);
+        
+        let voice_room = &mut ctx.accounts.voice_room;
+        voice_room.report_quorum = report_quorum;
+        voice_room.slash_bps = slash_bps;
+        
+        let room_treasury = &mut ctx.accounts.room_treasury;
+        room_treasury.room = voice_room.key();
+        room_treasury.bump = ctx.bumps.room_treasury;
+        
+        msg!("Room '{}' moderation: quorum {}, slash {} bps", 
+             voice_room.room_id(), report_quorum, slash_bps);
+        Ok(())
+    }
+
+    /// Report a sender. Each participant can report an offender once per session (one stay in
+    /// the room, named by its join time); at quorum the offender is muted and their stake is
+    /// slashed. A session that has ended stays reportable through the stake's cooldown.
+    pub fn report_sender(
+        ctx: Context<ReportSender>,
+        reason_code: u8,
+        offender: Pubkey,
+        session_joined_at: i64,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        require!(voice_room.report_quorum > 0, VoiceChatError::ModerationDisabled);
+        require_keys_neq!(offender, ctx.accounts.reporter.key(), VoiceChatError::CannotReportSelf);
+        
+        let now = Clock::get()?.unix_timestamp;
+        match ctx.accounts.offender_participant.as_ref() {
+            Some(offender_participant) => require!(
+                offender_participant.joined_at == session_joined_at,
+                VoiceChatError::OffenderNotReportable
+            ),
+            None => {
+                let offender_stake = ctx.accounts.offender_stake.as_ref()
+                    .ok_or(VoiceChatError::OffenderNotReportable)?;
+                require!(
+                    offender_stake.session_joined_at == session_joined_at
+                        && now < offender_stake.session_left_at + STAKE_COOLDOWN_SECONDS,
+                    VoiceChatError::OffenderNotReportable
+                );
+            }
+        }
+        
+        let report = &mut ctx.accounts.report;
+        report.room = voice_room.key();
+        report.offender = offender;
+        report.reporter = ctx.accounts.reporter.key();
+        report.reason_code = reason_code;
+        report.session_joined_at = session_joined_at;
+        report.created_at = now;
+        
+        // Tallies reset when the offender starts a new session
+        let report_tally = &mut ctx.accounts.report_tally;
+        if report_tally.session_joined_at != session_joined_at {
+            report_tally.room = voice_room.key();
+            report_tally.offender = offender;
+            report_tally.session_joined_at = session_joined_at;
+            report_tally.report_count = 0;
+            report_tally.slashed = false;
+            report_tally.bump = ctx.bumps.report_tally;
+        }
+        report_tally.report_count += 1;
+        
+        let offender_reputation = &mut ctx.accounts.offender_reputation;
+        offender_reputation.user = offender;
+        offender_reputation.bump = ctx.bumps.offender_reputation;
+        
+        msg!("Sender {} reported in room '{}' (reason {}, {}/{})", 
+             offender, voice_room.room_id(), reason_code, report_tally.report_count, voice_room.report_quorum);
+        
+        if report_tally.report_count < voice_room.report_quorum as u16 || report_tally.slashed {
+            return Ok(());
+        }
+        report_tally.slashed = true;
+        if let Some(offender_participant) = ctx.accounts.offender_participant.as_mut() {
+            offender_participant.muted = true;
+        }
+        if voice_room.record_upheld_report() {
+            msg!("Room '{}' flagged after {} upheld reports", voice_room.room_id(), voice_room.upheld_reports);
+        }
+        
+        // Rooms that require a stake must slash it; skipping the accounts is not an option
+        let mut slashed = 0;
+        if voice_room.sender_stake_lamports > 0 {
+            let offender_stake = ctx.accounts.offender_stake.as_mut()
+                .ok_or(VoiceChatError::SlashAccountsRequired)?;
+            let room_treasury = ctx.accounts.room_treasury.as_mut()
+                .ok_or(VoiceChatError::SlashAccountsRequired)?;
+            slashed = (offender_stake.amount as u128 * voice_room.slash_bps as u128 / 10_000) as u64;
+            **offender_stake.to_account_info().try_borrow_mut_lamports()? -= slashed;
+            **room_treasury.to_account_info().try_borrow_mut_lamports()? += slashed;
+            offender_stake.amount -= slashed;
+            room_treasury.slashed_lamports += slashed;
+        }
+        
+        let offender_reputation = &mut ctx.accounts.offender_reputation;
+        offender_reputation.reports_upheld += 1;
+        if slashed > 0 {
+            offender_reputation.slashes += 1;
+        }
+        
+        emit!(SenderSlashed {
+            room: voice_room.key(),
+            offender,
+            report_count: report_tally.report_count,
+            slashed_lamports: slashed,
+            timestamp: now,
+        });
+        
+        msg!("Sender {} muted and slashed {} lamports in room '{}'", 
+             offender, slashed, voice_room.room_id());
+        Ok(())
+    }
+
+    /// Withdraw slashed stake collected in the room treasury to the host
+    pub fn withdraw_room_treasury(ctx: Context<WithdrawRoomTreasury>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let treasury_info = ctx.accounts.room_treasury.to_account_info();
+        let rent_exempt_minimum = Rent::get()?.minimum_balance(treasury_info.data_len());
+        let withdrawable = treasury_info.lamports().saturating_sub(rent_exempt_minimum);
+        **treasury_info.try_borrow_mut_lamports()? -= withdrawable;
+        **ctx.accounts.host.to_account_info().try_borrow_mut_lamports()? += withdrawable;
+        
+        msg!("Host withdrew {} lamports from room '{}' treasury", 
+             withdrawable, ctx.accounts.voice_room.room_id());
+        Ok(())
+    }
+
+    /// Create a user's global reputation account; rooms read it for admission thresholds
+    pub fn initialize_reputation(ctx: Context<InitializeReputation>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let reputation = &mut ctx.accounts.reputation;
+        reputation.user = ctx.accounts.user.key();
+        reputation.sessions_completed = 0;
+        reputation.tips_received = 0;
+        reputation.reports_upheld = 0;
+        reputation.slashes = 0;
+        reputation.bump = ctx.bumps.reputation;
+        
+        msg!("Reputation initialized for {}", reputation.user);
+        Ok(())
+    }
+
+    /// Get a user's reputation
+    pub fn get_reputation(ctx: Context<GetReputation>) -> Result<()> {
+        let reputation = &ctx.accounts.reputation;
+        msg!("User {}: score {}, sessions {}, tips {}, reports upheld {}, slashes {}", 
+             reputation.user,
+             reputation.score(),
+             reputation.sessions_completed,
+             reputation.tips_received,
+             reputation.reports_upheld,
+             reputation.slashes);
+        Ok(())
+    }
+
+    /// Create a user's statistics account; joins, sends, leaves and tips update it when passed
+    pub fn initialize_user_stats(ctx: Context<InitializeUserStats>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let user_stats = &mut ctx.accounts.user_stats;
+        user_stats.user = ctx.accounts.user.key();
+        user_stats.rooms_joined = 0;
+        user_stats.messages_sent = 0;
+        user_stats.bytes_sent = 0;
+        user_stats.talk_time_seconds = 0;
+        user_stats.tips_received = 0;
+        user_stats.tip_lamports_received = 0;
+        user_stats.bump = ctx.bumps.user_stats;
+        
+        msg!("User stats initialized for {}", user_stats.user);
+        Ok(())
+    }
+
+    /// Get a user's statistics
+    pub fn get_user_stats(ctx: Context<GetUserStats>) -> Result<()> {
+        let user_stats = &ctx.accounts.user_stats;
+        msg!("User {}: {} rooms joined, {} messages ({} bytes), {}s talk time, {} tips ({} lamports)", 
+             user_stats.user,
+             user_stats.rooms_joined,
+             user_stats.messages_sent,
+             user_stats.bytes_sent,
+             user_stats.talk_time_seconds,
+             user_stats.tips_received,
+             user_stats.tip_lamports_received);
+        Ok(())
+    }
+
+    /// Require a minimum reputation score to send in this room (0 disables)
+    pub fn set_min_reputation(ctx: Context<UpdateRoomConfig>, min_reputation_to_speak: i64) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        voice_room.min_reputation_to_speak = min_reputation_to_speak;
+        
+        msg!("Room '{}' minimum reputation to speak set to {}", 
+             voice_room.room_id(), min_reputation_to_speak);
+        Ok(())
+    }
+
+    /// Create the program-wide protocol fee configuration and treasury; the program's upgrade
+    /// authority becomes admin
+    pub fn initialize_protocol_config(ctx: Context<InitializeProtocolConfig>, fee_bps: u16) -> Result<()> {
+        require!(fee_bps <= MAX_PROTOCOL_FEE_BPS, VoiceChatError::ProtocolFeeTooHigh);
+        
+        let protocol_config = &mut ctx.accounts.protocol_config;
+        protocol_config.admin = ctx.accounts.admin.key();
+        protocol_config.fee_bps = fee_bps;
+        protocol_config.paused = false;
+        protocol_config.bump = ctx.bumps.protocol_config;
+        protocol_config.pending_admin = None;
+        protocol_config.transcription_oracle = None;
+        protocol_config.storage_backend = None;
+        
+        let protocol_treasury = &mut ctx.accounts.protocol_treasury;
+        protocol_treasury.lamports_collected = 0;
+        protocol_treasury.bump = ctx.bumps.protocol_treasury;
+        
+        msg!("Protocol config initialized: admin {}, fee {} bps", protocol_config.admin, fee_bps);
+        Ok(())
+    }
+
+    /// Update the protocol fee charged on paid-room entry and tips
+    pub fn set_protocol_fee(ctx: Context<UpdateProtocolConfig>, fee_bps: u16) -> Result<()> {
+        require!(fee_bps <= MAX_PROTOCOL_FEE_BPS, VoiceChatError::ProtocolFeeTooHigh);
+        ctx.accounts.protocol_config.fee_bps = fee_bps;
+        
+        msg!("Protocol fee set to {} bps", fee_bps);
+        Ok(())
+    }
+
+    /// Pause or resume every state-mutating instruction across voice_chat_manager,
+    /// storage_manager and voicechat
+    pub fn set_paused(ctx: Context<UpdateProtocolConfig>, paused: bool) -> Result<()> {
+        ctx.accounts.protocol_config.paused = paused;
+        
+        msg!("Protocol {}", if paused { "paused" } else { "resumed" });
+        Ok(())
+    }
+
+    /// First step of an admin handover: nominate the new admin (None cancels a pending nomination)
+    pub fn propose_admin(ctx: Context<UpdateProtocolConfig>, new_admin: Option<Pubkey>) -> Result<()> {
+        ctx.accounts.protocol_config.pending_admin = new_admin;
+        
+        msg!("Protocol admin nomination: {:?}", new_admin);
+        Ok(())
+    }
+
+    /// Second step of an admin handover: the nominee proves it can sign before taking over
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        let protocol_config = &mut ctx.accounts.protocol_config;
+        protocol_config.admin = ctx.accounts.pending_admin.key();
+        protocol_config.pending_admin = None;
+        
+        msg!("Protocol admin is now {}", protocol_config.admin);
+        Ok(())
+    }
+
+    /// Clear a flagged room after review. Its upheld report count restarts and the host can
+    /// list it again with set_room_tags.
+    pub fn clear_room_flag(ctx: Context<ClearRoomFlag>) -> Result<()> {
+        let voice_room = &mut ctx.accounts.voice_room;
+        require!(voice_room.flagged, VoiceChatError::RoomNotFlagged);
+        voice_room.flagged = false;
+        voice_room.upheld_reports = 0;
+        
+        msg!("Room '{}' flag cleared by admin", voice_room.room_id());
+        Ok(())
+    }
+
+    /// Register the key allowed to attest session transcripts, or None to stop accepting them
+    pub fn set_transcription_oracle(ctx: Context<UpdateProtocolConfig>, oracle: Option<Pubkey>) -> Result<()> {
+        ctx.accounts.protocol_config.transcription_oracle = oracle;
+        
+        msg!("Transcription oracle: {:?}", oracle);
+        Ok(())
+    }
+
+    /// Point storage writes at another program implementing storage_interface, or None for
+    /// storage_manager. Rooms keep working unchanged; clients pass the new backend's chunks.
+    pub fn set_storage_backend(ctx: Context<UpdateProtocolConfig>, backend: Option<Pubkey>) -> Result<()> {
+        ctx.accounts.protocol_config.storage_backend = backend;
+        
+        msg!("Storage backend: {}", ctx.accounts.protocol_config.storage_backend());
+        Ok(())
+    }
+
+    /// Withdraw accrued protocol fees to the admin: lamports above rent and, when the
+    /// token accounts are passed, a treasury token account's full balance
+    pub fn withdraw_treasury<'info>(ctx: Context<'_, '_, '_, 'info, WithdrawTreasury<'info>>) -> Result<()> {
+        let treasury_info = ctx.accounts.protocol_treasury.to_account_info();
+        let rent_exempt_minimum = Rent::get()?.minimum_balance(treasury_info.data_len());
+        let withdrawable = treasury_info.lamports().saturating_sub(rent_exempt_minimum);
+        if withdrawable > 0 {
+            **treasury_info.try_borrow_mut_lamports()? -= withdrawable;
+            **ctx.accounts.admin.to_account_info().try_borrow_mut_lamports()? += withdrawable;
+        }
+        
+        let mut withdrawn_tokens = 0;
+        if let (Some(protocol_treasury_vault), Some(admin_token_account), Some(treasury_mint), Some(token_program)) = (
+            ctx.accounts.protocol_treasury_vault.as_ref(),
+            ctx.accounts.admin_token_account.as_mut(),
+            ctx.accounts.treasury_mint.as_ref(),
+            ctx.accounts.token_program.as_ref(),
+        ) {
+            require_keys_eq!(protocol_treasury_vault.owner, treasury_info.key(), VoiceChatError::InvalidTreasuryVault);
+            let signer_seeds: &[&[u8]] = &[b"protocol_treasury", &[ctx.accounts.protocol_treasury.bump]];
+            withdrawn_tokens = transfer_tokens_checked(
+                &token_program.to_account_info(),
+                protocol_treasury_vault,
+                treasury_mint,
+                admin_token_account,
+                &treasury_info,
+                ctx.remaining_accounts,
+                protocol_treasury_vault.amount,
+                &[signer_seeds],
+            )?;
+        }
+        
+        msg!("Admin withdrew {} lamports and {} tokens from the protocol treasury", 
+             withdrawable, withdrawn_tokens);
+        Ok(())
+    }
+
+    /// Follow another wallet; the edge PDA and event let clients build social discovery
+    pub fn follow(ctx: Context<Follow>, target: Pubkey) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require_keys_neq!(target, ctx.accounts.follower.key(), VoiceChatError::CannotFollowSelf);
+        
+        let follow_edge = &mut ctx.accounts.follow_edge;
+        follow_edge.follower = ctx.accounts.follower.key();
+        follow_edge.target = target;
+        follow_edge.created_at = Clock::get()?.unix_timestamp;
+        follow_edge.bump = ctx.bumps.follow_edge;
+        
+        emit!(FollowChanged {
+            follower: follow_edge.follower,
+            target,
+            following: true,
+            timestamp: follow_edge.created_at,
+        });
+        
+        msg!("{} now follows {}", follow_edge.follower, target);
+        Ok(())
+    }
+
+    /// Unfollow a wallet, closing the edge PDA back to the follower
+    pub fn unfollow(ctx: Context<Unfollow>, target: Pubkey) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        
+        emit!(FollowChanged {
+            follower: ctx.accounts.follower.key(),
+            target,
+            following: false,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("{} unfollowed {}", ctx.accounts.follower.key(), target);
+        Ok(())
+    }
+
+    /// Block a wallet: direct calls and invites from it are refused while the block PDA exists
+    pub fn block(ctx: Context<Block>, target: Pubkey) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require_keys_neq!(target, ctx.accounts.blocker.key(), VoiceChatError::CannotBlockSelf);
+        
+        let block_entry = &mut ctx.accounts.block_entry;
+        block_entry.blocker = ctx.accounts.blocker.key();
+        block_entry.blocked = target;
+        block_entry.created_at = Clock::get()?.unix_timestamp;
+        block_entry.bump = ctx.bumps.block_entry;
+        
+        msg!("{} blocked {}", block_entry.blocker, target);
+        Ok(())
+    }
+
+    /// Lift a block, closing the block PDA back to the blocker
+    pub fn unblock(ctx: Context<Unblock>, target: Pubkey) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        
+        msg!("{} unblocked {}", ctx.accounts.blocker.key(), target);
+        Ok(())
+    }
+
+    /// Ring another wallet for a private 1:1 call on a storage lane allocated for the call (the
+    /// caller pays its rent until the call closes). Refused while the callee has blocked the caller.
+    pub fn initiate_call(ctx: Context<InitiateCall>, callee: Pubkey) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require_keys_neq!(callee, ctx.accounts.caller.key(), VoiceChatError::CannotCallSelf);
+        require_not_blocked(&ctx.accounts.callee_block)?;
+        
+        // The call gets a lane of its own; no other room's or wallet's storage can be named
+        let direct_call_key = ctx.accounts.direct_call.key();
+        alloc_owned_lane(
+            &OwnedLane {
+                program: &ctx.accounts.storage_program,
+                lane: &ctx.accounts.storage_lane,
+                authority: &ctx.accounts.lane_authority,
+                authority_seeds: &[b"call_lane", direct_call_key.as_ref(), &[ctx.bumps.lane_authority]],
+                system_program: &ctx.accounts.system_program,
+                protocol_config: &ctx.accounts.protocol_config,
+            },
+            &ctx.accounts.caller,
+        )?;
+        
+        let now = Clock::get()?.unix_timestamp;
+        let direct_call = &mut ctx.accounts.direct_call;
+        direct_call.caller = ctx.accounts.caller.key();
+        direct_call.callee = callee;
+        direct_call.status = CallStatus::Ringing;
+        direct_call.storage_lane = ctx.accounts.storage_lane.key();
+        direct_call.created_at = now;
+        direct_call.answered_at = 0;
+        direct_call.last_activity = now;
+        direct_call.bump = ctx.bumps.direct_call;
+        
+        emit!(DirectCallUpdated {
+            caller: direct_call.caller,
+            callee,
+            status: CallStatus::Ringing,
+            timestamp: now,
+        });
+        
+        msg!("{} is calling {}", direct_call.caller, callee);
+        Ok(())
+    }
+
+    /// Callee picks up a ringing call
+    pub fn accept_call(ctx: Context<AnswerCall>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let direct_call = &mut ctx.accounts.direct_call;
+        require!(direct_call.status == CallStatus::Ringing, VoiceChatError::CallNotRinging);
+        
+        let now = Clock::get()?.unix_timestamp;
+        direct_call.status = CallStatus::Active;
+        direct_call.answered_at = now;
+        direct_call.last_activity = now;
+        
+        emit!(DirectCallUpdated {
+            caller: direct_call.caller,
+            callee: direct_call.callee,
+            status: CallStatus::Active,
+            timestamp: now,
+        });
+        
+        msg!("Call between {} and {} is active", direct_call.caller, direct_call.callee);
+        Ok(())
+    }
+
+    /// Callee declines a ringing call; the call PDA's rent returns to the caller
+    pub fn reject_call(ctx: Context<RejectCall>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let direct_call = &ctx.accounts.direct_call;
+        require!(direct_call.status == CallStatus::Ringing, VoiceChatError::CallNotRinging);
+        
+        let direct_call_key = direct_call.key();
+        free_owned_lane(
+            &OwnedLane {
+                program: &ctx.accounts.storage_program,
+                lane: &ctx.accounts.storage_lane,
+                authority: &ctx.accounts.lane_authority,
+                authority_seeds: &[b"call_lane", direct_call_key.as_ref(), &[ctx.bumps.lane_authority]],
+                system_program: &ctx.accounts.system_program,
+                protocol_config: &ctx.accounts.protocol_config,
+            },
+            &ctx.accounts.caller,
+        )?;
+        
+        emit!(DirectCallUpdated {
+            caller: direct_call.caller,
+            callee: direct_call.callee,
+            status: CallStatus::Rejected,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("{} rejected the call from {}", direct_call.callee, direct_call.caller);
+        Ok(())
+    }
+
+    /// Either party hangs up (the caller may also cancel while ringing); rent returns to the caller
+    pub fn end_call(ctx: Context<EndCall>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let direct_call = &ctx.accounts.direct_call;
+        
+        let direct_call_key = direct_call.key();
+        free_owned_lane(
+            &OwnedLane {
+                program: &ctx.accounts.storage_program,
+                lane: &ctx.accounts.storage_lane,
+                authority: &ctx.accounts.lane_authority,
+                authority_seeds: &[b"call_lane", direct_call_key.as_ref(), &[ctx.bumps.lane_authority]],
+                system_program: &ctx.accounts.system_program,
+                protocol_config: &ctx.accounts.protocol_config,
+            },
+            &ctx.accounts.caller,
+        )?;
+        
+        emit!(DirectCallUpdated {
+            caller: direct_call.caller,
+            callee: direct_call.callee,
+            status: CallStatus::Ended,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Call between {} and {} ended by {}", 
+             direct_call.caller, direct_call.callee, ctx.accounts.party.key());
+        Ok(())
+    }
+
+    /// Send voice data on an active call's dedicated storage lane
+    pub fn send_call_voice_data(
+        ctx: Context<SendCallVoiceData>,
+        voice_data: Vec<u8>,
+        sequence_number: u32,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(voice_data.len() <= MAX_VOICE_DATA_SIZE, VoiceChatError::VoiceDataTooLarge);
+        let direct_call = &mut ctx.accounts.direct_call;
+        require!(direct_call.status == CallStatus::Active, VoiceChatError::CallNotActive);
+        
+        let storage_writer = StorageWriter {
+            program: &ctx.accounts.storage_program,
+            writer: &ctx.accounts.storage_writer,
+            writer_bump: ctx.bumps.storage_writer,
+            protocol_config: &ctx.accounts.protocol_config,
+        };
+        write_storage_chunk(&ctx.accounts.storage_lane, &storage_writer, &voice_data)?;
+        direct_call.last_activity = Clock::get()?.unix_timestamp;
+        
+        msg!("Call voice data sent: {} bytes by {}, sequence {}", 
+             voice_data.len(), ctx.accounts.sender.key(), sequence_number);
+        Ok(())
+    }
+
+    /// Create the wallet's call log, where missed calls are recorded
+    pub fn initialize_call_log(ctx: Context<InitializeCallLog>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let call_log = &mut ctx.accounts.call_log;
+        call_log.owner = ctx.accounts.owner.key();
+        call_log.missed_calls = Vec::new();
+        call_log.bump = ctx.bumps.call_log;
+        
+        msg!("Call log initialized for {}", call_log.owner);
+        Ok(())
+    }
+
+    /// Permissionless crank: close a call that rang out unanswered, record it in the callee's
+    /// call log (when they have one) and pay the cranker from the call PDA's rent
+    pub fn expire_call(ctx: Context<ExpireCall>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let direct_call = &ctx.accounts.direct_call;
+        require!(direct_call.status == CallStatus::Ringing, VoiceChatError::CallNotRinging);
+        let now = Clock::get()?.unix_timestamp;
+        require!(now - direct_call.created_at >= CALL_RING_TIMEOUT_SECONDS, VoiceChatError::CallStillRinging);
+        
+        let direct_call_key = direct_call.key();
+        free_owned_lane(
+            &OwnedLane {
+                program: &ctx.accounts.storage_program,
+                lane: &ctx.accounts.storage_lane,
+                authority: &ctx.accounts.lane_authority,
+                authority_seeds: &[b"call_lane", direct_call_key.as_ref(), &[ctx.bumps.lane_authority]],
+                system_program: &ctx.accounts.system_program,
+                protocol_config: &ctx.accounts.protocol_config,
+            },
+            &ctx.accounts.caller,
+        )?;
+        
+        if let Some(call_log) = ctx.accounts.call_log.as_mut() {
+            if call_log.missed_calls.len() == MAX_MISSED_CALLS {
+                call_log.missed_calls.remove(0);
+            }
+            call_log.missed_calls.push(MissedCall {
+                caller: direct_call.caller,
+                rang_at: direct_call.created_at,
+            });
+        }
+        
+        emit!(DirectCallUpdated {
+            caller: direct_call.caller,
+            callee: direct_call.callee,
+            status: CallStatus::Missed,
+            timestamp: now,
+        });
+        
+        pay_crank_reward(&direct_call.to_account_info(), &ctx.accounts.cranker.to_account_info())?;
+        
+        msg!("Call from {} to {} expired unanswered", direct_call.caller, direct_call.callee);
+        Ok(())
+    }
+
+    /// Open the wallet's voicemail inbox so others can leave voicemails
+    pub fn initialize_voicemail_inbox(ctx: Context<InitializeVoicemailInbox>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voicemail_inbox = &mut ctx.accounts.voicemail_inbox;
+        voicemail_inbox.owner = ctx.accounts.owner.key();
+        voicemail_inbox.next_voicemail_id = 0;
+        voicemail_inbox.unplayed_count = 0;
+        voicemail_inbox.bump = ctx.bumps.voicemail_inbox;
+        
+        msg!("Voicemail inbox opened for {}", voicemail_inbox.owner);
+        Ok(())
+    }
+
+    /// Write a voicemail into a storage lane allocated for it (the sender pays its rent until the
+    /// voicemail is deleted) and append a pointer record to the recipient's inbox.
+    /// Refused while the recipient has blocked the sender.
+    pub fn leave_voicemail(ctx: Context<LeaveVoicemail>, recipient: Pubkey, audio: Vec<u8>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(audio.len() <= MAX_VOICE_DATA_SIZE, VoiceChatError::VoiceDataTooLarge);
+        require_not_blocked(&ctx.accounts.recipient_block)?;
+        
+        // The voicemail gets a lane of its own, so deleting it can never wipe anyone else's audio
+        let sender_key = ctx.accounts.sender.key();
+        let id_bytes = ctx.accounts.voicemail_inbox.next_voicemail_id.to_le_bytes();
+        let backend = ctx.accounts.storage_program.as_ref()
+            .ok_or(VoiceChatError::InvalidStorageBackend)?;
+        require_keys_eq!(backend.key(), ctx.accounts.protocol_config.storage_backend(), VoiceChatError::InvalidStorageBackend);
+        alloc_owned_lane(
+            &OwnedLane {
+                program: backend,
+                lane: &ctx.accounts.storage_lane,
+                authority: &ctx.accounts.lane_authority,
+                authority_seeds: &[
+                    b"voicemail_lane",
+                    recipient.as_ref(),
+                    sender_key.as_ref(),
+                    &id_bytes,
+                    &[ctx.bumps.lane_authority],
+                ],
+                system_program: &ctx.accounts.system_program,
+                protocol_config: &ctx.accounts.protocol_config,
+            },
+            &ctx.accounts.sender,
+        )?;
+        
+        let storage_writer = StorageWriter {
+            program: &ctx.accounts.storage_program,
+            writer: &ctx.accounts.storage_writer,
+            writer_bump: ctx.bumps.storage_writer,
+            protocol_config: &ctx.accounts.protocol_config,
+        };
+        write_storage_chunk(&ctx.accounts.storage_lane, &storage_writer, &audio)?;
+        
+        let voicemail_inbox = &mut ctx.accounts.voicemail_inbox;
+        let voicemail = &mut ctx.accounts.voicemail;
+        voicemail.recipient = recipient;
+        voicemail.sender = ctx.accounts.sender.key();
+        voicemail.id = voicemail_inbox.next_voicemail_id;
+        voicemail.storage_lane = ctx.accounts.storage_lane.key();
+        voicemail.data_length = audio.len() as u32;
+        voicemail.left_at = Clock::get()?.unix_timestamp;
+        voicemail.played = false;
+        voicemail.bump = ctx.bumps.voicemail;
+        
+        voicemail_inbox.next_voicemail_id += 1;
+        voicemail_inbox.unplayed_count += 1;
+        
+        msg!("Voicemail {} left for {} by {} ({} bytes)", 
+             voicemail.id, recipient, voicemail.sender, audio.len());
+        Ok(())
+    }
+
+    /// Mark a voicemail as played
+    pub fn mark_played(ctx: Context<MarkPlayed>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voicemail = &mut ctx.accounts.voicemail;
+        if !voicemail.played {
+            voicemail.played = true;
+            ctx.accounts.voicemail_inbox.unplayed_count -= 1;
+        }
+        
+        msg!("Voicemail {} played by {}", voicemail.id, voicemail.recipient);
+        Ok(())
+    }
+
+    /// Delete a voicemail: close its record to the recipient and free its storage lane, whose
+    /// rent returns to the sender
+    pub fn delete_voicemail(ctx: Context<DeleteVoicemail>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voicemail = &ctx.accounts.voicemail;
+        if !voicemail.played {
+            ctx.accounts.voicemail_inbox.unplayed_count -= 1;
+        }
+        
+        let id_bytes = voicemail.id.to_le_bytes();
+        free_owned_lane(
+            &OwnedLane {
+                program: &ctx.accounts.storage_program,
+                lane: &ctx.accounts.storage_lane,
+                authority: &ctx.accounts.lane_authority,
+                authority_seeds: &[
+                    b"voicemail_lane",
+                    voicemail.recipient.as_ref(),
+                    voicemail.sender.as_ref(),
+                    &id_bytes,
+                    &[ctx.bumps.lane_authority],
+                ],
+                system_program: &ctx.accounts.system_program,
+                protocol_config: &ctx.accounts.protocol_config,
+            },
+            &ctx.accounts.sender,
+        )?;
+        
+        msg!("Voicemail {} deleted by {}", voicemail.id, voicemail.recipient);
+        Ok(())
+    }
+
+    /// Set how long a participant may go without a heartbeat before eviction (0 restores the default)
+    pub fn set_participant_timeout(ctx: Context<UpdateRoomConfig>, participant_timeout_seconds: i64) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(participant_timeout_seconds >= 0, VoiceChatError::InvalidParticipantTimeout);
+        let voice_room = &mut ctx.accounts.voice_room;
+        voice_room.participant_timeout_seconds = participant_timeout_seconds;
+        
+        msg!("Room '{}' participant timeout set to {}s", 
+             voice_room.room_id(), voice_room.participant_timeout());
+        Ok(())
+    }
+
+    /// Permissionless: remove a participant whose heartbeat is older than the room's timeout,
+    /// returning their rent (minus the crank reward) and keeping participant_count honest
+    pub fn evict_stale(ctx: Context<EvictStale>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let now = Clock::get()?.unix_timestamp;
+        let voice_room = &mut ctx.accounts.voice_room;
+        require!(
+            now - ctx.accounts.participant_account.last_seen > voice_room.participant_timeout(),
+            VoiceChatError::ParticipantNotStale
+        );
+        
+        // A running stream is settled up to the last heartbeat and stopped so it can be closed
+        if ctx.accounts.participant_account.has_stream {
+            let payment_stream = ctx.accounts.payment_stream.as_mut()
+                .ok_or(VoiceChatError::PaymentStreamRequired)?;
+            let host = ctx.accounts.host.as_ref()
+                .ok_or(VoiceChatError::PaymentStreamRequired)?;
+            if payment_stream.stopped_at == 0 {
+                let last_seen = ctx.accounts.participant_account.last_seen;
+                settle_payment_stream(payment_stream, &host.to_account_info(), last_seen)?;
+                payment_stream.stopped_at = last_seen;
+            }
+        }
+        
+        pay_crank_reward(
+            &ctx.accounts.participant_account.to_account_info(),
+            &ctx.accounts.cranker.to_account_info(),
+        )?;
+        
+        voice_room.participant_count = voice_room.participant_count.saturating_sub(1);
+        voice_room.release_slot(ctx.accounts.participant_account.slot);
+        if voice_room.participant_count == 0 {
+            voice_room.is_active = false;
+        }
+        
+        msg!("Evicted stale participant {} from room '{}'. Participants: {}", 
+             ctx.accounts.participant.key(), voice_room.room_id(), voice_room.participant_count);
+        Ok(())
+    }
+
+    /// Signal that the participant started speaking (drives active-speaker indicators)
+    pub fn start_speaking(ctx: Context<ParticipantSignal>) -> Result<()> {
+        require!(!ctx.accounts.participant_account.muted, VoiceChatError::ParticipantMuted);
+        emit!(SpeakingStateChanged {
+            room: ctx.accounts.voice_room.key(),
+            participant: ctx.accounts.participant.key(),
+            speaking: true,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Signal that the participant stopped speaking
+    pub fn stop_speaking(ctx: Context<ParticipantSignal>) -> Result<()> {
+        emit!(SpeakingStateChanged {
+            room: ctx.accounts.voice_room.key(),
+            participant: ctx.accounts.participant.key(),
+            speaking: false,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Create the room's chat log, which keeps the most recent text messages on-chain
+    pub fn initialize_chat_log(ctx: Context<InitializeChatLog>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let chat_log = &mut ctx.accounts.chat_log;
+        chat_log.room = ctx.accounts.voice_room.key();
+        chat_log.messages = Vec::new();
+        chat_log.bump = ctx.bumps.chat_log;
+        
+        msg!("Chat log created for room '{}'", ctx.accounts.voice_room.room_id());
+        Ok(())
+    }
+
+    /// Send a text message to the room's chat rail. Always emitted as an event; also kept in
+    /// the room's chat log when one is passed.
+    pub fn send_text_message(ctx: Context<SendTextMessage>, content: String) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(content.len() <= MAX_TEXT_MESSAGE_LENGTH, VoiceChatError::TextMessageTooLong);
+        require!(!ctx.accounts.sender_participant.muted, VoiceChatError::ParticipantMuted);
+        
+        let timestamp = Clock::get()?.unix_timestamp;
+        if let Some(chat_log) = ctx.accounts.chat_log.as_mut() {
+            if chat_log.messages.len() == MAX_CHAT_LOG_ENTRIES {
+                chat_log.messages.remove(0);
+            }
+            chat_log.messages.push(ChatEntry {
+                sender: ctx.accounts.sender.key(),
+                content: content.clone(),
+                timestamp,
+            });
+        }
+        
+        emit!(TextMessageSent {
+            room: ctx.accounts.voice_room.key(),
+            sender: ctx.accounts.sender.key(),
+            content,
+            timestamp,
+        });
+        Ok(())
+    }
+
+    /// React to a voice message with an emoji; event-only, so reactions cost no state
+    pub fn react(ctx: Context<ParticipantSignal>, emoji_code: u16, target_sequence: u64) -> Result<()> {
+        emit!(Reaction {
+            room: ctx.accounts.voice_room.key(),
+            participant: ctx.accounts.participant.key(),
+            emoji_code,
+            target_sequence,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Host starts a live audience poll that accepts votes for `duration` seconds
+    pub fn create_poll(ctx: Context<CreatePoll>, options: Vec<String>, duration: i64) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(options.len() >= 2 && options.len() <= MAX_POLL_OPTIONS, VoiceChatError::InvalidPollOptions);
+        require!(
+            options.iter().all(|option| option.len() <= MAX_POLL_OPTION_LENGTH),
+            VoiceChatError::InvalidPollOptions
+        );
+        require!(duration > 0, VoiceChatError::InvalidPollDuration);
+        
+        let voice_room = &mut ctx.accounts.voice_room;
+        let now = Clock::get()?.unix_timestamp;
+        let poll = &mut ctx.accounts.poll;
+        poll.room = voice_room.key();
+        poll.id = voice_room.poll_count;
+        poll.votes = vec![0; options.len()];
+        poll.options = options;
+        poll.created_at = now;
+        poll.ends_at = now + duration;
+        poll.closed = false;
+        poll.bump = ctx.bumps.poll;
+        voice_room.poll_count += 1;
+        
+        msg!("Poll {} opened in room '{}' with {} options", 
+             poll.id, voice_room.room_id(), poll.options.len());
+        Ok(())
+    }
+
+    /// Cast a participant's single vote on an open poll
+    pub fn vote(ctx: Context<Vote>, option: u8) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let poll = &mut ctx.accounts.poll;
+        require!(
+            !poll.closed && Clock::get()?.unix_timestamp < poll.ends_at,
+            VoiceChatError::PollClosed
+        );
+        require!((option as usize) < poll.options.len(), VoiceChatError::InvalidPollOption);
+        
+        poll.votes[option as usize] += 1;
+        
+        let poll_vote = &mut ctx.accounts.poll_vote;
+        poll_vote.poll = poll.key();
+        poll_vote.voter = ctx.accounts.voter.key();
+        poll_vote.option = option;
+        
+        msg!("Vote for option {} on poll {}", option, poll.id);
+        Ok(())
+    }
+
+    /// Host closes a poll and publishes the final tally
+    pub fn close_poll(ctx: Context<ClosePoll>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let poll = &mut ctx.accounts.poll;
+        require!(!poll.closed, VoiceChatError::PollClosed);
+        poll.closed = true;
+        
+        emit!(PollClosed {
+            room: poll.room,
+            poll_id: poll.id,
+            votes: poll.votes.clone(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Poll {} closed: {:?}", poll.id, poll.votes);
+        Ok(())
+    }
+
+    /// Host starts a new session, reactivating the room and resetting its summary stats
+    pub fn start_session(ctx: Context<StartSession>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        require!(!voice_room.session_running(), VoiceChatError::SessionAlreadyRunning);
+        
+        let now = Clock::get()?.unix_timestamp;
+        let session = &mut ctx.accounts.session;
+        session.room = voice_room.key();
+        session.id = voice_room.session_count;
+        session.started_at = now;
+        session.ended_at = 0;
+        session.peak_participants = voice_room.participant_count;
+        session.total_frames = 0;
+        session.total_bytes = 0;
+        session.bump = ctx.bumps.session;
+        
+        voice_room.session_count += 1;
+        voice_room.session_ended_at = 0;
+        voice_room.session_peak_participants = voice_room.participant_count;
+        voice_room.session_frames = 0;
+        voice_room.session_bytes = 0;
+        voice_room.is_active = true;
+        voice_room.last_activity = now;
+        
+        msg!("Session {} started in room '{}'", session.id, voice_room.room_id());
+        Ok(())
+    }
+
+    /// Host ends the session and snapshots its summary stats into the Session PDA. Participants
+    /// still present who joined by `attendance_cutoff` and stayed at least `min_attendance_seconds`
+    /// can then claim an attendance record.
+    pub fn end_session(
+        ctx: Context<EndSession>,
+        attendance_cutoff: i64,
+        min_attendance_seconds: i64,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        require!(voice_room.session_running(), VoiceChatError::SessionNotRunning);
+        
+        voice_room.session_ended_at = Clock::get()?.unix_timestamp;
+        voice_room.attendance_cutoff = attendance_cutoff;
+        voice_room.min_attendance_seconds = min_attendance_seconds;
+        voice_room.is_active = false;
+        
+        let session = &mut ctx.accounts.session;
+        session.ended_at = voice_room.session_ended_at;
+        session.peak_participants = voice_room.session_peak_participants;
+        session.total_frames = voice_room.session_frames;
+        session.total_bytes = voice_room.session_bytes;
+        
+        msg!("Session {} ended in room '{}': peak {} participants, {} frames, {} bytes", 
+             session.id, voice_room.room_id(), session.peak_participants, session.total_frames, session.total_bytes);
+        Ok(())
+    }
+
+    /// Claim proof of attendance for the ended session. The record is a PDA of this program,
+    /// so it cannot be transferred and other programs can verify it by address and owner.
+    pub fn claim_attendance(ctx: Context<ClaimAttendance>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &ctx.accounts.voice_room;
+        require!(voice_room.session_ended_at > 0, VoiceChatError::SessionNotEnded);
+        
+        let joined_at = ctx.accounts.participant_account.joined_at;
+        let duration_seconds = voice_room.session_ended_at - joined_at;
+        require!(
+            joined_at <= voice_room.attendance_cutoff && duration_seconds >= voice_room.min_attendance_seconds,
+            VoiceChatError::AttendanceRequirementsNotMet
+        );
+        
+        let attendance_record = &mut ctx.accounts.attendance_record;
+        attendance_record.room = voice_room.key();
+        attendance_record.attendee = ctx.accounts.attendee.key();
+        attendance_record.session_ended_at = voice_room.session_ended_at;
+        attendance_record.joined_at = joined_at;
+        attendance_record.duration_seconds = duration_seconds;
+        attendance_record.bump = ctx.bumps.attendance_record;
+        
+        msg!("Attendance recorded for {} in room '{}' ({}s)", 
+             attendance_record.attendee, voice_room.room_id(), duration_seconds);
+        Ok(())
+    }
+
+    /// Give or withdraw consent to being recorded. Withdrawing consent stops a running recording.
+    pub fn set_recording_consent(ctx: Context<SetRecordingConsent>, consent: bool) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        let slot_bit = 1u16 << ctx.accounts.participant_account.slot;
+        if consent {
+            voice_room.recording_consent |= slot_bit;
+        } else {
+            voice_room.recording_consent &= !slot_bit;
+            if voice_room.is_recording {
+                voice_room.is_recording = false;
+                emit!(RecordingStateChanged {
+                    room: voice_room.key(),
+                    recording: false,
+                    timestamp: Clock::get()?.unix_timestamp,
+                });
+            }
+        }
+        
+        msg!("{} recording consent in room '{}': {}", 
+             ctx.accounts.participant.key(), voice_room.room_id(), consent);
+        Ok(())
+    }
+
+    /// Host or recorder starts recording once every current participant has consented
+    pub fn start_recording(ctx: Context<RecordingControl>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        require!(!voice_room.is_recording, VoiceChatError::AlreadyRecording);
+        require!(voice_room.all_consented_to_recording(), VoiceChatError::RecordingConsentMissing);
+        voice_room.is_recording = true;
+        
+        emit!(RecordingStateChanged {
+            room: voice_room.key(),
+            recording: true,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Recording started in room '{}'", voice_room.room_id());
+        Ok(())
+    }
+
+    /// Host or recorder stops recording
+    pub fn stop_recording(ctx: Context<RecordingControl>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        require!(voice_room.is_recording, VoiceChatError::NotRecording);
+        voice_room.is_recording = false;
+        
+        emit!(RecordingStateChanged {
+            room: voice_room.key(),
+            recording: false,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Recording stopped in room '{}'", voice_room.room_id());
+        Ok(())
+    }
+
+    /// Host or recorder publishes the off-chain (Arweave/IPFS) recording of a session. The manifest is
+    /// write-once, so listeners can verify the archive against its content hash.
+    pub fn finalize_recording(
+        ctx: Context<FinalizeRecording>,
+        cid_or_tx_id: String,
+        content_hash: [u8; 32],
+        duration_seconds: u32,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(cid_or_tx_id.len() <= MAX_CONTENT_ID_LENGTH, VoiceChatError::ContentIdTooLong);
+        require!(!ctx.accounts.voice_room.is_recording, VoiceChatError::AlreadyRecording);
+        
+        let recording = &mut ctx.accounts.recording;
+        recording.room = ctx.accounts.voice_room.key();
+        recording.session = ctx.accounts.session.key();
+        recording.cid_or_tx_id = cid_or_tx_id;
+        recording.content_hash = content_hash;
+        recording.duration_seconds = duration_seconds;
+        recording.finalized_at = Clock::get()?.unix_timestamp;
+        recording.bump = ctx.bumps.recording;
+        
+        msg!("Recording of session {} finalized: {}", 
+             ctx.accounts.session.id, recording.cid_or_tx_id);
+        Ok(())
+    }
+
+    /// Host mints a highlight clip of a finalized recording as a 1/1 NFT. The off-chain metadata
+    /// at `uri` describes the clip; the ClipNft PDA pins the recording and time range on-chain and
+    /// royalties go to the room's revenue split recipients.
+    #[allow(clippy::too_many_arguments)]
+    pub fn mint_clip(
+        ctx: Context<MintClip>,
+        name: String,
+        symbol: String,
+        uri: String,
+        start_ms: u32,
+        end_ms: u32,
+        seller_fee_basis_points: u16,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(
+            start_ms < end_ms && end_ms as u64 <= ctx.accounts.recording.duration_seconds as u64 * 1000,
+            VoiceChatError::InvalidClipRange
+        );
+        let shares = &ctx.accounts.revenue_split.shares;
+        require!(shares.len() <= MAX_METADATA_CREATORS, VoiceChatError::TooManyRoyaltyRecipients);
+        
+        // Creator shares are whole percentages; rounding dust goes to the first recipient
+        let creators = split_by_bps(100, shares)
+            .into_iter()
+            .zip(shares.iter())
+            .map(|(percent, share)| Creator {
+                address: share.recipient,
+                verified: false,
+                share: percent as u8,
+            })
+            .collect();
+        
+        token_interface::mint_to(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.clip_mint.to_account_info(),
+                    to: ctx.accounts.host_token_account.to_account_info(),
+                    authority: ctx.accounts.host.to_account_info(),
+                },
+            ),
+            1,
+        )?;
+        
+        create_metadata_accounts_v3(
+            CpiContext::new(
+                ctx.accounts.token_metadata_program.to_account_info(),
+                CreateMetadataAccountsV3 {
+                    metadata: ctx.accounts.metadata.to_account_info(),
+                    mint: ctx.accounts.clip_mint.to_account_info(),
+                    mint_authority: ctx.accounts.host.to_account_info(),
+                    payer: ctx.accounts.host.to_account_info(),
+                    update_authority: ctx.accounts.host.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    rent: ctx.accounts.rent.to_account_info(),
+                },
+            ),
+            DataV2 {
+                name,
+                symbol,
+                uri,
+                seller_fee_basis_points,
+                creators: Some(creators),
+                collection: None,
+                uses: None,
+            },
+            false,
+            true,
+            None,
+        )?;
+        
+        // Master edition with zero supply makes the clip a 1/1 and takes over mint authority
+        create_master_edition_v3(
+            CpiContext::new(
+                ctx.accounts.token_metadata_program.to_account_info(),
+                CreateMasterEditionV3 {
+                    edition: ctx.accounts.master_edition.to_account_info(),
+                    mint: ctx.accounts.clip_mint.to_account_info(),
+                    update_authority: ctx.accounts.host.to_account_info(),
+                    mint_authority: ctx.accounts.host.to_account_info(),
+                    payer: ctx.accounts.host.to_account_info(),
+                    metadata: ctx.accounts.metadata.to_account_info(),
+                    token_program: ctx.accounts.token_program.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    rent: ctx.accounts.rent.to_account_info(),
+                },
+            ),
+            Some(0),
+        )?;
+        
+        let clip_nft = &mut ctx.accounts.clip_nft;
+        clip_nft.mint = ctx.accounts.clip_mint.key();
+        clip_nft.recording = ctx.accounts.recording.key();
+        clip_nft.start_ms = start_ms;
+        clip_nft.end_ms = end_ms;
+        clip_nft.bump = ctx.bumps.clip_nft;
+        
+        msg!("Minted clip {} of recording {} ({}ms-{}ms)", 
+             clip_nft.mint, clip_nft.recording, start_ms, end_ms);
+        Ok(())
+    }
+
+    /// Transcription oracle attests a transcript of an ended session. Clients fetch `uri` and
+    /// check it against `hash` before indexing or displaying it.
+    pub fn submit_transcript(
+        ctx: Context<SubmitTranscript>,
+        uri: String,
+        hash: [u8; 32],
+        provider: String,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(uri.len() <= MAX_TRANSCRIPT_URI_LENGTH, VoiceChatError::ContentIdTooLong);
+        require!(provider.len() <= MAX_TRANSCRIPT_PROVIDER_LENGTH, VoiceChatError::ProviderNameTooLong);
+        require!(ctx.accounts.session.ended_at > 0, VoiceChatError::SessionNotEnded);
+        
+        let transcript = &mut ctx.accounts.transcript;
+        transcript.session = ctx.accounts.session.key();
+        transcript.oracle = ctx.accounts.oracle.key();
+        transcript.uri = uri;
+        transcript.hash = hash;
+        transcript.provider = provider;
+        transcript.submitted_at = Clock::get()?.unix_timestamp;
+        transcript.bump = ctx.bumps.transcript;
+        
+        msg!("Transcript for session {} submitted by {}: {}", 
+             transcript.session, transcript.provider, transcript.uri);
+        Ok(())
+    }
+
+    /// Offer a WebRTC session to `target`. The SDP blob itself lives in `storage_slot`; the PDA
+    /// only carries its hash so the answerer can verify what it fetched.
+    pub fn post_sdp_offer(
+        ctx: Context<PostSdpOffer>,
+        target: Pubkey,
+        blob_hash: [u8; 32],
+        storage_slot: Pubkey,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require_keys_neq!(target, ctx.accounts.offerer.key(), VoiceChatError::CannotCallSelf);
+        require_not_blocked(&ctx.accounts.target_block)?;
+        
+        let now = Clock::get()?.unix_timestamp;
+        let sdp_signal = &mut ctx.accounts.sdp_signal;
+        sdp_signal.offerer = ctx.accounts.offerer.key();
+        sdp_signal.answerer = target;
+        sdp_signal.offer_hash = blob_hash;
+        sdp_signal.offer_slot = storage_slot;
+        sdp_signal.answer_hash = None;
+        sdp_signal.answer_slot = None;
+        sdp_signal.created_at = now;
+        sdp_signal.bump = ctx.bumps.sdp_signal;
+        
+        emit!(SdpPosted {
+            offerer: sdp_signal.offerer,
+            answerer: target,
+            is_answer: false,
+            blob_hash,
+            storage_slot,
+            timestamp: now,
+        });
+        
+        msg!("SDP offer from {} to {}", sdp_signal.offerer, target);
+        Ok(())
+    }
+
+    /// Target answers a pending SDP offer
+    pub fn post_sdp_answer(
+        ctx: Context<PostSdpAnswer>,
+        blob_hash: [u8; 32],
+        storage_slot: Pubkey,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let sdp_signal = &mut ctx.accounts.sdp_signal;
+        require!(sdp_signal.answer_hash.is_none(), VoiceChatError::SdpAlreadyAnswered);
+        
+        sdp_signal.answer_hash = Some(blob_hash);
+        sdp_signal.answer_slot = Some(storage_slot);
+        
+        emit!(SdpPosted {
+            offerer: sdp_signal.offerer,
+            answerer: sdp_signal.answerer,
+            is_answer: true,
+            blob_hash,
+            storage_slot,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("SDP answer from {} to {}", sdp_signal.answerer, sdp_signal.offerer);
+        Ok(())
+    }
+
+    /// Either peer closes the signaling PDA once the handshake is done; rent returns to the offerer
+    pub fn close_sdp_signal(ctx: Context<CloseSdpSignal>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        
+        msg!("SDP signal between {} and {} closed", 
+             ctx.accounts.sdp_signal.offerer, ctx.accounts.sdp_signal.answerer);
+        Ok(())
+    }
+
+    /// Trickle ICE candidates to `target`. Each post replaces the previous batch; peers follow
+    /// IceCandidatesPosted events rather than polling the PDA.
+    pub fn post_ice_candidates(
+        ctx: Context<PostIceCandidates>,
+        target: Pubkey,
+        candidates_blob: Vec<u8>,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require_keys_neq!(target, ctx.accounts.sender.key(), VoiceChatError::CannotCallSelf);
+        require!(candidates_blob.len() <= MAX_ICE_CANDIDATES_LENGTH, VoiceChatError::IceCandidatesTooLarge);
+        require_not_blocked(&ctx.accounts.target_block)?;
+        
+        let now = Clock::get()?.unix_timestamp;
+        let ice_candidates = &mut ctx.accounts.ice_candidates;
+        ice_candidates.sender = ctx.accounts.sender.key();
+        ice_candidates.target = target;
+        ice_candidates.candidates = candidates_blob.clone();
+        ice_candidates.updated_at = now;
+        ice_candidates.bump = ctx.bumps.ice_candidates;
+        
+        emit!(IceCandidatesPosted {
+            sender: ice_candidates.sender,
+            target,
+            candidates: candidates_blob,
+            timestamp: now,
+        });
+        
+        msg!("ICE candidates from {} to {}", ice_candidates.sender, target);
+        Ok(())
+    }
+
+    /// Either peer closes the candidate PDA once connected; rent returns to the poster
+    pub fn close_ice_candidates(ctx: Context<CloseIceCandidates>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        
+        msg!("ICE candidates from {} to {} closed", 
+             ctx.accounts.ice_candidates.sender, ctx.accounts.ice_candidates.target);
+        Ok(())
+    }
+
+    /// Register as a relay/TURN operator, locking at least MIN_RELAY_STAKE_LAMPORTS
+    pub fn register_relay(
+        ctx: Context<RegisterRelay>,
+        endpoint_hash: [u8; 32],
+        region: u8,
+        stake_lamports: u64,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(stake_lamports >= MIN_RELAY_STAKE_LAMPORTS, VoiceChatError::InsufficientRelayStake);
+        
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.operator.to_account_info(),
+                    to: ctx.accounts.relay_node.to_account_info(),
+                },
+            ),
+            stake_lamports,
+        )?;
+        
+        let now = Clock::get()?.unix_timestamp;
+        let relay_node = &mut ctx.accounts.relay_node;
+        relay_node.operator = ctx.accounts.operator.key();
+        relay_node.endpoint_hash = endpoint_hash;
+        relay_node.region = region;
+        relay_node.stake_lamports = stake_lamports;
+        relay_node.registered_at = now;
+        relay_node.last_heartbeat = now;
+        relay_node.report_count = 0;
+        relay_node.slashed = false;
+        relay_node.deregistered_at = 0;
+        relay_node.bump = ctx.bumps.relay_node;
+        
+        msg!("Relay {} registered in {} with {} lamports staked", 
+             relay_node.operator, relay_node.region, stake_lamports);
+        Ok(())
+    }
+
+    /// Operator liveness ping; also lets the operator rotate its endpoint
+    pub fn relay_heartbeat(ctx: Context<RelayHeartbeat>, endpoint_hash: [u8; 32]) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let relay_node = &mut ctx.accounts.relay_node;
+        require!(relay_node.deregistered_at == 0, VoiceChatError::RelayNotActive);
+        relay_node.endpoint_hash = endpoint_hash;
+        relay_node.last_heartbeat = Clock::get()?.unix_timestamp;
+        
+        msg!("Relay {} heartbeat", relay_node.operator);
+        Ok(())
+    }
+
+    /// Stop serving; the stake stays slashable for STAKE_COOLDOWN_SECONDS
+    pub fn deregister_relay(ctx: Context<RelayHeartbeat>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let relay_node = &mut ctx.accounts.relay_node;
+        require!(relay_node.deregistered_at == 0, VoiceChatError::RelayNotActive);
+        relay_node.deregistered_at = Clock::get()?.unix_timestamp;
+        
+        msg!("Relay {} deregistered", relay_node.operator);
+        Ok(())
+    }
+
+    /// Close a deregistered relay once its cooldown has passed, returning the remaining stake
+    pub fn withdraw_relay_stake(ctx: Context<WithdrawRelayStake>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let relay_node = &ctx.accounts.relay_node;
+        require!(relay_node.deregistered_at > 0, VoiceChatError::StakeLocked);
+        require!(
+            Clock::get()?.unix_timestamp >= relay_node.deregistered_at + STAKE_COOLDOWN_SECONDS,
+            VoiceChatError::StakeLocked
+        );
+        
+        msg!("Relay {} withdrew {} lamports of stake", 
+             relay_node.operator, relay_node.stake_lamports);
+        Ok(())
+    }
+
+    /// Host pins a live relay for the room, or clears the pin when no relay is passed
+    pub fn set_room_relay(ctx: Context<SetRoomRelay>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let now = Clock::get()?.unix_timestamp;
+        let preferred_relay = match ctx.accounts.relay_node.as_ref() {
+            Some(relay_node) => {
+                require!(relay_node.is_live(now), VoiceChatError::RelayNotActive);
+                require!(
+                    ctx.accounts.voice_room.region == 0 || relay_node.region == ctx.accounts.voice_room.region,
+                    VoiceChatError::RelayRegionMismatch
+                );
+                Some(relay_node.key())
+            }
+            None => None,
+        };
+        
+        let voice_room = &mut ctx.accounts.voice_room;
+        voice_room.preferred_relay = preferred_relay;
+        
+        msg!("Room '{}' preferred relay: {:?}", voice_room.room_id(), preferred_relay);
+        Ok(())
+    }
+
+    /// Set the region the room's audience is in (0 = anywhere); pinned relays must then serve it
+    pub fn set_room_region(ctx: Context<UpdateRoomConfig>, region: u8) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        voice_room.region = region;
+        
+        msg!("Room '{}' region set to {}", voice_room.room_id(), region);
+        Ok(())
+    }
+
+    /// Filter candidate relays (passed via remaining_accounts, e.g. from a getProgramAccounts
+    /// query on RELAY_NODE_REGION_OFFSET) down to live ones in the room's region. Their keys
+    /// are logged and returned as return data.
+    pub fn get_region_relays(ctx: Context<GetRegionRelays>) -> Result<()> {
+        let voice_room = &ctx.accounts.voice_room;
+        let now = Clock::get()?.unix_timestamp;
+        let relays: Vec<Pubkey> = ctx.remaining_accounts.iter()
+            .filter(|info| info.owner == &crate::ID)
+            .filter_map(|info| Account::<RelayNode>::try_from(info).ok())
+            .filter(|relay_node| {
+                relay_node.is_live(now) && (voice_room.region == 0 || relay_node.region == voice_room.region)
+            })
+            .map(|relay_node| relay_node.key())
+            .collect();
+        
+        msg!("Room '{}' region {}: {} live relays {:?}", voice_room.room_id(), voice_room.region, relays.len(), relays);
+        let return_data = relays.try_to_vec()
+            .map_err(|_| anchor_lang::error::ErrorCode::AccountDidNotSerialize)?;
+        anchor_lang::solana_program::program::set_return_data(&return_data);
+        Ok(())
+    }
+
+    /// Participant of a room pinned to a relay reports it. Each user can report a relay once.
+    /// Reports alone never slash: participants are cheap to create, so at RELAY_REPORT_QUORUM
+    /// reports the protocol admin rules on them with adjudicate_relay_reports.
+    pub fn report_relay(ctx: Context<ReportRelay>, reason_code: u8) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let relay_report = &mut ctx.accounts.relay_report;
+        relay_report.relay = ctx.accounts.relay_node.key();
+        relay_report.reporter = ctx.accounts.reporter.key();
+        relay_report.room = ctx.accounts.voice_room.key();
+        relay_report.reason_code = reason_code;
+        relay_report.created_at = Clock::get()?.unix_timestamp;
+        
+        let relay_node = &mut ctx.accounts.relay_node;
+        relay_node.report_count += 1;
+        
+        msg!("Relay {} reported (reason {}, {}/{})", 
+             relay_node.operator, reason_code, relay_node.report_count, RELAY_REPORT_QUORUM);
+        Ok(())
+    }
+
+    /// Protocol admin rules on a relay that reached RELAY_REPORT_QUORUM reports: upholding
+    /// slashes part of its stake into the protocol treasury, dismissing clears the count
+    pub fn adjudicate_relay_reports(ctx: Context<AdjudicateRelayReports>, uphold: bool) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let relay_key = ctx.accounts.relay_node.key();
+        let relay_node = &mut ctx.accounts.relay_node;
+        require!(!relay_node.slashed, VoiceChatError::RelayNotActive);
+        require!(relay_node.report_count >= RELAY_REPORT_QUORUM, VoiceChatError::RelayReportsBelowQuorum);
+        
+        if !uphold {
+            relay_node.report_count = 0;
+            msg!("Reports against relay {} dismissed", relay_node.operator);
+            return Ok(());
+        }
+        relay_node.slashed = true;
+        
+        let slashed = (relay_node.stake_lamports as u128 * RELAY_SLASH_BPS as u128 / 10_000) as u64;
+        **relay_node.to_account_info().try_borrow_mut_lamports()? -= slashed;
+        **ctx.accounts.protocol_treasury.to_account_info().try_borrow_mut_lamports()? += slashed;
+        relay_node.stake_lamports -= slashed;
+        ctx.accounts.protocol_treasury.lamports_collected += slashed;
+        
+        emit!(RelaySlashed {
+            relay: relay_key,
+            operator: relay_node.operator,
+            report_count: relay_node.report_count,
+            slashed_lamports: slashed,
+            timestamp: now,
+        });
+        
+        msg!("Relay {} slashed {} lamports", relay_node.operator, slashed);
+        Ok(())
+    }
+
+    /// Pinned relay attests what it delivered in an ended session: a merkle root over the
+    /// sequence numbers it forwarded. Reward distribution and dropped-audio disputes check
+    /// inclusion proofs against this root.
+    pub fn attest_delivery(
+        ctx: Context<AttestDelivery>,
+        merkle_root: [u8; 32],
+        frames_delivered: u64,
+        first_sequence: u64,
+        last_sequence: u64,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(ctx.accounts.session.ended_at > 0, VoiceChatError::SessionNotEnded);
+        require!(first_sequence <= last_sequence, VoiceChatError::InvalidSequenceRange);
+        
+        let attestation = &mut ctx.accounts.delivery_attestation;
+        attestation.session = ctx.accounts.session.key();
+        attestation.relay = ctx.accounts.relay_node.key();
+        attestation.merkle_root = merkle_root;
+        attestation.frames_delivered = frames_delivered;
+        attestation.first_sequence = first_sequence;
+        attestation.last_sequence = last_sequence;
+        attestation.submitted_at = Clock::get()?.unix_timestamp;
+        attestation.bump = ctx.bumps.delivery_attestation;
+        
+        emit!(DeliveryAttested {
+            session: attestation.session,
+            relay: attestation.relay,
+            merkle_root,
+            frames_delivered,
+            timestamp: attestation.submitted_at,
+        });
+        
+        msg!("Relay {} attested {} frames for session {}", 
+             ctx.accounts.operator.key(), frames_delivered, ctx.accounts.session.id);
+        Ok(())
+    }
+
+    /// Delegate send authority to an ephemeral keypair until `expiry`, limited to `scope`
+    /// (SESSION_SCOPE_* bits). The client keeps the keypair in memory and funds it for fees.
+    pub fn create_session_key(ctx: Context<CreateSessionKey>, expiry: i64, scope: u8) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            expiry > now && expiry - now <= MAX_SESSION_KEY_SECONDS,
+            VoiceChatError::InvalidSessionKeyExpiry
+        );
+        
+        let session_key = &mut ctx.accounts.session_key;
+        session_key.owner = ctx.accounts.owner.key();
+        session_key.session_signer = ctx.accounts.session_signer.key();
+        session_key.expiry = expiry;
+        session_key.scope = scope;
+        session_key.bump = ctx.bumps.session_key;
+        
+        msg!("Session key {} for {} valid until {}", 
+             session_key.session_signer, session_key.owner, expiry);
+        Ok(())
+    }
+
+    /// Revoke a session key before it expires (or clean up an expired one)
+    pub fn revoke_session_key(ctx: Context<RevokeSessionKey>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        
+        msg!("Session key {} for {} revoked", 
+             ctx.accounts.session_key.session_signer, ctx.accounts.owner.key());
+        Ok(())
+    }
+
+    /// Gasless send: a relayer signs and pays while the frame is authorized by the sender's
+    /// Ed25519 signature over `sponsored_send_message`, verified by the Ed25519 program in the
+    /// instruction just before this one. Sequence numbers must increase per sender within an
+    /// epoch, which stops replays.
+    pub fn send_voice_data_sponsored(
+        ctx: Context<SendVoiceDataSponsored>,
+        voice_data: Vec<u8>,
+        target_pda_index: u8,
+        sequence_number: u32,
+        capture_timestamp_ms: i64,
+        duration_ms: u16,
+        force: bool,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        check_capture_timestamp(capture_timestamp_ms)?;
+        require!(voice_data.len() <= ctx.accounts.voice_room.max_frame_bytes(), VoiceChatError::VoiceDataTooLarge);
+        check_codec(&ctx.accounts.voice_room, &voice_data)?;
+        check_frame_quality(&ctx.accounts.voice_room, voice_data.len(), duration_ms)?;
+        let message = sponsored_send_message(
+            &ctx.accounts.voice_room.key(),
+            target_pda_index,
+            ctx.accounts.sender_participant.sequence_epoch,
+            sequence_number,
+            capture_timestamp_ms,
+            duration_ms,
+            force,
+            &voice_data,
+        );
+        verify_ed25519_signature(&ctx.accounts.instructions_sysvar, &ctx.accounts.sender.key(), &message)?;
+        check_sender(
+            &ctx.accounts.voice_room,
+            &ctx.accounts.sender_participant,
+            ctx.accounts.sender_stake.as_ref(),
+            ctx.accounts.sender_reputation.as_ref(),
+        )?;
+        require!((target_pda_index as usize) < STORAGE_LANE_COUNT, VoiceChatError::InvalidStoragePDA);
+        check_room_lane(
+            &ctx.accounts.voice_room,
+            &ctx.accounts.storage_pda,
+            target_pda_index,
+            &ctx.accounts.protocol_config,
+        )?;
+        let recent_sends = &mut ctx.accounts.recent_sends;
+        recent_sends.room = ctx.accounts.voice_room.key();
+        recent_sends.sender = ctx.accounts.sender.key();
+        recent_sends.bump = ctx.bumps.recent_sends;
+        recent_sends.track_epoch(ctx.accounts.sender_participant.sequence_epoch)?;
+        // A signed frame can only be relayed once
+        require!(sequence_number > recent_sends.last_sponsored_sequence, VoiceChatError::DuplicateSend);
+        recent_sends.last_sponsored_sequence = sequence_number;
+        check_stage_lane(
+            &ctx.accounts.voice_room,
+            &ctx.accounts.sender_participant,
+            &mut ctx.accounts.recent_sends,
+            &[target_pda_index],
+        )?;
+        check_lanes_consumed(&ctx.accounts.voice_room, &[target_pda_index], force)?;
+        check_lane_allocations(&ctx.accounts.voice_room, &ctx.accounts.sender_participant, &[target_pda_index])?;
+        
+        meter_storage_write(
+            &ctx.accounts.voice_room,
+            ctx.accounts.bandwidth_allowance.as_mut(),
+            voice_data.len(),
+        )?;
+        let storage_writer = StorageWriter {
+            program: &ctx.accounts.storage_program,
+            writer: &ctx.accounts.storage_writer,
+            writer_bump: ctx.bumps.storage_writer,
+            protocol_config: &ctx.accounts.protocol_config,
+        };
+        write_storage_chunk(&ctx.accounts.storage_pda, &storage_writer, &voice_data)?;
+        journal_storage_write(
+            &ctx.accounts.voice_room,
+            ctx.accounts.write_journal.as_deref_mut(),
+            &ctx.accounts.storage_pda,
+            &voice_data,
+        )?;
+        
+        let voice_message = &mut ctx.accounts.voice_message;
+        voice_message.sender = ctx.accounts.sender.key();
+        voice_message.room_id = ctx.accounts.voice_room.room_id().to_string();
+        voice_message.storage_pda_index = target_pda_index;
+        voice_message.sequence_number = sequence_number;
+        voice_message.data_length = voice_data.len() as u32;
+        voice_message.timestamp = Clock::get()?.unix_timestamp;
+        voice_message.capture_timestamp_ms = capture_timestamp_ms;
+        voice_message.sequence_epoch = ctx.accounts.sender_participant.sequence_epoch;
+        voice_message.storage_pda = ctx.accounts.storage_pda.key();
+        voice_message.content_hash = anchor_lang::solana_program::keccak::hash(&voice_data).to_bytes();
+        ctx.accounts.sender_participant.extend_contiguous(sequence_number, sequence_number);
+        
+        let voice_room = &mut ctx.accounts.voice_room;
+        voice_room.last_activity = voice_message.timestamp;
+        voice_room.record_lane_write(target_pda_index, ctx.accounts.sender_participant.slot, sequence_number);
+        voice_room.record_traffic(1, voice_data.len());
+        voice_room.message_count += 1;
+        ctx.accounts.message_index_page.append(voice_room, voice_message, ctx.bumps.message_index_page);
+        if let Some(sender_stats) = ctx.accounts.sender_stats.as_mut() {
+            sender_stats.record_message(voice_data.len());
+        }
+        
+        msg!("Sponsored voice data sent for {} by relayer {}: {} bytes, sequence {}", 
+             voice_message.sender, ctx.accounts.relayer.key(), voice_data.len(), sequence_number);
+        Ok(())
+    }
+
+    /// Upgrade a room created under an older layout: grow it to the current size (the payer
+    /// covers the extra rent), re-lay string-prefixed rooms into the fixed header and stamp
+    /// the current version. Fields added since are read as zero. Anyone can pay for this.
+    pub fn migrate_voice_room(ctx: Context<MigrateVoiceRoom>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let room_info = ctx.accounts.voice_room.to_account_info();
+        require!(
+            room_info.try_borrow_data()?.starts_with(VoiceRoom::DISCRIMINATOR),
+            VoiceChatError::InvalidAccountVersion
+        );
+        let old_len = room_info.data_len();
+        let string_prefixed = old_len == LEGACY_VOICE_ROOM_V4_SPACE || old_len <= LEGACY_VOICE_ROOM_V3_SPACE;
+        require!(string_prefixed || old_len < VoiceRoom::SPACE, VoiceChatError::InvalidAccountVersion);
+        let old_data = room_info.try_borrow_data()?.to_vec();
+        grow_account(
+            &room_info,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            VoiceRoom::SPACE,
+        )?;
+        if string_prefixed {
+            rewrite_legacy_voice_room(&old_data, &mut room_info.try_borrow_mut_data()?)?;
+        }
+        
+        let mut voice_room = Account::<VoiceRoom>::try_from(&room_info)?;
+        require!(voice_room.version < VOICE_ROOM_VERSION, VoiceChatError::InvalidAccountVersion);
+        let from_version = voice_room.version;
+        voice_room.version = VOICE_ROOM_VERSION;
+        // Only rooms under the plain room_id seeds can be re-derived here; breakouts and
+        // occurrences migrated from before version 28 keep a zero bump
+        if voice_room.bump == 0 {
+            let (address, bump) = Pubkey::find_program_address(
+                &[b"voice_room", voice_room.room_id().as_bytes()],
+                &crate::ID,
+            );
+            if address == room_info.key() {
+                voice_room.bump = bump;
+            }
+        }
+        voice_room.exit(&crate::ID)?;
+        
+        msg!("Room '{}' migrated from version {} to {}", 
+             voice_room.room_id(), from_version, VOICE_ROOM_VERSION);
+        Ok(())
+    }
+
+    /// Set the room's discovery category and tags (0 clears a slot)
+    pub fn set_room_tags(ctx: Context<UpdateRoomConfig>, category: u16, tags: [u16; MAX_ROOM_TAGS]) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        require!(!voice_room.flagged, VoiceChatError::RoomFlagged);
+        voice_room.category = category;
+        voice_room.tags = tags;
+        
+        msg!("Room '{}' category {} tags {:?}", voice_room.room_id(), category, tags);
+        Ok(())
+    }
+
+    /// Cap the size of a single frame in this room (0 restores the MAX_VOICE_DATA_SIZE default),
+    /// e.g. small Opus frames for low-bandwidth rooms
+    pub fn set_max_frame_bytes(ctx: Context<UpdateRoomConfig>, max_frame_bytes: u32) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(max_frame_bytes as usize <= MAX_VOICE_DATA_SIZE, VoiceChatError::VoiceDataTooLarge);
+        let voice_room = &mut ctx.accounts.voice_room;
+        voice_room.max_frame_bytes = max_frame_bytes;
+        
+        msg!("Room '{}' max frame size set to {} bytes", 
+             voice_room.room_id(), voice_room.max_frame_bytes());
+        Ok(())
+    }
+
+    /// Declare the codecs the room accepts as a bitmask of codec ids (0 accepts anything).
+    /// Frames must then lead with their codec id and joiners must support one of them.
+    pub fn set_accepted_codecs(ctx: Context<UpdateRoomConfig>, accepted_codecs: u16) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        voice_room.accepted_codecs = accepted_codecs;
+        
+        msg!("Room '{}' accepted codecs set to {:#06x}", voice_room.room_id(), accepted_codecs);
+        Ok(())
+    }
+
+    /// Trade latency against quality: cap frame duration and bitrate (0 lifts either cap).
+    /// Frames are checked against these through the duration their sender declares; clips
+    /// span many frames, so only their bitrate is held to the cap.
+    pub fn set_audio_quality(
+        ctx: Context<UpdateRoomConfig>,
+        target_bitrate_kbps: u16,
+        max_frame_duration_ms: u16,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        voice_room.target_bitrate_kbps = target_bitrate_kbps;
+        voice_room.max_frame_duration_ms = max_frame_duration_ms;
+        
+        msg!("Room '{}' audio quality: {} kbps, frames up to {} ms", 
+             voice_room.room_id(), target_bitrate_kbps, max_frame_duration_ms);
+        Ok(())
+    }
+
+    /// Reserve a storage lane for the host and stage speakers (None frees it) and set the minimum
+    /// slots between sends for the stage and the audience (0 = unlimited). Under contention the
+    /// audience is throttled first while stage audio keeps its own lane.
+    pub fn set_stage_lanes(
+        ctx: Context<UpdateRoomConfig>,
+        stage_lane: Option<u8>,
+        stage_send_interval_slots: u16,
+        audience_send_interval_slots: u16,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(stage_lane.map_or(true, |lane| (lane as usize) < STORAGE_LANE_COUNT), VoiceChatError::InvalidStoragePDA);
+        let voice_room = &mut ctx.accounts.voice_room;
+        voice_room.stage_lane = stage_lane;
+        voice_room.stage_send_interval_slots = stage_send_interval_slots;
+        voice_room.audience_send_interval_slots = audience_send_interval_slots;
+        
+        msg!("Room '{}' stage lane {:?}, send interval {} slots (stage) / {} slots (audience)", 
+             voice_room.room_id(), stage_lane, stage_send_interval_slots, audience_send_interval_slots);
+        Ok(())
+    }
+
+    /// Any host adds another wallet to the room's host set
+    pub fn add_host(ctx: Context<UpdateRoomConfig>, new_host: Pubkey) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        require!(new_host != Pubkey::default() && !voice_room.is_host(&new_host), VoiceChatError::InvalidHost);
+        let slot = voice_room.co_hosts.iter_mut()
+            .find(|co_host| **co_host == Pubkey::default())
+            .ok_or(VoiceChatError::TooManyHosts)?;
+        *slot = new_host;
+        
+        msg!("{} added {} as a host of room '{}'", ctx.accounts.host.key(), new_host, voice_room.room_id());
+        Ok(())
+    }
+
+    /// Any host removes a host, themselves included. Removing the primary host hands that role
+    /// to a co-host, so the room always keeps at least one host.
+    pub fn remove_host(ctx: Context<UpdateRoomConfig>, removed_host: Pubkey) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        require!(voice_room.is_host(&removed_host), VoiceChatError::InvalidHost);
+        
+        let slot = if removed_host == voice_room.host {
+            let successor = voice_room.co_hosts.iter()
+                .position(|co_host| *co_host != Pubkey::default())
+                .ok_or(VoiceChatError::LastHost)?;
+            voice_room.host = voice_room.co_hosts[successor];
+            successor
+        } else {
+            voice_room.co_hosts.iter()
+                .position(|co_host| *co_host == removed_host)
+                .ok_or(VoiceChatError::InvalidHost)?
+        };
+        voice_room.co_hosts[slot] = Pubkey::default();
+        
+        msg!("{} removed {} as a host of room '{}'; primary host is {}", 
+             ctx.accounts.host.key(), removed_host, voice_room.room_id(), voice_room.host);
+        Ok(())
+    }
+
+    /// Raise the room's capacity, even mid-session. The per-slot bitmaps are sized for
+    /// MAX_ROOM_CAPACITY up front, so the room account doesn't need to grow. That is also
+    /// a hard ceiling: the u16 bitmaps can't address a 17th slot, so larger audiences need
+    /// breakout rooms or a second room.
+    pub fn upgrade_capacity(ctx: Context<UpdateRoomConfig>, new_max: u8) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        require!(new_max <= MAX_ROOM_CAPACITY, VoiceChatError::RoomCapacityCeiling);
+        require!(new_max > voice_room.max_participants(), VoiceChatError::InvalidRoomCapacity);
+        voice_room.max_participants = new_max;
+        
+        msg!("Room '{}' capacity raised to {}", voice_room.room_id(), new_max);
+        Ok(())
+    }
+
+    /// Mute every participant who joins from now on until the host puts them on stage.
+    /// Participants already in the room keep their current state.
+    pub fn set_mute_on_join(ctx: Context<UpdateRoomConfig>, mute_on_join: bool) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        voice_room.mute_on_join = mute_on_join;
+        
+        msg!("Room '{}' mute on join: {}", voice_room.room_id(), mute_on_join);
+        Ok(())
+    }
+
+    /// Turn storage-write journaling on or off. The journal keeps the room's most recent
+    /// writes, so developers can see exactly what landed when playback comes out corrupted.
+    pub fn set_write_journal(ctx: Context<SetWriteJournal>, enabled: bool) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let write_journal = &mut ctx.accounts.write_journal;
+        write_journal.room = ctx.accounts.voice_room.key();
+        write_journal.bump = ctx.bumps.write_journal;
+        ctx.accounts.voice_room.journal_writes = enabled;
+        
+        msg!("Room '{}' write journaling: {}", ctx.accounts.voice_room.room_id(), enabled);
+        Ok(())
+    }
+
+    /// Host brings a participant onto the stage or sends them back to the audience. Speakers get
+    /// an audio channel; in rooms that mute on join this also unmutes them, and mutes them again
+    /// (giving up the channel) when they step down.
+    pub fn set_stage_speaker(ctx: Context<SetStageSpeaker>, on_stage: bool) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        voice_room.set_on_stage(&mut ctx.accounts.participant_account, on_stage);
+        
+        msg!("{} on stage in room '{}': {}", 
+             ctx.accounts.participant_account.user, voice_room.room_id(), on_stage);
+        Ok(())
+    }
+
+    /// Host opens a breakout room under one of their rooms. The breakout inherits the parent's
+    /// admission and moderation settings but no fees; hosts fill it with move_participant.
+    pub fn create_breakout(ctx: Context<CreateBreakout>, label: String) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(label.len() <= MAX_ROOM_ID_LENGTH, VoiceChatError::RoomIdTooLong);
+        
+        let parent = &ctx.accounts.parent_room;
+        let breakout = &mut ctx.accounts.breakout_room;
+        breakout.init(&label, parent.host, Clock::get()?.unix_timestamp, ctx.bumps.breakout_room);
+        breakout.parent_room = Some(parent.key());
+        breakout.inherit_config(parent);
+        
+        msg!("Breakout '{}' opened under room '{}'", breakout.room_id(), parent.room_id());
+        Ok(())
+    }
+
+    /// Host moves a participant between a room and its breakouts (or between sibling
+    /// breakouts). Mutes carry over; the old participant record's rent goes back to the user.
+    pub fn move_participant(ctx: Context<MoveParticipant>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let from_room = &ctx.accounts.from_room;
+        let to_room = &ctx.accounts.to_room;
+        require_keys_neq!(from_room.key(), to_room.key(), VoiceChatError::InvalidBreakoutMove);
+        require!(
+            to_room.parent_room == Some(from_room.key())
+                || from_room.parent_room == Some(to_room.key())
+                || (from_room.parent_room.is_some() && from_room.parent_room == to_room.parent_room),
+            VoiceChatError::InvalidBreakoutMove
+        );
+        require!(to_room.is_active, VoiceChatError::RoomNotActive);
+        require!(to_room.participant_count < to_room.max_participants(), VoiceChatError::RoomFull);
+        require!(!to_room.is_recording, VoiceChatError::AlreadyRecording);
+        
+        let now = Clock::get()?.unix_timestamp;
+        let from_participant = &ctx.accounts.from_participant;
+        let from_room = &mut ctx.accounts.from_room;
+        from_room.participant_count = from_room.participant_count.saturating_sub(1);
+        from_room.release_slot(from_participant.slot);
+        from_room.last_activity = now;
+        
+        let to_room = &mut ctx.accounts.to_room;
+        let slot = to_room.claim_slot()?;
+        to_room.add_participant();
+        to_room.last_activity = now;
+        
+        let to_participant = &mut ctx.accounts.to_participant;
+        to_participant.room = to_room.key();
+        to_participant.user = from_participant.user;
+        to_participant.joined_at = now;
+        to_participant.last_seen = now;
+        to_participant.muted = from_participant.muted;
+        to_participant.slot = slot;
+        if !to_participant.muted {
+            to_room.assign_channel(slot);
+        }
+        to_participant.supported_codecs = from_participant.supported_codecs;
+        to_participant.sequence_epoch = from_participant.sequence_epoch;
+        to_participant.highest_contiguous_sequence = from_participant.highest_contiguous_sequence;
+        to_participant.bump = ctx.bumps.to_participant;
+        to_participant.has_stream = false;
+        
+        msg!("Host moved {} from '{}' to '{}'", 
+             to_participant.user, ctx.accounts.from_room.room_id(), to_room.room_id());
+        Ok(())
+    }
+
+    /// Host sets up a recurring room: every `interval_seconds` from `first_start` a new room
+    /// with `template_room`'s settings can be opened. `funding_lamports` is deposited into the
+    /// schedule to cover each occurrence's rent and crank reward.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_recurring_schedule(
+        ctx: Context<CreateRecurringSchedule>,
+        label: String,
+        first_start: i64,
+        interval_seconds: i64,
+        duration_seconds: i64,
+        capacity: u16,
+        funding_lamports: u64,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(label.len() <= MAX_SCHEDULE_LABEL_LENGTH, VoiceChatError::RoomIdTooLong);
+        require!(
+            duration_seconds > 0 && interval_seconds >= duration_seconds,
+            VoiceChatError::InvalidRecurringSchedule
+        );
+        require!(first_start > Clock::get()?.unix_timestamp, VoiceChatError::InvalidRecurringSchedule);
+        require!(capacity < MAX_PARTICIPANTS as u16, VoiceChatError::InvalidTicketCapacity);
+        
+        if funding_lamports > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.host.to_account_info(),
+                        to: ctx.accounts.schedule.to_account_info(),
+                    },
+                ),
+                funding_lamports,
+            )?;
+        }
+        
+        let schedule = &mut ctx.accounts.schedule;
+        schedule.host = ctx.accounts.host.key();
+        schedule.template_room = ctx.accounts.template_room.key();
+        schedule.label = label;
+        schedule.next_start = first_start;
+        schedule.interval_seconds = interval_seconds;
+        schedule.duration_seconds = duration_seconds;
+        schedule.capacity = capacity;
+        schedule.occurrence_count = 0;
+        schedule.bump = ctx.bumps.schedule;
+        
+        msg!("Recurring schedule '{}' every {}s starting {}", 
+             schedule.label, interval_seconds, first_start);
+        Ok(())
+    }
+
+    /// Permissionless crank: once the next occurrence is due, open its room. The cranker
+    /// fronts the room's rent and is reimbursed from the schedule plus the crank reward.
+    pub fn open_next_occurrence(ctx: Context<OpenNextOccurrence>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let now = Clock::get()?.unix_timestamp;
+        let schedule = &mut ctx.accounts.schedule;
+        require!(now >= schedule.next_start, VoiceChatError::OccurrenceNotDue);
+        
+        let room_id = format!("{}#{}", schedule.label, schedule.occurrence_count);
+        let occurrence_room = &mut ctx.accounts.occurrence_room;
+        occurrence_room.init(&room_id, schedule.host, now, ctx.bumps.occurrence_room);
+        occurrence_room.inherit_config(&ctx.accounts.template_room);
+        occurrence_room.scheduled_start = schedule.next_start;
+        occurrence_room.ticket_capacity = schedule.capacity;
+        
+        schedule.occurrence_count += 1;
+        schedule.next_start += schedule.interval_seconds;
+        
+        // Reimburse the cranker without dipping into the schedule's own rent
+        let room_rent = ctx.accounts.occurrence_room.to_account_info().lamports();
+        let reimbursement = room_rent + CRANK_REWARD_LAMPORTS;
+        let schedule_info = ctx.accounts.schedule.to_account_info();
+        let schedule_rent = Rent::get()?.minimum_balance(schedule_info.data_len());
+        require!(
+            schedule_info.lamports() >= schedule_rent + reimbursement,
+            VoiceChatError::ScheduleUnderfunded
+        );
+        **schedule_info.try_borrow_mut_lamports()? -= reimbursement;
+        **ctx.accounts.cranker.to_account_info().try_borrow_mut_lamports()? += reimbursement;
+        
+        emit!(RoomOpened {
+            room: ctx.accounts.occurrence_room.key(),
+            host: ctx.accounts.occurrence_room.host,
+            timestamp: now,
+        });
+        
+        msg!("Opened occurrence '{}' of schedule '{}', next at {}", 
+             ctx.accounts.occurrence_room.room_id(), ctx.accounts.schedule.label, ctx.accounts.schedule.next_start);
+        Ok(())
+    }
+
+    /// Permissionless crank: once an occurrence has run for the schedule's `duration_seconds`,
+    /// deactivate its room so nobody but the host can join it any more.
+    pub fn close_occurrence(ctx: Context<CloseOccurrence>, occurrence_index: u32) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let now = Clock::get()?.unix_timestamp;
+        let occurrence_room = &mut ctx.accounts.occurrence_room;
+        require!(
+            now >= occurrence_room.scheduled_start + ctx.accounts.schedule.duration_seconds,
+            VoiceChatError::OccurrenceStillRunning
+        );
+        occurrence_room.is_active = false;
+        
+        msg!("Closed occurrence {} of schedule '{}'", occurrence_index, ctx.accounts.schedule.label);
+        Ok(())
+    }
+
+    /// Host stops a recurring schedule and recovers its remaining funding
+    pub fn cancel_recurring_schedule(ctx: Context<CancelRecurringSchedule>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        
+        msg!("Recurring schedule '{}' cancelled after {} occurrences", 
+             ctx.accounts.schedule.label, ctx.accounts.schedule.occurrence_count);
+        Ok(())
+    }
+
+    /// Save (or overwrite) a named set of room settings the host can stamp rooms from
+    pub fn save_room_template(
+        ctx: Context<SaveRoomTemplate>,
+        name: String,
+        settings: RoomSettings,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(name.len() <= MAX_ROOM_ID_LENGTH, VoiceChatError::RoomIdTooLong);
+        settings.validate()?;
+        
+        let room_template = &mut ctx.accounts.room_template;
+        room_template.host = ctx.accounts.host.key();
+        room_template.name = name;
+        room_template.settings = settings;
+        room_template.bump = ctx.bumps.room_template;
+        
+        msg!("Room template '{}' saved for {}", room_template.name, room_template.host);
+        Ok(())
+    }
+
+    /// Create a fully configured room from a saved template in one instruction. The escrow is
+    /// created up front; token-denominated fees still need initialize_escrow_vault.
+    pub fn create_room_from_template(ctx: Context<CreateRoomFromTemplate>, room_id: String) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(room_id.len() <= MAX_ROOM_ID_LENGTH, VoiceChatError::RoomIdTooLong);
+        
+        let voice_room = &mut ctx.accounts.voice_room;
+        voice_room.init(&room_id, ctx.accounts.host.key(), Clock::get()?.unix_timestamp, ctx.bumps.voice_room);
+        voice_room.apply_settings(&ctx.accounts.room_template.settings);
+        voice_room.add_participant(); // Host is first participant
+        let host_slot = voice_room.claim_slot()?;
+        voice_room.assign_channel(host_slot);
+        
+        let host_participant = &mut ctx.accounts.host_participant;
+        host_participant.room = voice_room.key();
+        host_participant.user = voice_room.host;
+        host_participant.joined_at = voice_room.created_at;
+        host_participant.last_seen = voice_room.created_at;
+        host_participant.muted = false;
+        host_participant.slot = host_slot;
+        host_participant.supported_codecs = 0;
+        host_participant.sequence_epoch = 0;
+        host_participant.highest_contiguous_sequence = 0;
+        host_participant.bump = ctx.bumps.host_participant;
+        host_participant.has_stream = false;
+        
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.room = voice_room.key();
+        escrow.bump = ctx.bumps.escrow;
+        
+        emit!(RoomOpened {
+            room: voice_room.key(),
+            host: voice_room.host,
+            timestamp: voice_room.created_at,
+        });
+        
+        msg!("Voice room '{}' created from template '{}'", 
+             voice_room.room_id(), ctx.accounts.room_template.name);
+        Ok(())
+    }
+
+    /// Open a top-N leaderboard for a metric over [window_start, window_end)
+    pub fn create_leaderboard(ctx: Context<CreateLeaderboard>, metric: u8, window_start: i64, window_end: i64) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(metric <= LEADERBOARD_METRIC_TIPS, VoiceChatError::InvalidLeaderboard);
+        require!(window_end > window_start, VoiceChatError::InvalidLeaderboard);
+        
+        let leaderboard = &mut ctx.accounts.leaderboard;
+        leaderboard.creator = ctx.accounts.creator.key();
+        leaderboard.metric = metric;
+        leaderboard.window_start = window_start;
+        leaderboard.window_end = window_end;
+        leaderboard.entries = [LeaderboardEntry::default(); LEADERBOARD_SIZE];
+        leaderboard.bump = ctx.bumps.leaderboard;
+        
+        msg!("Leaderboard {} opened for metric {} from {} to {}", 
+             leaderboard.key(), metric, window_start, window_end);
+        Ok(())
+    }
+
+    /// Permissionless crank folding one result into a leaderboard: an AttendanceRecord for
+    /// talk time, or a SOL TipTally for tips. Each source only counts what it gained since it
+    /// was last folded, so it can be cranked repeatedly; gains outside the window are skipped.
+    /// A tally first folded after the window opened, holding tips from before it, only sets the
+    /// baseline; fold tallies before window_start to count every tip inside the window.
+    pub fn update_leaderboard(ctx: Context<UpdateLeaderboard>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let now = Clock::get()?.unix_timestamp;
+        let leaderboard = &mut ctx.accounts.leaderboard;
+        let source = ctx.accounts.source.to_account_info();
+        
+        let fold = &mut ctx.accounts.fold;
+        let (user, value, in_window) = if leaderboard.metric == LEADERBOARD_METRIC_TALK_TIME {
+            let attendance_record = Account::<AttendanceRecord>::try_from(&source)
+                .map_err(|_| VoiceChatError::InvalidLeaderboardSource)?;
+            (
+                attendance_record.attendee,
+                attendance_record.duration_seconds.max(0) as u64,
+                leaderboard.covers(attendance_record.session_ended_at),
+            )
+        } else {
+            let tip_tally = Account::<TipTally>::try_from(&source)
+                .map_err(|_| VoiceChatError::InvalidLeaderboardSource)?;
+            require!(tip_tally.mint.is_none(), VoiceChatError::InvalidLeaderboardSource);
+            // Without a baseline from before the window, a lifetime total can't be split
+            let predates_window = fold.folded == 0 && tip_tally.first_tip_at < leaderboard.window_start;
+            (tip_tally.speaker, tip_tally.total_amount, leaderboard.covers(now) && !predates_window)
+        };
+        
+        let gained = value.saturating_sub(fold.folded);
+        fold.folded = value;
+        fold.bump = ctx.bumps.fold;
+        if in_window && gained > 0 {
+            leaderboard.add_score(user, gained);
+        }
+        
+        msg!("Leaderboard {} folded {} for {} ({})", 
+             leaderboard.key(), gained, user, if in_window { "counted" } else { "outside window" });
+        Ok(())
+    }
+
+    /// Configure the Pyth SOL/USD feed used to convert USD-priced fees. `min_protocol_fee_usd_cents`
+    /// is the smallest protocol fee charged on a USD-priced entry, whatever fee_bps works out to.
+    pub fn set_price_feed(
+        ctx: Context<SetPriceFeed>,
+        feed_id: [u8; 32],
+        max_age_seconds: u32,
+        min_protocol_fee_usd_cents: u64,
+    ) -> Result<()> {
+        require!(max_age_seconds > 0, VoiceChatError::InvalidPriceFeed);
+        let price_feed = &mut ctx.accounts.price_feed;
+        price_feed.feed_id = feed_id;
+        price_feed.max_age_seconds = max_age_seconds;
+        price_feed.min_protocol_fee_usd_cents = min_protocol_fee_usd_cents;
+        price_feed.bump = ctx.bumps.price_feed;
+        
+        msg!("SOL/USD price feed set: max age {}s, min protocol fee {} USD cents", 
+             max_age_seconds, min_protocol_fee_usd_cents);
+        Ok(())
+    }
+
+    /// Create the room's soulbound membership mint: a Token-2022 mint with the non-transferable
+    /// extension, whose mint authority and permanent delegate is the mint PDA itself so the host
+    /// can issue and revoke memberships through this program.
+    pub fn create_membership_mint(ctx: Context<CreateMembershipMint>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        require!(voice_room.membership_mint.is_none(), VoiceChatError::MembershipMintExists);
+        
+        let voice_room_key = voice_room.key();
+        let signer_seeds: &[&[u8]] = &[b"membership_mint", voice_room_key.as_ref(), &[ctx.bumps.membership_mint]];
+        create_soulbound_mint(
+            &ctx.accounts.membership_mint.to_account_info(),
+            &ctx.accounts.host.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.token_program.to_account_info(),
+            &[signer_seeds],
+        )?;
+        voice_room.membership_mint = Some(ctx.accounts.membership_mint.key());
+        
+        msg!("Room '{}' membership mint {}", voice_room.room_id(), ctx.accounts.membership_mint.key());
+        Ok(())
+    }
+
+    /// Host issues a membership token to `member`
+    pub fn issue_membership(ctx: Context<IssueMembership>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(ctx.accounts.member_token_account.amount == 0, VoiceChatError::AlreadyMember);
+        
+        let voice_room_key = ctx.accounts.voice_room.key();
+        let signer_seeds: &[&[u8]] = &[b"membership_mint", voice_room_key.as_ref(), &[ctx.bumps.membership_mint]];
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.membership_mint.to_account_info(),
+                    to: ctx.accounts.member_token_account.to_account_info(),
+                    authority: ctx.accounts.membership_mint.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            1,
+        )?;
+        
+        msg!("Membership of room '{}' issued to {}", ctx.accounts.voice_room.room_id(), ctx.accounts.member.key());
+        Ok(())
+    }
+
+    /// Host revokes a membership by burning it through the mint's permanent delegate
+    pub fn revoke_membership(ctx: Context<RevokeMembership>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let amount = ctx.accounts.member_token_account.amount;
+        require!(amount > 0, VoiceChatError::NotAMember);
+        
+        let voice_room_key = ctx.accounts.voice_room.key();
+        let signer_seeds: &[&[u8]] = &[b"membership_mint", voice_room_key.as_ref(), &[ctx.bumps.membership_mint]];
+        token_interface::burn(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.membership_mint.to_account_info(),
+                    from: ctx.accounts.member_token_account.to_account_info(),
+                    authority: ctx.accounts.membership_mint.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            amount,
+        )?;
+        
+        msg!("Membership of room '{}' revoked from {}", 
+             ctx.accounts.voice_room.room_id(), ctx.accounts.member_token_account.owner);
+        Ok(())
+    }
+
+    /// Issue this event's tickets as Bubblegum cNFTs instead of Ticket accounts. The tree account
+    /// must already be allocated and owned by spl-account-compression; the ticket tree PDA becomes
+    /// its private creator, so only this program can mint into it.
+    pub fn create_ticket_tree(
+        ctx: Context<CreateTicketTree>,
+        max_depth: u32,
+        max_buffer_size: u32,
+        uri: String,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &ctx.accounts.voice_room;
+        require!(voice_room.scheduled_start > 0, VoiceChatError::NotAScheduledEvent);
+        require!(
+            max_depth < 32 && 1u64 << max_depth >= voice_room.ticket_capacity as u64,
+            VoiceChatError::InvalidTicketCapacity
+        );
+        require!(uri.len() <= MAX_TICKET_URI_LENGTH, VoiceChatError::ContentIdTooLong);
+        
+        let voice_room_key = voice_room.key();
+        let bump = ctx.bumps.ticket_tree;
+        let signer_seeds: &[&[u8]] = &[b"ticket_tree", voice_room_key.as_ref(), &[bump]];
+        bubblegum::create_tree(
+            &ctx.accounts.bubblegum_program,
+            &ctx.accounts.tree_config,
+            &ctx.accounts.merkle_tree,
+            &ctx.accounts.host.to_account_info(),
+            &ctx.accounts.ticket_tree.to_account_info(),
+            &ctx.accounts.noop_program,
+            &ctx.accounts.compression_program,
+            &ctx.accounts.system_program.to_account_info(),
+            max_depth,
+            max_buffer_size,
+            &[signer_seeds],
+        )?;
+        
+        let ticket_tree = &mut ctx.accounts.ticket_tree;
+        ticket_tree.room = voice_room_key;
+        ticket_tree.merkle_tree = ctx.accounts.merkle_tree.key();
+        ticket_tree.uri = uri;
+        ticket_tree.minted = 0;
+        ticket_tree.escrowed = 0;
+        ticket_tree.redeemed = vec![0; TicketTree::bitmap_len(voice_room.ticket_capacity)];
+        ticket_tree.bump = bump;
+        
+        msg!("Room '{}' compressed ticket tree {} (depth {})", 
+             voice_room.room_id(), ticket_tree.merkle_tree, max_depth);
+        Ok(())
+    }
+
+    /// Buy a compressed ticket: the price goes into the room escrow (less the protocol fee) like
+    /// buy_ticket and the ticket is minted to the buyer as a cNFT, so no per-ticket account is created
+    pub fn buy_compressed_ticket(ctx: Context<BuyCompressedTicket>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        require!(voice_room.scheduled_start > 0, VoiceChatError::NotAScheduledEvent);
+        require!(voice_room.tickets_sold < voice_room.ticket_capacity, VoiceChatError::EventSoldOut);
+        
+        let (price, min_protocol_fee) = entry_fee_lamports(
+            voice_room,
+            ctx.accounts.price_feed.as_ref(),
+            ctx.accounts.sol_usd_price.as_ref(),
+        )?;
+        let mut room_share = 0;
+        if price > 0 {
+            let escrow = ctx.accounts.escrow.as_mut()
+                .ok_or(VoiceChatError::EscrowAccountsMissing)?;
+            let protocol_fee = collect_protocol_fee_lamports(
+                &ctx.accounts.protocol_config,
+                ctx.accounts.protocol_treasury.as_mut(),
+                &ctx.accounts.buyer.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                price,
+                min_protocol_fee,
+            )?;
+            room_share = price - protocol_fee;
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.buyer.to_account_info(),
+                        to: escrow.to_account_info(),
+                    },
+                ),
+                room_share,
+            )?;
+            escrow.lamports_collected += room_share;
+        }
+        voice_room.tickets_sold += 1;
+        
+        let ticket_tree = &mut ctx.accounts.ticket_tree;
+        require!(
+            (ticket_tree.minted as usize) < ticket_tree.redeemed.len() * 8,
+            VoiceChatError::EventSoldOut
+        );
+        let voice_room_key = voice_room.key();
+        let signer_seeds: &[&[u8]] = &[b"ticket_tree", voice_room_key.as_ref(), &[ticket_tree.bump]];
+        bubblegum::mint_v1(
+            &ctx.accounts.bubblegum_program,
+            &ctx.accounts.tree_config,
+            &ctx.accounts.buyer.to_account_info(),
+            &ctx.accounts.merkle_tree,
+            &ticket_tree.to_account_info(),
+            &ctx.accounts.noop_program,
+            &ctx.accounts.compression_program,
+            &ctx.accounts.system_program.to_account_info(),
+            bubblegum::ticket_metadata(voice_room.room_id(), &ticket_tree.uri)?,
+            &[signer_seeds],
+        )?;
+        ticket_tree.minted += 1;
+        // Only the escrowed share is refundable; the protocol fee is kept
+        ticket_tree.escrowed += room_share;
+        
+        msg!("Compressed ticket {}/{} for room '{}' bought by {} for {} lamports", 
+             voice_room.tickets_sold, voice_room.ticket_capacity, voice_room.room_id(), ctx.accounts.buyer.key(), price);
+        Ok(())
+    }
+
+    /// claim_refund for compressed tickets: the holder proves their unused cNFT (proof path in
+    /// remaining_accounts) and gets its share of the escrowed ticket sales back. The leaf is
+    /// marked in the tree's redeemed bitmap, so it can neither be refunded again nor admit anyone.
+    pub fn claim_compressed_refund<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimCompressedRefund<'info>>,
+        proof: CompressedTicketProof,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        let now = Clock::get()?.unix_timestamp;
+        require!(voice_room.scheduled_start > 0, VoiceChatError::NotAScheduledEvent);
+        
+        let host_no_show = !voice_room.event_started
+            && now > voice_room.scheduled_start + EVENT_GRACE_PERIOD_SECONDS;
+        require!(voice_room.event_cancelled || host_no_show, VoiceChatError::RefundNotAvailable);
+        // A no-show cancels the event so the host can no longer start it and keep the fees
+        voice_room.event_cancelled = true;
+        
+        let ticket_tree = &mut ctx.accounts.ticket_tree;
+        let leaf = bubblegum::leaf_hash(&ticket_tree.merkle_tree, &ctx.accounts.holder.key(), &proof);
+        account_compression::verify_leaf(
+            &ctx.accounts.compression_program,
+            &ctx.accounts.merkle_tree,
+            proof.root,
+            leaf,
+            proof.nonce as u32,
+            ctx.remaining_accounts,
+        )?;
+        let refund = ticket_tree.redeem(proof.nonce)?;
+        if refund > 0 {
+            let escrow = ctx.accounts.escrow.as_mut()
+                .ok_or(VoiceChatError::EscrowAccountsMissing)?;
+            **escrow.to_account_info().try_borrow_mut_lamports()? -= refund;
+            **ctx.accounts.holder.to_account_info().try_borrow_mut_lamports()? += refund;
+            escrow.lamports_collected = escrow.lamports_collected.saturating_sub(refund);
+        }
+        voice_room.tickets_sold = voice_room.tickets_sold.saturating_sub(1);
+        
+        msg!("Refunded {} lamports to {} for compressed ticket {} of event '{}'", 
+             refund, ctx.accounts.holder.key(), proof.nonce, voice_room.room_id());
+        Ok(())
+    }
+
+    /// Gate the room on holdings of `collection` (an address on Wormhole chain `source_chain`,
+    /// left-padded to 32 bytes). Holdings are proven by signed attestations from `attestor`
+    /// and/or Wormhole messages from the registered emitter; pass `source_chain = 0` to clear.
+    pub fn set_cross_chain_gate(
+        ctx: Context<SetCrossChainGate>,
+        source_chain: u16,
+        collection: [u8; 32],
+        min_balance: u64,
+        attestor: Option<Pubkey>,
+        emitter: Option<WormholeEmitter>,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        if source_chain != 0 {
+            require!(attestor.is_some() || emitter.is_some(), VoiceChatError::InvalidCrossChainGate);
+        }
+        voice_room.cross_chain_gated = source_chain != 0;
+        
+        let gate = &mut ctx.accounts.cross_chain_gate;
+        gate.room = voice_room.key();
+        gate.source_chain = source_chain;
+        gate.collection = collection;
+        gate.min_balance = min_balance;
+        gate.attestor = attestor;
+        gate.emitter = emitter;
+        gate.bump = ctx.bumps.cross_chain_gate;
+        
+        if voice_room.cross_chain_gated {
+            msg!("Room '{}' gated by collection on chain {} (min balance {})", 
+                 voice_room.room_id(), source_chain, min_balance);
+        } else {
+            msg!("Room '{}' cross-chain gate removed", voice_room.room_id());
+        }
+        Ok(())
+    }
+
+    /// Redeem a holdings attestation for a pass into a cross-chain gated room. The attestation is
+    /// either carried by a posted Wormhole VAA from the gate's emitter, or signed by the gate's
+    /// attestor in an Ed25519 program instruction right before this one.
+    pub fn attest_holdings(ctx: Context<AttestHoldings>, attestation: HoldingsAttestation) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let gate = &ctx.accounts.cross_chain_gate;
+        let now = Clock::get()?.unix_timestamp;
+        require_keys_eq!(attestation.room, gate.room, VoiceChatError::InvalidAttestation);
+        require_keys_eq!(attestation.holder, ctx.accounts.holder.key(), VoiceChatError::InvalidAttestation);
+        require!(
+            attestation.source_chain == gate.source_chain && attestation.collection == gate.collection,
+            VoiceChatError::InvalidAttestation
+        );
+        require!(attestation.balance >= gate.min_balance, VoiceChatError::InsufficientGateBalance);
+        require!(attestation.expires_at > now, VoiceChatError::InvalidAttestation);
+        
+        let payload = attestation.try_to_vec()
+            .map_err(|_| anchor_lang::error::ErrorCode::AccountDidNotSerialize)?;
+        match (ctx.accounts.posted_vaa.as_ref(), ctx.accounts.instructions_sysvar.as_ref()) {
+            (Some(posted_vaa), _) => {
+                let emitter = gate.emitter.as_ref().ok_or(VoiceChatError::InvalidAttestation)?;
+                let message = wormhole::read_posted_vaa(&posted_vaa.to_account_info())?;
+                require!(
+                    message.emitter_chain == emitter.chain && message.emitter_address == emitter.address,
+                    VoiceChatError::InvalidAttestation
+                );
+                require!(message.payload == payload, VoiceChatError::InvalidAttestation);
+            }
+            (None, Some(instructions_sysvar)) => {
+                let attestor = gate.attestor.ok_or(VoiceChatError::InvalidAttestation)?;
+                verify_ed25519_signature(instructions_sysvar, &attestor, &payload)
+                    .map_err(|_| error!(VoiceChatError::InvalidAttestation))?;
+            }
+            (None, None) => return err!(VoiceChatError::InvalidAttestation),
+        }
+        
+        let pass = &mut ctx.accounts.cross_chain_pass;
+        pass.room = gate.room;
+        pass.holder = attestation.holder;
+        pass.expires_at = attestation.expires_at;
+        pass.bump = ctx.bumps.cross_chain_pass;
+        
+        msg!("Cross-chain pass for {} valid until {}", pass.holder, pass.expires_at);
+        Ok(())
+    }
+
+    /// Any host bans `user` from the room until `ban_until` (i64::MAX for good), removing them
+    /// if they are in it. Joins are refused until the ban passes; banning again replaces it.
+    pub fn ban_participant(ctx: Context<BanParticipant>, user: Pubkey, ban_until: i64) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let now = Clock::get()?.unix_timestamp;
+        let voice_room = &mut ctx.accounts.voice_room;
+        require!(ban_until > now, VoiceChatError::InvalidBanDuration);
+        require!(!voice_room.is_host(&user), VoiceChatError::CannotBanHost);
+        
+        let room_ban = &mut ctx.accounts.room_ban;
+        room_ban.room = voice_room.key();
+        room_ban.user = user;
+        room_ban.ban_until = ban_until;
+        room_ban.bump = ctx.bumps.room_ban;
+        
+        // Kick the user if present; the participant account closes back to them
+        if let Some(participant_account) = ctx.accounts.participant_account.as_ref() {
+            voice_room.participant_count = voice_room.participant_count.saturating_sub(1);
+            voice_room.release_slot(participant_account.slot);
+            if voice_room.participant_count == 0 {
+                voice_room.is_active = false;
+            }
+        }
+        
+        msg!("{} banned from room '{}' until {}", user, voice_room.room_id(), ban_until);
+        Ok(())
+    }
+
+    /// Any host lifts a ban early, closing the ban PDA to them
+    pub fn lift_ban(ctx: Context<LiftBan>, user: Pubkey) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        
+        msg!("Ban on {} lifted in room '{}'", user, ctx.accounts.voice_room.room_id());
+        Ok(())
+    }
+
+    /// Set the price storage writes are charged at in metered rooms
+    pub fn set_bandwidth_price(ctx: Context<SetBandwidthPrice>, lamports_per_kb: u64) -> Result<()> {
+        require!(lamports_per_kb > 0, VoiceChatError::InvalidBandwidthPrice);
+        let bandwidth_pricing = &mut ctx.accounts.bandwidth_pricing;
+        bandwidth_pricing.lamports_per_kb = lamports_per_kb;
+        bandwidth_pricing.bump = ctx.bumps.bandwidth_pricing;
+        
+        msg!("Bandwidth price set to {} lamports/KB", lamports_per_kb);
+        Ok(())
+    }
+
+    /// Prepay storage writes for the room and turn metering on. The allowance takes the
+    /// current protocol price; lamports already charged for writes go to the protocol treasury.
+    pub fn fund_bandwidth(ctx: Context<FundBandwidth>, amount: u64) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(amount > 0, VoiceChatError::InvalidBandwidthDeposit);
+        
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.host.to_account_info(),
+                    to: ctx.accounts.bandwidth_allowance.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+        
+        let bandwidth_allowance = &mut ctx.accounts.bandwidth_allowance;
+        bandwidth_allowance.room = ctx.accounts.voice_room.key();
+        bandwidth_allowance.lamports_per_kb = ctx.accounts.bandwidth_pricing.lamports_per_kb;
+        bandwidth_allowance.balance_lamports += amount;
+        bandwidth_allowance.bump = ctx.bumps.bandwidth_allowance;
+        settle_bandwidth(bandwidth_allowance, &mut ctx.accounts.protocol_treasury)?;
+        
+        let voice_room = &mut ctx.accounts.voice_room;
+        voice_room.bandwidth_metered = true;
+        
+        msg!("Room '{}' bandwidth funded with {} lamports (balance {}, {} lamports/KB)", 
+             voice_room.room_id(), amount, bandwidth_allowance.balance_lamports, bandwidth_allowance.lamports_per_kb);
+        Ok(())
+    }
+
+    /// Refund the room's unspent bandwidth to the host and stop metering its writes
+    pub fn withdraw_bandwidth(ctx: Context<WithdrawBandwidth>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let bandwidth_allowance = &mut ctx.accounts.bandwidth_allowance;
+        settle_bandwidth(bandwidth_allowance, &mut ctx.accounts.protocol_treasury)?;
+        
+        let refund = bandwidth_allowance.balance_lamports;
+        **bandwidth_allowance.to_account_info().try_borrow_mut_lamports()? -= refund;
+        **ctx.accounts.host.to_account_info().try_borrow_mut_lamports()? += refund;
+        bandwidth_allowance.balance_lamports = 0;
+        
+        let voice_room = &mut ctx.accounts.voice_room;
+        voice_room.bandwidth_metered = false;
+        
+        msg!("Host withdrew {} lamports of unspent bandwidth from room '{}'", 
+             refund, voice_room.room_id());
+        Ok(())
+    }
+
+    /// Open the room's raise-hand queue, or change how it orders hands. Weighted queues rank
+    /// hands by the stake or gate-token balance snapshotted when the hand went up.
+    pub fn set_hand_queue(ctx: Context<SetHandQueue>, weighting: u8) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(weighting <= HAND_WEIGHT_TOKEN, VoiceChatError::InvalidHandWeighting);
+        require!(
+            weighting != HAND_WEIGHT_TOKEN || ctx.accounts.voice_room.gate_mint.is_some(),
+            VoiceChatError::InvalidHandWeighting
+        );
+        let hand_queue = &mut ctx.accounts.hand_queue;
+        hand_queue.room = ctx.accounts.voice_room.key();
+        hand_queue.weighting = weighting;
+        hand_queue.bump = ctx.bumps.hand_queue;
+        
+        msg!("Room '{}' hand queue weighting: {}", ctx.accounts.voice_room.room_id(), weighting);
+        Ok(())
+    }
+
+    /// Ask to speak. The weight the queue orders by is recorded now, so moving tokens or
+    /// stake afterwards doesn't change the hand's place.
+    pub fn raise_hand(ctx: Context<RaiseHand>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &ctx.accounts.voice_room;
+        require!(!voice_room.is_on_stage(&ctx.accounts.participant_account), VoiceChatError::AlreadyOnStage);
+        
+        let hand_queue = &mut ctx.accounts.hand_queue;
+        let weight = match hand_queue.weighting {
+            HAND_WEIGHT_STAKE => ctx.accounts.sender_stake.as_ref()
+                .ok_or(VoiceChatError::HandWeightAccountMissing)?
+                .amount,
+            HAND_WEIGHT_TOKEN => {
+                let weight_token_account = ctx.accounts.weight_token_account.as_ref()
+                    .ok_or(VoiceChatError::HandWeightAccountMissing)?;
+                require!(
+                    voice_room.gate_mint == Some(weight_token_account.mint),
+                    VoiceChatError::InvalidGateTokenAccount
+                );
+                require_keys_eq!(weight_token_account.owner, ctx.accounts.participant.key(), VoiceChatError::InvalidGateTokenAccount);
+                weight_token_account.amount
+            }
+            _ => 0,
+        };
+        hand_queue.raise(HandRaise {
+            user: ctx.accounts.participant.key(),
+            weight,
+            raised_at: Clock::get()?.unix_timestamp,
+        })?;
+        
+        msg!("{} raised a hand in room '{}' (weight {}, {} waiting)", 
+             ctx.accounts.participant.key(), voice_room.room_id(), weight, hand_queue.entries.len());
+        Ok(())
+    }
+
+    /// Take a hand down. The participant can lower their own; hosts can clear anyone's,
+    /// including hands left behind by participants who already left.
+    pub fn lower_hand(ctx: Context<LowerHand>, user: Pubkey) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let authority = ctx.accounts.authority.key();
+        require!(
+            authority == user || ctx.accounts.voice_room.is_host(&authority),
+            VoiceChatError::NotRoomHost
+        );
+        let hand_queue = &mut ctx.accounts.hand_queue;
+        let index = hand_queue.position(&user).ok_or(VoiceChatError::HandNotRaised)?;
+        hand_queue.entries.remove(index);
+        
+        msg!("{}'s hand lowered in room '{}'", user, ctx.accounts.voice_room.room_id());
+        Ok(())
+    }
+
+    /// Host brings the hand at the front of the queue onto the stage
+    pub fn promote_next_speaker(ctx: Context<PromoteNextSpeaker>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let hand_queue = &mut ctx.accounts.hand_queue;
+        let index = hand_queue.next_index().ok_or(VoiceChatError::HandNotRaised)?;
+        let participant_account = &mut ctx.accounts.participant_account;
+        require_keys_eq!(hand_queue.entries[index].user, participant_account.user, VoiceChatError::NotNextInQueue);
+        let hand = hand_queue.entries.remove(index);
+        
+        let voice_room = &mut ctx.accounts.voice_room;
+        voice_room.set_on_stage(participant_account, true);
+        
+        msg!("{} promoted from the hand queue in room '{}' (weight {})", 
+             hand.user, voice_room.room_id(), hand.weight);
+        Ok(())
+    }
+
+    /// Host invites a listener onto the stage. The invitation lapses after STAGE_INVITE_SECONDS;
+    /// inviting again refreshes it. Listeners who blocked the inviting host can't be invited.
+    pub fn invite_to_stage(ctx: Context<InviteToStage>, participant: Pubkey) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &ctx.accounts.voice_room;
+        require!(!voice_room.is_on_stage(&ctx.accounts.participant_account), VoiceChatError::AlreadyOnStage);
+        require_not_blocked(&ctx.accounts.participant_block)?;
+        
+        let stage_invite = &mut ctx.accounts.stage_invite;
+        stage_invite.room = voice_room.key();
+        stage_invite.user = participant;
+        stage_invite.invited_by = ctx.accounts.host.key();
+        stage_invite.expires_at = Clock::get()?.unix_timestamp + STAGE_INVITE_SECONDS;
+        stage_invite.bump = ctx.bumps.stage_invite;
+        
+        msg!("{} invited to the stage in room '{}' until {}", 
+             participant, voice_room.room_id(), stage_invite.expires_at);
+        Ok(())
+    }
+
+    /// Listener accepts a stage invitation and becomes a speaker. The invitation closes
+    /// back to the host who sent it.
+    pub fn accept_stage_invite(ctx: Context<AcceptStageInvite>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(
+            Clock::get()?.unix_timestamp <= ctx.accounts.stage_invite.expires_at,
+            VoiceChatError::StageInviteExpired
+        );
+        let voice_room = &mut ctx.accounts.voice_room;
+        voice_room.set_on_stage(&mut ctx.accounts.participant_account, true);
+        
+        msg!("{} accepted a stage invitation in room '{}'", 
+             ctx.accounts.participant.key(), voice_room.room_id());
+        Ok(())
+    }
+
+    /// Delegate recording to a participant, who can then start, stop and finalize recordings
+    /// alongside the hosts (None takes the role back)
+    pub fn assign_recorder(ctx: Context<UpdateRoomConfig>, recorder: Option<Pubkey>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        voice_room.recorder = recorder;
+        
+        msg!("Room '{}' recorder: {:?}", voice_room.room_id(), recorder);
+        Ok(())
+    }
+
+    /// Fold an ended session's messages into its SessionArchive and tombstone them, returning
+    /// most of the rent to their senders. Messages are passed as (voice_message, batch or clip,
+    /// sender) pairs in remaining_accounts, oldest first; large sessions are archived over several calls.
+    pub fn archive_session<'info>(ctx: Context<'_, '_, 'info, 'info, ArchiveSession<'info>>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let session = &ctx.accounts.session;
+        require!(session.ended_at > 0, VoiceChatError::SessionNotEnded);
+        require!(ctx.remaining_accounts.len() % 2 == 0, VoiceChatError::InvalidArchiveAccounts);
+        
+        let session_archive = &mut ctx.accounts.session_archive;
+        session_archive.room = ctx.accounts.voice_room.key();
+        session_archive.session = session.key();
+        session_archive.bump = ctx.bumps.session_archive;
+        
+        for pair in ctx.remaining_accounts.chunks(2) {
+            let (target, sender) = (&pair[0], &pair[1]);
+            require!(target.owner == &crate::ID, VoiceChatError::InvalidArchiveAccounts);
+            let message = ArchivedMessage::load(target)?;
+            require!(
+                message.room_id == ctx.accounts.voice_room.room_id()
+                    && message.sender == sender.key()
+                    && message.timestamp >= session.started_at
+                    && message.timestamp <= session.ended_at,
+                VoiceChatError::InvalidArchiveAccounts
+            );
+            require!(
+                message.timestamp >= session_archive.last_timestamp,
+                VoiceChatError::ArchiveOutOfOrder
+            );
+            session_archive.append(archive_leaf(&message))?;
+            session_archive.last_timestamp = message.timestamp;
+            tombstone_voice_message(target, sender)?;
+        }
+        
+        msg!("Session {} archive holds {} messages", session.id, session_archive.message_count);
+        Ok(())
+    }
+
+    /// Return one page of a session's export manifest: where each frame was written and what
+    /// it hashed to, so an archiver can pull the audio and verify it. The session's messages,
+    /// batches and clips are passed via remaining_accounts oldest first, at most
+    /// EXPORT_MANIFEST_PAGE_SIZE per call. A batch yields an entry per frame at its offset and a
+    /// clip one per chunk; the page stops once it holds EXPORT_MANIFEST_PAGE_SIZE entries, and
+    /// the next call passes how many of its first record's entries were already returned as
+    /// `skip_entries`. Build the manifest before archive_session closes the records.
+    pub fn build_export_manifest<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BuildExportManifest<'info>>,
+        skip_entries: u32,
+    ) -> Result<()> {
+        let session = &ctx.accounts.session;
+        require!(
+            ctx.remaining_accounts.len() <= EXPORT_MANIFEST_PAGE_SIZE,
+            VoiceChatError::InvalidManifestAccounts
+        );
+        
+        let mut last_timestamp = 0;
+        let mut skip = skip_entries as usize;
+        let mut entries = Vec::with_capacity(EXPORT_MANIFEST_PAGE_SIZE);
+        'records: for info in ctx.remaining_accounts {
+            require!(info.owner == &crate::ID, VoiceChatError::InvalidManifestAccounts);
+            let (room_id, timestamp, record_entries) = manifest_record(info)?;
+            require!(
+                room_id == ctx.accounts.voice_room.room_id()
+                    && timestamp >= session.started_at
+                    && (session.ended_at == 0 || timestamp <= session.ended_at)
+                    && timestamp >= last_timestamp,
+                VoiceChatError::InvalidManifestAccounts
+            );
+            last_timestamp = timestamp;
+            for entry in record_entries.into_iter().skip(skip) {
+                if entries.len() == EXPORT_MANIFEST_PAGE_SIZE {
+                    break 'records;
+                }
+                entries.push(entry);
+            }
+            skip = 0;
+        }
+        
+        msg!("Session {} export manifest page: {} frames", session.id, entries.len());
+        let return_data = entries.try_to_vec()
+            .map_err(|_| anchor_lang::error::ErrorCode::AccountDidNotSerialize)?;
+        anchor_lang::solana_program::program::set_return_data(&return_data);
+        Ok(())
+    }
+
+    /// Hand the caller a storage PDA index nobody else is writing to, so clients don't have to
+    /// guess a safe target_pda_index. Until the caller or a host acks it, sends from anyone else
+    /// to the lane are refused. The stage lane is only handed to speakers on stage. Returns the
+    /// index as return data.
+    pub fn allocate_write_slot(ctx: Context<AllocateWriteSlot>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        let on_stage = voice_room.is_on_stage(&ctx.accounts.participant_account);
+        let lane = (0..STORAGE_LANE_COUNT as u8)
+            .filter(|lane| on_stage || voice_room.stage_lane != Some(*lane))
+            .find(|lane| voice_room.lane_allocations[*lane as usize] == 0)
+            .ok_or(VoiceChatError::NoFreeWriteSlot)?;
+        voice_room.lane_allocations[lane as usize] = ctx.accounts.participant_account.slot + 1;
+        
+        msg!("Storage lane {} allocated to {} in room '{}'", 
+             lane, ctx.accounts.participant.key(), voice_room.room_id());
+        anchor_lang::solana_program::program::set_return_data(&[lane]);
+        Ok(())
+    }
+
+    /// The participant a lane was allocated to, or a host, hands it back to the pool
+    pub fn ack_write_slot(ctx: Context<AllocateWriteSlot>, target_pda_index: u8) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!((target_pda_index as usize) < STORAGE_LANE_COUNT, VoiceChatError::InvalidStoragePDA);
+        let voice_room = &mut ctx.accounts.voice_room;
+        let allocation = voice_room.lane_allocations[target_pda_index as usize];
+        require!(allocation != 0, VoiceChatError::WriteSlotNotAllocated);
+        require!(
+            allocation == ctx.accounts.participant_account.slot + 1
+                || voice_room.is_host(&ctx.accounts.participant.key()),
+            VoiceChatError::WriteSlotHeldByOther
+        );
+        voice_room.lane_allocations[target_pda_index as usize] = 0;
+        
+        msg!("Storage lane {} released in room '{}'", target_pda_index, voice_room.room_id());
+        Ok(())
+    }
+
+    /// A host marks the frame in a lane as consumed, so the next send to that lane doesn't
+    /// need `force`. Listeners go through ack_frames, which frees a lane only once all of them did.
+    pub fn ack_consumed(ctx: Context<AckConsumed>, target_pda_index: u8) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!((target_pda_index as usize) < STORAGE_LANE_COUNT, VoiceChatError::InvalidStoragePDA);
+        let voice_room = &mut ctx.accounts.voice_room;
+        voice_room.unread_lanes &= !(1 << target_pda_index);
+        
+        msg!("Lane {} consumed by {} in room '{}'", 
+             target_pda_index, ctx.accounts.host.key(), voice_room.room_id());
+        Ok(())
+    }
+
+    /// A listener acks every frame from `sender` up to `up_to_sequence`. Once all current
+    /// listeners have consumed the frame in a lane, the lane is free to overwrite without force.
+    pub fn ack_frames(ctx: Context<AckFrames>, sender: Pubkey, up_to_sequence: u32) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let voice_room = &mut ctx.accounts.voice_room;
+        let acked = voice_room.ack_lane_frames(
+            ctx.accounts.listener_account.slot,
+            ctx.accounts.sender_participant.slot,
+            up_to_sequence,
+        );
+        
+        msg!("{} consumed {}'s frames up to {} in room '{}' ({} lanes)", 
+             ctx.accounts.listener.key(), sender, up_to_sequence, voice_room.room_id(), acked);
+        Ok(())
+    }
+
+    /// Listener records how far they've consumed `sender`'s stream. The cursor lets a
+    /// reconnecting client resume exactly where it left off, and the frames it covers are
+    /// acked as in ack_frames so their lanes can be reclaimed.
+    pub fn advance_cursor(
+        ctx: Context<AdvanceCursor>,
+        sender: Pubkey,
+        sequence_epoch: u32,
+        sequence: u32,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let listener_cursor = &mut ctx.accounts.listener_cursor;
+        listener_cursor.room = ctx.accounts.voice_room.key();
+        listener_cursor.listener = ctx.accounts.listener.key();
+        listener_cursor.bump = ctx.bumps.listener_cursor;
+        listener_cursor.advance(CursorEntry { sender, sequence_epoch, sequence })?;
+        listener_cursor.updated_at = Clock::get()?.unix_timestamp;
+        
+        let voice_room = &mut ctx.accounts.voice_room;
+        if sequence_epoch == ctx.accounts.sender_participant.sequence_epoch {
+            voice_room.ack_lane_frames(
+                ctx.accounts.listener_account.slot,
+                ctx.accounts.sender_participant.slot,
+                sequence,
+            );
+        }
+        
+        msg!("{}'s cursor on {} in room '{}': epoch {}, sequence {}", 
+             ctx.accounts.listener.key(), sender, voice_room.room_id(), sequence_epoch, sequence);
+        Ok(())
+    }
+
+    /// Ask to be alerted whenever `host` opens a room. Notifier services index the
+    /// subscription PDAs by host and fan out on RoomOpened events.
+    pub fn subscribe_room(ctx: Context<SubscribeRoom>, host: Pubkey) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require_keys_neq!(host, ctx.accounts.subscriber.key(), VoiceChatError::CannotSubscribeToSelf);
+        
+        let room_subscription = &mut ctx.accounts.room_subscription;
+        room_subscription.host = host;
+        room_subscription.subscriber = ctx.accounts.subscriber.key();
+        room_subscription.created_at = Clock::get()?.unix_timestamp;
+        room_subscription.bump = ctx.bumps.room_subscription;
+        
+        emit!(RoomSubscriptionChanged {
+            subscriber: room_subscription.subscriber,
+            host,
+            subscribed: true,
+            timestamp: room_subscription.created_at,
+        });
+        
+        msg!("{} subscribed to rooms hosted by {}", room_subscription.subscriber, host);
+        Ok(())
+    }
+
+    /// Stop room alerts from `host`, closing the subscription PDA back to the subscriber
+    pub fn unsubscribe_room(ctx: Context<UnsubscribeRoom>, host: Pubkey) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        
+        emit!(RoomSubscriptionChanged {
+            subscriber: ctx.accounts.subscriber.key(),
+            host,
+            subscribed: false,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("{} unsubscribed from rooms hosted by {}", ctx.accounts.subscriber.key(), host);
+        Ok(())
+    }
+
+    /// Register where and for what the user wants to be notified. Only a hash of the
+    /// webhook or push endpoint is stored; delivery services match it against the endpoint
+    /// the user registered with them off-chain and treat the PDA as the user's consent.
+    pub fn set_notification_config(
+        ctx: Context<SetNotificationConfig>,
+        endpoint_hash: [u8; 32],
+        preferences: u8,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(preferences & !NOTIFY_ALL == 0, VoiceChatError::InvalidNotificationPreferences);
+        
+        let notification_config = &mut ctx.accounts.notification_config;
+        notification_config.user = ctx.accounts.user.key();
+        notification_config.endpoint_hash = endpoint_hash;
+        notification_config.preferences = preferences;
+        notification_config.updated_at = Clock::get()?.unix_timestamp;
+        notification_config.bump = ctx.bumps.notification_config;
+        
+        msg!("{} set notification preferences {:#05b}", notification_config.user, preferences);
+        Ok(())
+    }
+
+    /// Withdraw notification consent, closing the config PDA back to the user
+    pub fn close_notification_config(ctx: Context<CloseNotificationConfig>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        msg!("{} cleared their notification config", ctx.accounts.user.key());
+        Ok(())
+    }
+
+    /// Send a frame in an account of its own, sized to the payload, instead of a storage
+    /// lane. The sender pays the rent and gets it back once every listener present at send
+    /// time has acked the frame, or from tick_room after the retention window. Suits quiet
+    /// rooms where rent churn is cheaper than contending for lanes.
+    pub fn send_voice_frame(
+        ctx: Context<SendVoiceFrame>,
+        voice_data: Vec<u8>,
+        sequence_number: u32,
+        duration_ms: u16,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(voice_data.len() <= ctx.accounts.voice_room.max_frame_bytes(), VoiceChatError::VoiceDataTooLarge);
+        check_codec(&ctx.accounts.voice_room, &voice_data)?;
+        check_frame_quality(&ctx.accounts.voice_room, voice_data.len(), duration_ms)?;
+        check_sender(
+            &ctx.accounts.voice_room,
+            &ctx.accounts.sender_participant,
+            ctx.accounts.sender_stake.as_ref(),
+            ctx.accounts.sender_reputation.as_ref(),
+        )?;
+        
+        let sender_participant = &mut ctx.accounts.sender_participant;
+        let voice_room = &mut ctx.accounts.voice_room;
+        let frame = &mut ctx.accounts.frame;
+        frame.room = voice_room.key();
+        frame.sender = ctx.accounts.sender.key();
+        frame.sequence_epoch = sender_participant.sequence_epoch;
+        frame.sequence_number = sequence_number;
+        frame.timestamp = Clock::get()?.unix_timestamp;
+        frame.pending_acks = voice_room.occupied_slots & !(1 << sender_participant.slot);
+        frame.bump = ctx.bumps.frame;
+        frame.data = voice_data;
+        sender_participant.extend_contiguous(sequence_number, sequence_number);
+        
+        voice_room.last_activity = frame.timestamp;
+        voice_room.record_traffic(1, frame.data.len());
+        
+        msg!("Voice frame sent: {} bytes in its own account, sequence {}", 
+             frame.data.len(), sequence_number);
+        Ok(())
+    }
+
+    /// Listener marks a frame account consumed; the last pending listener's ack closes it
+    /// and refunds the sender
+    pub fn ack_voice_frame(ctx: Context<AckVoiceFrame>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let frame = &mut ctx.accounts.frame;
+        frame.pending_acks &= !(1 << ctx.accounts.listener_account.slot);
+        
+        msg!("{} consumed frame {} from {}", 
+             ctx.accounts.listener.key(), frame.sequence_number, frame.sender);
+        if frame.pending_acks == 0 {
+            frame.close(ctx.accounts.sender.to_account_info())?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(room_id: String, sns_domain: Option<Pubkey>)]
+pub struct InitializeVoiceRoom<'info> {
+    #[account(
+        init,
+        payer = host,
+        space = VoiceRoom::SPACE,
+        seeds = [b"voice_room", room_id.as_bytes()],
+        bump
+    )]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        init,
+        payer = host,
+        space = 8 + 32 + 32 + 8 + 8 + 1 + 1 + 2 + 4 + 4 + 1 + 1, // discriminator + room + user + joined_at + last_seen + muted + slot + supported_codecs + sequence_epoch + highest_contiguous_sequence + bump + has_stream
+        seeds = [b"participant", voice_room.key().as_ref(), host.key().as_ref()],
+        bump
+    )]
+    pub host_participant: Account<'info, Participant>,
+    
+    /// CHECK: SNS name record of the domain being bound, verified in sns::verify_sol_domain_owner
+    pub domain_record: Option<UncheckedAccount<'info>>,
+    
+    /// Domain -> room lookup (domain-bound rooms only)
+    #[account(
+        init,
+        payer = host,
+        space = 8 + 32 + 32 + 1, // discriminator + domain + room + bump
+        seeds = [b"room_domain", sns_domain.unwrap_or_default().as_ref()],
+        bump
+    )]
+    pub room_domain: Option<Account<'info, RoomDomain>>,
+    
+    #[account(mut)]
+    pub host: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct JoinVoiceRoom<'info> {
+    #[account(mut)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        init,
+        payer = participant,
+        space = 8 + 32 + 32 + 8 + 8 + 1 + 1 + 2 + 4 + 4 + 1 + 1, // discriminator + room + user + joined_at + last_seen + muted + slot + supported_codecs + sequence_epoch + highest_contiguous_sequence + bump + has_stream
+        seeds = [b"participant", voice_room.key().as_ref(), participant.key().as_ref()],
+        bump
+    )]
+    pub participant_account: Account<'info, Participant>,
+    
+    /// CHECK: Joiner's ban PDA for this room; must not exist or must have expired
+    #[account(
+        seeds = [b"room_ban", voice_room.key().as_ref(), participant.key().as_ref()],
+        bump
+    )]
+    pub room_ban: UncheckedAccount<'info>,
+    
+    /// Joiner's token account for the room's gate mint (token-gated rooms only).
+    /// May belong to either SPL Token or Token-2022.
+    pub gate_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    
+    /// Joiner's NFT token account (collection-gated rooms only)
+    pub nft_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    
+    /// Metaplex metadata for the NFT's mint (collection-gated rooms only)
+    pub nft_metadata: Option<Box<Account<'info, MetadataAccount>>>,
+    
+    /// Room escrow receiving entry fees (paid rooms only)
+    #[account(
+        mut,
+        seeds = [b"escrow", voice_room.key().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Option<Account<'info, RoomEscrow>>,
+    
+    /// Joiner's token account paying a token-denominated entry fee
+    #[account(mut)]
+    pub fee_payer_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow_vault", voice_room.key().as_ref()],
+        bump
+    )]
+    pub escrow_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    
+    pub fee_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    
+    /// Joiner's ticket (scheduled events only)
+    #[account(
+        mut,
+        seeds = [b"ticket", voice_room.key().as_ref(), participant.key().as_ref()],
+        bump
+    )]
+    pub ticket: Option<Account<'info, Ticket>>,
+    
+    /// Joiner's statistics, credited with the join
+    #[account(
+        mut,
+        seeds = [b"user_stats", participant.key().as_ref()],
+        bump = participant_stats.bump
+    )]
+    pub participant_stats: Option<Account<'info, UserStats>>,
+    
+    /// Joiner's membership token account (members of rooms with a membership mint only)
+    pub membership_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    
+    /// Joiner's pass proving holdings on another chain (cross-chain gated rooms only)
+    #[account(
+        seeds = [b"cross_chain_pass", voice_room.key().as_ref(), participant.key().as_ref()],
+        bump = cross_chain_pass.bump
+    )]
+    pub cross_chain_pass: Option<Account<'info, CrossChainPass>>,
+    
+    /// Event's compressed ticket tree (cNFT ticket holders only)
+    #[account(
+        mut,
+        seeds = [b"ticket_tree", voice_room.key().as_ref()],
+        bump = ticket_tree.bump
+    )]
+    pub ticket_tree: Option<Box<Account<'info, TicketTree>>>,
+    
+    /// CHECK: Bubblegum tree holding the ticket, checked against ticket_tree
+    pub ticket_merkle_tree: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: spl-account-compression program
+    #[account(address = account_compression::PROGRAM_ID)]
+    pub compression_program: Option<UncheckedAccount<'info>>,
+    
+    /// Protocol treasury receiving the protocol fee (required while the fee is non-zero)
+    #[account(
+        mut,
+        seeds = [b"protocol_treasury"],
+        bump = protocol_treasury.bump
+    )]
+    pub protocol_treasury: Option<Account<'info, ProtocolTreasury>>,
+    
+    /// Protocol treasury token account receiving token-denominated fees
+    #[account(mut)]
+    pub protocol_treasury_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    
+    /// SOL/USD feed configuration (USD-priced rooms only)
+    #[account(seeds = [b"price_feed"], bump = price_feed.bump)]
+    pub price_feed: Option<Account<'info, PriceFeedConfig>>,
+    
+    /// CHECK: Pyth price update for the configured feed, verified in pyth::read_price
+    pub sol_usd_price: Option<UncheckedAccount<'info>>,
+    
+    #[account(mut)]
+    pub participant: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(voice_data: Vec<u8>, target_pda_index: u8, sequence_number: u32, idempotency_key: u64)]
+pub struct SendVoiceData<'info> {
+    #[account(mut)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    /// CHECK: This is the storage PDA from storage_manager contract
+    #[account(mut)]
+    pub storage_pda: AccountInfo<'info>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 4 + MAX_ROOM_ID_LENGTH + 1 + 4 + 4 + 8 + 8 + 4 + 32 + 32, // discriminator + sender + room_id_len + room_id + storage_pda_index + sequence_number + data_length + timestamp + capture_timestamp_ms + sequence_epoch + storage_pda + content_hash
+        seeds = [b"voice_message", voice_room.key().as_ref(), &voice_room.message_count.to_le_bytes()],
+        bump
+    )]
+    pub voice_message: Account<'info, VoiceMessage>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + 32 + 32 + 8 * IDEMPOTENCY_WINDOW + 1 + 1 + 4 + 8 + 4, // discriminator + room + sender + recent_keys + cursor + bump + last_sponsored_sequence + last_sent_slot + sequence_epoch
+        seeds = [b"recent_sends", voice_room.key().as_ref(), sender.key().as_ref()],
+        bump
+    )]
+    pub recent_sends: Account<'info, RecentSends>,
+    
+    /// Open page of the room's message index
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = MessageIndexPage::SPACE,
+        seeds = [b"message_index", voice_room.key().as_ref(), &voice_room.current_index_page().to_le_bytes()],
+        bump
+    )]
+    pub message_index_page: Account<'info, MessageIndexPage>,
+    
+    /// Sender's participant record; muted participants can't send
+    #[account(
+        mut,
+        seeds = [b"participant", voice_room.key().as_ref(), sender.key().as_ref()],
+        bump = sender_participant.bump
+    )]
+    pub sender_participant: Account<'info, Participant>,
+    
+    /// Sender's anti-spam stake (rooms with a stake requirement only)
+    #[account(
+        seeds = [b"stake", voice_room.key().as_ref(), sender.key().as_ref()],
+        bump = sender_stake.bump
+    )]
+    pub sender_stake: Option<Account<'info, SenderStake>>,
+    
+    /// Sender's reputation (rooms with a reputation threshold only)
+    #[account(
+        seeds = [b"reputation", sender.key().as_ref()],
+        bump = sender_reputation.bump
+    )]
+    pub sender_reputation: Option<Account<'info, UserReputation>>,
+    
+    /// Sender's statistics, credited with the message
+    #[account(
+        mut,
+        seeds = [b"user_stats", sender.key().as_ref()],
+        bump = sender_stats.bump
+    )]
+    pub sender_stats: Option<Account<'info, UserStats>>,
+    
+    /// CHECK: Identity the frame is sent as; `authority` must be it or one of its session keys
+    pub sender: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// Session key delegating send authority from `sender` to `authority`
+    #[account(
+        seeds = [b"session_key", sender.key().as_ref(), authority.key().as_ref()],
+        bump = session_key.bump
+    )]
+    pub session_key: Option<Account<'info, SessionKey>>,
+    
+    pub system_program: Program<'info, System>,
+    
+    /// Room's write journal (rooms with journaling on only)
+    #[account(
+        mut,
+        seeds = [b"write_journal", voice_room.key().as_ref()],
+        bump = write_journal.bump
+    )]
+    pub write_journal: Option<Box<Account<'info, WriteJournal>>>,
+    
+    /// Room's prepaid bandwidth (metered rooms only)
+    #[account(
+        mut,
+        seeds = [b"bandwidth_allowance", voice_room.key().as_ref()],
+        bump = bandwidth_allowance.bump
+    )]
+    pub bandwidth_allowance: Option<Account<'info, BandwidthAllowance>>,
+    
+    /// CHECK: The protocol's storage backend, checked against protocol_config; when passed,
+    /// storage writes go through its storage_interface::write_chunk
+    pub storage_program: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: This program's PDA that signs write_chunk CPIs
+    #[account(seeds = [storage_layout::WRITER_SEED], bump)]
+    pub storage_writer: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(frames: Vec<FrameHeader>, payload: Vec<u8>, target_pda_index: u8, sequence_number: u32)]
+pub struct SendVoiceBatch<'info> {
+    #[account(mut)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    /// CHECK: This is the storage PDA from storage_manager contract
+    #[account(mut)]
+    pub storage_pda: AccountInfo<'info>,
+    
+    #[account(
+        init,
+        payer = sender,
+        space = 8 + 32 + 4 + MAX_ROOM_ID_LENGTH + 1 + 4 + 4 + 4 + MAX_BATCH_FRAMES * FrameHeader::SIZE + 8 + 4 + 32 + 32 + 4 + 32 * MAX_BATCH_FRAMES, // discriminator + sender + room_id_len + room_id + storage_pda_index + sequence_number + data_length + frames_len + frames + timestamp + sequence_epoch + storage_pda + content_hash + frame_hashes_len + frame_hashes
+        seeds = [b"voice_batch", voice_room.key().as_ref(), &voice_room.message_count.to_le_bytes()],
+        bump
+    )]
+    pub batch_message: Account<'info, VoiceBatchMessage>,
+    
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = 8 + 32 + 32 + 8 * IDEMPOTENCY_WINDOW + 1 + 1 + 4 + 8 + 4, // discriminator + room + sender + recent_keys + cursor + bump + last_sponsored_sequence + last_sent_slot + sequence_epoch
+        seeds = [b"recent_sends", voice_room.key().as_ref(), sender.key().as_ref()],
+        bump
+    )]
+    pub recent_sends: Account<'info, RecentSends>,
+    
+    /// Open page of the room's message index
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = MessageIndexPage::SPACE,
+        seeds = [b"message_index", voice_room.key().as_ref(), &voice_room.current_index_page().to_le_bytes()],
+        bump
+    )]
+    pub message_index_page: Account<'info, MessageIndexPage>,
+    
+    /// Sender's participant record; muted participants can't send
+    #[account(
+        mut,
+        seeds = [b"participant", voice_room.key().as_ref(), sender.key().as_ref()],
+        bump = sender_participant.bump
+    )]
+    pub sender_participant: Account<'info, Participant>,
+    
+    /// Sender's anti-spam stake (rooms with a stake requirement only)
+    #[account(
+        seeds = [b"stake", voice_room.key().as_ref(), sender.key().as_ref()],
+        bump = sender_stake.bump
+    )]
+    pub sender_stake: Option<Account<'info, SenderStake>>,
+    
+    /// Sender's reputation (rooms with a reputation threshold only)
+    #[account(
+        seeds = [b"reputation", sender.key().as_ref()],
+        bump = sender_reputation.bump
+    )]
+    pub sender_reputation: Option<Account<'info, UserReputation>>,
+    
+    /// Sender's statistics, credited with the message
+    #[account(
+        mut,
+        seeds = [b"user_stats", sender.key().as_ref()],
+        bump = sender_stats.bump
+    )]
+    pub sender_stats: Option<Account<'info, UserStats>>,
+    
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    /// Room's write journal (rooms with journaling on only)
+    #[account(
+        mut,
+        seeds = [b"write_journal", voice_room.key().as_ref()],
+        bump = write_journal.bump
+    )]
+    pub write_journal: Option<Box<Account<'info, WriteJournal>>>,
+    
+    /// Room's prepaid bandwidth (metered rooms only)
+    #[account(
+        mut,
+        seeds = [b"bandwidth_allowance", voice_room.key().as_ref()],
+        bump = bandwidth_allowance.bump
+    )]
+    pub bandwidth_allowance: Option<Account<'info, BandwidthAllowance>>,
+    
+    /// CHECK: The protocol's storage backend, checked against protocol_config; when passed,
+    /// storage writes go through its storage_interface::write_chunk
+    pub storage_program: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: This program's PDA that signs write_chunk CPIs
+    #[account(seeds = [storage_layout::WRITER_SEED], bump)]
+    pub storage_writer: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(payload: Vec<u8>, target_pdas: Vec<u8>, sequence_number: u32)]
+pub struct SendVoiceClip<'info> {
+    #[account(mut)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        init,
+        payer = sender,
+        space = 8 + 32 + 4 + MAX_ROOM_ID_LENGTH + 4 + MAX_CLIP_CHUNKS + 4 + 4 + 8 + 4 + 32 + 4 + 32 * MAX_CLIP_CHUNKS + 4 + 32 * MAX_CLIP_CHUNKS, // discriminator + sender + room_id_len + room_id + target_pdas_len + target_pdas + sequence_number + data_length + timestamp + sequence_epoch + content_hash + storage_pdas_len + storage_pdas + chunk_hashes_len + chunk_hashes
+        seeds = [b"voice_clip", voice_room.key().as_ref(), &voice_room.message_count.to_le_bytes()],
+        bump
+    )]
+    pub clip_message: Account<'info, VoiceClipMessage>,
+    
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = 8 + 32 + 32 + 8 * IDEMPOTENCY_WINDOW + 1 + 1 + 4 + 8 + 4, // discriminator + room + sender + recent_keys + cursor + bump + last_sponsored_sequence + last_sent_slot + sequence_epoch
+        seeds = [b"recent_sends", voice_room.key().as_ref(), sender.key().as_ref()],
+        bump
+    )]
+    pub recent_sends: Account<'info, RecentSends>,
+    
+    /// Open page of the room's message index
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = MessageIndexPage::SPACE,
+        seeds = [b"message_index", voice_room.key().as_ref(), &voice_room.current_index_page().to_le_bytes()],
+        bump
+    )]
+    pub message_index_page: Account<'info, MessageIndexPage>,
+    
+    /// Sender's participant record; muted participants can't send
+    #[account(
+        seeds = [b"participant", voice_room.key().as_ref(), sender.key().as_ref()],
+        bump = sender_participant.bump
+    )]
+    pub sender_participant: Account<'info, Participant>,
+    
+    /// Sender's anti-spam stake (rooms with a stake requirement only)
+    #[account(
+        seeds = [b"stake", voice_room.key().as_ref(), sender.key().as_ref()],
+        bump = sender_stake.bump
+    )]
+    pub sender_stake: Option<Account<'info, SenderStake>>,
+    
+    /// Sender's reputation (rooms with a reputation threshold only)
+    #[account(
+        seeds = [b"reputation", sender.key().as_ref()],
+        bump = sender_reputation.bump
+    )]
+    pub sender_reputation: Option<Account<'info, UserReputation>>,
+    
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    /// Room's write journal (rooms with journaling on only)
+    #[account(
+        mut,
+        seeds = [b"write_journal", voice_room.key().as_ref()],
+        bump = write_journal.bump
+    )]
+    pub write_journal: Option<Box<Account<'info, WriteJournal>>>,
+    
+    /// Room's prepaid bandwidth (metered rooms only)
+    #[account(
+        mut,
+        seeds = [b"bandwidth_allowance", voice_room.key().as_ref()],
+        bump = bandwidth_allowance.bump
+    )]
+    pub bandwidth_allowance: Option<Account<'info, BandwidthAllowance>>,
+    
+    /// CHECK: The protocol's storage backend, checked against protocol_config; when passed,
+    /// storage writes go through its storage_interface::write_chunk
+    pub storage_program: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: This program's PDA that signs write_chunk CPIs
+    #[account(seeds = [storage_layout::WRITER_SEED], bump)]
+    pub storage_writer: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    // remaining_accounts: storage PDAs (writable), one per chunk in target_pdas order
+}
+
+#[derive(Accounts)]
+pub struct InitializeMessageLog<'info> {
+    #[account(has_one = host)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        init,
+        payer = host,
+        space = 8 + 32 + 32 + 4 + 4 + 8 + 1, // discriminator + room + merkle_tree + max_depth + max_buffer_size + leaf_count + bump
+        seeds = [b"message_log", voice_room.key().as_ref()],
+        bump
+    )]
+    pub message_log: Account<'info, CompressedMessageLog>,
+    
+    /// CHECK: Pre-allocated concurrent merkle tree, initialized via CPI
+    #[account(mut, owner = account_compression::PROGRAM_ID)]
+    pub merkle_tree: AccountInfo<'info>,
+    
+    #[account(mut)]
+    pub host: Signer<'info>,
+    
+    /// CHECK: spl-account-compression program
+    #[account(address = account_compression::PROGRAM_ID)]
+    pub compression_program: AccountInfo<'info>,
+    
+    /// CHECK: spl-noop program
+    #[account(address = account_compression::NOOP_PROGRAM_ID)]
+    pub noop_program: AccountInfo<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SendVoiceDataCompressed<'info> {
+    #[account(mut)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    /// CHECK: This is the storage PDA from storage_manager contract
+    #[account(mut)]
+    pub storage_pda: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"message_log", voice_room.key().as_ref()],
+        bump = message_log.bump,
+        has_one = merkle_tree
+    )]
+    pub message_log: Account<'info, CompressedMessageLog>,
+    
+    /// CHECK: Concurrent merkle tree owned by spl-account-compression, matched against message_log
+    #[account(mut)]
+    pub merkle_tree: AccountInfo<'info>,
+    
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = 8 + 32 + 32 + 8 * IDEMPOTENCY_WINDOW + 1 + 1 + 4 + 8 + 4, // discriminator + room + sender + recent_keys + cursor + bump + last_sponsored_sequence + last_sent_slot + sequence_epoch
+        seeds = [b"recent_sends", voice_room.key().as_ref(), sender.key().as_ref()],
+        bump
+    )]
+    pub recent_sends: Account<'info, RecentSends>,
+    
+    /// Sender's participant record; muted participants can't send
+    #[account(
+        seeds = [b"participant", voice_room.key().as_ref(), sender.key().as_ref()],
+        bump = sender_participant.bump
+    )]
+    pub sender_participant: Account<'info, Participant>,
+    
+    /// Sender's anti-spam stake (rooms with a stake requirement only)
+    #[account(
+        seeds = [b"stake", voice_room.key().as_ref(), sender.key().as_ref()],
+        bump = sender_stake.bump
+    )]
+    pub sender_stake: Option<Account<'info, SenderStake>>,
+    
+    /// Sender's reputation (rooms with a reputation threshold only)
+    #[account(
+        seeds = [b"reputation", sender.key().as_ref()],
+        bump = sender_reputation.bump
+    )]
+    pub sender_reputation: Option<Account<'info, UserReputation>>,
+    
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    /// CHECK: spl-account-compression program
+    #[account(address = account_compression::PROGRAM_ID)]
+    pub compression_program: AccountInfo<'info>,
+    
+    /// CHECK: spl-noop program
+    #[account(address = account_compression::NOOP_PROGRAM_ID)]
+    pub noop_program: AccountInfo<'info>,
+    
+    /// Room's write journal (rooms with journaling on only)
+    #[account(
+        mut,
+        seeds = [b"write_journal", voice_room.key().as_ref()],
+        bump = write_journal.bump
+    )]
+    pub write_journal: Option<Box<Account<'info, WriteJournal>>>,
+    
+    /// Room's prepaid bandwidth (metered rooms only)
+    #[account(
+        mut,
+        seeds = [b"bandwidth_allowance", voice_room.key().as_ref()],
+        bump = bandwidth_allowance.bump
+    )]
+    pub bandwidth_allowance: Option<Account<'info, BandwidthAllowance>>,
+    
+    /// CHECK: The protocol's storage backend, checked against protocol_config; when passed,
+    /// storage writes go through its storage_interface::write_chunk
+    pub storage_program: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: This program's PDA that signs write_chunk CPIs
+    #[account(seeds = [storage_layout::WRITER_SEED], bump)]
+    pub storage_writer: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[cfg(feature = "light")]
+#[derive(Accounts)]
+pub struct SendVoiceDataLight<'info> {
+    #[account(mut)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    /// CHECK: This is the storage PDA from storage_manager contract
+    #[account(mut)]
+    pub storage_pda: AccountInfo<'info>,
+    
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = 8 + 32 + 32 + 8 * IDEMPOTENCY_WINDOW + 1 + 1 + 4 + 8 + 4, // discriminator + room + sender + recent_keys + cursor + bump + last_sponsored_sequence + last_sent_slot + sequence_epoch
+        seeds = [b"recent_sends", voice_room.key().as_ref(), sender.key().as_ref()],
+        bump
+    )]
+    pub recent_sends: Account<'info, RecentSends>,
+    
+    /// Sender's participant record; muted participants can't send
+    #[account(
+        seeds = [b"participant", voice_room.key().as_ref(), sender.key().as_ref()],
+        bump = sender_participant.bump
+    )]
+    pub sender_participant: Account<'info, Participant>,
+    
+    /// Sender's anti-spam stake (rooms with a stake requirement only)
+    #[account(
+        seeds = [b"stake", voice_room.key().as_ref(), sender.key().as_ref()],
+        bump = sender_stake.bump
+    )]
+    pub sender_stake: Option<Account<'info, SenderStake>>,
+    
+    /// Sender's reputation (rooms with a reputation threshold only)
+    #[account(
+        seeds = [b"reputation", sender.key().as_ref()],
+        bump = sender_reputation.bump
+    )]
+    pub sender_reputation: Option<Account<'info, UserReputation>>,
+    
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    /// Room's write journal (rooms with journaling on only)
+    #[account(
+        mut,
+        seeds = [b"write_journal", voice_room.key().as_ref()],
+        bump = write_journal.bump
+    )]
+    pub write_journal: Option<Box<Account<'info, WriteJournal>>>,
+    
+    /// Room's prepaid bandwidth (metered rooms only)
+    #[account(
+        mut,
+        seeds = [b"bandwidth_allowance", voice_room.key().as_ref()],
+        bump = bandwidth_allowance.bump
+    )]
+    pub bandwidth_allowance: Option<Account<'info, BandwidthAllowance>>,
+    
+    /// CHECK: The protocol's storage backend, checked against protocol_config; when passed,
+    /// storage writes go through its storage_interface::write_chunk
+    pub storage_program: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: This program's PDA that signs write_chunk CPIs
+    #[account(seeds = [storage_layout::WRITER_SEED], bump)]
+    pub storage_writer: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    // remaining_accounts: light system program accounts and merkle trees
+}
+
+#[cfg(feature = "light")]
+#[derive(Accounts)]
+pub struct BroadcastVoiceDataLight<'info> {
+    #[account(mut)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    /// Sender's participant record; muted participants can't send
+    #[account(
+        seeds = [b"participant", voice_room.key().as_ref(), sender.key().as_ref()],
+        bump = sender_participant.bump
+    )]
+    pub sender_participant: Account<'info, Participant>,
+    
+    /// Sender's anti-spam stake (rooms with a stake requirement only)
+    #[account(
+        seeds = [b"stake", voice_room.key().as_ref(), sender.key().as_ref()],
+        bump = sender_stake.bump
+    )]
+    pub sender_stake: Option<Account<'info, SenderStake>>,
+    
+    /// Sender's reputation (rooms with a reputation threshold only)
+    #[account(
+        seeds = [b"reputation", sender.key().as_ref()],
+        bump = sender_reputation.bump
+    )]
+    pub sender_reputation: Option<Account<'info, UserReputation>>,
+    
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    // remaining_accounts: light system program accounts and merkle trees
+}
+
+#[derive(Accounts)]
+pub struct GetVoiceData<'info> {
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    /// CHECK: This is the storage PDA from storage_manager contract
+    pub storage_pda: AccountInfo<'info>,
+    
+    pub requester: Signer<'info>,
+    
+    /// CHECK: The protocol's storage backend, checked against protocol_config; when passed,
+    /// the chunk is read through its storage_interface::read_chunk
+    pub storage_program: Option<UncheckedAccount<'info>>,
+    
+    /// Message that recorded the frame; when passed, the read is verified against its content_hash
+    pub voice_message: Option<Account<'info, VoiceMessage>>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct LeaveVoiceRoom<'info> {
+    #[account(mut)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        mut,
+        close = participant,
+        seeds = [b"participant", voice_room.key().as_ref(), participant.key().as_ref()],
+        bump = participant_account.bump
+    )]
+    pub participant_account: Account<'info, Participant>,
+    
+    /// Listener's payment stream, stopped on leave; required once the listener opened one
+    #[account(
+        mut,
+        seeds = [b"stream", voice_room.key().as_ref(), participant.key().as_ref()],
+        bump = payment_stream.bump
+    )]
+    pub payment_stream: Option<Account<'info, PaymentStream>>,
+    
+    /// Participant's sender stake, whose cooldown starts on leave
+    #[account(
+        mut,
+        seeds = [b"stake", voice_room.key().as_ref(), participant.key().as_ref()],
+        bump = sender_stake.bump
+    )]
+    pub sender_stake: Option<Account<'info, SenderStake>>,
+    
+    /// Participant's reputation, credited with a completed session
+    #[account(
+        mut,
+        seeds = [b"reputation", participant.key().as_ref()],
+        bump = reputation.bump
+    )]
+    pub reputation: Option<Account<'info, UserReputation>>,
+    
+    /// Participant's statistics, credited with the time spent in the room
+    #[account(
+        mut,
+        seeds = [b"user_stats", participant.key().as_ref()],
+        bump = participant_stats.bump
+    )]
+    pub participant_stats: Option<Account<'info, UserStats>>,
+    
+    #[account(mut)]
+    pub participant: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct GetRoomInfo<'info> {
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    pub requester: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetRegionRelays<'info> {
+    pub voice_room: Account<'info, VoiceRoom>,
+}
+
+#[derive(Accounts)]
+#[instruction(voice_data: Vec<u8>, target_pdas: Vec<u8>, sequence_number: u32)]
 pub struct BroadcastVoiceData<'info> {
     #[account(mut)]
     pub voice_room: Account<'info, VoiceRoom>,
@@ -256,60 +5991,6582 @@ pub struct BroadcastVoiceData<'info> {
     #[account(
         init,
         payer = sender,
-        space = 8 + 32 + 4 + MAX_ROOM_ID_LENGTH + 10 + 4 + 4 + 8, // discriminator + sender + room_id_len + room_id + target_pdas + sequence_number + data_length + timestamp
-        seeds = [b"broadcast_message", sender.key().as_ref(), &sequence_number.to_le_bytes()],
+        space = 8 + 32 + 4 + MAX_ROOM_ID_LENGTH + 4 + STORAGE_LANE_COUNT + 4 + 4 + 8, // discriminator + sender + room_id_len + room_id + target_pdas_len + target_pdas + sequence_number + data_length + timestamp
+        seeds = [b"broadcast_message", sender.key().as_ref(), &sequence_number.to_le_bytes()],
+        bump
+    )]
+    pub broadcast_message: Account<'info, BroadcastMessage>,
+    
+    /// Sender's participant record; muted participants can't send
+    #[account(
+        seeds = [b"participant", voice_room.key().as_ref(), sender.key().as_ref()],
+        bump = sender_participant.bump
+    )]
+    pub sender_participant: Account<'info, Participant>,
+    
+    /// Sender's anti-spam stake (rooms with a stake requirement only)
+    #[account(
+        seeds = [b"stake", voice_room.key().as_ref(), sender.key().as_ref()],
+        bump = sender_stake.bump
+    )]
+    pub sender_stake: Option<Account<'info, SenderStake>>,
+    
+    /// Sender's reputation (rooms with a reputation threshold only)
+    #[account(
+        seeds = [b"reputation", sender.key().as_ref()],
+        bump = sender_reputation.bump
+    )]
+    pub sender_reputation: Option<Account<'info, UserReputation>>,
+    
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct Heartbeat<'info> {
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        mut,
+        seeds = [b"participant", voice_room.key().as_ref(), participant.key().as_ref()],
+        bump = participant_account.bump
+    )]
+    pub participant_account: Account<'info, Participant>,
+    
+    pub participant: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct StartSequenceEpoch<'info> {
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        mut,
+        seeds = [b"participant", voice_room.key().as_ref(), participant.key().as_ref()],
+        bump = participant_account.bump
+    )]
+    pub participant_account: Account<'info, Participant>,
+    
+    /// Participant's send tracking, which remembers their epoch across rejoins
+    #[account(
+        init_if_needed,
+        payer = participant,
+        space = 8 + 32 + 32 + 8 * IDEMPOTENCY_WINDOW + 1 + 1 + 4 + 8 + 4, // discriminator + room + sender + recent_keys + cursor + bump + last_sponsored_sequence + last_sent_slot + sequence_epoch
+        seeds = [b"recent_sends", voice_room.key().as_ref(), participant.key().as_ref()],
+        bump
+    )]
+    pub recent_sends: Account<'info, RecentSends>,
+    
+    #[account(mut)]
+    pub participant: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct AckRange<'info> {
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        mut,
+        seeds = [b"participant", voice_room.key().as_ref(), participant.key().as_ref()],
+        bump = participant_account.bump
+    )]
+    pub participant_account: Account<'info, Participant>,
+    
+    pub participant: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct TickRoom<'info> {
+    #[account(mut)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    // remaining_accounts: (Participant | VoiceMessage, rent_recipient) pairs, all writable
+}
+
+#[derive(Accounts)]
+pub struct SetTokenGate<'info> {
+    #[account(mut, constraint = voice_room.is_host(&host.key()) @ VoiceChatError::NotRoomHost)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    pub host: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetEntryFee<'info> {
+    #[account(mut, has_one = host)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        init_if_needed,
+        payer = host,
+        space = 8 + 32 + 8 + 8 + 8 + 8 + 1 + 1, // discriminator + room + lamports_collected + tokens_collected + tip_lamports_collected + tip_tokens_collected + split_configured + bump
+        seeds = [b"escrow", voice_room.key().as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, RoomEscrow>,
+    
+    #[account(mut)]
+    pub host: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeEscrowVault<'info> {
+    #[account(
+        has_one = host,
+        constraint = voice_room.entry_fee_mint == Some(fee_mint.key()) @ VoiceChatError::InvalidFeeMint
+    )]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        seeds = [b"escrow", voice_room.key().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, RoomEscrow>,
+    
+    #[account(
+        init,
+        payer = host,
+        seeds = [b"escrow_vault", voice_room.key().as_ref()],
+        bump,
+        token::mint = fee_mint,
+        token::authority = escrow,
+        token::token_program = token_program
+    )]
+    pub escrow_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub fee_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(mut)]
+    pub host: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawEscrow<'info> {
+    #[account(has_one = host)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", voice_room.key().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, RoomEscrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow_vault", voice_room.key().as_ref()],
+        bump
+    )]
+    pub escrow_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    
+    #[account(mut)]
+    pub host_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    
+    pub fee_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    
+    #[account(mut)]
+    pub host: Signer<'info>,
+    
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ScheduleEvent<'info> {
+    #[account(mut, has_one = host)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    pub host: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct BuyTicket<'info> {
+    #[account(mut)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + 32 + 32 + 8 + 8 + 1, // discriminator + room + holder + purchased_at + price_paid + consumed
+        seeds = [b"ticket", voice_room.key().as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub ticket: Account<'info, Ticket>,
+    
+    /// Room escrow receiving the ticket price (paid events only)
+    #[account(
+        mut,
+        seeds = [b"escrow", voice_room.key().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Option<Account<'info, RoomEscrow>>,
+    
+    /// SOL/USD feed configuration (USD-priced rooms only)
+    #[account(seeds = [b"price_feed"], bump = price_feed.bump)]
+    pub price_feed: Option<Account<'info, PriceFeedConfig>>,
+    
+    /// CHECK: Pyth price update for the configured feed, verified in pyth::read_price
+    pub sol_usd_price: Option<UncheckedAccount<'info>>,
+    
+    /// Protocol treasury receiving the protocol fee (required while the fee is non-zero)
+    #[account(
+        mut,
+        seeds = [b"protocol_treasury"],
+        bump = protocol_treasury.bump
+    )]
+    pub protocol_treasury: Option<Account<'info, ProtocolTreasury>>,
+    
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(amount: u64, mint: Option<Pubkey>)]
+pub struct TipSpeaker<'info> {
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    /// Tips can only go to current participants
+    #[account(
+        seeds = [b"participant", voice_room.key().as_ref(), speaker.key().as_ref()],
+        bump = speaker_participant.bump
+    )]
+    pub speaker_participant: Account<'info, Participant>,
+    
+    #[account(
+        init_if_needed,
+        payer = tipper,
+        space = 8 + 32 + 32 + 1 + 32 + 8 + 4 + 8 + 8, // discriminator + room + speaker + mint + total_amount + tip_count + last_tip_at + first_tip_at
+        seeds = [b"tip_tally", voice_room.key().as_ref(), speaker.key().as_ref(), &mint.unwrap_or_default().to_bytes()],
+        bump
+    )]
+    pub tip_tally: Account<'info, TipTally>,
+    
+    #[account(
+        mut,
+        seeds = [b"reputation", speaker.key().as_ref()],
+        bump = speaker_reputation.bump
+    )]
+    pub speaker_reputation: Option<Account<'info, UserReputation>>,
+    
+    /// Speaker's statistics, credited with the tip
+    #[account(
+        mut,
+        seeds = [b"user_stats", speaker.key().as_ref()],
+        bump = speaker_stats.bump
+    )]
+    pub speaker_stats: Option<Account<'info, UserStats>>,
+    
+    /// CHECK: Tip recipient, verified through speaker_participant
+    #[account(mut)]
+    pub speaker: AccountInfo<'info>,
+    
+    #[account(mut)]
+    pub tipper_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    
+    #[account(mut)]
+    pub speaker_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    
+    pub tip_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    
+    /// Protocol treasury receiving the protocol fee (required while the fee is non-zero)
+    #[account(
+        mut,
+        seeds = [b"protocol_treasury"],
+        bump = protocol_treasury.bump
+    )]
+    pub protocol_treasury: Option<Account<'info, ProtocolTreasury>>,
+    
+    /// Protocol treasury token account receiving token-denominated fees
+    #[account(mut)]
+    pub protocol_treasury_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    
+    #[account(mut)]
+    pub tipper: Signer<'info>,
+    
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetRevenueSplit<'info> {
+    #[account(has_one = host)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        init_if_needed,
+        payer = host,
+        space = 8 + 32 + 4 + MAX_SPLIT_RECIPIENTS * SplitShare::SIZE + 1, // discriminator + room + shares_len + shares + bump
+        seeds = [b"revenue_split", voice_room.key().as_ref()],
+        bump
+    )]
+    pub revenue_split: Account<'info, RevenueSplit>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", voice_room.key().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, RoomEscrow>,
+    
+    #[account(mut)]
+    pub host: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct TipRoom<'info> {
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", voice_room.key().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, RoomEscrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow_vault", voice_room.key().as_ref()],
+        bump
+    )]
+    pub escrow_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    
+    #[account(mut)]
+    pub tipper_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    
+    pub tip_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    
+    /// Protocol treasury receiving the protocol fee (required while the fee is non-zero)
+    #[account(
+        mut,
+        seeds = [b"protocol_treasury"],
+        bump = protocol_treasury.bump
+    )]
+    pub protocol_treasury: Option<Account<'info, ProtocolTreasury>>,
+    
+    /// Protocol treasury token account receiving token-denominated fees
+    #[account(mut)]
+    pub protocol_treasury_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    
+    #[account(mut)]
+    pub tipper: Signer<'info>,
+    
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SettleRoom<'info> {
+    #[account(has_one = host)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        seeds = [b"revenue_split", voice_room.key().as_ref()],
+        bump = revenue_split.bump
+    )]
+    pub revenue_split: Account<'info, RevenueSplit>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", voice_room.key().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, RoomEscrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow_vault", voice_room.key().as_ref()],
+        bump
+    )]
+    pub escrow_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    
+    pub fee_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    
+    pub host: Signer<'info>,
+    
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    // remaining_accounts: recipient wallets, recipient token accounts, transfer hook accounts
+}
+
+#[derive(Accounts)]
+pub struct ClaimRefund<'info> {
+    #[account(mut)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        mut,
+        close = holder,
+        seeds = [b"ticket", voice_room.key().as_ref(), holder.key().as_ref()],
+        bump
+    )]
+    pub ticket: Account<'info, Ticket>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", voice_room.key().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Option<Account<'info, RoomEscrow>>,
+    
+    #[account(mut)]
+    pub holder: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRoomConfig<'info> {
+    #[account(mut, constraint = voice_room.is_host(&host.key()) @ VoiceChatError::NotRoomHost)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    pub host: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct OpenStream<'info> {
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        mut,
+        seeds = [b"participant", voice_room.key().as_ref(), listener.key().as_ref()],
+        bump = participant_account.bump
+    )]
+    pub participant_account: Account<'info, Participant>,
+    
+    #[account(
+        init,
+        payer = listener,
+        space = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 1, // discriminator + room + listener + rate + deposited + paid_out + last_settled_at + stopped_at + bump
+        seeds = [b"stream", voice_room.key().as_ref(), listener.key().as_ref()],
+        bump
+    )]
+    pub payment_stream: Account<'info, PaymentStream>,
+    
+    #[account(mut)]
+    pub listener: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct TopUpStream<'info> {
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        mut,
+        seeds = [b"stream", voice_room.key().as_ref(), listener.key().as_ref()],
+        bump = payment_stream.bump
+    )]
+    pub payment_stream: Account<'info, PaymentStream>,
+    
+    #[account(mut)]
+    pub listener: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SettleStream<'info> {
+    #[account(has_one = host)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        mut,
+        seeds = [b"stream", voice_room.key().as_ref(), payment_stream.listener.as_ref()],
+        bump = payment_stream.bump
+    )]
+    pub payment_stream: Account<'info, PaymentStream>,
+    
+    /// Listener's participant record; required while the stream is still running
+    #[account(
+        seeds = [b"participant", voice_room.key().as_ref(), payment_stream.listener.as_ref()],
+        bump = participant_account.bump
+    )]
+    pub participant_account: Option<Account<'info, Participant>>,
+    
+    /// CHECK: Stream payee, checked against voice_room.host
+    #[account(mut)]
+    pub host: AccountInfo<'info>,
+    
+    pub settler: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CloseStream<'info> {
+    #[account(has_one = host)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        mut,
+        close = listener,
+        seeds = [b"stream", voice_room.key().as_ref(), listener.key().as_ref()],
+        bump = payment_stream.bump
+    )]
+    pub payment_stream: Account<'info, PaymentStream>,
+    
+    /// CHECK: Listener's participant PDA; a stream can be closed unstopped once it no longer exists
+    #[account(
+        seeds = [b"participant", voice_room.key().as_ref(), listener.key().as_ref()],
+        bump
+    )]
+    pub participant_account: UncheckedAccount<'info>,
+    
+    /// CHECK: Stream payee, checked against voice_room.host
+    #[account(mut)]
+    pub host: AccountInfo<'info>,
+    
+    #[account(mut)]
+    pub listener: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct DepositStake<'info> {
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = 8 + 32 + 32 + 8 + 8 + 1 + 8 + 8, // discriminator + room + owner + amount + left_at + bump + session_joined_at + session_left_at
+        seeds = [b"stake", voice_room.key().as_ref(), sender.key().as_ref()],
+        bump
+    )]
+    pub sender_stake: Account<'info, SenderStake>,
+    
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawStake<'info> {
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        mut,
+        close = sender,
+        seeds = [b"stake", voice_room.key().as_ref(), sender.key().as_ref()],
+        bump = sender_stake.bump
+    )]
+    pub sender_stake: Account<'info, SenderStake>,
+    
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetModerationPolicy<'info> {
+    #[account(mut, constraint = voice_room.is_host(&host.key()) @ VoiceChatError::NotRoomHost)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        init_if_needed,
+        payer = host,
+        space = 8 + 32 + 8 + 1, // discriminator + room + slashed_lamports + bump
+        seeds = [b"room_treasury", voice_room.key().as_ref()],
+        bump
+    )]
+    pub room_treasury: Account<'info, RoomTreasury>,
+    
+    #[account(mut)]
+    pub host: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(reason_code: u8, offender: Pubkey, session_joined_at: i64)]
+pub struct ReportSender<'info> {
+    #[account(mut)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    /// Only participants can report
+    #[account(
+        seeds = [b"participant", voice_room.key().as_ref(), reporter.key().as_ref()],
+        bump = reporter_participant.bump
+    )]
+    pub reporter_participant: Account<'info, Participant>,
+    
+    /// Offender's participant record; absent once they left, when their stake must be in cooldown
+    #[account(
+        mut,
+        seeds = [b"participant", voice_room.key().as_ref(), offender.as_ref()],
+        bump = offender_participant.bump
+    )]
+    pub offender_participant: Option<Account<'info, Participant>>,
+    
+    /// One report per (offender session, reporter) - a second report fails to init
+    #[account(
+        init,
+        payer = reporter,
+        space = 8 + 32 + 32 + 32 + 1 + 8 + 8, // discriminator + room + offender + reporter + reason_code + session_joined_at + created_at
+        seeds = [
+            b"report",
+            voice_room.key().as_ref(),
+            offender.as_ref(),
+            reporter.key().as_ref(),
+            &session_joined_at.to_le_bytes()
+        ],
+        bump
+    )]
+    pub report: Account<'info, Report>,
+    
+    #[account(
+        init_if_needed,
+        payer = reporter,
+        space = 8 + 32 + 32 + 8 + 2 + 1 + 1, // discriminator + room + offender + session_joined_at + report_count + slashed + bump
+        seeds = [b"report_tally", voice_room.key().as_ref(), offender.as_ref()],
+        bump
+    )]
+    pub report_tally: Account<'info, ReportTally>,
+    
+    /// Required when the room sets a sender stake
+    #[account(
+        mut,
+        seeds = [b"stake", voice_room.key().as_ref(), offender.as_ref()],
+        bump = offender_stake.bump
+    )]
+    pub offender_stake: Option<Account<'info, SenderStake>>,
+    
+    /// Required when the room sets a sender stake
+    #[account(
+        mut,
+        seeds = [b"room_treasury", voice_room.key().as_ref()],
+        bump = room_treasury.bump
+    )]
+    pub room_treasury: Option<Account<'info, RoomTreasury>>,
+    
+    /// Created for the offender if they never set one up, so penalties can't be dodged
+    #[account(
+        init_if_needed,
+        payer = reporter,
+        space = 8 + 32 + 4 + 4 + 4 + 4 + 1, // discriminator + user + sessions_completed + tips_received + reports_upheld + slashes + bump
+        seeds = [b"reputation", offender.as_ref()],
+        bump
+    )]
+    pub offender_reputation: Account<'info, UserReputation>,
+    
+    #[account(mut)]
+    pub reporter: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawRoomTreasury<'info> {
+    #[account(has_one = host)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        mut,
+        seeds = [b"room_treasury", voice_room.key().as_ref()],
+        bump = room_treasury.bump
+    )]
+    pub room_treasury: Account<'info, RoomTreasury>,
+    
+    #[account(mut)]
+    pub host: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeReputation<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = 8 + 32 + 4 + 4 + 4 + 4 + 1, // discriminator + user + sessions_completed + tips_received + reports_upheld + slashes + bump
+        seeds = [b"reputation", user.key().as_ref()],
+        bump
+    )]
+    pub reputation: Account<'info, UserReputation>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct GetReputation<'info> {
+    #[account(
+        seeds = [b"reputation", reputation.user.as_ref()],
+        bump = reputation.bump
+    )]
+    pub reputation: Account<'info, UserReputation>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeUserStats<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = 8 + 32 + 4 + 8 + 8 + 8 + 4 + 8 + 1, // discriminator + user + rooms_joined + messages_sent + bytes_sent + talk_time_seconds + tips_received + tip_lamports_received + bump
+        seeds = [b"user_stats", user.key().as_ref()],
+        bump
+    )]
+    pub user_stats: Account<'info, UserStats>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct GetUserStats<'info> {
+    #[account(
+        seeds = [b"user_stats", user_stats.user.as_ref()],
+        bump = user_stats.bump
+    )]
+    pub user_stats: Account<'info, UserStats>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeProtocolConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + 32 + 2 + 1 + 1 + 1 + 32 + 1 + 32 + 1 + 32, // discriminator + admin + fee_bps + paused + bump + pending_admin + transcription_oracle + storage_backend
+        seeds = [b"protocol_config"],
+        bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + 8 + 1, // discriminator + lamports_collected + bump
+        seeds = [b"protocol_treasury"],
+        bump
+    )]
+    pub protocol_treasury: Account<'info, ProtocolTreasury>,
+    
+    /// Only the program's upgrade authority can claim the admin role
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = anchor_lang::solana_program::bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(admin.key()) @ VoiceChatError::NotUpgradeAuthority
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateProtocolConfig<'info> {
+    #[account(
+        mut,
+        has_one = admin,
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = protocol_config.pending_admin == Some(pending_admin.key()) @ VoiceChatError::NotPendingAdmin
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    
+    pub pending_admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    #[account(
+        has_one = admin,
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"protocol_treasury"],
+        bump = protocol_treasury.bump
+    )]
+    pub protocol_treasury: Account<'info, ProtocolTreasury>,
+    
+    /// Treasury-owned token account to sweep
+    #[account(mut)]
+    pub protocol_treasury_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    
+    #[account(mut)]
+    pub admin_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    
+    pub treasury_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(Accounts)]
+#[instruction(target: Pubkey)]
+pub struct Follow<'info> {
+    #[account(
+        init,
+        payer = follower,
+        space = 8 + 32 + 32 + 8 + 1, // discriminator + follower + target + created_at + bump
+        seeds = [b"follow", follower.key().as_ref(), target.as_ref()],
+        bump
+    )]
+    pub follow_edge: Account<'info, FollowEdge>,
+    
+    #[account(mut)]
+    pub follower: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(target: Pubkey)]
+pub struct Unfollow<'info> {
+    #[account(
+        mut,
+        close = follower,
+        seeds = [b"follow", follower.key().as_ref(), target.as_ref()],
+        bump = follow_edge.bump
+    )]
+    pub follow_edge: Account<'info, FollowEdge>,
+    
+    #[account(mut)]
+    pub follower: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(target: Pubkey)]
+pub struct Block<'info> {
+    #[account(
+        init,
+        payer = blocker,
+        space = 8 + 32 + 32 + 8 + 1, // discriminator + blocker + blocked + created_at + bump
+        seeds = [b"block", blocker.key().as_ref(), target.as_ref()],
+        bump
+    )]
+    pub block_entry: Account<'info, BlockEntry>,
+    
+    #[account(mut)]
+    pub blocker: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(target: Pubkey)]
+pub struct Unblock<'info> {
+    #[account(
+        mut,
+        close = blocker,
+        seeds = [b"block", blocker.key().as_ref(), target.as_ref()],
+        bump = block_entry.bump
+    )]
+    pub block_entry: Account<'info, BlockEntry>,
+    
+    #[account(mut)]
+    pub blocker: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(callee: Pubkey)]
+pub struct InitiateCall<'info> {
+    #[account(
+        init,
+        payer = caller,
+        space = 8 + 32 + 32 + 1 + 32 + 8 + 8 + 8 + 1, // discriminator + caller + callee + status + storage_lane + created_at + answered_at + last_activity + bump
+        seeds = [b"direct_call", caller.key().as_ref(), callee.as_ref()],
+        bump
+    )]
+    pub direct_call: Account<'info, DirectCall>,
+    
+    /// CHECK: Callee's block PDA for the caller; must not exist
+    #[account(
+        seeds = [b"block", callee.as_ref(), caller.key().as_ref()],
+        bump
+    )]
+    pub callee_block: UncheckedAccount<'info>,
+    
+    /// CHECK: This program's PDA that owns the call's storage lane; funded with its rent
+    #[account(mut, seeds = [b"call_lane", direct_call.key().as_ref()], bump)]
+    pub lane_authority: UncheckedAccount<'info>,
+    
+    /// CHECK: The call's storage lane, allocated here by the storage backend
+    #[account(
+        mut,
+        seeds = [b"storage", lane_authority.key().as_ref(), &[0]],
+        bump,
+        seeds::program = protocol_config.storage_backend()
+    )]
+    pub storage_lane: UncheckedAccount<'info>,
+    
+    /// CHECK: The protocol's storage backend, checked against protocol_config
+    #[account(address = protocol_config.storage_backend() @ VoiceChatError::InvalidStorageBackend)]
+    pub storage_program: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub caller: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct AnswerCall<'info> {
+    #[account(
+        mut,
+        has_one = callee,
+        seeds = [b"direct_call", direct_call.caller.as_ref(), callee.key().as_ref()],
+        bump = direct_call.bump
+    )]
+    pub direct_call: Account<'info, DirectCall>,
+    
+    pub callee: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RejectCall<'info> {
+    #[account(
+        mut,
+        close = caller,
+        has_one = caller,
+        has_one = callee,
+        seeds = [b"direct_call", caller.key().as_ref(), callee.key().as_ref()],
+        bump = direct_call.bump
+    )]
+    pub direct_call: Account<'info, DirectCall>,
+    
+    /// CHECK: Receives the call PDA's rent, verified through has_one
+    #[account(mut)]
+    pub caller: AccountInfo<'info>,
+    
+    pub callee: Signer<'info>,
+    
+    /// CHECK: This program's PDA that owns the call's storage lane
+    #[account(mut, seeds = [b"call_lane", direct_call.key().as_ref()], bump)]
+    pub lane_authority: UncheckedAccount<'info>,
+    
+    /// CHECK: The call's storage lane, freed with the call
+    #[account(mut, address = direct_call.storage_lane @ VoiceChatError::InvalidStoragePDA)]
+    pub storage_lane: UncheckedAccount<'info>,
+    
+    /// CHECK: The protocol's storage backend, checked against protocol_config
+    #[account(address = protocol_config.storage_backend() @ VoiceChatError::InvalidStorageBackend)]
+    pub storage_program: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct EndCall<'info> {
+    #[account(
+        mut,
+        close = caller,
+        has_one = caller,
+        seeds = [b"direct_call", caller.key().as_ref(), direct_call.callee.as_ref()],
+        bump = direct_call.bump,
+        constraint = party.key() == direct_call.caller || party.key() == direct_call.callee @ VoiceChatError::NotCallParty
+    )]
+    pub direct_call: Account<'info, DirectCall>,
+    
+    /// CHECK: Receives the call PDA's rent, verified through has_one
+    #[account(mut)]
+    pub caller: AccountInfo<'info>,
+    
+    pub party: Signer<'info>,
+    
+    /// CHECK: This program's PDA that owns the call's storage lane
+    #[account(mut, seeds = [b"call_lane", direct_call.key().as_ref()], bump)]
+    pub lane_authority: UncheckedAccount<'info>,
+    
+    /// CHECK: The call's storage lane, freed with the call
+    #[account(mut, address = direct_call.storage_lane @ VoiceChatError::InvalidStoragePDA)]
+    pub storage_lane: UncheckedAccount<'info>,
+    
+    /// CHECK: The protocol's storage backend, checked against protocol_config
+    #[account(address = protocol_config.storage_backend() @ VoiceChatError::InvalidStorageBackend)]
+    pub storage_program: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SendCallVoiceData<'info> {
+    #[account(
+        mut,
+        seeds = [b"direct_call", direct_call.caller.as_ref(), direct_call.callee.as_ref()],
+        bump = direct_call.bump,
+        constraint = sender.key() == direct_call.caller || sender.key() == direct_call.callee @ VoiceChatError::NotCallParty
+    )]
+    pub direct_call: Account<'info, DirectCall>,
+    
+    /// CHECK: The call's storage PDA from storage_manager contract
+    #[account(mut, address = direct_call.storage_lane @ VoiceChatError::InvalidStoragePDA)]
+    pub storage_lane: AccountInfo<'info>,
+    
+    pub sender: Signer<'info>,
+    
+    /// CHECK: The protocol's storage backend, checked against protocol_config; when passed,
+    /// storage writes go through its storage_interface::write_chunk
+    pub storage_program: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: This program's PDA that signs write_chunk CPIs
+    #[account(seeds = [storage_layout::WRITER_SEED], bump)]
+    pub storage_writer: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeCallLog<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + 32 + 4 + MAX_MISSED_CALLS * MissedCall::SIZE + 1, // discriminator + owner + missed_calls + bump
+        seeds = [b"call_log", owner.key().as_ref()],
+        bump
+    )]
+    pub call_log: Account<'info, CallLog>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ExpireCall<'info> {
+    #[account(
+        mut,
+        close = caller,
+        has_one = caller,
+        seeds = [b"direct_call", caller.key().as_ref(), direct_call.callee.as_ref()],
+        bump = direct_call.bump
+    )]
+    pub direct_call: Account<'info, DirectCall>,
+    
+    #[account(
+        mut,
+        seeds = [b"call_log", direct_call.callee.as_ref()],
+        bump = call_log.bump
+    )]
+    pub call_log: Option<Account<'info, CallLog>>,
+    
+    /// CHECK: Receives the call PDA's remaining rent, verified through has_one
+    #[account(mut)]
+    pub caller: AccountInfo<'info>,
+    
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+    
+    /// CHECK: This program's PDA that owns the call's storage lane
+    #[account(mut, seeds = [b"call_lane", direct_call.key().as_ref()], bump)]
+    pub lane_authority: UncheckedAccount<'info>,
+    
+    /// CHECK: The call's storage lane, freed with the call
+    #[account(mut, address = direct_call.storage_lane @ VoiceChatError::InvalidStoragePDA)]
+    pub storage_lane: UncheckedAccount<'info>,
+    
+    /// CHECK: The protocol's storage backend, checked against protocol_config
+    #[account(address = protocol_config.storage_backend() @ VoiceChatError::InvalidStorageBackend)]
+    pub storage_program: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeVoicemailInbox<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + 32 + 8 + 4 + 1, // discriminator + owner + next_voicemail_id + unplayed_count + bump
+        seeds = [b"voicemail_inbox", owner.key().as_ref()],
+        bump
+    )]
+    pub voicemail_inbox: Account<'info, VoicemailInbox>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(recipient: Pubkey)]
+pub struct LeaveVoicemail<'info> {
+    #[account(
+        mut,
+        seeds = [b"voicemail_inbox", recipient.as_ref()],
+        bump = voicemail_inbox.bump
+    )]
+    pub voicemail_inbox: Account<'info, VoicemailInbox>,
+    
+    #[account(
+        init,
+        payer = sender,
+        space = 8 + 32 + 32 + 8 + 32 + 4 + 8 + 1 + 1, // discriminator + recipient + sender + id + storage_lane + data_length + left_at + played + bump
+        seeds = [b"voicemail", recipient.as_ref(), &voicemail_inbox.next_voicemail_id.to_le_bytes()],
+        bump
+    )]
+    pub voicemail: Account<'info, Voicemail>,
+    
+    /// CHECK: Recipient's block PDA for the sender; must not exist
+    #[account(
+        seeds = [b"block", recipient.as_ref(), sender.key().as_ref()],
+        bump
+    )]
+    pub recipient_block: UncheckedAccount<'info>,
+    
+    /// CHECK: This program's PDA that owns the voicemail's storage lane; funded with its rent
+    #[account(
+        mut,
+        seeds = [
+            b"voicemail_lane",
+            recipient.as_ref(),
+            sender.key().as_ref(),
+            &voicemail_inbox.next_voicemail_id.to_le_bytes()
+        ],
+        bump
+    )]
+    pub lane_authority: UncheckedAccount<'info>,
+    
+    /// CHECK: The voicemail's storage lane, allocated here by the storage backend
+    #[account(
+        mut,
+        seeds = [b"storage", lane_authority.key().as_ref(), &[0]],
+        bump,
+        seeds::program = protocol_config.storage_backend()
+    )]
+    pub storage_lane: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    /// CHECK: The protocol's storage backend, checked against protocol_config; when passed,
+    /// storage writes go through its storage_interface::write_chunk
+    pub storage_program: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: This program's PDA that signs write_chunk CPIs
+    #[account(seeds = [storage_layout::WRITER_SEED], bump)]
+    pub storage_writer: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct MarkPlayed<'info> {
+    #[account(
+        mut,
+        seeds = [b"voicemail_inbox", recipient.key().as_ref()],
+        bump = voicemail_inbox.bump
+    )]
+    pub voicemail_inbox: Account<'info, VoicemailInbox>,
+    
+    #[account(
+        mut,
+        has_one = recipient,
+        seeds = [b"voicemail", recipient.key().as_ref(), &voicemail.id.to_le_bytes()],
+        bump = voicemail.bump
+    )]
+    pub voicemail: Account<'info, Voicemail>,
+    
+    pub recipient: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct DeleteVoicemail<'info> {
+    #[account(
+        mut,
+        seeds = [b"voicemail_inbox", recipient.key().as_ref()],
+        bump = voicemail_inbox.bump
+    )]
+    pub voicemail_inbox: Account<'info, VoicemailInbox>,
+    
+    #[account(
+        mut,
+        close = recipient,
+        has_one = recipient,
+        has_one = sender,
+        seeds = [b"voicemail", recipient.key().as_ref(), &voicemail.id.to_le_bytes()],
+        bump = voicemail.bump
+    )]
+    pub voicemail: Account<'info, Voicemail>,
+    
+    /// CHECK: This program's PDA that owns the voicemail's storage lane
+    #[account(
+        mut,
+        seeds = [
+            b"voicemail_lane",
+            recipient.key().as_ref(),
+            sender.key().as_ref(),
+            &voicemail.id.to_le_bytes()
+        ],
+        bump
+    )]
+    pub lane_authority: UncheckedAccount<'info>,
+    
+    /// CHECK: The voicemail's storage lane, freed with it
+    #[account(mut, address = voicemail.storage_lane @ VoiceChatError::InvalidStoragePDA)]
+    pub storage_lane: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub recipient: Signer<'info>,
+    
+    /// CHECK: The voicemail's sender, who paid for the lane and gets its rent back
+    #[account(mut)]
+    pub sender: AccountInfo<'info>,
+    
+    /// CHECK: The protocol's storage backend, checked against protocol_config
+    #[account(address = protocol_config.storage_backend() @ VoiceChatError::InvalidStorageBackend)]
+    pub storage_program: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct EvictStale<'info> {
+    #[account(mut)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        mut,
+        close = participant,
+        seeds = [b"participant", voice_room.key().as_ref(), participant.key().as_ref()],
+        bump = participant_account.bump
+    )]
+    pub participant_account: Account<'info, Participant>,
+    
+    /// CHECK: The stale participant's wallet, receiving the remaining rent
+    #[account(mut)]
+    pub participant: AccountInfo<'info>,
+    
+    /// Participant's payment stream; required when they opened one
+    #[account(
+        mut,
+        seeds = [b"stream", voice_room.key().as_ref(), participant.key().as_ref()],
+        bump = payment_stream.bump
+    )]
+    pub payment_stream: Option<Account<'info, PaymentStream>>,
+    
+    /// CHECK: Stream payee, must be voice_room.host
+    #[account(mut, address = voice_room.host)]
+    pub host: Option<UncheckedAccount<'info>>,
+    
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ParticipantSignal<'info> {
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        seeds = [b"participant", voice_room.key().as_ref(), participant.key().as_ref()],
+        bump = participant_account.bump
+    )]
+    pub participant_account: Account<'info, Participant>,
+    
+    pub participant: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeChatLog<'info> {
+    #[account(constraint = voice_room.is_host(&host.key()) @ VoiceChatError::NotRoomHost)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        init,
+        payer = host,
+        space = 8 + 32 + 4 + MAX_CHAT_LOG_ENTRIES * ChatEntry::SIZE + 1, // discriminator + room + messages + bump
+        seeds = [b"chat_log", voice_room.key().as_ref()],
+        bump
+    )]
+    pub chat_log: Account<'info, ChatLog>,
+    
+    #[account(mut)]
+    pub host: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SendTextMessage<'info> {
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        seeds = [b"participant", voice_room.key().as_ref(), sender.key().as_ref()],
+        bump = sender_participant.bump
+    )]
+    pub sender_participant: Account<'info, Participant>,
+    
+    #[account(
+        mut,
+        seeds = [b"chat_log", voice_room.key().as_ref()],
+        bump = chat_log.bump
+    )]
+    pub chat_log: Option<Account<'info, ChatLog>>,
+    
+    pub sender: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CreatePoll<'info> {
+    #[account(mut, constraint = voice_room.is_host(&host.key()) @ VoiceChatError::NotRoomHost)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        init,
+        payer = host,
+        space = 8 + 32 + 4 + 4 + MAX_POLL_OPTIONS * (4 + MAX_POLL_OPTION_LENGTH) + 4 + MAX_POLL_OPTIONS * 4 + 8 + 8 + 1 + 1, // discriminator + room + id + options + votes + created_at + ends_at + closed + bump
+        seeds = [b"poll", voice_room.key().as_ref(), &voice_room.poll_count.to_le_bytes()],
+        bump
+    )]
+    pub poll: Account<'info, Poll>,
+    
+    #[account(mut)]
+    pub host: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct Vote<'info> {
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        mut,
+        seeds = [b"poll", voice_room.key().as_ref(), &poll.id.to_le_bytes()],
+        bump = poll.bump
+    )]
+    pub poll: Account<'info, Poll>,
+    
+    /// Only participants can vote
+    #[account(
+        seeds = [b"participant", voice_room.key().as_ref(), voter.key().as_ref()],
+        bump = voter_participant.bump
+    )]
+    pub voter_participant: Account<'info, Participant>,
+    
+    /// One vote per participant - a second vote fails to init
+    #[account(
+        init,
+        payer = voter,
+        space = 8 + 32 + 32 + 1, // discriminator + poll + voter + option
+        seeds = [b"poll_vote", poll.key().as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub poll_vote: Account<'info, PollVote>,
+    
+    #[account(mut)]
+    pub voter: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ClosePoll<'info> {
+    #[account(constraint = voice_room.is_host(&host.key()) @ VoiceChatError::NotRoomHost)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        mut,
+        seeds = [b"poll", voice_room.key().as_ref(), &poll.id.to_le_bytes()],
+        bump = poll.bump
+    )]
+    pub poll: Account<'info, Poll>,
+    
+    pub host: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct StartSession<'info> {
+    #[account(mut, constraint = voice_room.is_host(&host.key()) @ VoiceChatError::NotRoomHost)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        init,
+        payer = host,
+        space = 8 + 32 + 4 + 8 + 8 + 1 + 8 + 8 + 1, // discriminator + room + id + started_at + ended_at + peak_participants + total_frames + total_bytes + bump
+        seeds = [b"session", voice_room.key().as_ref(), &voice_room.session_count.to_le_bytes()],
+        bump
+    )]
+    pub session: Account<'info, Session>,
+    
+    #[account(mut)]
+    pub host: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct EndSession<'info> {
+    #[account(mut, constraint = voice_room.is_host(&host.key()) @ VoiceChatError::NotRoomHost)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    /// The running session (the room's latest)
+    #[account(
+        mut,
+        seeds = [b"session", voice_room.key().as_ref(), &voice_room.session_count.saturating_sub(1).to_le_bytes()],
+        bump = session.bump
+    )]
+    pub session: Account<'info, Session>,
+    
+    pub host: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimAttendance<'info> {
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    /// Attendees claim before leaving; leaving closes this record
+    #[account(
+        seeds = [b"participant", voice_room.key().as_ref(), attendee.key().as_ref()],
+        bump = participant_account.bump
+    )]
+    pub participant_account: Account<'info, Participant>,
+    
+    #[account(
+        init,
+        payer = attendee,
+        space = 8 + 32 + 32 + 8 + 8 + 8 + 1, // discriminator + room + attendee + session_ended_at + joined_at + duration_seconds + bump
+        seeds = [
+            b"attendance",
+            voice_room.key().as_ref(),
+            attendee.key().as_ref(),
+            &voice_room.session_ended_at.to_le_bytes()
+        ],
+        bump
+    )]
+    pub attendance_record: Account<'info, AttendanceRecord>,
+    
+    #[account(mut)]
+    pub attendee: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetRecordingConsent<'info> {
+    #[account(mut)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        seeds = [b"participant", voice_room.key().as_ref(), participant.key().as_ref()],
+        bump = participant_account.bump
+    )]
+    pub participant_account: Account<'info, Participant>,
+    
+    pub participant: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeRecording<'info> {
+    #[account(constraint = voice_room.can_record(&recorder.key()) @ VoiceChatError::NotRecorder)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        seeds = [b"session", voice_room.key().as_ref(), &session.id.to_le_bytes()],
+        bump = session.bump
+    )]
+    pub session: Account<'info, Session>,
+    
+    #[account(
+        init,
+        payer = recorder,
+        space = 8 + 32 + 32 + 4 + MAX_CONTENT_ID_LENGTH + 32 + 4 + 8 + 1, // discriminator + room + session + cid_or_tx_id + content_hash + duration_seconds + finalized_at + bump
+        seeds = [b"recording", session.key().as_ref()],
+        bump
+    )]
+    pub recording: Account<'info, Recording>,
+    
+    /// A host or the room's designated recorder
+    #[account(mut)]
+    pub recorder: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct MintClip<'info> {
+    #[account(has_one = host)]
+    pub voice_room: Box<Account<'info, VoiceRoom>>,
+    
+    #[account(
+        seeds = [b"recording", recording.session.as_ref()],
+        bump = recording.bump,
+        constraint = recording.room == voice_room.key() @ VoiceChatError::InvalidRecording
+    )]
+    pub recording: Box<Account<'info, Recording>>,
+    
+    #[account(
+        seeds = [b"revenue_split", voice_room.key().as_ref()],
+        bump = revenue_split.bump
+    )]
+    pub revenue_split: Box<Account<'info, RevenueSplit>>,
+    
+    #[account(
+        init,
+        payer = host,
+        mint::decimals = 0,
+        mint::authority = host,
+        mint::freeze_authority = host,
+        mint::token_program = token_program
+    )]
+    pub clip_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        init,
+        payer = host,
+        associated_token::mint = clip_mint,
+        associated_token::authority = host,
+        associated_token::token_program = token_program
+    )]
+    pub host_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        init,
+        payer = host,
+        space = 8 + 32 + 32 + 4 + 4 + 1, // discriminator + mint + recording + start_ms + end_ms + bump
+        seeds = [b"clip_nft", clip_mint.key().as_ref()],
+        bump
+    )]
+    pub clip_nft: Box<Account<'info, ClipNft>>,
+    
+    /// CHECK: Created and validated by the token metadata program
+    #[account(mut)]
+    pub metadata: UncheckedAccount<'info>,
+    
+    /// CHECK: Created and validated by the token metadata program
+    #[account(mut)]
+    pub master_edition: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub host: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    
+    pub token_metadata_program: Program<'info, Metadata>,
+    
+    pub system_program: Program<'info, System>,
+    
+    pub rent: Sysvar<'info, Rent>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitTranscript<'info> {
+    #[account(
+        seeds = [b"session", session.room.as_ref(), &session.id.to_le_bytes()],
+        bump = session.bump
+    )]
+    pub session: Account<'info, Session>,
+    
+    #[account(
+        init,
+        payer = oracle,
+        space = 8 + 32 + 32 + 4 + MAX_TRANSCRIPT_URI_LENGTH + 32 + 4 + MAX_TRANSCRIPT_PROVIDER_LENGTH + 8 + 1, // discriminator + session + oracle + uri + hash + provider + submitted_at + bump
+        seeds = [b"transcript", session.key().as_ref()],
+        bump
+    )]
+    pub transcript: Account<'info, Transcript>,
+    
+    #[account(
+        mut,
+        constraint = protocol_config.transcription_oracle == Some(oracle.key()) @ VoiceChatError::NotTranscriptionOracle
+    )]
+    pub oracle: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(target: Pubkey)]
+pub struct PostSdpOffer<'info> {
+    #[account(
+        init,
+        payer = offerer,
+        space = 8 + 32 + 32 + 32 + 32 + 1 + 32 + 1 + 32 + 8 + 1, // discriminator + offerer + answerer + offer_hash + offer_slot + answer_hash + answer_slot + created_at + bump
+        seeds = [b"sdp", offerer.key().as_ref(), target.as_ref()],
+        bump
+    )]
+    pub sdp_signal: Account<'info, SdpSignal>,
+    
+    /// CHECK: Target's block PDA for the offerer; must not exist
+    #[account(
+        seeds = [b"block", target.as_ref(), offerer.key().as_ref()],
+        bump
+    )]
+    pub target_block: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub offerer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct PostSdpAnswer<'info> {
+    #[account(
+        mut,
+        has_one = answerer,
+        seeds = [b"sdp", sdp_signal.offerer.as_ref(), answerer.key().as_ref()],
+        bump = sdp_signal.bump
+    )]
+    pub sdp_signal: Account<'info, SdpSignal>,
+    
+    pub answerer: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CloseSdpSignal<'info> {
+    #[account(
+        mut,
+        close = offerer,
+        has_one = offerer,
+        seeds = [b"sdp", offerer.key().as_ref(), sdp_signal.answerer.as_ref()],
+        bump = sdp_signal.bump,
+        constraint = party.key() == sdp_signal.offerer || party.key() == sdp_signal.answerer @ VoiceChatError::NotCallParty
+    )]
+    pub sdp_signal: Account<'info, SdpSignal>,
+    
+    /// CHECK: Receives the signal PDA's rent, verified through has_one
+    #[account(mut)]
+    pub offerer: AccountInfo<'info>,
+    
+    pub party: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(target: Pubkey)]
+pub struct PostIceCandidates<'info> {
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = 8 + 32 + 32 + 4 + MAX_ICE_CANDIDATES_LENGTH + 8 + 1, // discriminator + sender + target + candidates + updated_at + bump
+        seeds = [b"ice", sender.key().as_ref(), target.as_ref()],
+        bump
+    )]
+    pub ice_candidates: Account<'info, IceCandidates>,
+    
+    /// CHECK: Target's block PDA for the sender; must not exist
+    #[account(
+        seeds = [b"block", target.as_ref(), sender.key().as_ref()],
+        bump
+    )]
+    pub target_block: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CloseIceCandidates<'info> {
+    #[account(
+        mut,
+        close = sender,
+        has_one = sender,
+        seeds = [b"ice", sender.key().as_ref(), ice_candidates.target.as_ref()],
+        bump = ice_candidates.bump,
+        constraint = party.key() == ice_candidates.sender || party.key() == ice_candidates.target @ VoiceChatError::NotCallParty
+    )]
+    pub ice_candidates: Account<'info, IceCandidates>,
+    
+    /// CHECK: Receives the candidate PDA's rent, verified through has_one
+    #[account(mut)]
+    pub sender: AccountInfo<'info>,
+    
+    pub party: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterRelay<'info> {
+    #[account(
+        init,
+        payer = operator,
+        space = 8 + 32 + 32 + 1 + 8 + 8 + 8 + 2 + 1 + 8 + 1, // discriminator + operator + endpoint_hash + region + stake_lamports + registered_at + last_heartbeat + report_count + slashed + deregistered_at + bump
+        seeds = [b"relay", operator.key().as_ref()],
+        bump
+    )]
+    pub relay_node: Account<'info, RelayNode>,
+    
+    #[account(mut)]
+    pub operator: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RelayHeartbeat<'info> {
+    #[account(
+        mut,
+        has_one = operator,
+        seeds = [b"relay", operator.key().as_ref()],
+        bump = relay_node.bump
+    )]
+    pub relay_node: Account<'info, RelayNode>,
+    
+    pub operator: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawRelayStake<'info> {
+    #[account(
+        mut,
+        close = operator,
+        has_one = operator,
+        seeds = [b"relay", operator.key().as_ref()],
+        bump = relay_node.bump
+    )]
+    pub relay_node: Account<'info, RelayNode>,
+    
+    #[account(mut)]
+    pub operator: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetRoomRelay<'info> {
+    #[account(mut, constraint = voice_room.is_host(&host.key()) @ VoiceChatError::NotRoomHost)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        seeds = [b"relay", relay_node.operator.as_ref()],
+        bump = relay_node.bump
+    )]
+    pub relay_node: Option<Account<'info, RelayNode>>,
+    
+    pub host: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ReportRelay<'info> {
+    #[account(
+        constraint = voice_room.preferred_relay == Some(relay_node.key()) @ VoiceChatError::RelayNotPinned
+    )]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    /// Only participants of a room using the relay can report it
+    #[account(
+        seeds = [b"participant", voice_room.key().as_ref(), reporter.key().as_ref()],
+        bump = reporter_participant.bump
+    )]
+    pub reporter_participant: Account<'info, Participant>,
+    
+    #[account(
+        mut,
+        seeds = [b"relay", relay_node.operator.as_ref()],
+        bump = relay_node.bump
+    )]
+    pub relay_node: Account<'info, RelayNode>,
+    
+    /// One report per (relay, reporter) - a second report fails to init
+    #[account(
+        init,
+        payer = reporter,
+        space = 8 + 32 + 32 + 32 + 1 + 8, // discriminator + relay + reporter + room + reason_code + created_at
+        seeds = [b"relay_report", relay_node.key().as_ref(), reporter.key().as_ref()],
+        bump
+    )]
+    pub relay_report: Account<'info, RelayReport>,
+    
+    #[account(mut)]
+    pub reporter: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct AdjudicateRelayReports<'info> {
+    #[account(
+        mut,
+        seeds = [b"relay", relay_node.operator.as_ref()],
+        bump = relay_node.bump
+    )]
+    pub relay_node: Account<'info, RelayNode>,
+    
+    #[account(
+        mut,
+        seeds = [b"protocol_treasury"],
+        bump = protocol_treasury.bump
+    )]
+    pub protocol_treasury: Account<'info, ProtocolTreasury>,
+    
+    #[account(
+        has_one = admin,
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AttestDelivery<'info> {
+    #[account(
+        constraint = voice_room.preferred_relay == Some(relay_node.key()) @ VoiceChatError::RelayNotPinned
+    )]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        seeds = [b"session", voice_room.key().as_ref(), &session.id.to_le_bytes()],
+        bump = session.bump
+    )]
+    pub session: Account<'info, Session>,
+    
+    #[account(
+        has_one = operator,
+        seeds = [b"relay", operator.key().as_ref()],
+        bump = relay_node.bump,
+        constraint = !relay_node.slashed @ VoiceChatError::RelayNotActive
+    )]
+    pub relay_node: Account<'info, RelayNode>,
+    
+    #[account(
+        init,
+        payer = operator,
+        space = 8 + 32 + 32 + 32 + 8 + 8 + 8 + 8 + 1, // discriminator + session + relay + merkle_root + frames_delivered + first_sequence + last_sequence + submitted_at + bump
+        seeds = [b"delivery", session.key().as_ref(), relay_node.key().as_ref()],
+        bump
+    )]
+    pub delivery_attestation: Account<'info, DeliveryAttestation>,
+    
+    #[account(mut)]
+    pub operator: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CreateSessionKey<'info> {
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + 32 + 32 + 8 + 1 + 1, // discriminator + owner + session_signer + expiry + scope + bump
+        seeds = [b"session_key", owner.key().as_ref(), session_signer.key().as_ref()],
+        bump
+    )]
+    pub session_key: Account<'info, SessionKey>,
+    
+    /// CHECK: Ephemeral public key being granted authority
+    pub session_signer: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeSessionKey<'info> {
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner,
+        seeds = [b"session_key", owner.key().as_ref(), session_key.session_signer.as_ref()],
+        bump = session_key.bump
+    )]
+    pub session_key: Account<'info, SessionKey>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(voice_data: Vec<u8>, target_pda_index: u8, sequence_number: u32)]
+pub struct SendVoiceDataSponsored<'info> {
+    #[account(mut)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    /// CHECK: This is the storage PDA from storage_manager contract
+    #[account(mut)]
+    pub storage_pda: AccountInfo<'info>,
+    
+    #[account(
+        init,
+        payer = relayer,
+        space = 8 + 32 + 4 + MAX_ROOM_ID_LENGTH + 1 + 4 + 4 + 8 + 8 + 4 + 32 + 32, // discriminator + sender + room_id_len + room_id + storage_pda_index + sequence_number + data_length + timestamp + capture_timestamp_ms + sequence_epoch + storage_pda + content_hash
+        seeds = [b"voice_message", voice_room.key().as_ref(), &voice_room.message_count.to_le_bytes()],
+        bump
+    )]
+    pub voice_message: Account<'info, VoiceMessage>,
+    
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = 8 + 32 + 32 + 8 * IDEMPOTENCY_WINDOW + 1 + 1 + 4 + 8 + 4, // discriminator + room + sender + recent_keys + cursor + bump + last_sponsored_sequence + last_sent_slot + sequence_epoch
+        seeds = [b"recent_sends", voice_room.key().as_ref(), sender.key().as_ref()],
+        bump
+    )]
+    pub recent_sends: Account<'info, RecentSends>,
+    
+    /// Open page of the room's message index
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = MessageIndexPage::SPACE,
+        seeds = [b"message_index", voice_room.key().as_ref(), &voice_room.current_index_page().to_le_bytes()],
+        bump
+    )]
+    pub message_index_page: Account<'info, MessageIndexPage>,
+    
+    /// Sender's participant record; muted participants can't send
+    #[account(
+        mut,
+        seeds = [b"participant", voice_room.key().as_ref(), sender.key().as_ref()],
+        bump = sender_participant.bump
+    )]
+    pub sender_participant: Account<'info, Participant>,
+    
+    /// Sender's anti-spam stake (rooms with a stake requirement only)
+    #[account(
+        seeds = [b"stake", voice_room.key().as_ref(), sender.key().as_ref()],
+        bump = sender_stake.bump
+    )]
+    pub sender_stake: Option<Account<'info, SenderStake>>,
+    
+    /// Sender's reputation (rooms with a reputation threshold only)
+    #[account(
+        seeds = [b"reputation", sender.key().as_ref()],
+        bump = sender_reputation.bump
+    )]
+    pub sender_reputation: Option<Account<'info, UserReputation>>,
+    
+    /// Sender's statistics, credited with the message
+    #[account(
+        mut,
+        seeds = [b"user_stats", sender.key().as_ref()],
+        bump = sender_stats.bump
+    )]
+    pub sender_stats: Option<Account<'info, UserStats>>,
+    
+    /// CHECK: Authorizes the frame through the Ed25519 signature, not as a transaction signer
+    pub sender: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+    
+    /// CHECK: Instructions sysvar, checked by address
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions_sysvar: AccountInfo<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    /// Room's write journal (rooms with journaling on only)
+    #[account(
+        mut,
+        seeds = [b"write_journal", voice_room.key().as_ref()],
+        bump = write_journal.bump
+    )]
+    pub write_journal: Option<Box<Account<'info, WriteJournal>>>,
+    
+    /// Room's prepaid bandwidth (metered rooms only)
+    #[account(
+        mut,
+        seeds = [b"bandwidth_allowance", voice_room.key().as_ref()],
+        bump = bandwidth_allowance.bump
+    )]
+    pub bandwidth_allowance: Option<Account<'info, BandwidthAllowance>>,
+    
+    /// CHECK: The protocol's storage backend, checked against protocol_config; when passed,
+    /// storage writes go through its storage_interface::write_chunk
+    pub storage_program: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: This program's PDA that signs write_chunk CPIs
+    #[account(seeds = [storage_layout::WRITER_SEED], bump)]
+    pub storage_writer: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateVoiceRoom<'info> {
+    /// CHECK: Possibly too small to deserialize; the handler checks the discriminator
+    #[account(mut, owner = crate::ID)]
+    pub voice_room: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(label: String)]
+pub struct CreateBreakout<'info> {
+    #[account(has_one = host)]
+    pub parent_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        init,
+        payer = host,
+        space = VoiceRoom::SPACE,
+        seeds = [b"breakout", parent_room.key().as_ref(), label.as_bytes()],
+        bump
+    )]
+    pub breakout_room: Account<'info, VoiceRoom>,
+    
+    #[account(mut)]
+    pub host: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct MoveParticipant<'info> {
+    #[account(mut, constraint = from_room.is_host(&host.key()) @ VoiceChatError::NotRoomHost)]
+    pub from_room: Box<Account<'info, VoiceRoom>>,
+    
+    #[account(mut, constraint = to_room.is_host(&host.key()) @ VoiceChatError::NotRoomHost)]
+    pub to_room: Box<Account<'info, VoiceRoom>>,
+    
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"participant", from_room.key().as_ref(), user.key().as_ref()],
+        bump = from_participant.bump
+    )]
+    pub from_participant: Account<'info, Participant>,
+    
+    #[account(
+        init,
+        payer = host,
+        space = 8 + 32 + 32 + 8 + 8 + 1 + 1 + 2 + 4 + 4 + 1 + 1, // discriminator + room + user + joined_at + last_seen + muted + slot + supported_codecs + sequence_epoch + highest_contiguous_sequence + bump + has_stream
+        seeds = [b"participant", to_room.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub to_participant: Account<'info, Participant>,
+    
+    /// CHECK: Participant being moved; receives the old participant record's rent
+    #[account(mut)]
+    pub user: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub host: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(label: String)]
+pub struct CreateRecurringSchedule<'info> {
+    /// Room whose settings every occurrence copies
+    #[account(has_one = host)]
+    pub template_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        init,
+        payer = host,
+        space = 8 + 32 + 32 + 4 + MAX_SCHEDULE_LABEL_LENGTH + 8 + 8 + 8 + 2 + 4 + 1, // discriminator + host + template_room + label + next_start + interval_seconds + duration_seconds + capacity + occurrence_count + bump
+        seeds = [b"schedule", host.key().as_ref(), label.as_bytes()],
+        bump
+    )]
+    pub schedule: Account<'info, RecurringSchedule>,
+    
+    #[account(mut)]
+    pub host: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct OpenNextOccurrence<'info> {
+    #[account(
+        mut,
+        has_one = template_room,
+        seeds = [b"schedule", schedule.host.as_ref(), schedule.label.as_bytes()],
+        bump = schedule.bump
+    )]
+    pub schedule: Account<'info, RecurringSchedule>,
+    
+    pub template_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        init,
+        payer = cranker,
+        space = VoiceRoom::SPACE,
+        seeds = [b"occurrence", schedule.key().as_ref(), &schedule.occurrence_count.to_le_bytes()],
+        bump
+    )]
+    pub occurrence_room: Account<'info, VoiceRoom>,
+    
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(occurrence_index: u32)]
+pub struct CloseOccurrence<'info> {
+    #[account(
+        seeds = [b"schedule", schedule.host.as_ref(), schedule.label.as_bytes()],
+        bump = schedule.bump
+    )]
+    pub schedule: Account<'info, RecurringSchedule>,
+    
+    #[account(
+        mut,
+        seeds = [b"occurrence", schedule.key().as_ref(), &occurrence_index.to_le_bytes()],
+        bump = occurrence_room.bump
+    )]
+    pub occurrence_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CancelRecurringSchedule<'info> {
+    #[account(
+        mut,
+        close = host,
+        has_one = host,
+        seeds = [b"schedule", host.key().as_ref(), schedule.label.as_bytes()],
+        bump = schedule.bump
+    )]
+    pub schedule: Account<'info, RecurringSchedule>,
+    
+    #[account(mut)]
+    pub host: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct SaveRoomTemplate<'info> {
+    #[account(
+        init_if_needed,
+        payer = host,
+        space = 8 + 32 + 4 + MAX_ROOM_ID_LENGTH + RoomSettings::SIZE + 1, // discriminator + host + name + settings + bump
+        seeds = [b"room_template", host.key().as_ref(), name.as_bytes()],
+        bump
+    )]
+    pub room_template: Account<'info, RoomTemplate>,
+    
+    #[account(mut)]
+    pub host: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(room_id: String)]
+pub struct CreateRoomFromTemplate<'info> {
+    #[account(
+        has_one = host,
+        seeds = [b"room_template", host.key().as_ref(), room_template.name.as_bytes()],
+        bump = room_template.bump
+    )]
+    pub room_template: Account<'info, RoomTemplate>,
+    
+    #[account(
+        init,
+        payer = host,
+        space = VoiceRoom::SPACE,
+        seeds = [b"voice_room", room_id.as_bytes()],
+        bump
+    )]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        init,
+        payer = host,
+        space = 8 + 32 + 32 + 8 + 8 + 1 + 1 + 2 + 4 + 4 + 1 + 1, // discriminator + room + user + joined_at + last_seen + muted + slot + supported_codecs + sequence_epoch + highest_contiguous_sequence + bump + has_stream
+        seeds = [b"participant", voice_room.key().as_ref(), host.key().as_ref()],
+        bump
+    )]
+    pub host_participant: Account<'info, Participant>,
+    
+    #[account(
+        init,
+        payer = host,
+        space = 8 + 32 + 8 + 8 + 8 + 8 + 1 + 1, // discriminator + room + lamports_collected + tokens_collected + tip_lamports_collected + tip_tokens_collected + split_configured + bump
+        seeds = [b"escrow", voice_room.key().as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, RoomEscrow>,
+    
+    #[account(mut)]
+    pub host: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+/// The header up to room_id is fixed-size so indexers can memcmp-filter on host, is_active,
+/// category and tags at the VOICE_ROOM_*_OFFSET constants without deserializing rooms.
+#[derive(Accounts)]
+#[instruction(metric: u8, window_start: i64)]
+pub struct CreateLeaderboard<'info> {
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + 32 + 1 + 8 + 8 + LeaderboardEntry::SIZE * LEADERBOARD_SIZE + 1, // discriminator + creator + metric + window_start + window_end + entries + bump
+        seeds = [b"leaderboard", creator.key().as_ref(), &[metric], &window_start.to_le_bytes()],
+        bump
+    )]
+    pub leaderboard: Account<'info, Leaderboard>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateLeaderboard<'info> {
+    #[account(
+        mut,
+        seeds = [b"leaderboard", leaderboard.creator.as_ref(), &[leaderboard.metric], &leaderboard.window_start.to_le_bytes()],
+        bump = leaderboard.bump
+    )]
+    pub leaderboard: Account<'info, Leaderboard>,
+    
+    /// CHECK: AttendanceRecord or TipTally matching the leaderboard's metric; deserialized
+    /// (which checks owner and discriminator) in the handler
+    pub source: UncheckedAccount<'info>,
+    
+    /// How much of the source has already been folded into this leaderboard
+    #[account(
+        init_if_needed,
+        payer = cranker,
+        space = 8 + 8 + 1, // discriminator + folded + bump
+        seeds = [b"leaderboard_fold", leaderboard.key().as_ref(), source.key().as_ref()],
+        bump
+    )]
+    pub fold: Account<'info, LeaderboardFold>,
+    
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetStageSpeaker<'info> {
+    #[account(mut, constraint = voice_room.is_host(&host.key()) @ VoiceChatError::NotRoomHost)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        mut,
+        seeds = [b"participant", voice_room.key().as_ref(), participant_account.user.as_ref()],
+        bump = participant_account.bump
+    )]
+    pub participant_account: Account<'info, Participant>,
+    
+    pub host: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetWriteJournal<'info> {
+    #[account(mut, has_one = host)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        init_if_needed,
+        payer = host,
+        space = 8 + 32 + 8 + JournalEntry::SIZE * WRITE_JOURNAL_CAPACITY + 1, // discriminator + room + write_count + entries + bump
+        seeds = [b"write_journal", voice_room.key().as_ref()],
+        bump
+    )]
+    pub write_journal: Box<Account<'info, WriteJournal>>,
+    
+    #[account(mut)]
+    pub host: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetPriceFeed<'info> {
+    #[account(
+        has_one = admin,
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + 32 + 4 + 8 + 1, // discriminator + feed_id + max_age_seconds + min_protocol_fee_usd_cents + bump
+        seeds = [b"price_feed"],
+        bump
+    )]
+    pub price_feed: Account<'info, PriceFeedConfig>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateMembershipMint<'info> {
+    #[account(mut, has_one = host)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    /// CHECK: Created and initialized as a Token-2022 mint in create_soulbound_mint
+    #[account(
+        mut,
+        seeds = [b"membership_mint", voice_room.key().as_ref()],
+        bump
+    )]
+    pub membership_mint: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub host: Signer<'info>,
+    
+    pub token_program: Program<'info, Token2022>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct IssueMembership<'info> {
+    #[account(has_one = host)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        mut,
+        seeds = [b"membership_mint", voice_room.key().as_ref()],
+        bump,
+        mint::token_program = token_program
+    )]
+    pub membership_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    /// CHECK: Any wallet can be made a member
+    pub member: UncheckedAccount<'info>,
+    
+    #[account(
+        init_if_needed,
+        payer = host,
+        associated_token::mint = membership_mint,
+        associated_token::authority = member,
+        associated_token::token_program = token_program
+    )]
+    pub member_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub host: Signer<'info>,
+    
+    pub token_program: Program<'info, Token2022>,
+    
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeMembership<'info> {
+    #[account(has_one = host)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        mut,
+        seeds = [b"membership_mint", voice_room.key().as_ref()],
+        bump,
+        mint::token_program = token_program
+    )]
+    pub membership_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        token::mint = membership_mint,
+        token::token_program = token_program
+    )]
+    pub member_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub host: Signer<'info>,
+    
+    pub token_program: Program<'info, Token2022>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CreateTicketTree<'info> {
+    #[account(has_one = host)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        init,
+        payer = host,
+        space = TicketTree::space(voice_room.ticket_capacity),
+        seeds = [b"ticket_tree", voice_room.key().as_ref()],
+        bump
+    )]
+    pub ticket_tree: Account<'info, TicketTree>,
+    
+    /// CHECK: Bubblegum tree config PDA, created and validated by Bubblegum
+    #[account(mut)]
+    pub tree_config: AccountInfo<'info>,
+    
+    /// CHECK: Pre-allocated concurrent merkle tree, initialized via CPI
+    #[account(mut, owner = account_compression::PROGRAM_ID)]
+    pub merkle_tree: AccountInfo<'info>,
+    
+    #[account(mut)]
+    pub host: Signer<'info>,
+    
+    /// CHECK: mpl-bubblegum program
+    #[account(address = bubblegum::PROGRAM_ID)]
+    pub bubblegum_program: AccountInfo<'info>,
+    
+    /// CHECK: spl-account-compression program
+    #[account(address = account_compression::PROGRAM_ID)]
+    pub compression_program: AccountInfo<'info>,
+    
+    /// CHECK: spl-noop program
+    #[account(address = account_compression::NOOP_PROGRAM_ID)]
+    pub noop_program: AccountInfo<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct BuyCompressedTicket<'info> {
+    #[account(mut)]
+    pub voice_room: Box<Account<'info, VoiceRoom>>,
+    
+    #[account(
+        mut,
+        seeds = [b"ticket_tree", voice_room.key().as_ref()],
+        bump = ticket_tree.bump,
+        has_one = merkle_tree
+    )]
+    pub ticket_tree: Box<Account<'info, TicketTree>>,
+    
+    /// CHECK: Bubblegum tree config PDA, validated by Bubblegum
+    #[account(mut)]
+    pub tree_config: AccountInfo<'info>,
+    
+    /// CHECK: Ticket tree's merkle tree
+    #[account(mut)]
+    pub merkle_tree: AccountInfo<'info>,
+    
+    /// Room escrow receiving the ticket price (paid events only)
+    #[account(
+        mut,
+        seeds = [b"escrow", voice_room.key().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Option<Account<'info, RoomEscrow>>,
+    
+    /// SOL/USD feed configuration (USD-priced rooms only)
+    #[account(seeds = [b"price_feed"], bump = price_feed.bump)]
+    pub price_feed: Option<Account<'info, PriceFeedConfig>>,
+    
+    /// CHECK: Pyth price update for the configured feed, verified in pyth::read_price
+    pub sol_usd_price: Option<UncheckedAccount<'info>>,
+    
+    /// Protocol treasury receiving the protocol fee (required while the fee is non-zero)
+    #[account(
+        mut,
+        seeds = [b"protocol_treasury"],
+        bump = protocol_treasury.bump
+    )]
+    pub protocol_treasury: Option<Account<'info, ProtocolTreasury>>,
+    
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    
+    /// CHECK: mpl-bubblegum program
+    #[account(address = bubblegum::PROGRAM_ID)]
+    pub bubblegum_program: AccountInfo<'info>,
+    
+    /// CHECK: spl-account-compression program
+    #[account(address = account_compression::PROGRAM_ID)]
+    pub compression_program: AccountInfo<'info>,
+    
+    /// CHECK: spl-noop program
+    #[account(address = account_compression::NOOP_PROGRAM_ID)]
+    pub noop_program: AccountInfo<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimCompressedRefund<'info> {
+    #[account(mut)]
+    pub voice_room: Box<Account<'info, VoiceRoom>>,
+    
+    #[account(
+        mut,
+        seeds = [b"ticket_tree", voice_room.key().as_ref()],
+        bump = ticket_tree.bump,
+        has_one = merkle_tree
+    )]
+    pub ticket_tree: Box<Account<'info, TicketTree>>,
+    
+    /// CHECK: Ticket tree's merkle tree, read by spl-account-compression's verify_leaf
+    pub merkle_tree: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", voice_room.key().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Option<Account<'info, RoomEscrow>>,
+    
+    #[account(mut)]
+    pub holder: Signer<'info>,
+    
+    /// CHECK: spl-account-compression program
+    #[account(address = account_compression::PROGRAM_ID)]
+    pub compression_program: AccountInfo<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    // remaining_accounts: the leaf's proof path
+}
+
+#[derive(Accounts)]
+pub struct SetCrossChainGate<'info> {
+    #[account(mut, has_one = host)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        init_if_needed,
+        payer = host,
+        space = 8 + 32 + 2 + 32 + 8 + 33 + 35 + 1, // discriminator + room + source_chain + collection + min_balance + attestor + emitter + bump
+        seeds = [b"cross_chain_gate", voice_room.key().as_ref()],
+        bump
+    )]
+    pub cross_chain_gate: Account<'info, CrossChainGate>,
+    
+    #[account(mut)]
+    pub host: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct AttestHoldings<'info> {
+    #[account(
+        seeds = [b"cross_chain_gate", cross_chain_gate.room.as_ref()],
+        bump = cross_chain_gate.bump
+    )]
+    pub cross_chain_gate: Account<'info, CrossChainGate>,
+    
+    #[account(
+        init_if_needed,
+        payer = holder,
+        space = 8 + 32 + 32 + 8 + 1, // discriminator + room + holder + expires_at + bump
+        seeds = [b"cross_chain_pass", cross_chain_gate.room.as_ref(), holder.key().as_ref()],
+        bump
+    )]
+    pub cross_chain_pass: Account<'info, CrossChainPass>,
+    
+    /// CHECK: Posted VAA carrying the attestation (Wormhole path only), verified in wormhole::read_posted_vaa
+    pub posted_vaa: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Instructions sysvar (attestor path only), checked by address
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions_sysvar: Option<AccountInfo<'info>>,
+    
+    #[account(mut)]
+    pub holder: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct BanParticipant<'info> {
+    #[account(mut, constraint = voice_room.is_host(&host.key()) @ VoiceChatError::NotRoomHost)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        init_if_needed,
+        payer = host,
+        space = 8 + 32 + 32 + 8 + 1, // discriminator + room + user + ban_until + bump
+        seeds = [b"room_ban", voice_room.key().as_ref(), user.as_ref()],
+        bump
+    )]
+    pub room_ban: Account<'info, RoomBan>,
+    
+    /// Banned user's participant account, closed if they are in the room
+    #[account(
+        mut,
+        close = banned_user,
+        seeds = [b"participant", voice_room.key().as_ref(), user.as_ref()],
+        bump = participant_account.bump
+    )]
+    pub participant_account: Option<Account<'info, Participant>>,
+    
+    /// CHECK: Receives the participant account's rent; must be the banned user
+    #[account(mut, address = user)]
+    pub banned_user: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub host: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct LiftBan<'info> {
+    #[account(constraint = voice_room.is_host(&host.key()) @ VoiceChatError::NotRoomHost)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        mut,
+        close = host,
+        seeds = [b"room_ban", voice_room.key().as_ref(), user.as_ref()],
+        bump = room_ban.bump
+    )]
+    pub room_ban: Account<'info, RoomBan>,
+    
+    #[account(mut)]
+    pub host: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ClearRoomFlag<'info> {
+    #[account(mut)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        has_one = admin,
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetBandwidthPrice<'info> {
+    #[account(
+        has_one = admin,
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + 8 + 1, // discriminator + lamports_per_kb + bump
+        seeds = [b"bandwidth_pricing"],
+        bump
+    )]
+    pub bandwidth_pricing: Account<'info, BandwidthPricing>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FundBandwidth<'info> {
+    #[account(mut, has_one = host)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        init_if_needed,
+        payer = host,
+        space = 8 + 32 + 8 + 8 + 8 + 8 + 1, // discriminator + room + lamports_per_kb + balance_lamports + unsettled_lamports + bytes_written + bump
+        seeds = [b"bandwidth_allowance", voice_room.key().as_ref()],
+        bump
+    )]
+    pub bandwidth_allowance: Account<'info, BandwidthAllowance>,
+    
+    #[account(
+        seeds = [b"bandwidth_pricing"],
+        bump = bandwidth_pricing.bump
+    )]
+    pub bandwidth_pricing: Account<'info, BandwidthPricing>,
+    
+    #[account(
+        mut,
+        seeds = [b"protocol_treasury"],
+        bump = protocol_treasury.bump
+    )]
+    pub protocol_treasury: Account<'info, ProtocolTreasury>,
+    
+    #[account(mut)]
+    pub host: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawBandwidth<'info> {
+    #[account(mut, has_one = host)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        mut,
+        seeds = [b"bandwidth_allowance", voice_room.key().as_ref()],
+        bump = bandwidth_allowance.bump
+    )]
+    pub bandwidth_allowance: Account<'info, BandwidthAllowance>,
+    
+    #[account(
+        mut,
+        seeds = [b"protocol_treasury"],
+        bump = protocol_treasury.bump
+    )]
+    pub protocol_treasury: Account<'info, ProtocolTreasury>,
+    
+    #[account(mut)]
+    pub host: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetHandQueue<'info> {
+    #[account(constraint = voice_room.is_host(&host.key()) @ VoiceChatError::NotRoomHost)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        init_if_needed,
+        payer = host,
+        space = HandQueue::SPACE,
+        seeds = [b"hand_queue", voice_room.key().as_ref()],
+        bump
+    )]
+    pub hand_queue: Account<'info, HandQueue>,
+    
+    #[account(mut)]
+    pub host: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RaiseHand<'info> {
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        mut,
+        seeds = [b"hand_queue", voice_room.key().as_ref()],
+        bump = hand_queue.bump
+    )]
+    pub hand_queue: Account<'info, HandQueue>,
+    
+    #[account(
+        seeds = [b"participant", voice_room.key().as_ref(), participant.key().as_ref()],
+        bump = participant_account.bump
+    )]
+    pub participant_account: Account<'info, Participant>,
+    
+    /// Participant's stake (stake-weighted queues only)
+    #[account(
+        seeds = [b"stake", voice_room.key().as_ref(), participant.key().as_ref()],
+        bump = sender_stake.bump
+    )]
+    pub sender_stake: Option<Account<'info, SenderStake>>,
+    
+    /// Participant's token account for the room's gate mint (token-weighted queues only)
+    pub weight_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    
+    pub participant: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct LowerHand<'info> {
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        mut,
+        seeds = [b"hand_queue", voice_room.key().as_ref()],
+        bump = hand_queue.bump
+    )]
+    pub hand_queue: Account<'info, HandQueue>,
+    
+    /// The hand's owner or a host
+    pub authority: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct PromoteNextSpeaker<'info> {
+    #[account(mut, constraint = voice_room.is_host(&host.key()) @ VoiceChatError::NotRoomHost)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        mut,
+        seeds = [b"hand_queue", voice_room.key().as_ref()],
+        bump = hand_queue.bump
+    )]
+    pub hand_queue: Account<'info, HandQueue>,
+    
+    /// Participant record of the hand at the front of the queue
+    #[account(
+        mut,
+        seeds = [b"participant", voice_room.key().as_ref(), participant_account.user.as_ref()],
+        bump = participant_account.bump
+    )]
+    pub participant_account: Account<'info, Participant>,
+    
+    pub host: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(participant: Pubkey)]
+pub struct InviteToStage<'info> {
+    #[account(constraint = voice_room.is_host(&host.key()) @ VoiceChatError::NotRoomHost)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    /// Invitee's participant record; only people in the room can be invited
+    #[account(
+        seeds = [b"participant", voice_room.key().as_ref(), participant.as_ref()],
+        bump = participant_account.bump
+    )]
+    pub participant_account: Account<'info, Participant>,
+    
+    /// CHECK: Invitee's block PDA for the host; must not exist
+    #[account(
+        seeds = [b"block", participant.as_ref(), host.key().as_ref()],
+        bump
+    )]
+    pub participant_block: UncheckedAccount<'info>,
+    
+    #[account(
+        init_if_needed,
+        payer = host,
+        space = 8 + 32 + 32 + 32 + 8 + 1, // discriminator + room + user + invited_by + expires_at + bump
+        seeds = [b"stage_invite", voice_room.key().as_ref(), participant.as_ref()],
+        bump
+    )]
+    pub stage_invite: Account<'info, StageInvite>,
+    
+    #[account(mut)]
+    pub host: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptStageInvite<'info> {
+    #[account(mut)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        mut,
+        close = invited_by,
+        has_one = invited_by,
+        seeds = [b"stage_invite", voice_room.key().as_ref(), participant.key().as_ref()],
+        bump = stage_invite.bump
+    )]
+    pub stage_invite: Account<'info, StageInvite>,
+    
+    /// CHECK: Receives the invitation's rent, verified through has_one
+    #[account(mut)]
+    pub invited_by: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"participant", voice_room.key().as_ref(), participant.key().as_ref()],
+        bump = participant_account.bump
+    )]
+    pub participant_account: Account<'info, Participant>,
+    
+    pub participant: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RecordingControl<'info> {
+    #[account(mut, constraint = voice_room.can_record(&recorder.key()) @ VoiceChatError::NotRecorder)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    /// A host or the room's designated recorder
+    pub recorder: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ArchiveSession<'info> {
+    #[account(constraint = voice_room.is_host(&host.key()) @ VoiceChatError::NotRoomHost)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        seeds = [b"session", voice_room.key().as_ref(), &session.id.to_le_bytes()],
+        bump = session.bump
+    )]
+    pub session: Account<'info, Session>,
+    
+    #[account(
+        init_if_needed,
+        payer = host,
+        space = SessionArchive::SPACE,
+        seeds = [b"session_archive", session.key().as_ref()],
+        bump
+    )]
+    pub session_archive: Box<Account<'info, SessionArchive>>,
+    
+    #[account(mut)]
+    pub host: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct BuildExportManifest<'info> {
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        seeds = [b"session", voice_room.key().as_ref(), &session.id.to_le_bytes()],
+        bump = session.bump
+    )]
+    pub session: Account<'info, Session>,
+}
+
+#[derive(Accounts)]
+pub struct AllocateWriteSlot<'info> {
+    #[account(mut)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        seeds = [b"participant", voice_room.key().as_ref(), participant.key().as_ref()],
+        bump = participant_account.bump
+    )]
+    pub participant_account: Account<'info, Participant>,
+    
+    pub participant: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct AckConsumed<'info> {
+    #[account(mut, constraint = voice_room.is_host(&host.key()) @ VoiceChatError::NotRoomHost)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    pub host: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(sender: Pubkey)]
+pub struct AckFrames<'info> {
+    #[account(mut)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        seeds = [b"participant", voice_room.key().as_ref(), listener.key().as_ref()],
+        bump = listener_account.bump
+    )]
+    pub listener_account: Account<'info, Participant>,
+    
+    #[account(
+        seeds = [b"participant", voice_room.key().as_ref(), sender.as_ref()],
+        bump = sender_participant.bump
+    )]
+    pub sender_participant: Account<'info, Participant>,
+    
+    pub listener: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(sender: Pubkey)]
+pub struct AdvanceCursor<'info> {
+    #[account(mut)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        init_if_needed,
+        payer = listener,
+        space = ListenerCursor::SPACE,
+        seeds = [b"listener_cursor", voice_room.key().as_ref(), listener.key().as_ref()],
+        bump
+    )]
+    pub listener_cursor: Account<'info, ListenerCursor>,
+    
+    #[account(
+        seeds = [b"participant", voice_room.key().as_ref(), listener.key().as_ref()],
+        bump = listener_account.bump
+    )]
+    pub listener_account: Account<'info, Participant>,
+    
+    #[account(
+        seeds = [b"participant", voice_room.key().as_ref(), sender.as_ref()],
+        bump = sender_participant.bump
+    )]
+    pub sender_participant: Account<'info, Participant>,
+    
+    #[account(mut)]
+    pub listener: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(host: Pubkey)]
+pub struct SubscribeRoom<'info> {
+    #[account(
+        init,
+        payer = subscriber,
+        space = 8 + 32 + 32 + 8 + 1, // discriminator + host + subscriber + created_at + bump
+        seeds = [b"room_subscription", host.as_ref(), subscriber.key().as_ref()],
+        bump
+    )]
+    pub room_subscription: Account<'info, RoomSubscription>,
+    
+    #[account(mut)]
+    pub subscriber: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(host: Pubkey)]
+pub struct UnsubscribeRoom<'info> {
+    #[account(
+        mut,
+        close = subscriber,
+        seeds = [b"room_subscription", host.as_ref(), subscriber.key().as_ref()],
+        bump = room_subscription.bump
+    )]
+    pub room_subscription: Account<'info, RoomSubscription>,
+    
+    #[account(mut)]
+    pub subscriber: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetNotificationConfig<'info> {
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + 32 + 32 + 1 + 8 + 1, // discriminator + user + endpoint_hash + preferences + updated_at + bump
+        seeds = [b"notification_config", user.key().as_ref()],
+        bump
+    )]
+    pub notification_config: Account<'info, NotificationConfig>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CloseNotificationConfig<'info> {
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"notification_config", user.key().as_ref()],
+        bump = notification_config.bump
+    )]
+    pub notification_config: Account<'info, NotificationConfig>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(voice_data: Vec<u8>, sequence_number: u32)]
+pub struct SendVoiceFrame<'info> {
+    #[account(mut)]
+    pub voice_room: Account<'info, VoiceRoom>,
+    
+    #[account(
+        init,
+        payer = sender,
+        space = EphemeralFrame::BASE_SPACE + voice_data.len(),
+        seeds = [
+            b"frame",
+            voice_room.key().as_ref(),
+            sender.key().as_ref(),
+            &sender_participant.sequence_epoch.to_le_bytes(),
+            &sequence_number.to_le_bytes()
+        ],
         bump
     )]
-    pub broadcast_message: Account<'info, BroadcastMessage>,
+    pub frame: Account<'info, EphemeralFrame>,
+    
+    /// Sender's participant record; muted participants can't send
+    #[account(
+        mut,
+        seeds = [b"participant", voice_room.key().as_ref(), sender.key().as_ref()],
+        bump = sender_participant.bump
+    )]
+    pub sender_participant: Account<'info, Participant>,
+    
+    /// Sender's anti-spam stake (rooms with a stake requirement only)
+    #[account(
+        seeds = [b"stake", voice_room.key().as_ref(), sender.key().as_ref()],
+        bump = sender_stake.bump
+    )]
+    pub sender_stake: Option<Account<'info, SenderStake>>,
+    
+    /// Sender's reputation (rooms with a reputation threshold only)
+    #[account(
+        seeds = [b"reputation", sender.key().as_ref()],
+        bump = sender_reputation.bump
+    )]
+    pub sender_reputation: Option<Account<'info, UserReputation>>,
     
     #[account(mut)]
     pub sender: Signer<'info>,
     
-    pub system_program: Program<'info, System>,
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct AckVoiceFrame<'info> {
+    #[account(mut, has_one = sender)]
+    pub frame: Account<'info, EphemeralFrame>,
+    
+    #[account(
+        seeds = [b"participant", frame.room.as_ref(), listener.key().as_ref()],
+        bump = listener_account.bump
+    )]
+    pub listener_account: Account<'info, Participant>,
+    
+    pub listener: Signer<'info>,
+    
+    /// CHECK: The frame's sender, refunded the rent when the frame closes
+    #[account(mut)]
+    pub sender: UncheckedAccount<'info>,
+    
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[account]
+pub struct VoiceRoom {
+    pub host: Pubkey,
+    pub is_active: bool,
+    pub category: u16, // 0 = uncategorized
+    pub tags: [u16; MAX_ROOM_TAGS], // 0 = unused
+    pub room_id: [u8; MAX_ROOM_ID_LENGTH], // Zero-padded UTF-8; read through room_id()
+    pub participant_count: u8,
+    pub created_at: i64,
+    pub last_activity: i64,
+    pub gate_mint: Option<Pubkey>,
+    pub gate_min_balance: u64,
+    pub gate_collection: Option<Pubkey>,
+    pub entry_fee_lamports: u64,
+    pub entry_fee_mint: Option<Pubkey>,
+    pub entry_fee_amount: u64,
+    pub scheduled_start: i64, // 0 = not a scheduled event
+    pub ticket_capacity: u16,
+    pub tickets_sold: u16,
+    pub event_started: bool,
+    pub event_cancelled: bool,
+    pub stream_rate_lamports_per_minute: u64, // 0 = streaming payments disabled
+    pub sender_stake_lamports: u64, // 0 = no stake required to send
+    pub report_quorum: u8, // 0 = report-and-slash disabled
+    pub slash_bps: u16,
+    pub min_reputation_to_speak: i64, // 0 = no reputation threshold
+    pub participant_timeout_seconds: i64, // 0 = PARTICIPANT_TIMEOUT_SECONDS
+    pub poll_count: u32,
+    pub session_ended_at: i64, // 0 = session still running
+    pub attendance_cutoff: i64,
+    pub min_attendance_seconds: i64,
+    pub session_count: u32,
+    // Running stats for the current session, snapshotted into its Session PDA on end_session
+    pub session_peak_participants: u8,
+    pub session_frames: u64,
+    pub session_bytes: u64,
+    // Bit i belongs to the participant in slot i
+    pub occupied_slots: u16,
+    pub recording_consent: u16,
+    pub is_recording: bool,
+    pub preferred_relay: Option<Pubkey>, // RelayNode PDA clients should route through
+    pub version: u8,
+    pub max_frame_bytes: u32, // 0 = MAX_VOICE_DATA_SIZE
+    pub parent_room: Option<Pubkey>, // Set on breakout rooms
+    pub indexed_messages: u64, // Frames recorded in the room's MessageIndexPages
+    pub message_count: u64, // Next VoiceMessage index; only ever increases
+    // Lifetime analytics, never reset by sessions
+    pub peak_participants: u8,
+    pub total_messages: u64,
+    pub total_bytes: u64,
+    // Host and stage speakers can use the reserved lane; the audience is throttled separately
+    pub stage_lane: Option<u8>, // Storage PDA index reserved for stage audio
+    pub stage_slots: u16, // Bit i set = participant in slot i is on stage
+    pub stage_send_interval_slots: u16, // Minimum slots between sends, 0 = unlimited
+    pub audience_send_interval_slots: u16,
+    pub journal_writes: bool, // Every storage write must be appended to the room's WriteJournal
+    pub accepted_codecs: u16, // Bit i set = codec id i may be sent, 0 = any
+    pub target_bitrate_kbps: u16, // 0 = unrestricted
+    pub max_frame_duration_ms: u16, // 0 = unrestricted
+    pub entry_fee_usd_cents: u64, // Converted at payment time; 0 = use entry_fee_lamports
+    pub sns_domain: Option<Pubkey>, // .sol name record the host proved ownership of at creation
+    pub membership_mint: Option<Pubkey>, // Non-transferable Token-2022 mint whose holders join freely
+    pub cross_chain_gated: bool, // Joiners need a CrossChainPass; the gate itself is the CrossChainGate PDA
+    pub mute_on_join: bool, // Joiners start muted until put on stage
+    pub max_participants: u8, // 0 = MAX_PARTICIPANTS
+    pub co_hosts: [Pubkey; MAX_CO_HOSTS], // Default key = empty; host stays the payee for room funds
+    pub upheld_reports: u16, // Reports that reached quorum, across all senders
+    pub flagged: bool, // Delisted until the protocol admin clears it
+    pub region: u8, // Audience region code for relay selection, 0 = any
+    pub bandwidth_metered: bool, // Storage writes are charged to the room's BandwidthAllowance
+    pub channel_map: [u8; MAX_ROOM_CAPACITY as usize], // Slot i's audio channel + 1, 0 = no channel
+    pub recorder: Option<Pubkey>, // Participant the hosts delegated recording to
+    pub lane_allocations: [u8; STORAGE_LANE_COUNT], // Slot + 1 of whoever allocate_write_slot handed storage PDA i to, 0 = free
+    pub unread_lanes: u16, // Bit i set = storage PDA i holds a frame no listener has acked yet
+    pub lane_writers: [u8; STORAGE_LANE_COUNT], // Slot + 1 of whoever wrote the frame in storage PDA i, 0 = unknown
+    pub lane_sequences: [u32; STORAGE_LANE_COUNT], // Sequence number of that frame
+    pub lane_acks: [u16; STORAGE_LANE_COUNT], // Bit j set = listener in slot j has consumed it
+    pub bump: u8, // Canonical bump for whichever seeds created the room
+}
+
+impl VoiceRoom {
+    pub const SPACE: usize = 8 + 32 + 1 + 2 + 2 * MAX_ROOM_TAGS + MAX_ROOM_ID_LENGTH + 1 + 8 + 8 + 1 + 32 + 8 + 1 + 32 + 8 + 1 + 32 + 8 + 8 + 2 + 2 + 1 + 1 + 8 + 8 + 1 + 2 + 8 + 8 + 4 + 8 + 8 + 8 + 4 + 1 + 8 + 8 + 2 + 2 + 1 + 1 + 32 + 1 + 4 + 1 + 32 + 8 + 8 + 1 + 8 + 8 + 1 + 1 + 2 + 2 + 2 + 1 + 2 + 2 + 2 + 8 + 33 + 33 + 1 + 1 + 1 + 32 * MAX_CO_HOSTS + 2 + 1 + 1 + 1 + MAX_ROOM_CAPACITY as usize + 33 + STORAGE_LANE_COUNT + 2 + STORAGE_LANE_COUNT + 4 * STORAGE_LANE_COUNT + 2 * STORAGE_LANE_COUNT + 1; // discriminator + host + is_active + category + tags + room_id + participant_count + created_at + last_activity + gate_mint + gate_min_balance + gate_collection + entry_fee_lamports + entry_fee_mint + entry_fee_amount + scheduled_start + ticket_capacity + tickets_sold + event_started + event_cancelled + stream_rate_lamports_per_minute + sender_stake_lamports + report_quorum + slash_bps + min_reputation_to_speak + participant_timeout_seconds + poll_count + session_ended_at + attendance_cutoff + min_attendance_seconds + session_count + session_peak_participants + session_frames + session_bytes + occupied_slots + recording_consent + is_recording + preferred_relay + version + max_frame_bytes + parent_room + indexed_messages + message_count + peak_participants + total_messages + total_bytes + stage_lane + stage_slots + stage_send_interval_slots + audience_send_interval_slots + journal_writes + accepted_codecs + target_bitrate_kbps + max_frame_duration_ms + entry_fee_usd_cents + sns_domain + membership_mint + cross_chain_gated + mute_on_join + max_participants + co_hosts + upheld_reports + flagged + region + bandwidth_metered + channel_map + recorder + lane_allocations + unread_lanes + lane_writers + lane_sequences + lane_acks + bump
+
+    /// Fresh room with every feature off; callers add the host participant if there is one
+    pub fn init(&mut self, room_id: &str, host: Pubkey, now: i64, bump: u8) {
+        self.category = 0;
+        self.tags = [0; MAX_ROOM_TAGS];
+        self.room_id = [0; MAX_ROOM_ID_LENGTH];
+        self.room_id[..room_id.len()].copy_from_slice(room_id.as_bytes());
+        self.host = host;
+        self.participant_count = 0;
+        self.is_active = true;
+        self.created_at = now;
+        self.last_activity = now;
+        self.gate_mint = None;
+        self.gate_min_balance = 0;
+        self.gate_collection = None;
+        self.entry_fee_lamports = 0;
+        self.entry_fee_mint = None;
+        self.entry_fee_amount = 0;
+        self.scheduled_start = 0;
+        self.ticket_capacity = 0;
+        self.tickets_sold = 0;
+        self.event_started = false;
+        self.event_cancelled = false;
+        self.stream_rate_lamports_per_minute = 0;
+        self.sender_stake_lamports = 0;
+        self.report_quorum = 0;
+        self.slash_bps = 0;
+        self.min_reputation_to_speak = 0;
+        self.participant_timeout_seconds = 0;
+        self.poll_count = 0;
+        self.session_ended_at = 0;
+        self.attendance_cutoff = 0;
+        self.min_attendance_seconds = 0;
+        self.session_count = 0;
+        self.session_peak_participants = 0;
+        self.session_frames = 0;
+        self.session_bytes = 0;
+        self.occupied_slots = 0;
+        self.recording_consent = 0;
+        self.is_recording = false;
+        self.preferred_relay = None;
+        self.version = VOICE_ROOM_VERSION;
+        self.max_frame_bytes = 0;
+        self.parent_room = None;
+        self.indexed_messages = 0;
+        self.message_count = 0;
+        self.peak_participants = 0;
+        self.total_messages = 0;
+        self.total_bytes = 0;
+        self.stage_lane = None;
+        self.stage_slots = 0;
+        self.stage_send_interval_slots = 0;
+        self.audience_send_interval_slots = 0;
+        self.journal_writes = false;
+        self.accepted_codecs = 0;
+        self.target_bitrate_kbps = 0;
+        self.max_frame_duration_ms = 0;
+        self.entry_fee_usd_cents = 0;
+        self.sns_domain = None;
+        self.membership_mint = None;
+        self.cross_chain_gated = false;
+        self.mute_on_join = false;
+        self.max_participants = 0;
+        self.co_hosts = [Pubkey::default(); MAX_CO_HOSTS];
+        self.upheld_reports = 0;
+        self.flagged = false;
+        self.region = 0;
+        self.bandwidth_metered = false;
+        self.channel_map = [0; MAX_ROOM_CAPACITY as usize];
+        self.recorder = None;
+        self.lane_allocations = [0; STORAGE_LANE_COUNT];
+        self.unread_lanes = 0;
+        self.lane_writers = [0; STORAGE_LANE_COUNT];
+        self.lane_sequences = [0; STORAGE_LANE_COUNT];
+        self.lane_acks = [0; STORAGE_LANE_COUNT];
+        self.bump = bump;
+    }
+
+    /// Copy admission and moderation settings from another room. Fees are left off since
+    /// they need the room's own escrow.
+    pub fn inherit_config(&mut self, source: &VoiceRoom) {
+        self.gate_mint = source.gate_mint;
+        self.gate_min_balance = source.gate_min_balance;
+        self.gate_collection = source.gate_collection;
+        self.sender_stake_lamports = source.sender_stake_lamports;
+        self.report_quorum = source.report_quorum;
+        self.slash_bps = source.slash_bps;
+        self.min_reputation_to_speak = source.min_reputation_to_speak;
+        self.participant_timeout_seconds = source.participant_timeout_seconds;
+        self.max_frame_bytes = source.max_frame_bytes;
+        self.accepted_codecs = source.accepted_codecs;
+        self.target_bitrate_kbps = source.target_bitrate_kbps;
+        self.max_frame_duration_ms = source.max_frame_duration_ms;
+        self.mute_on_join = source.mute_on_join;
+        self.region = source.region;
+        self.preferred_relay = source.preferred_relay;
+    }
+
+    /// Page of the message index the next frame is appended to
+    pub fn current_index_page(&self) -> u64 {
+        self.indexed_messages / MESSAGE_INDEX_PAGE_CAPACITY as u64
+    }
+
+    /// Room id without its zero padding
+    pub fn room_id(&self) -> &str {
+        let len = self.room_id.iter().position(|byte| *byte == 0).unwrap_or(MAX_ROOM_ID_LENGTH);
+        std::str::from_utf8(&self.room_id[..len]).unwrap_or_default()
+    }
+
+    /// Configure the room from a template's settings
+    pub fn apply_settings(&mut self, settings: &RoomSettings) {
+        self.gate_mint = settings.gate_mint;
+        self.gate_min_balance = settings.gate_min_balance;
+        self.gate_collection = settings.gate_collection;
+        self.entry_fee_lamports = settings.entry_fee_lamports;
+        self.entry_fee_mint = settings.entry_fee_mint;
+        self.entry_fee_amount = settings.entry_fee_amount;
+        self.ticket_capacity = settings.ticket_capacity;
+        self.sender_stake_lamports = settings.sender_stake_lamports;
+        self.report_quorum = settings.report_quorum;
+        self.slash_bps = settings.slash_bps;
+        self.min_reputation_to_speak = settings.min_reputation_to_speak;
+        self.participant_timeout_seconds = settings.participant_timeout_seconds;
+        self.max_frame_bytes = settings.max_frame_bytes;
+        self.preferred_relay = settings.preferred_relay;
+        self.accepted_codecs = settings.accepted_codecs;
+        self.target_bitrate_kbps = settings.target_bitrate_kbps;
+        self.max_frame_duration_ms = settings.max_frame_duration_ms;
+    }
+
+    /// Take the lowest free participant slot; a new occupant starts without recording consent
+    pub fn claim_slot(&mut self) -> Result<u8> {
+        let slot = (0..self.max_participants())
+            .find(|slot| self.occupied_slots & (1 << slot) == 0)
+            .ok_or(VoiceChatError::RoomFull)?;
+        self.occupied_slots |= 1 << slot;
+        self.recording_consent &= !(1 << slot);
+        Ok(slot)
+    }
+
+    pub fn release_slot(&mut self, slot: u8) {
+        self.occupied_slots &= !(1 << slot);
+        self.recording_consent &= !(1 << slot);
+        self.stage_slots &= !(1 << slot);
+        self.release_channel(slot);
+        // Whoever takes the slot next hasn't consumed anything, frames from a departed
+        // writer can no longer be acked by sender, and its write slots go back to the pool
+        for lane in 0..STORAGE_LANE_COUNT {
+            self.lane_acks[lane] &= !(1 << slot);
+            if self.lane_writers[lane] == slot + 1 {
+                self.lane_writers[lane] = 0;
+            }
+            if self.lane_allocations[lane] == slot + 1 {
+                self.lane_allocations[lane] = 0;
+            }
+        }
+        self.settle_lanes();
+    }
+
+    /// Remember which frame now sits in `lane` and mark it unread
+    pub fn record_lane_write(&mut self, lane: u8, writer_slot: u8, sequence: u32) {
+        self.unread_lanes |= 1 << lane;
+        self.lane_writers[lane as usize] = writer_slot + 1;
+        self.lane_sequences[lane as usize] = sequence;
+        self.lane_acks[lane as usize] = 0;
+    }
+
+    /// Credit the listener with every lane frame from the writer up to `up_to_sequence`.
+    /// Returns how many lanes that covered.
+    pub fn ack_lane_frames(&mut self, listener_slot: u8, writer_slot: u8, up_to_sequence: u32) -> u32 {
+        let mut acked = 0;
+        for lane in 0..STORAGE_LANE_COUNT {
+            if self.lane_writers[lane] == writer_slot + 1 && self.lane_sequences[lane] <= up_to_sequence {
+                self.lane_acks[lane] |= 1 << listener_slot;
+                acked += 1;
+            }
+        }
+        self.settle_lanes();
+        acked
+    }
+
+    /// Lanes whose frame every current participant besides its writer has consumed are free again
+    pub fn settle_lanes(&mut self) {
+        for lane in 0..STORAGE_LANE_COUNT {
+            let writer = self.lane_writers[lane];
+            if writer == 0 {
+                continue;
+            }
+            let listeners = self.occupied_slots & !(1 << (writer - 1));
+            if listeners & !self.lane_acks[lane] == 0 {
+                self.unread_lanes &= !(1 << lane);
+            }
+        }
+    }
+
+    /// Audio channel of the participant in `slot`. Only participants who can be heard hold
+    /// one, so multi-channel clients mix the room as channels 0..N in a fixed order.
+    pub fn channel(&self, slot: u8) -> Option<u8> {
+        self.channel_map[slot as usize].checked_sub(1)
+    }
+
+    /// Give the slot the lowest free channel; a slot that already holds one keeps it
+    pub fn assign_channel(&mut self, slot: u8) {
+        if self.channel_map[slot as usize] != 0 {
+            return;
+        }
+        let channel = (1..=MAX_ROOM_CAPACITY)
+            .find(|channel| !self.channel_map.contains(channel))
+            .unwrap_or(0);
+        self.channel_map[slot as usize] = channel;
+    }
+
+    pub fn release_channel(&mut self, slot: u8) {
+        self.channel_map[slot as usize] = 0;
+    }
+
+    /// Bring a participant onto the stage or send them back to the audience. Speakers get an
+    /// audio channel; rooms that mute on join unmute them, and mute them again on the way down.
+    pub fn set_on_stage(&mut self, participant: &mut Participant, on_stage: bool) {
+        let slot_bit = 1u16 << participant.slot;
+        if on_stage {
+            self.stage_slots |= slot_bit;
+            self.assign_channel(participant.slot);
+        } else {
+            self.stage_slots &= !slot_bit;
+        }
+        if self.mute_on_join && !self.is_host(&participant.user) {
+            participant.muted = !on_stage;
+            if !on_stage {
+                self.release_channel(participant.slot);
+            }
+        }
+    }
+
+    /// Hosts are always on stage; speakers are marked per slot
+    pub fn is_on_stage(&self, participant: &Participant) -> bool {
+        self.is_host(&participant.user) || self.stage_slots & (1 << participant.slot) != 0
+    }
+
+    /// Count an upheld report. Crossing ROOM_FLAG_THRESHOLD flags the room and clears its
+    /// category and tags so it drops out of directory queries. Returns true when newly flagged.
+    pub fn record_upheld_report(&mut self) -> bool {
+        self.upheld_reports = self.upheld_reports.saturating_add(1);
+        if self.flagged || self.upheld_reports < ROOM_FLAG_THRESHOLD {
+            return false;
+        }
+        self.flagged = true;
+        self.category = 0;
+        self.tags = [0; MAX_ROOM_TAGS];
+        true
+    }
+
+    /// The primary host or one of the co-hosts; any of them can moderate the room
+    pub fn is_host(&self, key: &Pubkey) -> bool {
+        *key == self.host || (*key != Pubkey::default() && self.co_hosts.contains(key))
+    }
+
+    /// Hosts and the designated recorder control recordings
+    pub fn can_record(&self, key: &Pubkey) -> bool {
+        self.is_host(key) || self.recorder == Some(*key)
+    }
+
+    /// Every current participant has consented to recording
+    pub fn all_consented_to_recording(&self) -> bool {
+        self.recording_consent & self.occupied_slots == self.occupied_slots
+    }
+
+    /// Count sent audio towards the current session's summary stats and the lifetime totals
+    pub fn record_traffic(&mut self, frames: usize, bytes: usize) {
+        self.session_frames += frames as u64;
+        self.session_bytes += bytes as u64;
+        self.total_messages += frames as u64;
+        self.total_bytes += bytes as u64;
+    }
+
+    /// Count a new participant towards the session and lifetime peaks
+    pub fn add_participant(&mut self) {
+        self.participant_count += 1;
+        self.session_peak_participants = self.session_peak_participants.max(self.participant_count);
+        self.peak_participants = self.peak_participants.max(self.participant_count);
+    }
+
+    /// A session runs from start_session until end_session
+    pub fn session_running(&self) -> bool {
+        self.session_count > 0 && self.session_ended_at == 0
+    }
+
+    /// Largest single frame senders may post to this room
+    pub fn max_frame_bytes(&self) -> usize {
+        if self.max_frame_bytes > 0 {
+            self.max_frame_bytes as usize
+        } else {
+            MAX_VOICE_DATA_SIZE
+        }
+    }
+
+    pub fn max_participants(&self) -> u8 {
+        if self.max_participants > 0 {
+            self.max_participants
+        } else {
+            MAX_PARTICIPANTS
+        }
+    }
+
+    /// Heartbeat age after which a participant counts as gone
+    pub fn participant_timeout(&self) -> i64 {
+        if self.participant_timeout_seconds > 0 {
+            self.participant_timeout_seconds
+        } else {
+            PARTICIPANT_TIMEOUT_SECONDS
+        }
+    }
+
+    /// Escrowed funds stay locked until the session is over and, for scheduled events,
+    /// until the event actually started (otherwise ticket holders are owed refunds)
+    pub fn escrow_unlocked(&self) -> bool {
+        !self.is_active && (self.scheduled_start == 0 || (self.event_started && !self.event_cancelled))
+    }
+}
+
+/// Message N of a room lives at `["voice_message", room, N]`, N taken from `message_count`
+#[account]
+pub struct VoiceMessage {
+    pub sender: Pubkey,
+    pub room_id: String,
+    pub storage_pda_index: u8,
+    pub sequence_number: u32,
+    pub data_length: u32,
+    pub timestamp: i64,
+    pub capture_timestamp_ms: i64, // Client capture time, within CAPTURE_TIMESTAMP_TOLERANCE_MS of `timestamp`
+    pub sequence_epoch: u32,
+    pub storage_pda: Pubkey, // Where the frame was written, at offset 0
+    pub content_hash: [u8; 32], // keccak256 of the written bytes
+}
+
+/// Batch N of a room lives at `["voice_batch", room, N]`, N taken from the same `message_count`
+/// as VoiceMessage and VoiceClipMessage, so each message number holds exactly one of them
+#[account]
+pub struct VoiceBatchMessage {
+    pub sender: Pubkey,
+    pub room_id: String,
+    pub storage_pda_index: u8,
+    pub sequence_number: u32, // First frame's
+    pub data_length: u32,
+    pub frames: Vec<FrameHeader>,
+    pub timestamp: i64,
+    pub sequence_epoch: u32,
+    pub storage_pda: Pubkey, // Where the frames were written back-to-back, from offset 0
+    pub content_hash: [u8; 32], // keccak256 of the whole payload
+    pub frame_hashes: Vec<[u8; 32]>, // keccak256 of each frame, in `frames` order
+}
+
+/// Clip N of a room lives at `["voice_clip", room, N]`, numbered like VoiceBatchMessage
+#[account]
+pub struct VoiceClipMessage {
+    pub sender: Pubkey,
+    pub room_id: String,
+    pub target_pdas: Vec<u8>, // Lane of each STORAGE_CHUNK_SIZE chunk, in payload order
+    pub sequence_number: u32,
+    pub data_length: u32,
+    pub timestamp: i64,
+    pub sequence_epoch: u32,
+    pub content_hash: [u8; 32], // keccak256 of the whole payload
+    pub storage_pdas: Vec<Pubkey>, // Lane account of each chunk, written from offset 0
+    pub chunk_hashes: Vec<[u8; 32]>, // keccak256 of each chunk
+}
+
+#[account]
+pub struct CompressedMessageLog {
+    pub room: Pubkey,
+    pub merkle_tree: Pubkey,
+    pub max_depth: u32,
+    pub max_buffer_size: u32,
+    pub leaf_count: u64,
+    pub bump: u8,
+}
+
+/// Leaf schema for voice messages recorded in a compressed message log.
+/// Leaves are keccak256(borsh(CompressedVoiceMessage)).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct CompressedVoiceMessage {
+    pub room: Pubkey,
+    pub sender: Pubkey,
+    pub storage_pda_index: u8,
+    pub sequence_number: u32,
+    pub data_length: u32,
+    pub timestamp: i64,
+    pub leaf_index: u64,
+}
+
+/// Compressed counterpart of VoiceMessage for the light backend
+#[cfg(feature = "light")]
+#[derive(Clone, Debug, Default, AnchorSerialize, AnchorDeserialize, LightDiscriminator, LightHasher)]
+pub struct LightVoiceMessage {
+    #[hash]
+    pub sender: Pubkey,
+    #[hash]
+    pub room: Pubkey,
+    pub storage_pda_index: u8,
+    pub sequence_number: u32,
+    pub data_length: u32,
+    pub timestamp: i64,
+}
+
+/// Compressed counterpart of BroadcastMessage for the light backend
+#[cfg(feature = "light")]
+#[derive(Clone, Debug, Default, AnchorSerialize, AnchorDeserialize, LightDiscriminator, LightHasher)]
+pub struct LightBroadcastMessage {
+    #[hash]
+    pub sender: Pubkey,
+    #[hash]
+    pub room: Pubkey,
+    pub target_pda_mask: u16,
+    pub sequence_number: u32,
+    pub data_length: u32,
+    pub timestamp: i64,
+}
+
+/// Describes one frame inside a batched payload
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct FrameHeader {
+    pub sequence_number: u32,
+    pub length: u16,
+    pub duration_ms: u16,
+}
+
+impl FrameHeader {
+    pub const SIZE: usize = 4 + 2 + 2;
+}
+
+#[account]
+pub struct BroadcastMessage {
+    pub sender: Pubkey,
+    pub room_id: String,
+    pub target_pdas: Vec<u8>,
+    pub sequence_number: u32,
+    pub data_length: u32,
+    pub timestamp: i64,
+}
+
+#[account]
+pub struct Participant {
+    pub room: Pubkey,
+    pub user: Pubkey,
+    pub joined_at: i64,
+    pub last_seen: i64,
+    pub muted: bool,
+    pub slot: u8, // index into the room's occupancy and consent bitmaps
+    pub supported_codecs: u16, // Codec bitmask registered on join, 0 = not declared
+    pub sequence_epoch: u32, // Bumped by the sender whenever their sequence numbers restart
+    pub highest_contiguous_sequence: u32, // Every sequence from 1 up to this one has been sent; 0 = none yet
+    pub bump: u8,
+    pub has_stream: bool, // Set by open_stream; leaving or eviction must then stop the stream
+}
+
+impl Participant {
+    /// Advance the contiguous watermark over `first..=last` when the range continues it;
+    /// ranges past a gap leave it where it is
+    pub fn extend_contiguous(&mut self, first: u32, last: u32) {
+        if first <= self.highest_contiguous_sequence.saturating_add(1) && last > self.highest_contiguous_sequence {
+            self.highest_contiguous_sequence = last;
+        }
+    }
+}
+
+#[account]
+pub struct RoomEscrow {
+    pub room: Pubkey,
+    pub lamports_collected: u64,
+    pub tokens_collected: u64,
+    pub tip_lamports_collected: u64,
+    pub tip_tokens_collected: u64,
+    pub split_configured: bool, // Funds can then only leave through settle_room
+    pub bump: u8,
+}
+
+#[account]
+pub struct Ticket {
+    pub room: Pubkey,
+    pub holder: Pubkey,
+    pub purchased_at: i64,
+    pub price_paid: u64,
+    pub consumed: bool,
+}
+
+#[account]
+pub struct TipTally {
+    pub room: Pubkey,
+    pub speaker: Pubkey,
+    pub mint: Option<Pubkey>, // None = SOL
+    pub total_amount: u64,
+    pub tip_count: u32,
+    pub last_tip_at: i64,
+    pub first_tip_at: i64,
+}
+
+#[account]
+pub struct RevenueSplit {
+    pub room: Pubkey,
+    pub shares: Vec<SplitShare>,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct SplitShare {
+    pub recipient: Pubkey,
+    pub bps: u16,
+}
+
+impl SplitShare {
+    pub const SIZE: usize = 32 + 2;
+}
+
+#[account]
+pub struct PaymentStream {
+    pub room: Pubkey,
+    pub listener: Pubkey,
+    pub rate_lamports_per_minute: u64,
+    pub deposited: u64,
+    pub paid_out: u64,
+    pub last_settled_at: i64,
+    pub stopped_at: i64, // 0 = still listening
+    pub bump: u8,
+}
+
+#[account]
+pub struct SenderStake {
+    pub room: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub left_at: i64, // 0 = locked while participating
+    pub bump: u8,
+    pub session_joined_at: i64, // Last ended session, reportable for STAKE_COOLDOWN_SECONDS after session_left_at
+    pub session_left_at: i64,
+}
+
+#[account]
+pub struct RoomTreasury {
+    pub room: Pubkey,
+    pub slashed_lamports: u64,
+    pub bump: u8,
+}
+
+#[account]
+pub struct Report {
+    pub room: Pubkey,
+    pub offender: Pubkey,
+    pub reporter: Pubkey,
+    pub reason_code: u8,
+    pub session_joined_at: i64,
+    pub created_at: i64,
+}
+
+#[account]
+pub struct ReportTally {
+    pub room: Pubkey,
+    pub offender: Pubkey,
+    pub session_joined_at: i64,
+    pub report_count: u16,
+    pub slashed: bool,
+    pub bump: u8,
+}
+
+#[account]
+pub struct UserReputation {
+    pub user: Pubkey,
+    pub sessions_completed: u32,
+    pub tips_received: u32,
+    pub reports_upheld: u32,
+    pub slashes: u32,
+    pub bump: u8,
+}
+
+impl UserReputation {
+    pub fn score(&self) -> i64 {
+        self.sessions_completed as i64 * REPUTATION_SESSION_WEIGHT
+            + self.tips_received as i64 * REPUTATION_TIP_WEIGHT
+            - self.reports_upheld as i64 * REPUTATION_REPORT_PENALTY
+            - self.slashes as i64 * REPUTATION_SLASH_PENALTY
+    }
+}
+
+/// Per-user activity counters for profiles and reward programs
+#[account]
+pub struct UserStats {
+    pub user: Pubkey,
+    pub rooms_joined: u32,
+    pub messages_sent: u64,
+    pub bytes_sent: u64,
+    pub talk_time_seconds: u64, // Time spent in rooms, credited on leave
+    pub tips_received: u32,
+    pub tip_lamports_received: u64, // SOL tips only; token tips are tallied per mint in TipTally
+    pub bump: u8,
+}
+
+impl UserStats {
+    pub fn record_message(&mut self, bytes: usize) {
+        self.messages_sent += 1;
+        self.bytes_sent += bytes as u64;
+    }
+}
+
+/// Program-wide settings. `admin` is any signer: a keypair, a Squads multisig vault or an
+/// SPL Governance PDA, the latter two signing through their own execute instructions.
+#[account]
+pub struct ProtocolConfig {
+    pub admin: Pubkey,
+    pub fee_bps: u16,
+    pub paused: bool, // storage_manager and voicechat read this at a fixed offset
+    pub bump: u8,
+    pub pending_admin: Option<Pubkey>,
+    pub transcription_oracle: Option<Pubkey>, // None = transcripts disabled
+    pub storage_backend: Option<Pubkey>, // Program implementing storage_interface, None = storage_manager
+}
+
+impl ProtocolConfig {
+    pub fn storage_backend(&self) -> Pubkey {
+        self.storage_backend.unwrap_or(storage_layout::STORAGE_MANAGER_PROGRAM_ID)
+    }
+
+    pub fn fee_on(&self, amount: u64) -> u64 {
+        (amount as u128 * self.fee_bps as u128 / 10_000) as u64
+    }
+}
+
+#[account]
+pub struct ProtocolTreasury {
+    pub lamports_collected: u64,
+    pub bump: u8,
+}
+
+#[account]
+pub struct FollowEdge {
+    pub follower: Pubkey,
+    pub target: Pubkey,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+pub struct BlockEntry {
+    pub blocker: Pubkey,
+    pub blocked: Pubkey,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+pub struct DirectCall {
+    pub caller: Pubkey,
+    pub callee: Pubkey,
+    pub status: CallStatus,
+    pub storage_lane: Pubkey,
+    pub created_at: i64,
+    pub answered_at: i64,
+    pub last_activity: i64,
+    pub bump: u8,
+}
+
+#[account]
+pub struct CallLog {
+    pub owner: Pubkey,
+    pub missed_calls: Vec<MissedCall>, // oldest first, capped at MAX_MISSED_CALLS
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct MissedCall {
+    pub caller: Pubkey,
+    pub rang_at: i64,
+}
+
+impl MissedCall {
+    pub const SIZE: usize = 32 + 8;
+}
+
+#[account]
+pub struct VoicemailInbox {
+    pub owner: Pubkey,
+    pub next_voicemail_id: u64,
+    pub unplayed_count: u32,
+    pub bump: u8,
+}
+
+#[account]
+pub struct Voicemail {
+    pub recipient: Pubkey,
+    pub sender: Pubkey,
+    pub id: u64,
+    pub storage_lane: Pubkey,
+    pub data_length: u32,
+    pub left_at: i64,
+    pub played: bool,
+    pub bump: u8,
+}
+
+#[account]
+pub struct ChatLog {
+    pub room: Pubkey,
+    pub messages: Vec<ChatEntry>, // oldest first, capped at MAX_CHAT_LOG_ENTRIES
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ChatEntry {
+    pub sender: Pubkey,
+    pub content: String,
+    pub timestamp: i64,
+}
+
+impl ChatEntry {
+    pub const SIZE: usize = 32 + 4 + MAX_TEXT_MESSAGE_LENGTH + 8;
+}
+
+#[account]
+pub struct Poll {
+    pub room: Pubkey,
+    pub id: u32,
+    pub options: Vec<String>,
+    pub votes: Vec<u32>, // one tally per option
+    pub created_at: i64,
+    pub ends_at: i64,
+    pub closed: bool,
+    pub bump: u8,
+}
+
+#[account]
+pub struct PollVote {
+    pub poll: Pubkey,
+    pub voter: Pubkey,
+    pub option: u8,
+}
+
+#[account]
+pub struct Session {
+    pub room: Pubkey,
+    pub id: u32,
+    pub started_at: i64,
+    pub ended_at: i64, // 0 = still running
+    pub peak_participants: u8,
+    pub total_frames: u64,
+    pub total_bytes: u64,
+    pub bump: u8,
+}
+
+#[account]
+pub struct AttendanceRecord {
+    pub room: Pubkey,
+    pub attendee: Pubkey,
+    pub session_ended_at: i64,
+    pub joined_at: i64,
+    pub duration_seconds: i64,
+    pub bump: u8,
+}
+
+#[account]
+pub struct Recording {
+    pub room: Pubkey,
+    pub session: Pubkey,
+    pub cid_or_tx_id: String,
+    pub content_hash: [u8; 32],
+    pub duration_seconds: u32,
+    pub finalized_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+pub struct ClipNft {
+    pub mint: Pubkey,
+    pub recording: Pubkey,
+    pub start_ms: u32,
+    pub end_ms: u32,
+    pub bump: u8,
+}
+
+#[account]
+pub struct Transcript {
+    pub session: Pubkey,
+    pub oracle: Pubkey,
+    pub uri: String,
+    pub hash: [u8; 32],
+    pub provider: String,
+    pub submitted_at: i64,
+    pub bump: u8,
+}
+
+/// Ephemeral WebRTC handshake between two peers, closed once the connection is up
+#[account]
+pub struct SdpSignal {
+    pub offerer: Pubkey,
+    pub answerer: Pubkey,
+    pub offer_hash: [u8; 32],
+    pub offer_slot: Pubkey,
+    pub answer_hash: Option<[u8; 32]>,
+    pub answer_slot: Option<Pubkey>,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+/// Latest ICE candidate batch from one peer to another; short-lived like SdpSignal
+#[account]
+pub struct IceCandidates {
+    pub sender: Pubkey,
+    pub target: Pubkey,
+    pub candidates: Vec<u8>,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+/// A staked relay/TURN operator. The PDA holds the stake on top of its rent.
+#[account]
+pub struct RelayNode {
+    pub operator: Pubkey,
+    pub endpoint_hash: [u8; 32], // Hash of the relay URL; clients resolve it off-chain
+    pub region: u8, // Operator-declared region code, 0 = unspecified
+    pub stake_lamports: u64,
+    pub registered_at: i64,
+    pub last_heartbeat: i64,
+    pub report_count: u16,
+    pub slashed: bool,
+    pub deregistered_at: i64, // 0 = still serving
+    pub bump: u8,
+}
+
+impl RelayNode {
+    /// Registered, unslashed and heartbeating
+    pub fn is_live(&self, now: i64) -> bool {
+        self.deregistered_at == 0 && !self.slashed && now - self.last_heartbeat <= RELAY_HEARTBEAT_TIMEOUT_SECONDS
+    }
+}
+
+#[account]
+pub struct RelayReport {
+    pub relay: Pubkey,
+    pub reporter: Pubkey,
+    pub room: Pubkey,
+    pub reason_code: u8,
+    pub created_at: i64,
+}
+
+#[account]
+pub struct DeliveryAttestation {
+    pub session: Pubkey,
+    pub relay: Pubkey,
+    pub merkle_root: [u8; 32], // Root over the delivered frames' sequence numbers
+    pub frames_delivered: u64,
+    pub first_sequence: u64,
+    pub last_sequence: u64,
+    pub submitted_at: i64,
+    pub bump: u8,
+}
+
+/// Ephemeral key allowed to act for `owner` until `expiry`
+#[account]
+pub struct SessionKey {
+    pub owner: Pubkey,
+    pub session_signer: Pubkey,
+    pub expiry: i64,
+    pub scope: u8, // SESSION_SCOPE_* bits
+    pub bump: u8,
+}
+
+/// Ring buffer of a sender's recent idempotency keys in a room
+#[account]
+pub struct RecentSends {
+    pub room: Pubkey,
+    pub sender: Pubkey,
+    pub recent_keys: [u64; IDEMPOTENCY_WINDOW],
+    pub cursor: u8,
+    pub bump: u8,
+    pub last_sponsored_sequence: u32, // Sponsored sends must use increasing sequence numbers
+    pub last_sent_slot: u64,
+    pub sequence_epoch: u32, // Highest epoch the sender has used here; outlives their participant record
+}
+
+impl RecentSends {
+    /// Hold sends to the latest epoch. A rejoined sender's participant record restarts at
+    /// epoch 0, so they start a new epoch before sending again.
+    pub fn track_epoch(&mut self, sequence_epoch: u32) -> Result<()> {
+        require!(sequence_epoch >= self.sequence_epoch, VoiceChatError::SequenceEpochBehind);
+        self.sequence_epoch = sequence_epoch;
+        Ok(())
+    }
+    
+    /// Remember `key`, failing if it was seen within the window. 0 opts out of the check.
+    pub fn record(&mut self, key: u64) -> Result<()> {
+        if key == 0 {
+            return Ok(());
+        }
+        require!(!self.recent_keys.contains(&key), VoiceChatError::DuplicateSend);
+        self.recent_keys[self.cursor as usize] = key;
+        self.cursor = ((self.cursor as usize + 1) % IDEMPOTENCY_WINDOW) as u8;
+        Ok(())
+    }
+}
+
+/// A room that reopens every `interval_seconds`. The account also holds the lamports that
+/// fund each occurrence's rent.
+#[account]
+pub struct RecurringSchedule {
+    pub host: Pubkey,
+    pub template_room: Pubkey,
+    pub label: String,
+    pub next_start: i64,
+    pub interval_seconds: i64,
+    pub duration_seconds: i64, // close_occurrence deactivates an occurrence this long after its start
+    pub capacity: u16, // Ticket capacity of each occurrence
+    pub occurrence_count: u32,
+    pub bump: u8,
+}
+
+/// Host-chosen room configuration, stored in templates and applied on room creation
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct RoomSettings {
+    pub gate_mint: Option<Pubkey>,
+    pub gate_min_balance: u64,
+    pub gate_collection: Option<Pubkey>,
+    pub entry_fee_lamports: u64,
+    pub entry_fee_mint: Option<Pubkey>,
+    pub entry_fee_amount: u64,
+    pub ticket_capacity: u16,
+    pub sender_stake_lamports: u64,
+    pub report_quorum: u8,
+    pub slash_bps: u16,
+    pub min_reputation_to_speak: i64,
+    pub participant_timeout_seconds: i64,
+    pub max_frame_bytes: u32, // Codec frame size cap; 0 = MAX_VOICE_DATA_SIZE
+    pub preferred_relay: Option<Pubkey>,
+    pub accepted_codecs: u16, // Bit i set = codec id i may be sent, 0 = any
+    pub target_bitrate_kbps: u16, // 0 = unrestricted
+    pub max_frame_duration_ms: u16, // 0 = unrestricted
+}
+
+impl RoomSettings {
+    pub const SIZE: usize = 33 + 8 + 33 + 8 + 33 + 8 + 2 + 8 + 1 + 2 + 8 + 8 + 4 + 33 + 2 + 2 + 2;
+
+    /// Same bounds the individual room setters enforce
+    pub fn validate(&self) -> Result<()> {
+        require!(self.ticket_capacity < MAX_PARTICIPANTS as u16, VoiceChatError::InvalidTicketCapacity);
+        require!(self.slash_bps <= 10_000, VoiceChatError::InvalidSlashBps);
+        require!(self.participant_timeout_seconds >= 0, VoiceChatError::InvalidParticipantTimeout);
+        require!(self.max_frame_bytes as usize <= MAX_VOICE_DATA_SIZE, VoiceChatError::VoiceDataTooLarge);
+        Ok(())
+    }
+}
+
+#[account]
+pub struct RoomTemplate {
+    pub host: Pubkey,
+    pub name: String,
+    pub settings: RoomSettings,
+    pub bump: u8,
+}
+
+/// One fixed-capacity page of a room's frame index. Pages fill in order and are never
+/// rewritten, so readers walk page 0, 1, ... until they reach one that isn't full.
+#[account]
+pub struct MessageIndexPage {
+    pub room: Pubkey,
+    pub page: u64,
+    pub entries: Vec<MessageIndexEntry>,
+    pub bump: u8,
+}
+
+impl MessageIndexPage {
+    pub const SPACE: usize = 8 + 32 + 8 + 4 + MessageIndexEntry::SIZE * MESSAGE_INDEX_PAGE_CAPACITY + 1; // discriminator + room + page + entries_len + entries + bump
+
+    /// Record a frame on the room's open page. The room's counter picks the page, so the
+    /// next frame lands on a fresh page once this one holds MESSAGE_INDEX_PAGE_CAPACITY.
+    pub fn append(&mut self, voice_room: &mut Account<VoiceRoom>, message: &VoiceMessage, bump: u8) {
+        self.push(voice_room, MessageIndexEntry {
+            sender: message.sender,
+            sequence_epoch: message.sequence_epoch,
+            sequence_number: message.sequence_number,
+            storage_pda_index: message.storage_pda_index,
+            timestamp: message.timestamp,
+        }, bump);
+    }
+
+    /// Record a batch under its first frame's sequence number
+    pub fn append_batch(&mut self, voice_room: &mut Account<VoiceRoom>, batch: &VoiceBatchMessage, bump: u8) {
+        self.push(voice_room, MessageIndexEntry {
+            sender: batch.sender,
+            sequence_epoch: batch.sequence_epoch,
+            sequence_number: batch.sequence_number,
+            storage_pda_index: batch.storage_pda_index,
+            timestamp: batch.timestamp,
+        }, bump);
+    }
+
+    /// Record a clip under its first chunk's lane
+    pub fn append_clip(&mut self, voice_room: &mut Account<VoiceRoom>, clip: &VoiceClipMessage, bump: u8) {
+        self.push(voice_room, MessageIndexEntry {
+            sender: clip.sender,
+            sequence_epoch: clip.sequence_epoch,
+            sequence_number: clip.sequence_number,
+            storage_pda_index: clip.target_pdas[0],
+            timestamp: clip.timestamp,
+        }, bump);
+    }
+
+    fn push(&mut self, voice_room: &mut Account<VoiceRoom>, entry: MessageIndexEntry, bump: u8) {
+        self.room = voice_room.key();
+        self.page = voice_room.current_index_page();
+        self.bump = bump;
+        self.entries.push(entry);
+        voice_room.indexed_messages += 1;
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct MessageIndexEntry {
+    pub sender: Pubkey,
+    pub sequence_epoch: u32,
+    pub sequence_number: u32,
+    pub storage_pda_index: u8,
+    pub timestamp: i64,
+}
+
+impl MessageIndexEntry {
+    pub const SIZE: usize = 32 + 4 + 4 + 1 + 8;
+}
+
+/// Top LEADERBOARD_SIZE users by a metric over a time window, highest score first.
+/// A user pushed off the board loses their accumulated score.
+#[account]
+pub struct Leaderboard {
+    pub creator: Pubkey,
+    pub metric: u8,
+    pub window_start: i64,
+    pub window_end: i64,
+    pub entries: [LeaderboardEntry; LEADERBOARD_SIZE], // Unused slots have a zero score
+    pub bump: u8,
+}
+
+impl Leaderboard {
+    pub fn covers(&self, timestamp: i64) -> bool {
+        timestamp >= self.window_start && timestamp < self.window_end
+    }
+
+    /// Add to a user's score, taking the lowest slot if they aren't ranked yet
+    pub fn add_score(&mut self, user: Pubkey, amount: u64) {
+        if let Some(entry) = self.entries.iter_mut().find(|entry| entry.score > 0 && entry.user == user) {
+            entry.score += amount;
+        } else {
+            // Entries stay sorted, so the last one is the lowest
+            let lowest = &mut self.entries[LEADERBOARD_SIZE - 1];
+            if amount > lowest.score {
+                *lowest = LeaderboardEntry { user, score: amount };
+            }
+        }
+        self.entries.sort_by(|a, b| b.score.cmp(&a.score));
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default)]
+pub struct LeaderboardEntry {
+    pub user: Pubkey,
+    pub score: u64,
+}
+
+impl LeaderboardEntry {
+    pub const SIZE: usize = 32 + 8;
+}
+
+#[account]
+pub struct LeaderboardFold {
+    pub folded: u64,
+    pub bump: u8,
+}
+
+/// Ring buffer of a room's most recent storage writes, for diagnosing corrupted playback
+#[account]
+pub struct WriteJournal {
+    pub room: Pubkey,
+    pub write_count: u64, // Total writes journaled; the next entry goes to write_count % WRITE_JOURNAL_CAPACITY
+    pub entries: [JournalEntry; WRITE_JOURNAL_CAPACITY],
+    pub bump: u8,
+}
+
+impl WriteJournal {
+    pub fn append(&mut self, entry: JournalEntry) {
+        self.entries[(self.write_count % WRITE_JOURNAL_CAPACITY as u64) as usize] = entry;
+        self.write_count += 1;
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default)]
+pub struct JournalEntry {
+    pub storage_pda: Pubkey,
+    pub offset: u32, // Into the storage PDA's data section
+    pub length: u32,
+    pub hash: [u8; 32], // keccak256 of the written bytes
+    pub slot: u64,
+}
+
+impl JournalEntry {
+    pub const SIZE: usize = 32 + 4 + 4 + 32 + 8;
+}
+
+/// Protocol-wide SOL/USD oracle settings for USD-priced fees
+#[account]
+pub struct PriceFeedConfig {
+    pub feed_id: [u8; 32], // Pyth price feed id
+    pub max_age_seconds: u32, // Older price updates are rejected
+    pub min_protocol_fee_usd_cents: u64,
+    pub bump: u8,
+}
+
+/// Resolves a bound .sol domain to its room
+#[account]
+pub struct RoomDomain {
+    pub domain: Pubkey, // SNS name record
+    pub room: Pubkey,
+    pub bump: u8,
+}
+
+/// Bubblegum tree an event's compressed tickets are minted into, with a bitmap of redeemed
+/// leaf nonces so each cNFT admits its holder once
+#[account]
+pub struct TicketTree {
+    pub room: Pubkey,
+    pub merkle_tree: Pubkey,
+    pub uri: String, // Off-chain metadata for every ticket
+    pub minted: u64,
+    pub escrowed: u64, // Room share of compressed ticket sales still owed to unused tickets
+    pub redeemed: Vec<u8>, // Bit n set = ticket with nonce n has been used or refunded
+    pub bump: u8,
+}
+
+impl TicketTree {
+    pub fn bitmap_len(ticket_capacity: u16) -> usize {
+        (ticket_capacity as usize).div_ceil(8)
+    }
+
+    pub fn space(ticket_capacity: u16) -> usize {
+        8 + 32 + 32 + 4 + MAX_TICKET_URI_LENGTH + 8 + 8 + 4 + Self::bitmap_len(ticket_capacity) + 1 // discriminator + room + merkle_tree + uri + minted + escrowed + redeemed + bump
+    }
+
+    /// Mark a ticket as used, failing if it already was. Returns the ticket's even share of
+    /// what is still escrowed, which a refund pays out and a used ticket leaves to the event.
+    pub fn redeem(&mut self, nonce: u64) -> Result<u64> {
+        require!(nonce < self.minted, VoiceChatError::InvalidCompressedTicket);
+        let (byte, bit) = ((nonce / 8) as usize, 1u8 << (nonce % 8));
+        require!(self.redeemed[byte] & bit == 0, VoiceChatError::TicketAlreadyUsed);
+        let used: u64 = self.redeemed.iter().map(|byte| byte.count_ones() as u64).sum();
+        let share = self.escrowed / (self.minted - used);
+        self.escrowed -= share;
+        self.redeemed[byte] |= bit;
+        Ok(share)
+    }
+}
+
+/// What a joiner supplies to prove they hold a compressed ticket; the hashes come from the
+/// asset's indexed data and the proof path goes in remaining_accounts
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct CompressedTicketProof {
+    pub root: [u8; 32],
+    pub data_hash: [u8; 32],
+    pub creator_hash: [u8; 32],
+    pub nonce: u64, // Also the leaf index
+    pub delegate: Pubkey, // Leaf delegate, the owner unless delegated
+}
+
+/// How a room checks holdings on another chain
+#[account]
+pub struct CrossChainGate {
+    pub room: Pubkey,
+    pub source_chain: u16, // Wormhole chain id, 0 = gate off
+    pub collection: [u8; 32], // Token contract on the source chain, left-padded
+    pub min_balance: u64,
+    pub attestor: Option<Pubkey>, // Ed25519 key trusted to sign attestations
+    pub emitter: Option<WormholeEmitter>, // Wormhole emitter trusted to publish attestations
+    pub bump: u8,
+}
+
+/// Proof of cross-chain holdings, valid until `expires_at`
+#[account]
+pub struct CrossChainPass {
+    pub room: Pubkey,
+    pub holder: Pubkey,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct WormholeEmitter {
+    pub chain: u16,
+    pub address: [u8; 32],
+}
+
+/// Statement that `holder` controls an address holding `balance` of `collection` on
+/// `source_chain`. Signed or published as its borsh encoding.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct HoldingsAttestation {
+    pub room: Pubkey,
+    pub holder: Pubkey,
+    pub source_chain: u16,
+    pub collection: [u8; 32],
+    pub balance: u64,
+    pub expires_at: i64,
+}
+
+/// Keeps `user` out of `room` until `ban_until`
+#[account]
+pub struct RoomBan {
+    pub room: Pubkey,
+    pub user: Pubkey,
+    pub ban_until: i64,
+    pub bump: u8,
+}
+
+/// Protocol-wide price of storage writes in metered rooms
+#[account]
+pub struct BandwidthPricing {
+    pub lamports_per_kb: u64,
+    pub bump: u8,
+}
+
+/// Host-funded escrow that pays for a room's storage writes
+#[account]
+pub struct BandwidthAllowance {
+    pub room: Pubkey,
+    pub lamports_per_kb: u64, // Protocol price when the allowance was last funded
+    pub balance_lamports: u64, // Unspent; refundable to the host
+    pub unsettled_lamports: u64, // Charged for writes, owed to the protocol treasury
+    pub bytes_written: u64,
+    pub bump: u8,
+}
+
+impl BandwidthAllowance {
+    /// Deduct a write of `bytes` at the allowance's price, rounding up to the lamport
+    pub fn charge(&mut self, bytes: usize) -> Result<()> {
+        let cost = (bytes as u128 * self.lamports_per_kb as u128).div_ceil(1024) as u64;
+        require!(cost <= self.balance_lamports, VoiceChatError::BandwidthExhausted);
+        self.balance_lamports -= cost;
+        self.unsettled_lamports += cost;
+        self.bytes_written += bytes as u64;
+        Ok(())
+    }
+}
+
+/// Participants waiting to be brought on stage
+#[account]
+pub struct HandQueue {
+    pub room: Pubkey,
+    pub weighting: u8, // HAND_WEIGHT_*
+    pub entries: Vec<HandRaise>, // In the order hands went up
+    pub bump: u8,
+}
+
+impl HandQueue {
+    pub const SPACE: usize = 8 + 32 + 1 + 4 + HandRaise::SIZE * MAX_ROOM_CAPACITY as usize + 1; // discriminator + room + weighting + entries + bump
+
+    pub fn raise(&mut self, hand: HandRaise) -> Result<()> {
+        require!(self.position(&hand.user).is_none(), VoiceChatError::HandAlreadyRaised);
+        require!(self.entries.len() < MAX_ROOM_CAPACITY as usize, VoiceChatError::HandQueueFull);
+        self.entries.push(hand);
+        Ok(())
+    }
+
+    pub fn position(&self, user: &Pubkey) -> Option<usize> {
+        self.entries.iter().position(|hand| hand.user == *user)
+    }
+
+    /// Heaviest hand first when weighted, earliest first otherwise and among equal weights
+    pub fn next_index(&self) -> Option<usize> {
+        let weighted = self.weighting != HAND_WEIGHT_NONE;
+        self.entries
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, hand)| (std::cmp::Reverse(if weighted { hand.weight } else { 0 }), hand.raised_at))
+            .map(|(index, _)| index)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default)]
+pub struct HandRaise {
+    pub user: Pubkey,
+    pub weight: u64, // Snapshot at hand-raise time, 0 in unweighted queues
+    pub raised_at: i64,
 }
 
+impl HandRaise {
+    pub const SIZE: usize = 32 + 8 + 8;
+}
+
+/// Host's offer to bring `user` onto the stage, open until `expires_at`
 #[account]
-pub struct VoiceRoom {
-    pub room_id: String,
-    pub host: Pubkey,
-    pub participant_count: u8,
-    pub is_active: bool,
-    pub created_at: i64,
-    pub last_activity: i64,
+pub struct StageInvite {
+    pub room: Pubkey,
+    pub user: Pubkey,
+    pub invited_by: Pubkey, // Host who paid the rent and gets it back on accept
+    pub expires_at: i64,
+    pub bump: u8,
 }
 
+/// Append-only merkle tree over a session's archived messages. `root` commits to every
+/// message folded in so far; leaves are `archive_leaf` hashes in archive order.
 #[account]
-pub struct VoiceMessage {
+pub struct SessionArchive {
+    pub room: Pubkey,
+    pub session: Pubkey,
+    pub root: [u8; 32],
+    pub frontier: [[u8; 32]; SESSION_ARCHIVE_DEPTH], // Rightmost filled node at each level
+    pub message_count: u64,
+    pub last_timestamp: i64, // Messages must be archived oldest first
+    pub bump: u8,
+}
+
+impl SessionArchive {
+    pub const SPACE: usize = 8 + 32 + 32 + 32 + 32 * SESSION_ARCHIVE_DEPTH + 8 + 8 + 1; // discriminator + room + session + root + frontier + message_count + last_timestamp + bump
+
+    pub fn append(&mut self, leaf: [u8; 32]) -> Result<()> {
+        require!(self.message_count < 1 << SESSION_ARCHIVE_DEPTH, VoiceChatError::ArchiveFull);
+        let mut node = leaf;
+        let mut index = self.message_count;
+        for level in 0..SESSION_ARCHIVE_DEPTH {
+            if index & 1 == 0 {
+                self.frontier[level] = node;
+                break;
+            }
+            node = anchor_lang::solana_program::keccak::hashv(&[&self.frontier[level], &node]).to_bytes();
+            index >>= 1;
+        }
+        self.message_count += 1;
+        self.root = self.compute_root();
+        Ok(())
+    }
+
+    /// Root of the tree with every leaf past message_count empty
+    fn compute_root(&self) -> [u8; 32] {
+        let mut node = [0u8; 32];
+        let mut zero = [0u8; 32];
+        let mut size = self.message_count;
+        for level in 0..SESSION_ARCHIVE_DEPTH {
+            node = if size & 1 == 1 {
+                anchor_lang::solana_program::keccak::hashv(&[&self.frontier[level], &node]).to_bytes()
+            } else {
+                anchor_lang::solana_program::keccak::hashv(&[&node, &zero]).to_bytes()
+            };
+            zero = anchor_lang::solana_program::keccak::hashv(&[&zero, &zero]).to_bytes();
+            size >>= 1;
+        }
+        node
+    }
+}
+
+/// One frame of a session export manifest
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default)]
+pub struct ManifestEntry {
+    pub storage_pda: Pubkey,
+    pub offset: u32, // Into the storage PDA's data section
+    pub length: u32,
+    pub hash: [u8; 32], // keccak256 of the frame's bytes
+}
+
+/// How far a listener has consumed each sender in a room. Entries are kept most recently
+/// advanced last; once MAX_ROOM_CAPACITY senders are tracked the stalest is dropped.
+#[account]
+pub struct ListenerCursor {
+    pub room: Pubkey,
+    pub listener: Pubkey,
+    pub entries: Vec<CursorEntry>,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+impl ListenerCursor {
+    pub const SPACE: usize = 8 + 32 + 32 + 4 + CursorEntry::SIZE * MAX_ROOM_CAPACITY as usize + 8 + 1; // discriminator + room + listener + entries + updated_at + bump
+
+    /// Move the cursor for a sender forward. Within an epoch it never goes back; a newer
+    /// epoch (the sender restarted their sequence numbers) starts over.
+    pub fn advance(&mut self, entry: CursorEntry) -> Result<()> {
+        if let Some(index) = self.entries.iter().position(|existing| existing.sender == entry.sender) {
+            let existing = self.entries.remove(index);
+            require!(
+                (entry.sequence_epoch, entry.sequence) >= (existing.sequence_epoch, existing.sequence),
+                VoiceChatError::CursorRegression
+            );
+        }
+        if self.entries.len() == MAX_ROOM_CAPACITY as usize {
+            self.entries.remove(0);
+        }
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    pub fn position(&self, sender: &Pubkey) -> Option<&CursorEntry> {
+        self.entries.iter().find(|entry| entry.sender == *sender)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default)]
+pub struct CursorEntry {
     pub sender: Pubkey,
-    pub room_id: String,
-    pub storage_pda_index: u8,
-    pub sequence_number: u32,
-    pub data_length: u32,
-    pub timestamp: i64,
+    pub sequence_epoch: u32,
+    pub sequence: u32, // Last sequence consumed in that epoch
+}
+
+impl CursorEntry {
+    pub const SIZE: usize = 32 + 4 + 4;
 }
 
 #[account]
-pub struct BroadcastMessage {
+pub struct RoomSubscription {
+    pub host: Pubkey, // First field so notifiers can filter by host at offset 8
+    pub subscriber: Pubkey,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+pub struct NotificationConfig {
+    pub user: Pubkey,
+    pub endpoint_hash: [u8; 32], // Hash of the webhook URL or push token, never the endpoint itself
+    pub preferences: u8, // NOTIFY_* bitmask
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+impl NotificationConfig {
+    pub fn wants(&self, preference: u8) -> bool {
+        self.preferences & preference != 0
+    }
+}
+
+/// Accounts for writing storage through the protocol's storage backend. Writes go over
+/// storage_interface::write_chunk when the backend's program account is passed, signed by
+/// this program's storage_writer PDA; without it storage_manager PDAs are written in place.
+#[cfg_attr(not(feature = "storage-cpi"), allow(dead_code))]
+struct StorageWriter<'a, 'info> {
+    program: &'a Option<UncheckedAccount<'info>>,
+    writer: &'a Option<UncheckedAccount<'info>>,
+    writer_bump: Option<u8>,
+    protocol_config: &'a Account<'info, ProtocolConfig>,
+}
+
+/// Storage PDAs must belong to storage_manager and carry the layout version this program
+/// was built against, so a layout change on either side fails loudly instead of corrupting data
+fn check_storage_layout(storage_account_info: &AccountInfo) -> Result<()> {
+    require_keys_eq!(
+        *storage_account_info.owner,
+        storage_layout::STORAGE_MANAGER_PROGRAM_ID,
+        VoiceChatError::InvalidStoragePDA
+    );
+    require!(
+        storage_layout::layout_version(&storage_account_info.try_borrow_data()?) == storage_layout::LAYOUT_VERSION,
+        VoiceChatError::StorageLayoutMismatch
+    );
+    Ok(())
+}
+
+/// One frame held in an account sized to its payload, the alternative to storage lanes
+#[account]
+pub struct EphemeralFrame {
+    pub room: Pubkey,
     pub sender: Pubkey,
-    pub room_id: String,
-    pub target_pdas: Vec<u8>,
+    pub sequence_epoch: u32,
     pub sequence_number: u32,
-    pub data_length: u32,
     pub timestamp: i64,
+    pub pending_acks: u16, // Bit j set = listener in slot j at send time hasn't consumed it yet
+    pub bump: u8,
+    pub data: Vec<u8>,
+}
+
+impl EphemeralFrame {
+    pub const BASE_SPACE: usize = 8 + 32 + 32 + 4 + 4 + 8 + 2 + 1 + 4; // discriminator + room + sender + sequence_epoch + sequence_number + timestamp + pending_acks + bump + data_len
+}
+
+/// Write bytes to the start of a storage PDA's data section and update its data_length
+fn write_storage_chunk<'info>(
+    storage_account_info: &AccountInfo<'info>,
+    storage_writer: &StorageWriter<'_, 'info>,
+    bytes: &[u8],
+) -> Result<()> {
+    if let Some(storage_program) = storage_writer.program.as_ref() {
+        require_keys_eq!(
+            storage_program.key(),
+            storage_writer.protocol_config.storage_backend(),
+            VoiceChatError::InvalidStorageBackend
+        );
+        return write_storage_chunk_cpi(storage_account_info, storage_program, storage_writer, bytes);
+    }
+    check_storage_layout(storage_account_info)?;
+    require!(
+        storage_layout::manager_writes(&storage_account_info.try_borrow_data()?),
+        VoiceChatError::StorageNotDelegated
+    );
+    
+    let mut storage_data = storage_account_info.try_borrow_mut_data()?;
+    storage_data[STORAGE_DATA_OFFSET..STORAGE_DATA_OFFSET + bytes.len()].copy_from_slice(bytes);
+    
+    let new_length = bytes.len() as u32;
+    storage_data[STORAGE_DATA_LENGTH_OFFSET..STORAGE_DATA_LENGTH_OFFSET + 4].copy_from_slice(&new_length.to_le_bytes());
+    Ok(())
+}
+
+#[cfg(feature = "storage-cpi")]
+fn write_storage_chunk_cpi<'info>(
+    storage_account_info: &AccountInfo<'info>,
+    storage_program: &UncheckedAccount<'info>,
+    storage_writer: &StorageWriter<'_, 'info>,
+    bytes: &[u8],
+) -> Result<()> {
+    let (Some(writer), Some(writer_bump)) = (storage_writer.writer.as_ref(), storage_writer.writer_bump) else {
+        return err!(VoiceChatError::StorageWriterMissing);
+    };
+    let ix = storage_interface::write_chunk(
+        storage_program.key(),
+        storage_account_info.key(),
+        writer.key(),
+        storage_writer.protocol_config.key(),
+        bytes,
+    );
+    anchor_lang::solana_program::program::invoke_signed(
+        &ix,
+        &[
+            storage_account_info.clone(),
+            writer.to_account_info(),
+            storage_writer.protocol_config.to_account_info(),
+            storage_program.to_account_info(),
+        ],
+        &[&[storage_layout::WRITER_SEED, &[writer_bump]]],
+    )?;
+    Ok(())
+}
+
+#[cfg(not(feature = "storage-cpi"))]
+fn write_storage_chunk_cpi<'info>(
+    _storage_account_info: &AccountInfo<'info>,
+    _storage_program: &UncheckedAccount<'info>,
+    _storage_writer: &StorageWriter<'_, 'info>,
+    _bytes: &[u8],
+) -> Result<()> {
+    err!(VoiceChatError::StorageCpiDisabled)
+}
+
+/// A storage lane whose authority is one of this program's PDAs (a call's or a voicemail's),
+/// so only the record it was allocated for can ever point writes at it
+struct OwnedLane<'a, 'info> {
+    program: &'a UncheckedAccount<'info>,
+    lane: &'a AccountInfo<'info>,
+    authority: &'a AccountInfo<'info>,
+    authority_seeds: &'a [&'a [u8]],
+    system_program: &'a AccountInfo<'info>,
+    protocol_config: &'a Account<'info, ProtocolConfig>,
+}
+
+/// Allocate lane 0 of the lane authority through storage_interface::alloc and delegate its
+/// writes to the storage_writer PDA; `payer` funds the authority with exactly the lane's
+/// rent, which the backend then spends on the account
+fn alloc_owned_lane<'info>(owned_lane: &OwnedLane<'_, 'info>, payer: &AccountInfo<'info>) -> Result<()> {
+    system_program::transfer(
+        CpiContext::new(
+            owned_lane.system_program.clone(),
+            system_program::Transfer {
+                from: payer.clone(),
+                to: owned_lane.authority.clone(),
+            },
+        ),
+        Rent::get()?.minimum_balance(storage_layout::SPACE),
+    )?;
+    let ix = storage_interface::alloc(
+        owned_lane.program.key(),
+        owned_lane.lane.key(),
+        owned_lane.authority.key(),
+        owned_lane.protocol_config.key(),
+        0,
+    );
+    anchor_lang::solana_program::program::invoke_signed(
+        &ix,
+        &[
+            owned_lane.lane.clone(),
+            owned_lane.authority.clone(),
+            owned_lane.system_program.clone(),
+            owned_lane.protocol_config.to_account_info(),
+            owned_lane.program.to_account_info(),
+        ],
+        &[owned_lane.authority_seeds],
+    )?;
+    let ix = storage_interface::delegate(
+        owned_lane.program.key(),
+        owned_lane.lane.key(),
+        owned_lane.authority.key(),
+        owned_lane.protocol_config.key(),
+        true,
+    );
+    anchor_lang::solana_program::program::invoke_signed(
+        &ix,
+        &[
+            owned_lane.lane.clone(),
+            owned_lane.authority.clone(),
+            owned_lane.protocol_config.to_account_info(),
+            owned_lane.program.to_account_info(),
+        ],
+        &[owned_lane.authority_seeds],
+    )?;
+    Ok(())
+}
+
+/// Free the lane through storage_interface::free and pass its rent on to `recipient`
+fn free_owned_lane<'info>(owned_lane: &OwnedLane<'_, 'info>, recipient: &AccountInfo<'info>) -> Result<()> {
+    let ix = storage_interface::free(
+        owned_lane.program.key(),
+        owned_lane.lane.key(),
+        owned_lane.authority.key(),
+        owned_lane.protocol_config.key(),
+    );
+    anchor_lang::solana_program::program::invoke_signed(
+        &ix,
+        &[
+            owned_lane.lane.clone(),
+            owned_lane.authority.clone(),
+            owned_lane.protocol_config.to_account_info(),
+            owned_lane.program.to_account_info(),
+        ],
+        &[owned_lane.authority_seeds],
+    )?;
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            owned_lane.system_program.clone(),
+            system_program::Transfer {
+                from: owned_lane.authority.clone(),
+                to: recipient.clone(),
+            },
+            &[owned_lane.authority_seeds],
+        ),
+        owned_lane.authority.lamports(),
+    )?;
+    Ok(())
+}
+
+/// Append a storage write to the room's journal when journaling is on. Writes always start
+/// at the beginning of the storage PDA's data section, so the offset is 0 for now.
+fn journal_storage_write(
+    voice_room: &VoiceRoom,
+    write_journal: Option<&mut Account<WriteJournal>>,
+    storage_account_info: &AccountInfo,
+    bytes: &[u8],
+) -> Result<()> {
+    if !voice_room.journal_writes {
+        return Ok(());
+    }
+    let write_journal = write_journal.ok_or(VoiceChatError::WriteJournalMissing)?;
+    write_journal.append(JournalEntry {
+        storage_pda: storage_account_info.key(),
+        offset: 0,
+        length: bytes.len() as u32,
+        hash: anchor_lang::solana_program::keccak::hash(bytes).to_bytes(),
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
+
+/// Charge a storage write to the room's prepaid bandwidth. Unmetered rooms write freely;
+/// metered rooms fail the send once the allowance can't cover it.
+fn meter_storage_write(
+    voice_room: &VoiceRoom,
+    bandwidth_allowance: Option<&mut Account<BandwidthAllowance>>,
+    bytes: usize,
+) -> Result<()> {
+    if !voice_room.bandwidth_metered {
+        return Ok(());
+    }
+    let bandwidth_allowance = bandwidth_allowance.ok_or(VoiceChatError::BandwidthAllowanceMissing)?;
+    bandwidth_allowance.charge(bytes)
+}
+
+/// Move lamports charged for writes out of the allowance into the protocol treasury
+fn settle_bandwidth(
+    bandwidth_allowance: &mut Account<BandwidthAllowance>,
+    protocol_treasury: &mut Account<ProtocolTreasury>,
+) -> Result<u64> {
+    let settled = bandwidth_allowance.unsettled_lamports;
+    if settled > 0 {
+        **bandwidth_allowance.to_account_info().try_borrow_mut_lamports()? -= settled;
+        **protocol_treasury.to_account_info().try_borrow_mut_lamports()? += settled;
+        protocol_treasury.lamports_collected += settled;
+        bandwidth_allowance.unsettled_lamports = 0;
+    }
+    Ok(settled)
+}
+
+/// Re-lay a string-prefixed room (versions 0-4) into the fixed-header layout. Everything
+/// from created_at on kept its order, so it is copied across unchanged.
+fn rewrite_legacy_voice_room(old: &[u8], data: &mut [u8]) -> Result<()> {
+    let invalid = |_| error!(VoiceChatError::InvalidAccountVersion);
+    let mut body = &old[8..];
+    let (category, tags) = if old.len() == LEGACY_VOICE_ROOM_V4_SPACE {
+        (
+            u16::deserialize(&mut body).map_err(invalid)?,
+            <[u16; MAX_ROOM_TAGS]>::deserialize(&mut body).map_err(invalid)?,
+        )
+    } else {
+        (0, [0; MAX_ROOM_TAGS])
+    };
+    let room_id = String::deserialize(&mut body).map_err(invalid)?;
+    let host = Pubkey::deserialize(&mut body).map_err(invalid)?;
+    let participant_count = u8::deserialize(&mut body).map_err(invalid)?;
+    let is_active = bool::deserialize(&mut body).map_err(invalid)?;
+    require!(room_id.len() <= MAX_ROOM_ID_LENGTH, VoiceChatError::InvalidAccountVersion);
+    
+    let mut fixed = Vec::with_capacity(VoiceRoom::SPACE);
+    fixed.extend_from_slice(host.as_ref());
+    fixed.push(is_active as u8);
+    fixed.extend_from_slice(&category.to_le_bytes());
+    tags.iter().for_each(|tag| fixed.extend_from_slice(&tag.to_le_bytes()));
+    let mut room_id_bytes = [0u8; MAX_ROOM_ID_LENGTH];
+    room_id_bytes[..room_id.len()].copy_from_slice(room_id.as_bytes());
+    fixed.extend_from_slice(&room_id_bytes);
+    fixed.push(participant_count);
+    fixed.extend_from_slice(body);
+    
+    // The old tail ends in the unused room_id padding, which may not all fit
+    let len = fixed.len().min(data.len() - 8);
+    data[8..].fill(0);
+    data[8..8 + len].copy_from_slice(&fixed[..len]);
+    Ok(())
+}
+
+/// Grow a program-owned account to `new_len`, topping it up to rent exemption from `payer`.
+/// The new bytes are zeroed.
+fn grow_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program_info: &AccountInfo<'info>,
+    new_len: usize,
+) -> Result<()> {
+    if account.data_len() >= new_len {
+        return Ok(());
+    }
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(new_len);
+    let shortfall = rent_exempt_minimum.saturating_sub(account.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program_info.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    account.resize(new_len)?;
+    Ok(())
+}
+
+/// A block PDA (seeds "block" + blocker + blocked) only exists while the block is in place
+fn require_not_blocked(block_entry: &AccountInfo) -> Result<()> {
+    require!(block_entry.data_is_empty(), VoiceChatError::BlockedByTarget);
+    Ok(())
+}
+
+/// A ban PDA that exists keeps its user out until `ban_until` has passed
+fn require_not_banned(room_ban: &AccountInfo, now: i64) -> Result<()> {
+    if room_ban.data_is_empty() {
+        return Ok(());
+    }
+    let room_ban = Account::<RoomBan>::try_from(room_ban)?;
+    require!(now >= room_ban.ban_until, VoiceChatError::BannedFromRoom);
+    Ok(())
+}
+
+/// Transfer tokens with transfer_checked, forwarding Token-2022 transfer hook accounts.
+/// Returns the amount that actually arrived, which is less than `amount` for mints with a transfer fee.
+#[allow(clippy::too_many_arguments)]
+fn transfer_tokens_checked<'info>(
+    token_program: &AccountInfo<'info>,
+    from: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    to: &mut InterfaceAccount<'info, TokenAccount>,
+    authority: &AccountInfo<'info>,
+    hook_accounts: &[AccountInfo<'info>],
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<u64> {
+    let balance_before = to.amount;
+    spl_token_2022::onchain::invoke_transfer_checked(
+        token_program.key,
+        from.to_account_info(),
+        mint.to_account_info(),
+        to.to_account_info(),
+        authority.clone(),
+        hook_accounts,
+        amount,
+        mint.decimals,
+        signer_seeds,
+    )?;
+    to.reload()?;
+    Ok(to.amount - balance_before)
+}
+
+/// Guard for state-mutating instructions: nothing moves while the admin has paused the program
+fn require_not_paused(protocol_config: &ProtocolConfig) -> Result<()> {
+    require!(!protocol_config.paused, VoiceChatError::ProgramPaused);
+    Ok(())
+}
+
+/// Charge the protocol fee on a lamport payment into the protocol treasury, but never less than
+/// `min_fee` (capped at the payment). Returns the fee taken.
+fn collect_protocol_fee_lamports<'info>(
+    protocol_config: &ProtocolConfig,
+    protocol_treasury: Option<&mut Account<'info, ProtocolTreasury>>,
+    payer: &AccountInfo<'info>,
+    system_program_info: &AccountInfo<'info>,
+    amount: u64,
+    min_fee: u64,
+) -> Result<u64> {
+    let fee = protocol_config.fee_on(amount).max(min_fee).min(amount);
+    if fee == 0 {
+        return Ok(0);
+    }
+    let protocol_treasury = protocol_treasury.ok_or(VoiceChatError::ProtocolAccountsMissing)?;
+    system_program::transfer(
+        CpiContext::new(
+            system_program_info.clone(),
+            system_program::Transfer {
+                from: payer.clone(),
+                to: protocol_treasury.to_account_info(),
+            },
+        ),
+        fee,
+    )?;
+    protocol_treasury.lamports_collected += fee;
+    Ok(fee)
+}
+
+/// Charge the protocol fee on a token payment into a treasury-owned token account.
+/// Returns the fee taken from the payer.
+#[allow(clippy::too_many_arguments)]
+fn collect_protocol_fee_tokens<'info>(
+    protocol_config: &ProtocolConfig,
+    protocol_treasury: Option<&Account<'info, ProtocolTreasury>>,
+    protocol_treasury_vault: Option<&mut Box<InterfaceAccount<'info, TokenAccount>>>,
+    token_program: &AccountInfo<'info>,
+    from: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    authority: &AccountInfo<'info>,
+    hook_accounts: &[AccountInfo<'info>],
+    amount: u64,
+) -> Result<u64> {
+    let fee = protocol_config.fee_on(amount);
+    if fee == 0 {
+        return Ok(0);
+    }
+    let (Some(protocol_treasury), Some(protocol_treasury_vault)) = (protocol_treasury, protocol_treasury_vault) else {
+        return err!(VoiceChatError::ProtocolAccountsMissing);
+    };
+    require_keys_eq!(protocol_treasury_vault.owner, protocol_treasury.key(), VoiceChatError::InvalidTreasuryVault);
+    transfer_tokens_checked(
+        token_program,
+        from,
+        mint,
+        protocol_treasury_vault,
+        authority,
+        hook_accounts,
+        fee,
+        &[],
+    )?;
+    Ok(fee)
+}
+
+/// Split `total` by each share's basis points; rounding dust goes to the first share
+fn split_by_bps(total: u64, shares: &[SplitShare]) -> Vec<u64> {
+    let mut payouts: Vec<u64> = shares
+        .iter()
+        .map(|share| (total as u128 * share.bps as u128 / 10_000) as u64)
+        .collect();
+    let dust = total - payouts.iter().sum::<u64>();
+    if let Some(first) = payouts.first_mut() {
+        *first += dust;
+    }
+    payouts
+}
+
+/// Bytes a sender signs to authorize a sponsored send: room, storage PDA index, sequence
+/// epoch and number, capture timestamp, frame duration, the force flag and the keccak hash
+/// of the frame. Signing the epoch keeps frames signed before a sequence restart from being
+/// relayed after it.
+fn sponsored_send_message(
+    room: &Pubkey,
+    target_pda_index: u8,
+    sequence_epoch: u32,
+    sequence_number: u32,
+    capture_timestamp_ms: i64,
+    duration_ms: u16,
+    force: bool,
+    voice_data: &[u8],
+) -> Vec<u8> {
+    let mut message = Vec::with_capacity(32 + 1 + 4 + 4 + 8 + 2 + 1 + 32);
+    message.extend_from_slice(room.as_ref());
+    message.push(target_pda_index);
+    message.extend_from_slice(&sequence_epoch.to_le_bytes());
+    message.extend_from_slice(&sequence_number.to_le_bytes());
+    message.extend_from_slice(&capture_timestamp_ms.to_le_bytes());
+    message.extend_from_slice(&duration_ms.to_le_bytes());
+    message.push(force as u8);
+    message.extend_from_slice(&anchor_lang::solana_program::keccak::hash(voice_data).to_bytes());
+    message
+}
+
+/// Check that the previous instruction is an Ed25519 program verification of exactly one
+/// signature by `signer` over `message`, with all data inline in that instruction.
+fn verify_ed25519_signature(instructions_sysvar: &AccountInfo, signer: &Pubkey, message: &[u8]) -> Result<()> {
+    use anchor_lang::solana_program::sysvar::instructions::{load_current_index_checked, load_instruction_at_checked};
+    
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    require!(current_index > 0, VoiceChatError::InvalidSenderSignature);
+    let ed25519_ix = load_instruction_at_checked(current_index as usize - 1, instructions_sysvar)?;
+    require_keys_eq!(
+        ed25519_ix.program_id,
+        anchor_lang::solana_program::ed25519_program::ID,
+        VoiceChatError::InvalidSenderSignature
+    );
+    
+    // Header: signature count, padding, then one 14-byte offsets entry
+    let data = &ed25519_ix.data;
+    require!(data.len() >= 16 && data[0] == 1, VoiceChatError::InvalidSenderSignature);
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let public_key_offset = read_u16(6) as usize;
+    let message_offset = read_u16(10) as usize;
+    let message_size = read_u16(12) as usize;
+    require!(
+        read_u16(4) == u16::MAX && read_u16(8) == u16::MAX && read_u16(14) == u16::MAX,
+        VoiceChatError::InvalidSenderSignature
+    );
+    
+    let public_key = data.get(public_key_offset..public_key_offset + 32);
+    let signed_message = data.get(message_offset..message_offset + message_size);
+    require!(public_key == Some(signer.as_ref()), VoiceChatError::InvalidSenderSignature);
+    require!(signed_message == Some(message), VoiceChatError::InvalidSenderSignature);
+    Ok(())
+}
+
+/// The signer is the sender itself or holds an unexpired session key of theirs covering `scope`
+fn authorize_sender(
+    sender: &AccountInfo,
+    authority: &Signer,
+    session_key: Option<&Account<SessionKey>>,
+    scope: u8,
+) -> Result<()> {
+    if authority.key() == sender.key() {
+        return Ok(());
+    }
+    let session_key = session_key.ok_or(VoiceChatError::InvalidSessionKey)?;
+    require!(session_key.scope & scope == scope, VoiceChatError::InvalidSessionKey);
+    require!(Clock::get()?.unix_timestamp < session_key.expiry, VoiceChatError::SessionKeyExpired);
+    Ok(())
+}
+
+/// Gate every send path: the sender must not be muted, must meet the room's reputation
+/// threshold and, in rooms with a stake requirement, must have a locked, sufficient stake
+fn check_sender(
+    voice_room: &VoiceRoom,
+    sender_participant: &Participant,
+    sender_stake: Option<&Account<SenderStake>>,
+    sender_reputation: Option<&Account<UserReputation>>,
+) -> Result<()> {
+    require!(!sender_participant.muted, VoiceChatError::ParticipantMuted);
+    if voice_room.min_reputation_to_speak != 0 {
+        let sender_reputation = sender_reputation.ok_or(VoiceChatError::InsufficientReputation)?;
+        require!(
+            sender_reputation.score() >= voice_room.min_reputation_to_speak,
+            VoiceChatError::InsufficientReputation
+        );
+    }
+    if voice_room.sender_stake_lamports == 0 {
+        return Ok(());
+    }
+    let sender_stake = sender_stake.ok_or(VoiceChatError::SenderStakeRequired)?;
+    require!(sender_stake.left_at == 0, VoiceChatError::SenderStakeRequired);
+    require!(sender_stake.amount >= voice_room.sender_stake_lamports, VoiceChatError::InsufficientSenderStake);
+    Ok(())
+}
+
+/// Client capture times may only drift CAPTURE_TIMESTAMP_TOLERANCE_MS from the chain clock
+fn check_capture_timestamp(capture_timestamp_ms: i64) -> Result<()> {
+    let now_ms = Clock::get()?.unix_timestamp * 1000;
+    require!(
+        (capture_timestamp_ms - now_ms).abs() <= CAPTURE_TIMESTAMP_TOLERANCE_MS,
+        VoiceChatError::InvalidCaptureTimestamp
+    );
+    Ok(())
+}
+
+/// In rooms that restrict codecs every frame starts with a one-byte codec id, which must be
+/// one the room accepts
+fn check_codec(voice_room: &VoiceRoom, frame: &[u8]) -> Result<()> {
+    if voice_room.accepted_codecs == 0 {
+        return Ok(());
+    }
+    let codec = *frame.first().ok_or(VoiceChatError::CodecNotAccepted)?;
+    require!(
+        codec < 16 && voice_room.accepted_codecs & (1 << codec) != 0,
+        VoiceChatError::CodecNotAccepted
+    );
+    Ok(())
+}
+
+/// Hold a frame of `length` bytes lasting `duration_ms` to the room's max frame duration
+/// and to its target bitrate
+fn check_frame_quality(voice_room: &VoiceRoom, length: usize, duration_ms: u16) -> Result<()> {
+    if voice_room.max_frame_duration_ms != 0 {
+        require!(duration_ms <= voice_room.max_frame_duration_ms, VoiceChatError::FrameQualityExceeded);
+    }
+    check_bitrate(voice_room, length, duration_ms)
+}
+
+/// Allow `duration_ms * target_bitrate_kbps` bits of audio (1 kbps = 1 bit per ms)
+fn check_bitrate(voice_room: &VoiceRoom, length: usize, duration_ms: u16) -> Result<()> {
+    if voice_room.target_bitrate_kbps != 0 {
+        let max_bits = duration_ms as u64 * voice_room.target_bitrate_kbps as u64;
+        require!(length as u64 * 8 <= max_bits, VoiceChatError::FrameQualityExceeded);
+    }
+    Ok(())
+}
+
+/// A room's lanes are its host's storage PDAs; tie the passed account to `target_pda_index`
+/// so the lane bitmaps and stage checks that key on the index describe the account written
+fn check_room_lane(
+    voice_room: &VoiceRoom,
+    storage_pda: &AccountInfo,
+    target_pda_index: u8,
+    protocol_config: &ProtocolConfig,
+) -> Result<()> {
+    let (expected_lane, _) = Pubkey::find_program_address(
+        &[b"storage", voice_room.host.as_ref(), &[target_pda_index]],
+        &protocol_config.storage_backend(),
+    );
+    require_keys_eq!(storage_pda.key(), expected_lane, VoiceChatError::InvalidStoragePDA);
+    Ok(())
+}
+
+/// Keep the audience off the room's stage lane and enforce the sender's send interval:
+/// the stage's (usually relaxed) one for the host and speakers, the audience's otherwise.
+/// `target_pdas` must already be bound to the accounts being written (check_room_lane).
+fn check_stage_lane(
+    voice_room: &VoiceRoom,
+    sender_participant: &Participant,
+    recent_sends: &mut RecentSends,
+    target_pdas: &[u8],
+) -> Result<()> {
+    let on_stage = voice_room.is_on_stage(sender_participant);
+    if let Some(stage_lane) = voice_room.stage_lane {
+        require!(on_stage || !target_pdas.contains(&stage_lane), VoiceChatError::StageLaneReserved);
+    }
+    let interval = if on_stage {
+        voice_room.stage_send_interval_slots
+    } else {
+        voice_room.audience_send_interval_slots
+    };
+    let slot = Clock::get()?.slot;
+    require!(
+        recent_sends.last_sent_slot == 0 || slot >= recent_sends.last_sent_slot + interval as u64,
+        VoiceChatError::SendRateLimited
+    );
+    recent_sends.last_sent_slot = slot;
+    Ok(())
+}
+
+/// Lanes allocate_write_slot handed out are reserved for their allocator until released
+fn check_lane_allocations(voice_room: &VoiceRoom, sender_participant: &Participant, target_pdas: &[u8]) -> Result<()> {
+    require!(
+        target_pdas.iter().all(|lane| {
+            let allocation = voice_room.lane_allocations[*lane as usize];
+            allocation == 0 || allocation == sender_participant.slot + 1
+        }),
+        VoiceChatError::WriteSlotHeldByOther
+    );
+    Ok(())
+}
+
+/// Refuse to overwrite a lane whose last frame hasn't been consumed yet, unless `force` is set.
+/// Like check_stage_lane, this trusts `target_pdas` only once check_room_lane has bound them.
+fn check_lanes_consumed(voice_room: &VoiceRoom, target_pdas: &[u8], force: bool) -> Result<()> {
+    require!(
+        force || target_pdas.iter().all(|lane| voice_room.unread_lanes & (1 << lane) == 0),
+        VoiceChatError::UnreadFrameInLane
+    );
+    Ok(())
+}
+
+/// Pay the host for listening time between the stream's last settlement and `until`,
+/// capped by what is left of the listener's deposit. Returns the lamports moved.
+fn settle_payment_stream(
+    payment_stream: &mut Account<PaymentStream>,
+    host: &AccountInfo,
+    until: i64,
+) -> Result<u64> {
+    if until <= payment_stream.last_settled_at {
+        return Ok(0);
+    }
+    let elapsed_seconds = (until - payment_stream.last_settled_at) as u128;
+    let owed = (elapsed_seconds * payment_stream.rate_lamports_per_minute as u128 / 60) as u64;
+    let remaining = payment_stream.deposited - payment_stream.paid_out;
+    let payment = std::cmp::min(owed, remaining);
+    
+    let stream_info = payment_stream.to_account_info();
+    **stream_info.try_borrow_mut_lamports()? -= payment;
+    **host.try_borrow_mut_lamports()? += payment;
+    
+    payment_stream.paid_out += payment;
+    payment_stream.last_settled_at = until;
+    Ok(payment)
+}
+
+/// Move the crank reward out of an account that is about to be closed
+fn pay_crank_reward(target: &AccountInfo, cranker: &AccountInfo) -> Result<()> {
+    let reward = std::cmp::min(CRANK_REWARD_LAMPORTS, target.lamports());
+    **target.try_borrow_mut_lamports()? -= reward;
+    **cranker.try_borrow_mut_lamports()? += reward;
+    Ok(())
+}
+
+/// Retire a VoiceMessage (or batch or clip record) without freeing its address: the account is wiped down to a zeroed
+/// stub that stays program-owned, so `init` can never put a message with different content at
+/// the same seeds. Lamports above the stub's rent go to `recipient`.
+fn tombstone_voice_message(target: &AccountInfo, recipient: &AccountInfo) -> Result<()> {
+    target.try_borrow_mut_data()?.fill(0);
+    target.resize(VOICE_MESSAGE_TOMBSTONE_SPACE)?;
+    let refund = target.lamports().saturating_sub(Rent::get()?.minimum_balance(VOICE_MESSAGE_TOMBSTONE_SPACE));
+    **target.try_borrow_mut_lamports()? -= refund;
+    **recipient.try_borrow_mut_lamports()? += refund;
+    Ok(())
+}
+
+/// The fields archive_session commits to, read from a VoiceMessage, VoiceBatchMessage or
+/// VoiceClipMessage
+struct ArchivedMessage {
+    sender: Pubkey,
+    room_id: String,
+    sequence_epoch: u32,
+    sequence_number: u32, // First frame's for a batch
+    lanes: Vec<u8>, // Storage PDA indices the audio went to, in payload order
+    data_length: u32,
+    content_hash: [u8; 32],
+    timestamp: i64,
+}
+
+impl ArchivedMessage {
+    fn load<'info>(target: &'info AccountInfo<'info>) -> Result<Self> {
+        if let Ok(voice_message) = Account::<VoiceMessage>::try_from(target) {
+            return Ok(Self {
+                sender: voice_message.sender,
+                room_id: voice_message.room_id.clone(),
+                sequence_epoch: voice_message.sequence_epoch,
+                sequence_number: voice_message.sequence_number,
+                lanes: vec![voice_message.storage_pda_index],
+                data_length: voice_message.data_length,
+                content_hash: voice_message.content_hash,
+                timestamp: voice_message.timestamp,
+            });
+        }
+        if let Ok(batch_message) = Account::<VoiceBatchMessage>::try_from(target) {
+            return Ok(Self {
+                sender: batch_message.sender,
+                room_id: batch_message.room_id.clone(),
+                sequence_epoch: batch_message.sequence_epoch,
+                sequence_number: batch_message.sequence_number,
+                lanes: vec![batch_message.storage_pda_index],
+                data_length: batch_message.data_length,
+                content_hash: batch_message.content_hash,
+                timestamp: batch_message.timestamp,
+            });
+        }
+        let clip_message = Account::<VoiceClipMessage>::try_from(target)?;
+        Ok(Self {
+            sender: clip_message.sender,
+            room_id: clip_message.room_id.clone(),
+            sequence_epoch: clip_message.sequence_epoch,
+            sequence_number: clip_message.sequence_number,
+            lanes: clip_message.target_pdas.clone(),
+            data_length: clip_message.data_length,
+            content_hash: clip_message.content_hash,
+            timestamp: clip_message.timestamp,
+        })
+    }
+}
+
+/// Room, timestamp and manifest entries of a VoiceMessage, VoiceBatchMessage or VoiceClipMessage
+fn manifest_record<'info>(info: &'info AccountInfo<'info>) -> Result<(String, i64, Vec<ManifestEntry>)> {
+    if let Ok(voice_message) = Account::<VoiceMessage>::try_from(info) {
+        let entry = ManifestEntry {
+            storage_pda: voice_message.storage_pda,
+            offset: 0,
+            length: voice_message.data_length,
+            hash: voice_message.content_hash,
+        };
+        return Ok((voice_message.room_id.clone(), voice_message.timestamp, vec![entry]));
+    }
+    if let Ok(batch_message) = Account::<VoiceBatchMessage>::try_from(info) {
+        let mut offset = 0;
+        let entries = batch_message.frames.iter().zip(&batch_message.frame_hashes)
+            .map(|(frame, hash)| {
+                let entry = ManifestEntry {
+                    storage_pda: batch_message.storage_pda,
+                    offset,
+                    length: frame.length as u32,
+                    hash: *hash,
+                };
+                offset += frame.length as u32;
+                entry
+            })
+            .collect();
+        return Ok((batch_message.room_id.clone(), batch_message.timestamp, entries));
+    }
+    let clip_message = Account::<VoiceClipMessage>::try_from(info)?;
+    let mut remaining = clip_message.data_length;
+    let entries = clip_message.storage_pdas.iter().zip(&clip_message.chunk_hashes)
+        .map(|(storage_pda, hash)| {
+            let length = remaining.min(STORAGE_CHUNK_SIZE as u32);
+            remaining -= length;
+            ManifestEntry {
+                storage_pda: *storage_pda,
+                offset: 0,
+                length,
+                hash: *hash,
+            }
+        })
+        .collect();
+    Ok((clip_message.room_id.clone(), clip_message.timestamp, entries))
+}
+
+/// Leaf committing to an archived message: who sent it, its place in their sequence,
+/// where its audio was stored, what the audio hashed to and when it landed
+fn archive_leaf(message: &ArchivedMessage) -> [u8; 32] {
+    anchor_lang::solana_program::keccak::hashv(&[
+        message.sender.as_ref(),
+        &message.sequence_epoch.to_le_bytes(),
+        &message.sequence_number.to_le_bytes(),
+        &message.lanes,
+        &message.data_length.to_le_bytes(),
+        &message.content_hash,
+        &message.timestamp.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Create and initialize a PDA Token-2022 mint (0 decimals) that can't be transferred and whose
+/// own address is its mint authority and permanent delegate
+fn create_soulbound_mint<'info>(
+    mint: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program_info: &AccountInfo<'info>,
+    token_program_info: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    use spl_token_2022::extension::ExtensionType;
+    
+    let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[
+        ExtensionType::NonTransferable,
+        ExtensionType::PermanentDelegate,
+    ])?;
+    system_program::create_account(
+        CpiContext::new_with_signer(
+            system_program_info.clone(),
+            system_program::CreateAccount {
+                from: payer.clone(),
+                to: mint.clone(),
+            },
+            signer_seeds,
+        ),
+        Rent::get()?.minimum_balance(space),
+        space as u64,
+        token_program_info.key,
+    )?;
+    
+    // Extensions must be initialized before the mint itself
+    let token_program_id = token_program_info.key;
+    for ix in [
+        spl_token_2022::instruction::initialize_non_transferable_mint(token_program_id, mint.key)?,
+        spl_token_2022::instruction::initialize_permanent_delegate(token_program_id, mint.key, mint.key)?,
+        spl_token_2022::instruction::initialize_mint2(token_program_id, mint.key, mint.key, None, 0)?,
+    ] {
+        anchor_lang::solana_program::program::invoke(&ix, &[mint.clone()])?;
+    }
+    Ok(())
+}
+
+/// Lamports a room's entry fee costs right now, plus the minimum protocol fee to charge on it.
+/// USD-priced rooms are converted at the configured Pyth SOL/USD price.
+fn entry_fee_lamports(
+    voice_room: &VoiceRoom,
+    price_feed: Option<&Account<PriceFeedConfig>>,
+    sol_usd_price: Option<&UncheckedAccount>,
+) -> Result<(u64, u64)> {
+    if voice_room.entry_fee_usd_cents == 0 {
+        return Ok((voice_room.entry_fee_lamports, 0));
+    }
+    let (Some(price_feed), Some(sol_usd_price)) = (price_feed, sol_usd_price) else {
+        return err!(VoiceChatError::PriceFeedMissing);
+    };
+    let price = pyth::read_price(&sol_usd_price.to_account_info(), &price_feed.feed_id)?;
+    let now = Clock::get()?.unix_timestamp;
+    require!(price.price > 0, VoiceChatError::UnreliablePrice);
+    require!(
+        now - price.publish_time <= price_feed.max_age_seconds as i64,
+        VoiceChatError::UnreliablePrice
+    );
+    require!(
+        price.conf as u128 * 10_000 <= price.price as u128 * MAX_PRICE_CONFIDENCE_BPS as u128,
+        VoiceChatError::UnreliablePrice
+    );
+    
+    Ok((
+        usd_cents_to_lamports(voice_room.entry_fee_usd_cents, price.price as u64, price.exponent)?,
+        usd_cents_to_lamports(price_feed.min_protocol_fee_usd_cents, price.price as u64, price.exponent)?,
+    ))
+}
+
+/// Convert USD cents to lamports at `price * 10^exponent` USD per SOL, rounding up
+fn usd_cents_to_lamports(cents: u64, price: u64, exponent: i32) -> Result<u64> {
+    // lamports = cents / 100 * 10^9 / (price * 10^exponent) = cents * 10^(7 - exponent) / price
+    let scale = 7 - exponent;
+    let (numerator, denominator) = if scale >= 0 {
+        let factor = 10u128.checked_pow(scale as u32).ok_or(VoiceChatError::InvalidPriceFeed)?;
+        (cents as u128 * factor, price as u128)
+    } else {
+        let factor = 10u128.checked_pow(scale.unsigned_abs()).ok_or(VoiceChatError::InvalidPriceFeed)?;
+        (cents as u128, price as u128 * factor)
+    };
+    u64::try_from(numerator.div_ceil(denominator)).map_err(|_| error!(VoiceChatError::InvalidPriceFeed))
+}
+
+/// Minimal CPI bindings for spl-account-compression and spl-noop
+mod account_compression {
+    use anchor_lang::prelude::*;
+    use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+    use anchor_lang::solana_program::program::{invoke, invoke_signed};
+
+    pub const PROGRAM_ID: Pubkey = anchor_lang::solana_program::pubkey!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+    pub const NOOP_PROGRAM_ID: Pubkey = anchor_lang::solana_program::pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+
+    // Anchor sighashes: sha256("global:<instruction>")[..8]
+    const INIT_EMPTY_MERKLE_TREE_DISCRIMINATOR: [u8; 8] = [191, 11, 119, 7, 180, 107, 220, 110];
+    const APPEND_DISCRIMINATOR: [u8; 8] = [149, 120, 18, 222, 236, 225, 88, 203];
+    const VERIFY_LEAF_DISCRIMINATOR: [u8; 8] = [124, 220, 22, 223, 104, 10, 250, 224];
+
+    pub fn init_empty_merkle_tree<'info>(
+        compression_program: &AccountInfo<'info>,
+        merkle_tree: &AccountInfo<'info>,
+        authority: &AccountInfo<'info>,
+        noop_program: &AccountInfo<'info>,
+        max_depth: u32,
+        max_buffer_size: u32,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let mut data = INIT_EMPTY_MERKLE_TREE_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&max_depth.to_le_bytes());
+        data.extend_from_slice(&max_buffer_size.to_le_bytes());
+        
+        let ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(merkle_tree.key(), false),
+                AccountMeta::new_readonly(authority.key(), true),
+                AccountMeta::new_readonly(noop_program.key(), false),
+            ],
+            data,
+        };
+        invoke_signed(
+            &ix,
+            &[merkle_tree.clone(), authority.clone(), noop_program.clone(), compression_program.clone()],
+            signer_seeds,
+        )?;
+        Ok(())
+    }
+
+    pub fn append<'info>(
+        compression_program: &AccountInfo<'info>,
+        merkle_tree: &AccountInfo<'info>,
+        authority: &AccountInfo<'info>,
+        noop_program: &AccountInfo<'info>,
+        leaf: [u8; 32],
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let mut data = APPEND_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&leaf);
+        
+        let ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(merkle_tree.key(), false),
+                AccountMeta::new_readonly(authority.key(), true),
+                AccountMeta::new_readonly(noop_program.key(), false),
+            ],
+            data,
+        };
+        invoke_signed(
+            &ix,
+            &[merkle_tree.clone(), authority.clone(), noop_program.clone(), compression_program.clone()],
+            signer_seeds,
+        )?;
+        Ok(())
+    }
+
+    /// Check that `leaf` is at `index` under `root`; the proof path is passed as the node accounts
+    pub fn verify_leaf<'info>(
+        compression_program: &AccountInfo<'info>,
+        merkle_tree: &AccountInfo<'info>,
+        root: [u8; 32],
+        leaf: [u8; 32],
+        index: u32,
+        proof: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        let mut data = VERIFY_LEAF_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&root);
+        data.extend_from_slice(&leaf);
+        data.extend_from_slice(&index.to_le_bytes());
+        
+        let mut accounts = vec![AccountMeta::new_readonly(merkle_tree.key(), false)];
+        accounts.extend(proof.iter().map(|node| AccountMeta::new_readonly(node.key(), false)));
+        let mut account_infos = vec![merkle_tree.clone()];
+        account_infos.extend(proof.iter().cloned());
+        account_infos.push(compression_program.clone());
+        
+        let ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts,
+            data,
+        };
+        invoke(&ix, &account_infos)?;
+        Ok(())
+    }
+
+    /// Log arbitrary bytes through the noop program so they land in the transaction's inner instructions
+    pub fn wrap_application_data<'info>(noop_program: &AccountInfo<'info>, data: Vec<u8>) -> Result<()> {
+        let ix = Instruction {
+            program_id: NOOP_PROGRAM_ID,
+            accounts: vec![],
+            data,
+        };
+        invoke(&ix, &[noop_program.clone()])?;
+        Ok(())
+    }
+}
+
+/// Minimal reader for Pyth receiver `PriceUpdateV2` accounts
+mod pyth {
+    use anchor_lang::prelude::*;
+
+    pub const RECEIVER_PROGRAM_ID: Pubkey = anchor_lang::solana_program::pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+
+    // Anchor account discriminator: sha256("account:PriceUpdateV2")[..8]
+    const PRICE_UPDATE_V2_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+    // discriminator + write_authority + verification_level tag; only fully verified updates are accepted
+    const VERIFICATION_LEVEL_OFFSET: usize = 8 + 32;
+    const VERIFICATION_FULL: u8 = 1;
+    const PRICE_MESSAGE_OFFSET: usize = VERIFICATION_LEVEL_OFFSET + 1;
+
+    pub struct Price {
+        pub price: i64,
+        pub conf: u64,
+        pub exponent: i32,
+        pub publish_time: i64,
+    }
+
+    /// Read the price from a fully verified update for `feed_id`
+    pub fn read_price(price_update: &AccountInfo, feed_id: &[u8; 32]) -> Result<Price> {
+        require_keys_eq!(*price_update.owner, RECEIVER_PROGRAM_ID, crate::VoiceChatError::InvalidPriceFeed);
+        let data = price_update.try_borrow_data()?;
+        require!(data.len() >= PRICE_MESSAGE_OFFSET + 32 + 8 + 8 + 4 + 8, crate::VoiceChatError::InvalidPriceFeed);
+        require!(data[..8] == PRICE_UPDATE_V2_DISCRIMINATOR, crate::VoiceChatError::InvalidPriceFeed);
+        require!(data[VERIFICATION_LEVEL_OFFSET] == VERIFICATION_FULL, crate::VoiceChatError::InvalidPriceFeed);
+        
+        // PriceFeedMessage: feed_id, price, conf, exponent, publish_time, ...
+        let message = &data[PRICE_MESSAGE_OFFSET..];
+        require!(&message[..32] == feed_id, crate::VoiceChatError::InvalidPriceFeed);
+        Ok(Price {
+            price: i64::from_le_bytes(message[32..40].try_into().unwrap()),
+            conf: u64::from_le_bytes(message[40..48].try_into().unwrap()),
+            exponent: i32::from_le_bytes(message[48..52].try_into().unwrap()),
+            publish_time: i64::from_le_bytes(message[52..60].try_into().unwrap()),
+        })
+    }
+}
+
+/// Minimal reader for Solana Name Service name records
+mod sns {
+    use anchor_lang::prelude::*;
+
+    pub const NAME_SERVICE_PROGRAM_ID: Pubkey = anchor_lang::solana_program::pubkey!("namesLPneVptA9Z5rqUDD9tMTWEJwofgaYwp8cawRkX");
+    pub const SOL_TLD: Pubkey = anchor_lang::solana_program::pubkey!("58PwtjSDuFHuUkYjH9BYnnQKHfwo9reZhC2zMJv9JPkx");
+
+    // NameRecordHeader: parent_name, owner, class
+    const HEADER_LEN: usize = 32 + 32 + 32;
+
+    /// Check that `name_record` is a .sol domain currently owned by `owner`
+    pub fn verify_sol_domain_owner(name_record: &AccountInfo, owner: &Pubkey) -> Result<()> {
+        require_keys_eq!(*name_record.owner, NAME_SERVICE_PROGRAM_ID, crate::VoiceChatError::InvalidDomainRecord);
+        let data = name_record.try_borrow_data()?;
+        require!(data.len() >= HEADER_LEN, crate::VoiceChatError::InvalidDomainRecord);
+        
+        let parent_name = Pubkey::try_from(&data[..32]).unwrap();
+        let record_owner = Pubkey::try_from(&data[32..64]).unwrap();
+        require_keys_eq!(parent_name, SOL_TLD, crate::VoiceChatError::InvalidDomainRecord);
+        require_keys_eq!(record_owner, *owner, crate::VoiceChatError::InvalidDomainRecord);
+        Ok(())
+    }
+}
+
+/// Minimal CPI bindings for mpl-bubblegum compressed NFTs
+mod bubblegum {
+    use anchor_lang::prelude::*;
+    use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+    use anchor_lang::solana_program::keccak;
+    use anchor_lang::solana_program::program::invoke_signed;
+
+    pub const PROGRAM_ID: Pubkey = anchor_lang::solana_program::pubkey!("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+
+    // Anchor sighashes: sha256("global:<instruction>")[..8]
+    const CREATE_TREE_DISCRIMINATOR: [u8; 8] = [165, 83, 136, 142, 89, 202, 47, 220];
+    const MINT_V1_DISCRIMINATOR: [u8; 8] = [145, 98, 192, 118, 184, 147, 118, 104];
+    const LEAF_SCHEMA_V1: u8 = 1;
+    const TICKET_SYMBOL: &str = "TICKET";
+
+    /// Create a private tree with `tree_creator` as its creator and mint delegate
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_tree<'info>(
+        bubblegum_program: &AccountInfo<'info>,
+        tree_config: &AccountInfo<'info>,
+        merkle_tree: &AccountInfo<'info>,
+        payer: &AccountInfo<'info>,
+        tree_creator: &AccountInfo<'info>,
+        noop_program: &AccountInfo<'info>,
+        compression_program: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+        max_depth: u32,
+        max_buffer_size: u32,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let mut data = CREATE_TREE_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&max_depth.to_le_bytes());
+        data.extend_from_slice(&max_buffer_size.to_le_bytes());
+        data.extend_from_slice(&[1, 0]); // public: Some(false)
+        
+        let ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(tree_config.key(), false),
+                AccountMeta::new(merkle_tree.key(), false),
+                AccountMeta::new(payer.key(), true),
+                AccountMeta::new_readonly(tree_creator.key(), true),
+                AccountMeta::new_readonly(noop_program.key(), false),
+                AccountMeta::new_readonly(compression_program.key(), false),
+                AccountMeta::new_readonly(system_program.key(), false),
+            ],
+            data,
+        };
+        invoke_signed(
+            &ix,
+            &[
+                tree_config.clone(), merkle_tree.clone(), payer.clone(), tree_creator.clone(),
+                noop_program.clone(), compression_program.clone(), system_program.clone(), bubblegum_program.clone(),
+            ],
+            signer_seeds,
+        )?;
+        Ok(())
+    }
+
+    /// Mint a cNFT to `leaf_owner`, who also pays; `tree_delegate` signs through `signer_seeds`
+    #[allow(clippy::too_many_arguments)]
+    pub fn mint_v1<'info>(
+        bubblegum_program: &AccountInfo<'info>,
+        tree_config: &AccountInfo<'info>,
+        leaf_owner: &AccountInfo<'info>,
+        merkle_tree: &AccountInfo<'info>,
+        tree_delegate: &AccountInfo<'info>,
+        noop_program: &AccountInfo<'info>,
+        compression_program: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+        metadata: Vec<u8>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let mut data = MINT_V1_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&metadata);
+        
+        let ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(tree_config.key(), false),
+                AccountMeta::new_readonly(leaf_owner.key(), false),
+                AccountMeta::new_readonly(leaf_owner.key(), false), // leaf_delegate
+                AccountMeta::new(merkle_tree.key(), false),
+                AccountMeta::new(leaf_owner.key(), true), // payer
+                AccountMeta::new_readonly(tree_delegate.key(), true),
+                AccountMeta::new_readonly(noop_program.key(), false),
+                AccountMeta::new_readonly(compression_program.key(), false),
+                AccountMeta::new_readonly(system_program.key(), false),
+            ],
+            data,
+        };
+        invoke_signed(
+            &ix,
+            &[
+                tree_config.clone(), leaf_owner.clone(), merkle_tree.clone(), tree_delegate.clone(),
+                noop_program.clone(), compression_program.clone(), system_program.clone(), bubblegum_program.clone(),
+            ],
+            signer_seeds,
+        )?;
+        Ok(())
+    }
+
+    /// Borsh-encoded MetadataArgs for a ticket: non-fungible, immutable, no creators or royalties
+    pub fn ticket_metadata(name: &str, uri: &str) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        (
+            name.to_string(),
+            TICKET_SYMBOL.to_string(),
+            uri.to_string(),
+            0u16, // seller_fee_basis_points
+            true, // primary_sale_happened
+            false, // is_mutable
+            None::<u8>, // edition_nonce
+            Some(0u8), // token_standard: NonFungible
+            None::<u8>, // collection
+            None::<u8>, // uses
+            0u8, // token_program_version: Original
+            Vec::<u8>::new(), // creators
+        )
+            .serialize(&mut data)
+            .map_err(|_| anchor_lang::error::ErrorCode::AccountDidNotSerialize)?;
+        Ok(data)
+    }
+
+    /// Bubblegum's V1 leaf hash for a ticket owned by `owner`
+    pub fn leaf_hash(merkle_tree: &Pubkey, owner: &Pubkey, proof: &crate::CompressedTicketProof) -> [u8; 32] {
+        let nonce = proof.nonce.to_le_bytes();
+        let (asset_id, _) = Pubkey::find_program_address(&[b"asset", merkle_tree.as_ref(), &nonce], &PROGRAM_ID);
+        keccak::hashv(&[
+            &[LEAF_SCHEMA_V1],
+            asset_id.as_ref(),
+            owner.as_ref(),
+            proof.delegate.as_ref(),
+            &nonce,
+            &proof.data_hash,
+            &proof.creator_hash,
+        ])
+        .to_bytes()
+    }
 }
 
-#[error_code]
-pub enum VoiceChatError {
-    #[msg("Voice room is not active")]
-    RoomNotActive,
-    #[msg("Voice room is full")]
-    RoomFull,
-    #[msg("Voice data exceeds maximum size")]
-    VoiceDataTooLarge,
-    #[msg("Invalid storage PDA index")]
-    InvalidStoragePDA,
-    #[msg("Room ID too long")]
-    RoomIdTooLong,
-    #[msg("Too many target PDAs for broadcast")]
-    TooManyTargetPDAs,
+/// Minimal reader for Wormhole core bridge posted VAA accounts
+mod wormhole {
+    use anchor_lang::prelude::*;
+
+    pub const CORE_BRIDGE_PROGRAM_ID: Pubkey = anchor_lang::solana_program::pubkey!("worm2ZoG2kUd4vFXhvjh93UUH596ayRfgQ2MgjNMTth");
+
+    // "vaa" magic, then version, consistency_level, vaa_time, signature_set, submission_time, nonce, sequence
+    const EMITTER_CHAIN_OFFSET: usize = 3 + 1 + 1 + 4 + 32 + 4 + 4 + 8;
+    const PAYLOAD_OFFSET: usize = EMITTER_CHAIN_OFFSET + 2 + 32;
+
+    pub struct PostedMessage {
+        pub emitter_chain: u16,
+        pub emitter_address: [u8; 32],
+        pub payload: Vec<u8>,
+    }
+
+    /// Read a VAA the core bridge has verified and posted
+    pub fn read_posted_vaa(posted_vaa: &AccountInfo) -> Result<PostedMessage> {
+        require_keys_eq!(*posted_vaa.owner, CORE_BRIDGE_PROGRAM_ID, crate::VoiceChatError::InvalidAttestation);
+        let data = posted_vaa.try_borrow_data()?;
+        require!(data.len() >= PAYLOAD_OFFSET + 4 && &data[..3] == b"vaa", crate::VoiceChatError::InvalidAttestation);
+        
+        let emitter_chain = u16::from_le_bytes(data[EMITTER_CHAIN_OFFSET..EMITTER_CHAIN_OFFSET + 2].try_into().unwrap());
+        let emitter_address = data[EMITTER_CHAIN_OFFSET + 2..PAYLOAD_OFFSET].try_into().unwrap();
+        let payload_len = u32::from_le_bytes(data[PAYLOAD_OFFSET..PAYLOAD_OFFSET + 4].try_into().unwrap()) as usize;
+        let payload = data.get(PAYLOAD_OFFSET + 4..PAYLOAD_OFFSET + 4 + payload_len)
+            .ok_or(crate::VoiceChatError::InvalidAttestation)?
+            .to_vec();
+        Ok(PostedMessage { emitter_chain, emitter_address, payload })
+    }
 }
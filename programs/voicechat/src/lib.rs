@@ -1,7 +1,21 @@
 use anchor_lang::prelude::*;
+pub use voice_chat_shared::VoicechatError as VoiceChatError;
 
 declare_id!("HPxbCqRWpSxCEE2L6Vy1S1oMTc3D9aknrBGwZ9WTAvSK");
 
+// Program-wide pause switch owned by voice_chat_manager
+const VOICE_CHAT_MANAGER_PROGRAM_ID: Pubkey = anchor_lang::solana_program::pubkey!("AGpoLxtMeNW17NZL7nWLFLmmhKPW5nbxfdY8BVaMxDNy");
+const PROTOCOL_CONFIG_PAUSED_OFFSET: usize = 8 + 32 + 2; // discriminator + admin + fee_bps
+
+const PDA_ACCOUNT_VERSION: u8 = 2;
+const PDA_VERSION_OFFSET: usize = 8 + 2 + 32 + 8 + 4; // discriminator + index + authority + created_at + data_length
+const PDA_BUMP_OFFSET: usize = PDA_VERSION_OFFSET + 1; // Added in version 2
+const PDA_DATA_START: usize = PDA_BUMP_OFFSET + 1; // Raw data follows the struct fields
+
+// PDA index limits: authorities without a PDARegistry keep the original 10 chunks
+const DEFAULT_MAX_PDAS: u16 = 10;
+const MAX_PDAS_LIMIT: u16 = 1024;
+
 #[program]
 pub mod voicechat {
     use super::*;
@@ -16,7 +30,8 @@ pub mod voicechat {
         pda_index: u16,
         data: Vec<u8>,
     ) -> Result<()> {
-        require!(pda_index < 10, VoiceChatError::InvalidPDAIndex);
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(pda_index < max_pdas(&ctx.accounts.pda_registry), VoiceChatError::InvalidPDAIndex);
         require!(data.len() <= 10240, VoiceChatError::DataTooLarge);
 
         let pda_account = &mut ctx.accounts.pda_account;
@@ -24,11 +39,13 @@ pub mod voicechat {
         pda_account.authority = ctx.accounts.authority.key();
         pda_account.created_at = Clock::get()?.unix_timestamp;
         pda_account.data_length = data.len() as u32;
+        pda_account.version = PDA_ACCOUNT_VERSION;
+        pda_account.bump = ctx.bumps.pda_account;
         
         // Write data to the account's data section
         let account_info = pda_account.to_account_info();
         let mut account_data = account_info.try_borrow_mut_data()?;
-        let data_start = 8 + 2 + 32 + 8 + 4; // Skip the struct fields (index is now u16 = 2 bytes)
+        let data_start = PDA_DATA_START;
         
         if !data.is_empty() {
             let copy_len = std::cmp::min(data.len(), 10240);
@@ -40,9 +57,10 @@ pub mod voicechat {
     }
 
     pub fn create_all_pdas(ctx: Context<CreateAllPDAs>, pda_index: u16) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
         msg!("Creating PDA account {} for authority: {}", pda_index, ctx.accounts.authority.key());
         
-        require!(pda_index < 10, VoiceChatError::InvalidPDAIndex);
+        require!(pda_index < max_pdas(&ctx.accounts.pda_registry), VoiceChatError::InvalidPDAIndex);
         
         // Initialize the PDA with 30KB of space (allocated upfront)
         let pda_account = &mut ctx.accounts.pda_account;
@@ -50,11 +68,13 @@ pub mod voicechat {
         pda_account.authority = ctx.accounts.authority.key();
         pda_account.created_at = Clock::get()?.unix_timestamp;
         pda_account.data_length = 0; // No initial data
+        pda_account.version = PDA_ACCOUNT_VERSION;
+        pda_account.bump = ctx.bumps.pda_account;
         
         // Initialize the data section with zeros
         let account_info = pda_account.to_account_info();
         let mut account_data = account_info.try_borrow_mut_data()?;
-        let data_start = 8 + 2 + 32 + 8 + 4; // Skip the struct fields (index is now u16 = 2 bytes)
+        let data_start = PDA_DATA_START;
         
         // Fill with zeros (this is the default but being explicit)
         for i in data_start..data_start + 1048576 {
@@ -65,36 +85,73 @@ pub mod voicechat {
         Ok(())
     }
 
+    /// Create the authority's PDA registry, which raises (or lowers) how many PDA accounts
+    /// the authority may provision from the default of 10
+    pub fn initialize_pda_registry(ctx: Context<InitializePDARegistry>, max_pdas: u16) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(max_pdas > 0 && max_pdas <= MAX_PDAS_LIMIT, VoiceChatError::InvalidPDALimit);
+        
+        let pda_registry = &mut ctx.accounts.pda_registry;
+        pda_registry.authority = ctx.accounts.authority.key();
+        pda_registry.max_pdas = max_pdas;
+        pda_registry.bump = ctx.bumps.pda_registry;
+        
+        msg!("PDA registry for {} allows {} PDA accounts", pda_registry.authority, max_pdas);
+        Ok(())
+    }
+
+    /// Change the authority's PDA limit. Accounts already created above a lowered limit are
+    /// left as they are; only new creations are checked.
+    pub fn set_max_pdas(ctx: Context<SetMaxPDAs>, max_pdas: u16) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        require!(max_pdas > 0 && max_pdas <= MAX_PDAS_LIMIT, VoiceChatError::InvalidPDALimit);
+        
+        ctx.accounts.pda_registry.max_pdas = max_pdas;
+        
+        msg!("PDA registry for {} now allows {} PDA accounts", ctx.accounts.authority.key(), max_pdas);
+        Ok(())
+    }
+
+    /// Write `new_data` at `offset` into the data section. Bytes before `offset` are always
+    /// kept; with `preserve_tail` the bytes after the written region are kept too, otherwise
+    /// they are cleared and the data ends with this write.
     pub fn update_pda_data(
         ctx: Context<UpdatePDAData>,
         new_data: Vec<u8>,
+        offset: u32,
+        preserve_tail: bool,
     ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
         let account_info = ctx.accounts.pda_account.to_account_info();
         let current_account_size = account_info.data_len();
-        let data_start = 8 + 2 + 32 + 8 + 4; // Skip the struct fields (index is now u16 = 2 bytes)
+        let data_start = PDA_DATA_START;
         let available_data_space = current_account_size.saturating_sub(data_start);
+        let offset = offset as usize;
+        let write_end = offset.checked_add(new_data.len()).ok_or(VoiceChatError::DataTooLarge)?;
         
-        require!(new_data.len() <= available_data_space, VoiceChatError::DataTooLarge);
+        require!(write_end <= available_data_space, VoiceChatError::DataTooLarge);
 
         let pda_account = &mut ctx.accounts.pda_account;
-        pda_account.data_length = new_data.len() as u32;
+        let data_end = if preserve_tail {
+            write_end.max(pda_account.data_length as usize)
+        } else {
+            write_end
+        };
+        pda_account.data_length = data_end as u32;
         
         // Update the data in the account's data section
         let mut account_data = account_info.try_borrow_mut_data()?;
         
-        // Update the data
-        let copy_len = std::cmp::min(new_data.len(), available_data_space);
-        account_data[data_start..data_start + copy_len].copy_from_slice(&new_data[..copy_len]);
+        // Patch only the requested region
+        account_data[data_start + offset..data_start + write_end].copy_from_slice(&new_data);
         
-        // Clear remaining bytes if new data is smaller
-        if copy_len < available_data_space {
-            for i in data_start + copy_len..data_start + available_data_space {
-                account_data[i] = 0;
-            }
+        // Clear everything after the write unless the caller asked to keep it
+        if !preserve_tail {
+            account_data[data_start + write_end..data_start + available_data_space].fill(0);
         }
 
-        msg!("Updated PDA account {} with {} bytes of data (available space: {} bytes)", 
-             pda_account.index, new_data.len(), available_data_space);
+        msg!("Updated PDA account {} with {} bytes of data at offset {} (data length: {}, available space: {} bytes)", 
+             pda_account.index, new_data.len(), offset, data_end, available_data_space);
         Ok(())
     }
 
@@ -104,6 +161,7 @@ pub mod voicechat {
         ctx: Context<ReallocatePDAAccount>,
         target_size: usize,
     ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
         let pda_account = ctx.accounts.pda_account.to_account_info();
         let current_size = pda_account.data_len();
         
@@ -111,7 +169,7 @@ pub mod voicechat {
         let size_increase = std::cmp::min(target_size.saturating_sub(current_size), 10240);
         
         require!(size_increase > 0, VoiceChatError::NoReallocNeeded);
-        require!(target_size <= 1048576 + 8 + 2 + 32 + 8 + 4, VoiceChatError::TargetSizeTooLarge); // Include struct overhead
+        require!(target_size <= 1048576 + PDA_DATA_START, VoiceChatError::TargetSizeTooLarge); // Include struct overhead
         
         let new_size = current_size + size_increase;
         
@@ -152,20 +210,166 @@ pub mod voicechat {
         Ok(())
     }
 
-    /// Helper function to calculate how many reallocation steps are needed
+    /// Same growth step as reallocate_pda_account, but paid from lamports already held by the
+    /// PDA above its current rent-exempt minimum. The authority tops the account up in advance
+    /// with a plain transfer, and then anyone (e.g. a crank) can grow it without the
+    /// authority signing.
+    pub fn reallocate_pda_account_prepaid(
+        ctx: Context<ReallocatePDAAccountPrepaid>,
+        target_size: usize,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let pda_account = ctx.accounts.pda_account.to_account_info();
+        let current_size = pda_account.data_len();
+        
+        let size_increase = std::cmp::min(target_size.saturating_sub(current_size), 10240);
+        
+        require!(size_increase > 0, VoiceChatError::NoReallocNeeded);
+        require!(target_size <= 1048576 + PDA_DATA_START, VoiceChatError::TargetSizeTooLarge); // Include struct overhead
+        
+        let new_size = current_size + size_increase;
+        let new_rent_exempt_balance = Rent::get()?.minimum_balance(new_size);
+        require!(
+            pda_account.lamports() >= new_rent_exempt_balance,
+            VoiceChatError::InsufficientPrepaidBalance
+        );
+        
+        pda_account.resize(new_size)?;
+        
+        msg!("Reallocated PDA account from {} to {} bytes from its prepaid balance ({} lamports left over)", 
+             current_size, new_size, pda_account.lamports() - new_rent_exempt_balance);
+        Ok(())
+    }
+
+    /// Helper function to calculate how many reallocation steps are needed. The plan is
+    /// logged and returned as a Borsh-encoded ReallocationPlan in return data, so clients
+    /// can budget lamports and queue one reallocate_pda_account per step.
     pub fn get_reallocation_steps_needed(
         ctx: Context<GetReallocationInfo>,
         target_size: usize,
     ) -> Result<()> {
-        let current_size = ctx.accounts.pda_account.to_account_info().data_len();
+        let pda_account = ctx.accounts.pda_account.to_account_info();
+        let current_size = pda_account.data_len();
         let remaining_bytes = target_size.saturating_sub(current_size);
         let steps_needed = (remaining_bytes + 10240 - 1) / 10240; // Ceiling division
+        let rent_needed = Rent::get()?.minimum_balance(current_size.max(target_size))
+            .saturating_sub(pda_account.lamports());
+        
+        let plan = ReallocationPlan {
+            current_size: current_size as u64,
+            target_size: target_size as u64,
+            steps: steps_needed as u32,
+            step_size: std::cmp::min(remaining_bytes, 10240) as u32,
+            last_step_size: (remaining_bytes - steps_needed.saturating_sub(1) * 10240) as u32,
+            rent_needed,
+        };
         
         msg!("Current size: {} bytes", current_size);
         msg!("Target size: {} bytes", target_size);
         msg!("Remaining bytes: {} bytes", remaining_bytes);
         msg!("Reallocation steps needed: {}", steps_needed);
+        msg!("Additional rent needed: {} lamports", rent_needed);
+        
+        let return_data = plan.try_to_vec()
+            .map_err(|_| anchor_lang::error::ErrorCode::AccountDidNotSerialize)?;
+        anchor_lang::solana_program::program::set_return_data(&return_data);
+        Ok(())
+    }
+
+    /// Clear a PDA account's data and shrink it back to its header, refunding the rent of the
+    /// freed space to the authority. The account (and its index) stays reserved and can be
+    /// grown again with reallocate_pda_account.
+    pub fn deactivate_pda_account(ctx: Context<DeactivatePDAAccount>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let pda_account = &mut ctx.accounts.pda_account;
+        pda_account.data_length = 0;
+        
+        let account_info = pda_account.to_account_info();
+        let old_size = account_info.data_len();
+        account_info.try_borrow_mut_data()?[PDA_DATA_START..].fill(0);
+        account_info.resize(PDA_DATA_START)?;
+        
+        let refund = account_info.lamports().saturating_sub(Rent::get()?.minimum_balance(PDA_DATA_START));
+        if refund > 0 {
+            **account_info.try_borrow_mut_lamports()? -= refund;
+            **ctx.accounts.authority.to_account_info().try_borrow_mut_lamports()? += refund;
+        }
         
+        msg!("Deactivated PDA account {}: {} -> {} bytes, refunded {} lamports", 
+             pda_account.index, old_size, PDA_DATA_START, refund);
+        Ok(())
+    }
+
+    /// Close a PDA account, zeroing it and returning all of its rent to the authority
+    pub fn close_pda_account(ctx: Context<ClosePDAAccount>) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let account_info = ctx.accounts.pda_account.to_account_info();
+        account_info.try_borrow_mut_data()?[PDA_DATA_START..].fill(0);
+        
+        msg!("Closed PDA account {} ({} lamports returned)", 
+             ctx.accounts.pda_account.index, account_info.lamports());
+        Ok(())
+    }
+
+    /// Upgrade an older PDA account: grow it by the header bytes added since (version, then
+    /// bump) and shift the raw data along to make room. The caller names the layout the
+    /// account is in, since a pre-version account's first data byte sits where the version
+    /// byte now lives and can hold any value.
+    pub fn migrate_pda_account(ctx: Context<MigratePDAAccount>, from_version: u8) -> Result<()> {
+        require_not_paused(&ctx.accounts.protocol_config)?;
+        let pda_account = ctx.accounts.pda_account.to_account_info();
+        let old_size = pda_account.data_len();
+        let (old_data_start, bump) = {
+            let data = pda_account.try_borrow_data()?;
+            require!(data.starts_with(PDAAccount::DISCRIMINATOR), VoiceChatError::InvalidAccountVersion);
+            require!(
+                data.get(8 + 2..8 + 2 + 32) == Some(ctx.accounts.authority.key().as_ref()),
+                VoiceChatError::InvalidAccountVersion
+            );
+            require!(from_version < PDA_ACCOUNT_VERSION, VoiceChatError::InvalidAccountVersion);
+            // Version 1 and later stamp their version; pre-version accounts have nothing to check
+            if from_version > 0 {
+                require!(
+                    data.get(PDA_VERSION_OFFSET) == Some(&from_version),
+                    VoiceChatError::InvalidAccountVersion
+                );
+            }
+            let old_data_start = PDA_VERSION_OFFSET + from_version as usize;
+            
+            let index = [data[8], data[9]];
+            let (address, bump) = Pubkey::find_program_address(
+                &[b"pda", ctx.accounts.authority.key().as_ref(), &index],
+                &crate::ID,
+            );
+            require_keys_eq!(address, pda_account.key(), VoiceChatError::InvalidAccountVersion);
+            (old_data_start, bump)
+        };
+        
+        let new_size = old_size.max(old_data_start) + PDA_DATA_START - old_data_start;
+        let rent = Rent::get()?;
+        let lamports_needed = rent.minimum_balance(new_size).saturating_sub(pda_account.lamports());
+        if lamports_needed > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to: pda_account.clone(),
+                    },
+                ),
+                lamports_needed,
+            )?;
+        }
+        pda_account.resize(new_size)?;
+        
+        let mut account_data = pda_account.try_borrow_mut_data()?;
+        if old_size > old_data_start {
+            account_data.copy_within(old_data_start..old_size, PDA_DATA_START);
+        }
+        account_data[PDA_VERSION_OFFSET] = PDA_ACCOUNT_VERSION;
+        account_data[PDA_BUMP_OFFSET] = bump;
+        
+        msg!("Migrated PDA account {} to version {}", pda_account.key(), PDA_ACCOUNT_VERSION);
         Ok(())
     }
 }
@@ -179,7 +383,7 @@ pub struct CreatePDAAccount<'info> {
     #[account(
         init,
         payer = authority,
-        space = 8 + 2 + 32 + 8 + 4 + 10240, // discriminator + index(u16) + authority + created_at + data_length + initial_data
+        space = PDA_DATA_START + 10240, // discriminator + index(u16) + authority + created_at + data_length + version + bump + initial_data
         seeds = [b"pda", authority.key().as_ref(), &pda_index.to_le_bytes()],
         bump
     )]
@@ -189,6 +393,22 @@ pub struct CreatePDAAccount<'info> {
     pub authority: Signer<'info>,
     
     pub system_program: Program<'info, System>,
+    
+    /// Raises the index limit above the default of 10 when present
+    #[account(
+        seeds = [b"pda_registry", authority.key().as_ref()],
+        bump = pda_registry.bump,
+        has_one = authority
+    )]
+    pub pda_registry: Option<Account<'info, PDARegistry>>,
+    
+    /// CHECK: voice_chat_manager's ProtocolConfig, read by require_not_paused
+    #[account(
+        seeds = [b"protocol_config"],
+        bump,
+        seeds::program = VOICE_CHAT_MANAGER_PROGRAM_ID
+    )]
+    pub protocol_config: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
@@ -197,7 +417,7 @@ pub struct CreateAllPDAs<'info> {
     #[account(
         init,
         payer = authority,
-        space = 8 + 2 + 32 + 8 + 4 + 10240, // discriminator + index(u16) + authority + created_at + data_length + initial_data
+        space = PDA_DATA_START + 10240, // discriminator + index(u16) + authority + created_at + data_length + version + bump + initial_data
         seeds = [b"pda", authority.key().as_ref(), &pda_index.to_le_bytes()],
         bump
     )]
@@ -207,6 +427,68 @@ pub struct CreateAllPDAs<'info> {
     pub authority: Signer<'info>,
     
     pub system_program: Program<'info, System>,
+    
+    /// Raises the index limit above the default of 10 when present
+    #[account(
+        seeds = [b"pda_registry", authority.key().as_ref()],
+        bump = pda_registry.bump,
+        has_one = authority
+    )]
+    pub pda_registry: Option<Account<'info, PDARegistry>>,
+    
+    /// CHECK: voice_chat_manager's ProtocolConfig, read by require_not_paused
+    #[account(
+        seeds = [b"protocol_config"],
+        bump,
+        seeds::program = VOICE_CHAT_MANAGER_PROGRAM_ID
+    )]
+    pub protocol_config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializePDARegistry<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 2 + 1, // discriminator + authority + max_pdas + bump
+        seeds = [b"pda_registry", authority.key().as_ref()],
+        bump
+    )]
+    pub pda_registry: Account<'info, PDARegistry>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    /// CHECK: voice_chat_manager's ProtocolConfig, read by require_not_paused
+    #[account(
+        seeds = [b"protocol_config"],
+        bump,
+        seeds::program = VOICE_CHAT_MANAGER_PROGRAM_ID
+    )]
+    pub protocol_config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMaxPDAs<'info> {
+    #[account(
+        mut,
+        seeds = [b"pda_registry", authority.key().as_ref()],
+        bump = pda_registry.bump,
+        has_one = authority
+    )]
+    pub pda_registry: Account<'info, PDARegistry>,
+    
+    pub authority: Signer<'info>,
+    
+    /// CHECK: voice_chat_manager's ProtocolConfig, read by require_not_paused
+    #[account(
+        seeds = [b"protocol_config"],
+        bump,
+        seeds::program = VOICE_CHAT_MANAGER_PROGRAM_ID
+    )]
+    pub protocol_config: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
@@ -214,12 +496,20 @@ pub struct UpdatePDAData<'info> {
     #[account(
         mut,
         seeds = [b"pda", authority.key().as_ref(), &pda_account.index.to_le_bytes()],
-        bump,
+        bump = pda_account.bump,
         has_one = authority
     )]
     pub pda_account: Account<'info, PDAAccount>,
     
     pub authority: Signer<'info>,
+    
+    /// CHECK: voice_chat_manager's ProtocolConfig, read by require_not_paused
+    #[account(
+        seeds = [b"protocol_config"],
+        bump,
+        seeds::program = VOICE_CHAT_MANAGER_PROGRAM_ID
+    )]
+    pub protocol_config: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
@@ -227,7 +517,7 @@ pub struct ReallocatePDAAccount<'info> {
     #[account(
         mut,
         seeds = [b"pda", authority.key().as_ref(), &pda_account.index.to_le_bytes()],
-        bump,
+        bump = pda_account.bump,
         has_one = authority
     )]
     pub pda_account: Account<'info, PDAAccount>,
@@ -236,18 +526,113 @@ pub struct ReallocatePDAAccount<'info> {
     pub authority: Signer<'info>,
     
     pub system_program: Program<'info, System>,
+    
+    /// CHECK: voice_chat_manager's ProtocolConfig, read by require_not_paused
+    #[account(
+        seeds = [b"protocol_config"],
+        bump,
+        seeds::program = VOICE_CHAT_MANAGER_PROGRAM_ID
+    )]
+    pub protocol_config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReallocatePDAAccountPrepaid<'info> {
+    #[account(
+        mut,
+        seeds = [b"pda", authority.key().as_ref(), &pda_account.index.to_le_bytes()],
+        bump = pda_account.bump,
+        has_one = authority
+    )]
+    pub pda_account: Account<'info, PDAAccount>,
+    
+    /// CHECK: Only used for the PDA seeds; the authority doesn't need to sign
+    pub authority: UncheckedAccount<'info>,
+    
+    /// CHECK: voice_chat_manager's ProtocolConfig, read by require_not_paused
+    #[account(
+        seeds = [b"protocol_config"],
+        bump,
+        seeds::program = VOICE_CHAT_MANAGER_PROGRAM_ID
+    )]
+    pub protocol_config: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
 pub struct GetReallocationInfo<'info> {
     #[account(
         seeds = [b"pda", authority.key().as_ref(), &pda_account.index.to_le_bytes()],
+        bump = pda_account.bump,
+        has_one = authority
+    )]
+    pub pda_account: Account<'info, PDAAccount>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DeactivatePDAAccount<'info> {
+    #[account(
+        mut,
+        seeds = [b"pda", authority.key().as_ref(), &pda_account.index.to_le_bytes()],
+        bump = pda_account.bump,
+        has_one = authority
+    )]
+    pub pda_account: Account<'info, PDAAccount>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: voice_chat_manager's ProtocolConfig, read by require_not_paused
+    #[account(
+        seeds = [b"protocol_config"],
         bump,
+        seeds::program = VOICE_CHAT_MANAGER_PROGRAM_ID
+    )]
+    pub protocol_config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClosePDAAccount<'info> {
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"pda", authority.key().as_ref(), &pda_account.index.to_le_bytes()],
+        bump = pda_account.bump,
         has_one = authority
     )]
     pub pda_account: Account<'info, PDAAccount>,
     
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: voice_chat_manager's ProtocolConfig, read by require_not_paused
+    #[account(
+        seeds = [b"protocol_config"],
+        bump,
+        seeds::program = VOICE_CHAT_MANAGER_PROGRAM_ID
+    )]
+    pub protocol_config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigratePDAAccount<'info> {
+    /// CHECK: Old layout can't be deserialized; the handler checks discriminator and authority
+    #[account(mut, owner = crate::ID)]
+    pub pda_account: UncheckedAccount<'info>,
+    
+    #[account(mut)]
     pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    /// CHECK: voice_chat_manager's ProtocolConfig, read by require_not_paused
+    #[account(
+        seeds = [b"protocol_config"],
+        bump,
+        seeds::program = VOICE_CHAT_MANAGER_PROGRAM_ID
+    )]
+    pub protocol_config: UncheckedAccount<'info>,
 }
 
 #[account]
@@ -256,17 +641,43 @@ pub struct PDAAccount {
     pub authority: Pubkey,
     pub created_at: i64,
     pub data_length: u32,
+    pub version: u8,
+    pub bump: u8,
     // The actual data will be stored as raw bytes after the struct
 }
 
-#[error_code]
-pub enum VoiceChatError {
-    #[msg("Invalid PDA index. Must be between 0 and 9.")]
-    InvalidPDAIndex,
-    #[msg("Data size exceeds maximum allowed size of 1MB.")]
-    DataTooLarge,
-    #[msg("No reallocation needed - account is already at or above target size.")]
-    NoReallocNeeded,
-    #[msg("Target size exceeds maximum allowed size of 1MB.")]
-    TargetSizeTooLarge,
+/// Per-authority cap on PDA indices, for authorities that need more than the default 10
+#[account]
+pub struct PDARegistry {
+    pub authority: Pubkey,
+    pub max_pdas: u16,
+    pub bump: u8,
+}
+
+/// Return data of get_reallocation_steps_needed. Every step but the last grows the account
+/// by step_size bytes; rent_needed is what the authority has to top up across all steps.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ReallocationPlan {
+    pub current_size: u64,
+    pub target_size: u64,
+    pub steps: u32,
+    pub step_size: u32,
+    pub last_step_size: u32,
+    pub rent_needed: u64,
+}
+
+/// Number of PDA indices the authority may use: its registry's limit, or the default
+fn max_pdas(pda_registry: &Option<Account<PDARegistry>>) -> u16 {
+    pda_registry.as_ref().map_or(DEFAULT_MAX_PDAS, |registry| registry.max_pdas)
+}
+
+/// Guard for state-mutating instructions. The pause switch lives in voice_chat_manager's
+/// ProtocolConfig; until that config exists the program is treated as unpaused.
+fn require_not_paused(protocol_config: &AccountInfo) -> Result<()> {
+    if protocol_config.owner != &VOICE_CHAT_MANAGER_PROGRAM_ID {
+        return Ok(());
+    }
+    let data = protocol_config.try_borrow_data()?;
+    require!(data.get(PROTOCOL_CONFIG_PAUSED_OFFSET) != Some(&1), VoiceChatError::ProgramPaused);
+    Ok(())
 }
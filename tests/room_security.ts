@@ -0,0 +1,855 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { StorageManager } from "../target/types/storage_manager";
+import { VoiceChatManager } from "../target/types/voice_chat_manager";
+import { expect } from "chai";
+
+describe("Room security", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.VoiceChatManager as Program<VoiceChatManager>;
+  const storageProgram = anchor.workspace.StorageManager as Program<StorageManager>;
+  const payer = (provider.wallet as anchor.Wallet).payer;
+
+  // Every state-mutating instruction reads the program-wide ProtocolConfig
+  before(async () => {
+    const [protocolConfig] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("protocol_config")],
+      program.programId
+    );
+    if (!(await provider.connection.getAccountInfo(protocolConfig))) {
+      await program.methods.initializeProtocolConfig(0).accounts({ admin: payer.publicKey }).rpc();
+    }
+  });
+
+  const fundedKeypair = async () => {
+    const keypair = anchor.web3.Keypair.generate();
+    const signature = await provider.connection.requestAirdrop(
+      keypair.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(signature, "confirmed");
+    return keypair;
+  };
+
+  const pda = (...seeds: Buffer[]) =>
+    anchor.web3.PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+  const createRoom = async (prefix: string) => {
+    const roomId = `${prefix}-${Date.now()}`;
+    await program.methods.initializeVoiceRoom(roomId, null).accounts({ host: payer.publicKey }).rpc();
+    return pda(Buffer.from("voice_room"), Buffer.from(roomId));
+  };
+
+  const join = async (voiceRoom: anchor.web3.PublicKey, user: anchor.web3.Keypair) =>
+    program.methods
+      .joinVoiceRoom(0, null)
+      .accounts({ voiceRoom, participant: user.publicKey })
+      .signers([user])
+      .rpc();
+
+  const expectError = async (promise: Promise<unknown>, code: string) => {
+    try {
+      await promise;
+      expect.fail(`expected ${code}`);
+    } catch (error) {
+      expect(error.error.errorCode.code).to.equal(code);
+    }
+  };
+
+  // Voice messages, batches and clips take the room's next message number and are indexed on its open page
+  const messageRecords = async (voiceRoom: anchor.web3.PublicKey) => {
+    const room = await program.account.voiceRoom.fetch(voiceRoom);
+    const messageNumber = room.messageCount.toArrayLike(Buffer, "le", 8);
+    return {
+      voiceMessage: pda(Buffer.from("voice_message"), voiceRoom.toBuffer(), messageNumber),
+      batchMessage: pda(Buffer.from("voice_batch"), voiceRoom.toBuffer(), messageNumber),
+      clipMessage: pda(Buffer.from("voice_clip"), voiceRoom.toBuffer(), messageNumber),
+      messageIndexPage: pda(
+        Buffer.from("message_index"),
+        voiceRoom.toBuffer(),
+        room.indexedMessages.divn(64).toArrayLike(Buffer, "le", 8)
+      ),
+    };
+  };
+
+  // A room's lanes are its host's storage PDAs, delegated to voice_chat_manager's writer
+  const storageLane = async (index: number, delegated = true) => {
+    const storagePda = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("storage"), payer.publicKey.toBuffer(), Buffer.from([index])],
+      storageProgram.programId
+    )[0];
+    if (!(await provider.connection.getAccountInfo(storagePda))) {
+      await storageProgram.methods.createAllStoragePdas(index).accounts({ authority: payer.publicKey }).rpc();
+    }
+    await storageProgram.methods.delegate(delegated).accounts({ storagePda, authority: payer.publicKey }).rpc();
+    return storagePda;
+  };
+
+  describe("payment streams", () => {
+    it("requires the stream on leave and stops it", async () => {
+      const voiceRoom = await createRoom("stream-leave");
+      await program.methods.setStreamRate(new anchor.BN(60_000)).accounts({ voiceRoom, host: payer.publicKey }).rpc();
+
+      const listener = await fundedKeypair();
+      await join(voiceRoom, listener);
+      await program.methods
+        .openStream(new anchor.BN(1_000_000))
+        .accounts({ voiceRoom, listener: listener.publicKey })
+        .signers([listener])
+        .rpc();
+      const paymentStream = pda(Buffer.from("stream"), voiceRoom.toBuffer(), listener.publicKey.toBuffer());
+
+      // Leaving without the stream would strand the deposit
+      await expectError(
+        program.methods
+          .leaveVoiceRoom()
+          .accounts({ voiceRoom, participant: listener.publicKey, paymentStream: null })
+          .signers([listener])
+          .rpc(),
+        "PaymentStreamRequired"
+      );
+
+      await program.methods
+        .leaveVoiceRoom()
+        .accounts({ voiceRoom, participant: listener.publicKey, paymentStream })
+        .signers([listener])
+        .rpc();
+      expect((await program.account.paymentStream.fetch(paymentStream)).stoppedAt.toNumber()).to.be.greaterThan(0);
+
+      await program.methods
+        .closeStream()
+        .accounts({ voiceRoom, host: payer.publicKey, listener: listener.publicKey })
+        .signers([listener])
+        .rpc();
+      expect(await provider.connection.getAccountInfo(paymentStream)).to.be.null;
+    });
+
+    it("lets a banned listener close their stream", async () => {
+      const voiceRoom = await createRoom("stream-ban");
+      await program.methods.setStreamRate(new anchor.BN(60_000)).accounts({ voiceRoom, host: payer.publicKey }).rpc();
+
+      const listener = await fundedKeypair();
+      await join(voiceRoom, listener);
+      await program.methods
+        .openStream(new anchor.BN(1_000_000))
+        .accounts({ voiceRoom, listener: listener.publicKey })
+        .signers([listener])
+        .rpc();
+      const paymentStream = pda(Buffer.from("stream"), voiceRoom.toBuffer(), listener.publicKey.toBuffer());
+
+      // While the listener is still in the room the stream can't be closed unstopped
+      await expectError(
+        program.methods
+          .closeStream()
+          .accounts({ voiceRoom, host: payer.publicKey, listener: listener.publicKey })
+          .signers([listener])
+          .rpc(),
+        "StreamNotStopped"
+      );
+
+      const banUntil = new anchor.BN(Math.floor(Date.now() / 1000) + 3600);
+      await program.methods
+        .banParticipant(listener.publicKey, banUntil)
+        .accounts({ voiceRoom, bannedUser: listener.publicKey, host: payer.publicKey })
+        .rpc();
+
+      const before = await provider.connection.getBalance(listener.publicKey);
+      await program.methods
+        .closeStream()
+        .accounts({ voiceRoom, host: payer.publicKey, listener: listener.publicKey })
+        .signers([listener])
+        .rpc();
+      expect(await provider.connection.getAccountInfo(paymentStream)).to.be.null;
+      expect(await provider.connection.getBalance(listener.publicKey)).to.be.greaterThan(before);
+    });
+  });
+
+  describe("sender reports", () => {
+    it("slashes the stake in rooms that require one", async () => {
+      const voiceRoom = await createRoom("report-stake");
+      await program.methods.setSenderStake(new anchor.BN(100_000)).accounts({ voiceRoom, host: payer.publicKey }).rpc();
+      await program.methods.setModerationPolicy(1, 5_000).accounts({ voiceRoom, host: payer.publicKey }).rpc();
+
+      const offender = await fundedKeypair();
+      const reporter = await fundedKeypair();
+      await join(voiceRoom, offender);
+      await join(voiceRoom, reporter);
+      await program.methods
+        .depositStake(new anchor.BN(100_000))
+        .accounts({ voiceRoom, sender: offender.publicKey })
+        .signers([offender])
+        .rpc();
+
+      const offenderParticipant = pda(Buffer.from("participant"), voiceRoom.toBuffer(), offender.publicKey.toBuffer());
+      const { joinedAt } = await program.account.participant.fetch(offenderParticipant);
+      const offenderStake = pda(Buffer.from("stake"), voiceRoom.toBuffer(), offender.publicKey.toBuffer());
+      const roomTreasury = pda(Buffer.from("room_treasury"), voiceRoom.toBuffer());
+
+      // Leaving out the stake must not dodge the slash
+      await expectError(
+        program.methods
+          .reportSender(1, offender.publicKey, joinedAt)
+          .accounts({
+            voiceRoom,
+            offenderParticipant,
+            offenderStake: null,
+            roomTreasury: null,
+            reporter: reporter.publicKey,
+          })
+          .signers([reporter])
+          .rpc(),
+        "SlashAccountsRequired"
+      );
+
+      const stakeBefore = await provider.connection.getBalance(offenderStake);
+      const treasuryBefore = await provider.connection.getBalance(roomTreasury);
+      await program.methods
+        .reportSender(1, offender.publicKey, joinedAt)
+        .accounts({
+          voiceRoom,
+          offenderParticipant,
+          offenderStake,
+          roomTreasury,
+          reporter: reporter.publicKey,
+        })
+        .signers([reporter])
+        .rpc();
+
+      expect(await provider.connection.getBalance(offenderStake)).to.equal(stakeBefore - 50_000);
+      expect(await provider.connection.getBalance(roomTreasury)).to.equal(treasuryBefore + 50_000);
+      expect((await program.account.senderStake.fetch(offenderStake)).amount.toNumber()).to.equal(50_000);
+      expect((await program.account.participant.fetch(offenderParticipant)).muted).to.be.true;
+    });
+  });
+
+  describe("direct calls", () => {
+    const storageManager = new anchor.web3.PublicKey("SU6CRGJXz5ksvXPyUuWXYfW2qmba6ZgHa3sxdr9aYMz");
+
+    it("writes only to a lane allocated for the call", async () => {
+      const caller = await fundedKeypair();
+      const callee = await fundedKeypair();
+      const directCall = pda(Buffer.from("direct_call"), caller.publicKey.toBuffer(), callee.publicKey.toBuffer());
+      const laneAuthority = pda(Buffer.from("call_lane"), directCall.toBuffer());
+      const [storageLane] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("storage"), laneAuthority.toBuffer(), Buffer.from([0])],
+        storageManager
+      );
+
+      // Somebody else's storage PDA can't stand in for the call's lane
+      const [roomLane] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("storage"), payer.publicKey.toBuffer(), Buffer.from([0])],
+        storageManager
+      );
+      await expectError(
+        program.methods
+          .initiateCall(callee.publicKey)
+          .accounts({ caller: caller.publicKey, storageLane: roomLane, storageProgram: storageManager })
+          .signers([caller])
+          .rpc(),
+        "ConstraintSeeds"
+      );
+
+      const before = await provider.connection.getBalance(caller.publicKey);
+      await program.methods
+        .initiateCall(callee.publicKey)
+        .accounts({ caller: caller.publicKey, storageLane, storageProgram: storageManager })
+        .signers([caller])
+        .rpc();
+      expect((await program.account.directCall.fetch(directCall)).storageLane.toString()).to.equal(storageLane.toString());
+      expect(await provider.connection.getAccountInfo(storageLane)).to.not.be.null;
+
+      // Ending the call frees the lane and returns both rents to the caller
+      await program.methods
+        .endCall()
+        .accounts({ directCall, caller: caller.publicKey, party: caller.publicKey, storageLane, storageProgram: storageManager })
+        .signers([caller])
+        .rpc();
+      expect(await provider.connection.getAccountInfo(storageLane)).to.be.null;
+      expect(await provider.connection.getAccountInfo(laneAuthority)).to.be.null;
+      expect(await provider.connection.getBalance(caller.publicKey)).to.be.greaterThan(before - 20_000);
+    });
+  });
+
+  describe("voicemail", () => {
+    const storageManager = new anchor.web3.PublicKey("SU6CRGJXz5ksvXPyUuWXYfW2qmba6ZgHa3sxdr9aYMz");
+
+    it("refuses a storage lane that wasn't allocated for the voicemail", async () => {
+      const recipient = await fundedKeypair();
+      const sender = await fundedKeypair();
+      await program.methods
+        .initializeVoicemailInbox()
+        .accounts({ owner: recipient.publicKey })
+        .signers([recipient])
+        .rpc();
+
+      // A room's storage PDA: writing here (and deleting the voicemail later) would wipe its audio
+      const [roomLane] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("storage"), payer.publicKey.toBuffer(), Buffer.from([0])],
+        storageManager
+      );
+      await expectError(
+        program.methods
+          .leaveVoicemail(recipient.publicKey, Buffer.from("hello"))
+          .accounts({ sender: sender.publicKey, storageLane: roomLane, storageProgram: storageManager })
+          .signers([sender])
+          .rpc(),
+        "ConstraintSeeds"
+      );
+    });
+  });
+
+  describe("relay reports", () => {
+    it("only slashes a relay once the admin upholds the reports", async () => {
+      const operator = anchor.web3.Keypair.generate();
+      const signature = await provider.connection.requestAirdrop(operator.publicKey, 3 * anchor.web3.LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(signature, "confirmed");
+      const stake = new anchor.BN(anchor.web3.LAMPORTS_PER_SOL);
+      await program.methods
+        .registerRelay(Array(32).fill(7), 0, stake)
+        .accounts({ operator: operator.publicKey })
+        .signers([operator])
+        .rpc();
+      const relayNode = pda(Buffer.from("relay"), operator.publicKey.toBuffer());
+
+      const voiceRoom = await createRoom("relay-report");
+      await program.methods.setRoomRelay().accounts({ voiceRoom, relayNode, host: payer.publicKey }).rpc();
+
+      // A quorum of throwaway participants is no longer enough to slash
+      for (let i = 0; i < 3; i++) {
+        const reporter = await fundedKeypair();
+        await join(voiceRoom, reporter);
+        await program.methods
+          .reportRelay(1)
+          .accounts({ voiceRoom, relayNode, reporter: reporter.publicKey })
+          .signers([reporter])
+          .rpc();
+      }
+      let relay = await program.account.relayNode.fetch(relayNode);
+      expect(relay.reportCount).to.equal(3);
+      expect(relay.slashed).to.be.false;
+      expect(relay.stakeLamports.toString()).to.equal(stake.toString());
+
+      // Only the protocol admin can rule on them
+      await expectError(
+        program.methods
+          .adjudicateRelayReports(true)
+          .accounts({ relayNode, admin: operator.publicKey })
+          .signers([operator])
+          .rpc(),
+        "ConstraintHasOne"
+      );
+
+      const relayBefore = await provider.connection.getBalance(relayNode);
+      await program.methods.adjudicateRelayReports(true).accounts({ relayNode, admin: payer.publicKey }).rpc();
+      relay = await program.account.relayNode.fetch(relayNode);
+      expect(relay.slashed).to.be.true;
+      expect(relay.stakeLamports.toString()).to.equal(stake.divn(2).toString());
+      expect(await provider.connection.getBalance(relayNode)).to.equal(relayBefore - stake.divn(2).toNumber());
+    });
+  });
+
+  describe("storage lanes", () => {
+    it("keeps every send path off a lane until its frame is consumed", async () => {
+      const voiceRoom = await createRoom("unread-lane");
+      const lane = 3;
+      const storagePda = await storageLane(lane);
+
+      const sendVoiceData = async (force: boolean) => {
+        const { voiceMessage, messageIndexPage } = await messageRecords(voiceRoom);
+        return program.methods
+          .sendVoiceData(
+            Array.from(Buffer.from("frame")),
+            lane,
+            Math.floor(Math.random() * 1_000_000),
+            new anchor.BN(0),
+            new anchor.BN(Date.now()),
+            20,
+            force
+          )
+          .accounts({
+            voiceRoom,
+            storagePda,
+            voiceMessage,
+            messageIndexPage,
+            sender: payer.publicKey,
+            authority: payer.publicKey,
+          })
+          .rpc();
+      };
+      const sendVoiceBatch = async (force: boolean) => {
+        const { batchMessage, messageIndexPage } = await messageRecords(voiceRoom);
+        const sequenceNumber = Math.floor(Math.random() * 1_000_000);
+        const payload = Buffer.from("batched");
+        return program.methods
+          .sendVoiceBatch([{ sequenceNumber, length: payload.length, durationMs: 20 }], payload, lane, sequenceNumber, force)
+          .accounts({ voiceRoom, storagePda, batchMessage, messageIndexPage, sender: payer.publicKey })
+          .rpc();
+      };
+
+      await sendVoiceData(false);
+      expect((await program.account.voiceRoom.fetch(voiceRoom)).unreadLanes & (1 << lane)).to.not.equal(0);
+
+      // Neither the single-frame nor the batch path may silently overwrite the unread frame
+      await expectError(sendVoiceData(false), "UnreadFrameInLane");
+      await expectError(sendVoiceBatch(false), "UnreadFrameInLane");
+
+      // The batch marks the lane unread again when it forces its way in
+      await sendVoiceBatch(true);
+      expect((await program.account.voiceRoom.fetch(voiceRoom)).unreadLanes & (1 << lane)).to.not.equal(0);
+      await expectError(sendVoiceData(false), "UnreadFrameInLane");
+
+      // Naming another lane while passing this one's account can't clobber the unread frame either
+      const { batchMessage, messageIndexPage } = await messageRecords(voiceRoom);
+      await expectError(
+        program.methods
+          .sendVoiceBatch([{ sequenceNumber: 1, length: 5, durationMs: 20 }], Buffer.from("clobb"), 4, 1, false)
+          .accounts({ voiceRoom, storagePda, batchMessage, messageIndexPage, sender: payer.publicKey })
+          .rpc(),
+        "InvalidStoragePDA"
+      );
+
+      // A single listener can't wave the frame through for everyone else
+      const listener = await fundedKeypair();
+      await join(voiceRoom, listener);
+      await expectError(
+        program.methods.ackConsumed(lane).accounts({ voiceRoom, host: listener.publicKey }).signers([listener]).rpc(),
+        "NotRoomHost"
+      );
+      await program.methods.ackConsumed(lane).accounts({ voiceRoom, host: payer.publicKey }).rpc();
+      await sendVoiceData(false);
+    });
+
+    it("only writes the host's lane for the given index, and only once delegated", async () => {
+      const voiceRoom = await createRoom("lane-binding");
+      const lanes = { 5: await storageLane(5, false), 6: await storageLane(6) };
+
+      const send = async (index: number, storagePda: anchor.web3.PublicKey) => {
+        const { batchMessage, messageIndexPage } = await messageRecords(voiceRoom);
+        const sequenceNumber = Math.floor(Math.random() * 1_000_000);
+        const payload = Buffer.from("bound");
+        return program.methods
+          .sendVoiceBatch([{ sequenceNumber, length: payload.length, durationMs: 20 }], payload, index, sequenceNumber, false)
+          .accounts({ voiceRoom, storagePda, batchMessage, messageIndexPage, sender: payer.publicKey })
+          .rpc();
+      };
+
+      // Lane 6's account passed as lane 5 would dodge lane 5's bitmaps
+      await expectError(send(5, lanes[6]), "InvalidStoragePDA");
+      await expectError(send(5, lanes[5]), "StorageNotDelegated");
+      await send(6, lanes[6]);
+    });
+
+    it("keeps the audience off the stage lane whatever index it claims", async () => {
+      const voiceRoom = await createRoom("stage-lane");
+      const stageLane = await storageLane(7);
+      const audienceLane = await storageLane(8);
+      await program.methods.setStageLanes(7, 0, 0).accounts({ voiceRoom, host: payer.publicKey }).rpc();
+      const listener = await fundedKeypair();
+      await join(voiceRoom, listener);
+
+      const send = async (index: number, storagePda: anchor.web3.PublicKey) => {
+        const { batchMessage, messageIndexPage } = await messageRecords(voiceRoom);
+        const sequenceNumber = Math.floor(Math.random() * 1_000_000);
+        const payload = Buffer.from("audience");
+        return program.methods
+          .sendVoiceBatch([{ sequenceNumber, length: payload.length, durationMs: 20 }], payload, index, sequenceNumber, true)
+          .accounts({ voiceRoom, storagePda, batchMessage, messageIndexPage, sender: listener.publicKey })
+          .signers([listener])
+          .rpc();
+      };
+
+      await expectError(send(7, stageLane), "StageLaneReserved");
+      // Claiming lane 8 while passing the stage lane's account no longer slips past the check
+      await expectError(send(8, stageLane), "InvalidStoragePDA");
+      await send(8, audienceLane);
+    });
+
+    it("records a batch of consecutive frames and refuses gaps", async () => {
+      const voiceRoom = await createRoom("batch-send");
+      const storagePda = await storageLane(4);
+      const payload = Buffer.from("abcdefg");
+
+      const send = async (sequenceNumbers: number[]) => {
+        const { batchMessage, messageIndexPage } = await messageRecords(voiceRoom);
+        const frames = sequenceNumbers.map((sequenceNumber, i) => ({
+          sequenceNumber,
+          length: i === 0 ? 3 : 4,
+          durationMs: 20,
+        }));
+        await program.methods
+          .sendVoiceBatch(frames, payload, 4, sequenceNumbers[0], false)
+          .accounts({ voiceRoom, storagePda, batchMessage, messageIndexPage, sender: payer.publicKey })
+          .rpc();
+        return batchMessage;
+      };
+
+      // A hole in the numbering would leave the sender's contiguous range claiming a frame never sent
+      await expectError(send([10, 12]), "BatchSequenceMismatch");
+
+      const batchMessage = await send([10, 11]);
+      const batch = await program.account.voiceBatchMessage.fetch(batchMessage);
+      expect(batch.frames.map((frame) => frame.sequenceNumber)).to.deep.equal([10, 11]);
+      expect(batch.frameHashes).to.have.length(2);
+      expect(batch.storagePda.toString()).to.equal(storagePda.toString());
+      expect((await program.account.voiceRoom.fetch(voiceRoom)).messageCount.toNumber()).to.equal(1);
+    });
+
+    it("writes a clip only once every chunk's lane checks out", async () => {
+      const voiceRoom = await createRoom("clip-send");
+      const lanes = [await storageLane(1), await storageLane(2)];
+      const payload = Buffer.from("clip-audio");
+
+      const send = async (targetPdas: number[], storagePdas: anchor.web3.PublicKey[]) => {
+        const { clipMessage, messageIndexPage } = await messageRecords(voiceRoom);
+        await program.methods
+          .sendVoiceClip(payload, Buffer.from(targetPdas), 1, 20, false)
+          .accounts({ voiceRoom, clipMessage, messageIndexPage, sender: payer.publicKey })
+          .remainingAccounts(storagePdas.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true })))
+          .rpc();
+        return clipMessage;
+      };
+
+      // Lane 2's account can't stand in for lane 1, and a one-chunk clip can't claim two lanes
+      await expectError(send([1], [lanes[1]]), "InvalidStoragePDA");
+      await expectError(send([1, 2], lanes), "ClipChunkMismatch");
+      expect((await program.account.voiceRoom.fetch(voiceRoom)).messageCount.toNumber()).to.equal(0);
+
+      const clipMessage = await send([1], [lanes[0]]);
+      const clip = await program.account.voiceClipMessage.fetch(clipMessage);
+      expect(clip.storagePdas.map((key) => key.toString())).to.deep.equal([lanes[0].toString()]);
+      expect(clip.chunkHashes).to.have.length(1);
+      expect(clip.dataLength).to.equal(payload.length);
+      expect((await program.account.voiceRoom.fetch(voiceRoom)).messageCount.toNumber()).to.equal(1);
+    });
+
+    it("reserves an allocated lane for its allocator until released", async () => {
+      const voiceRoom = await createRoom("write-slot");
+      const storagePda = await storageLane(0);
+      const holder = await fundedKeypair();
+      const other = await fundedKeypair();
+      await join(voiceRoom, holder);
+      await join(voiceRoom, other);
+      const holderParticipant = pda(Buffer.from("participant"), voiceRoom.toBuffer(), holder.publicKey.toBuffer());
+      const { slot } = await program.account.participant.fetch(holderParticipant);
+
+      await program.methods
+        .allocateWriteSlot()
+        .accounts({ voiceRoom, participant: holder.publicKey })
+        .signers([holder])
+        .rpc();
+      expect((await program.account.voiceRoom.fetch(voiceRoom)).laneAllocations[0]).to.equal(slot + 1);
+
+      const hostSend = async () => {
+        const { batchMessage, messageIndexPage } = await messageRecords(voiceRoom);
+        const sequenceNumber = Math.floor(Math.random() * 1_000_000);
+        const payload = Buffer.from("taken");
+        return program.methods
+          .sendVoiceBatch([{ sequenceNumber, length: payload.length, durationMs: 20 }], payload, 0, sequenceNumber, false)
+          .accounts({ voiceRoom, storagePda, batchMessage, messageIndexPage, sender: payer.publicKey })
+          .rpc();
+      };
+
+      // Nobody else may write to the lane or hand it back
+      await expectError(hostSend(), "WriteSlotHeldByOther");
+      await expectError(
+        program.methods
+          .ackWriteSlot(0)
+          .accounts({ voiceRoom, participant: other.publicKey })
+          .signers([other])
+          .rpc(),
+        "WriteSlotHeldByOther"
+      );
+
+      await program.methods
+        .ackWriteSlot(0)
+        .accounts({ voiceRoom, participant: holder.publicKey })
+        .signers([holder])
+        .rpc();
+      expect((await program.account.voiceRoom.fetch(voiceRoom)).laneAllocations[0]).to.equal(0);
+      await hostSend();
+    });
+
+    it("frees a lane once every listener acked its frame", async () => {
+      const voiceRoom = await createRoom("ack-frames");
+      const lane = 9;
+      const storagePda = await storageLane(lane);
+      const listener = await fundedKeypair();
+      await join(voiceRoom, listener);
+
+      const sendVoiceData = async (sequenceNumber: number) => {
+        const { voiceMessage, messageIndexPage } = await messageRecords(voiceRoom);
+        return program.methods
+          .sendVoiceData(Array.from(Buffer.from("frame")), lane, sequenceNumber, new anchor.BN(0), new anchor.BN(Date.now()), 20, false)
+          .accounts({ voiceRoom, storagePda, voiceMessage, messageIndexPage, sender: payer.publicKey, authority: payer.publicKey })
+          .rpc();
+      };
+      const ackFrames = (listener: anchor.web3.Keypair, upToSequence: number) =>
+        program.methods
+          .ackFrames(payer.publicKey, upToSequence)
+          .accounts({ voiceRoom, listener: listener.publicKey })
+          .signers([listener])
+          .rpc();
+
+      await sendVoiceData(5);
+      await expectError(sendVoiceData(6), "UnreadFrameInLane");
+
+      // Acking short of the lane's frame, or from outside the room, leaves it unread
+      await ackFrames(listener, 4);
+      expect((await program.account.voiceRoom.fetch(voiceRoom)).unreadLanes & (1 << lane)).to.not.equal(0);
+      await expectError(ackFrames(await fundedKeypair(), 5), "AccountNotInitialized");
+
+      await ackFrames(listener, 5);
+      expect((await program.account.voiceRoom.fetch(voiceRoom)).unreadLanes & (1 << lane)).to.equal(0);
+      await sendVoiceData(6);
+    });
+
+    it("advances a listener's cursor, acks what it covers and never goes back", async () => {
+      const voiceRoom = await createRoom("listener-cursor");
+      const lane = 9;
+      const storagePda = await storageLane(lane);
+      const listener = await fundedKeypair();
+      await join(voiceRoom, listener);
+      const listenerCursor = pda(Buffer.from("listener_cursor"), voiceRoom.toBuffer(), listener.publicKey.toBuffer());
+
+      const { voiceMessage, messageIndexPage } = await messageRecords(voiceRoom);
+      await program.methods
+        .sendVoiceData(Array.from(Buffer.from("frame")), lane, 7, new anchor.BN(0), new anchor.BN(Date.now()), 20, false)
+        .accounts({ voiceRoom, storagePda, voiceMessage, messageIndexPage, sender: payer.publicKey, authority: payer.publicKey })
+        .rpc();
+
+      const advanceCursor = (sequence: number) =>
+        program.methods
+          .advanceCursor(payer.publicKey, 0, sequence)
+          .accounts({ voiceRoom, listener: listener.publicKey })
+          .signers([listener])
+          .rpc();
+
+      await advanceCursor(7);
+      const cursor = await program.account.listenerCursor.fetch(listenerCursor);
+      expect(cursor.entries).to.have.length(1);
+      expect(cursor.entries[0].sender.toString()).to.equal(payer.publicKey.toString());
+      expect(cursor.entries[0].sequence).to.equal(7);
+      expect((await program.account.voiceRoom.fetch(voiceRoom)).unreadLanes & (1 << lane)).to.equal(0);
+
+      await expectError(advanceCursor(6), "CursorRegression");
+    });
+
+    it("closes a frame account back to its sender once every listener acked it", async () => {
+      const voiceRoom = await createRoom("frame-account");
+      const listener = await fundedKeypair();
+      await join(voiceRoom, listener);
+      const sequenceNumber = 1;
+      const frame = pda(
+        Buffer.from("frame"),
+        voiceRoom.toBuffer(),
+        payer.publicKey.toBuffer(),
+        new anchor.BN(0).toArrayLike(Buffer, "le", 4),
+        new anchor.BN(sequenceNumber).toArrayLike(Buffer, "le", 4)
+      );
+
+      await program.methods
+        .sendVoiceFrame(Buffer.from("frame"), sequenceNumber, 20)
+        .accounts({ voiceRoom, frame, sender: payer.publicKey })
+        .rpc();
+      expect((await program.account.ephemeralFrame.fetch(frame)).pendingAcks).to.not.equal(0);
+
+      const ackVoiceFrame = (listener: anchor.web3.Keypair) =>
+        program.methods
+          .ackVoiceFrame()
+          .accounts({ frame, listener: listener.publicKey, sender: payer.publicKey })
+          .signers([listener])
+          .rpc();
+
+      // Only participants of the frame's room can ack it
+      await expectError(ackVoiceFrame(await fundedKeypair()), "AccountNotInitialized");
+
+      const before = await provider.connection.getBalance(payer.publicKey);
+      await ackVoiceFrame(listener);
+      expect(await provider.connection.getAccountInfo(frame)).to.be.null;
+      expect(await provider.connection.getBalance(payer.publicKey)).to.be.greaterThan(before);
+    });
+  });
+
+  describe("sequence epochs", () => {
+    it("keeps counting up after the sender rejoins", async () => {
+      const voiceRoom = await createRoom("epoch-rejoin");
+      const sender = await fundedKeypair();
+      const participantAccount = pda(Buffer.from("participant"), voiceRoom.toBuffer(), sender.publicKey.toBuffer());
+      const recentSends = pda(Buffer.from("recent_sends"), voiceRoom.toBuffer(), sender.publicKey.toBuffer());
+      const startEpoch = () =>
+        program.methods
+          .startSequenceEpoch()
+          .accounts({ voiceRoom, participant: sender.publicKey })
+          .signers([sender])
+          .rpc();
+
+      await join(voiceRoom, sender);
+      await startEpoch();
+      await startEpoch();
+      expect((await program.account.participant.fetch(participantAccount)).sequenceEpoch).to.equal(2);
+
+      await program.methods
+        .leaveVoiceRoom()
+        .accounts({ voiceRoom, participant: sender.publicKey, paymentStream: null })
+        .signers([sender])
+        .rpc();
+      await join(voiceRoom, sender);
+      expect((await program.account.participant.fetch(participantAccount)).sequenceEpoch).to.equal(0);
+
+      // The fresh participant record must not reuse epochs from the previous visit
+      await startEpoch();
+      expect((await program.account.participant.fetch(participantAccount)).sequenceEpoch).to.equal(3);
+      expect((await program.account.recentSends.fetch(recentSends)).sequenceEpoch).to.equal(3);
+    });
+  });
+
+  describe("session exports", () => {
+    it("builds an export manifest, then archives the session's messages once it ended", async () => {
+      const voiceRoom = await createRoom("session-export");
+      const storagePda = await storageLane(4);
+      const session = pda(Buffer.from("session"), voiceRoom.toBuffer(), new anchor.BN(0).toArrayLike(Buffer, "le", 4));
+      const sessionArchive = pda(Buffer.from("session_archive"), session.toBuffer());
+      await program.methods.startSession().accounts({ voiceRoom, host: payer.publicKey }).rpc();
+
+      const payload = Buffer.from("frame");
+      const { voiceMessage, messageIndexPage } = await messageRecords(voiceRoom);
+      await program.methods
+        .sendVoiceData(Array.from(payload), 4, 1, new anchor.BN(0), new anchor.BN(Date.now()), 20, false)
+        .accounts({ voiceRoom, storagePda, voiceMessage, messageIndexPage, sender: payer.publicKey, authority: payer.publicKey })
+        .rpc();
+      const archive = () =>
+        program.methods
+          .archiveSession()
+          .accounts({ voiceRoom, session, host: payer.publicKey })
+          .remainingAccounts([
+            { pubkey: voiceMessage, isSigner: false, isWritable: true },
+            { pubkey: payer.publicKey, isSigner: false, isWritable: true },
+          ])
+          .rpc();
+
+      // A running session can't be archived out from under its listeners
+      await expectError(archive(), "SessionNotEnded");
+      await program.methods.endSession(new anchor.BN(0), new anchor.BN(0)).accounts({ voiceRoom, host: payer.publicKey }).rpc();
+
+      // Only this room's message records can go into its manifest
+      await expectError(
+        program.methods
+          .buildExportManifest(0)
+          .accounts({ voiceRoom, session })
+          .remainingAccounts([{ pubkey: payer.publicKey, isSigner: false, isWritable: false }])
+          .rpc(),
+        "InvalidManifestAccounts"
+      );
+
+      const { raw } = await program.methods
+        .buildExportManifest(0)
+        .accounts({ voiceRoom, session })
+        .remainingAccounts([{ pubkey: voiceMessage, isSigner: false, isWritable: false }])
+        .simulate();
+      const prefix = `Program return: ${program.programId} `;
+      const returnData = Buffer.from(raw.find((log) => log.startsWith(prefix)).slice(prefix.length), "base64");
+      // Vec<ManifestEntry>: length, then storage_pda + offset + length + hash per entry
+      expect(returnData.readUInt32LE(0)).to.equal(1);
+      expect(new anchor.web3.PublicKey(returnData.subarray(4, 36)).toString()).to.equal(storagePda.toString());
+      expect(returnData.readUInt32LE(36)).to.equal(0);
+      expect(returnData.readUInt32LE(40)).to.equal(payload.length);
+
+      await archive();
+      expect((await program.account.sessionArchive.fetch(sessionArchive)).messageCount.toNumber()).to.equal(1);
+      expect((await provider.connection.getAccountInfo(voiceMessage)).data.every((byte) => byte === 0)).to.be.true;
+    });
+  });
+
+  describe("compressed tickets", () => {
+    const bubblegum = new anchor.web3.PublicKey("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+    const compression = new anchor.web3.PublicKey("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+    // Depth 3, buffer 8 concurrent merkle tree without a canopy: header + counters + changelogs + rightmost proof
+    const maxDepth = 3;
+    const maxBufferSize = 8;
+    const merkleTreeSpace = 56 + 24 + (maxBufferSize + 1) * (40 + 32 * maxDepth);
+
+    const allocateMerkleTree = async () => {
+      const merkleTree = anchor.web3.Keypair.generate();
+      const lamports = await provider.connection.getMinimumBalanceForRentExemption(merkleTreeSpace);
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          anchor.web3.SystemProgram.createAccount({
+            fromPubkey: payer.publicKey,
+            newAccountPubkey: merkleTree.publicKey,
+            lamports,
+            space: merkleTreeSpace,
+            programId: compression,
+          })
+        ),
+        [merkleTree]
+      );
+      const treeConfig = anchor.web3.PublicKey.findProgramAddressSync([merkleTree.publicKey.toBuffer()], bubblegum)[0];
+      return { merkleTree: merkleTree.publicKey, treeConfig };
+    };
+
+    it("only issues compressed tickets for scheduled events", async () => {
+      const voiceRoom = await createRoom("ticket-tree-unscheduled");
+      const { merkleTree, treeConfig } = await allocateMerkleTree();
+      await expectError(
+        program.methods
+          .createTicketTree(maxDepth, maxBufferSize, "https://example.com/ticket.json")
+          .accounts({ voiceRoom, treeConfig, merkleTree, host: payer.publicKey })
+          .rpc(),
+        "NotAScheduledEvent"
+      );
+    });
+
+    // Minting needs Bubblegum and spl-account-compression, which only clusters that carry them
+    // (e.g. a validator cloning them from mainnet) can run
+    it("mints a ticket into the tree and holds its refund while the event is on", async function () {
+      if (!(await provider.connection.getAccountInfo(bubblegum))) {
+        this.skip();
+      }
+      const voiceRoom = await createRoom("ticket-tree");
+      const startsAt = new anchor.BN(Math.floor(Date.now() / 1000) + 3600);
+      await program.methods.scheduleEvent(startsAt, 4).accounts({ voiceRoom, host: payer.publicKey }).rpc();
+      const { merkleTree, treeConfig } = await allocateMerkleTree();
+      await program.methods
+        .createTicketTree(maxDepth, maxBufferSize, "https://example.com/ticket.json")
+        .accounts({ voiceRoom, treeConfig, merkleTree, host: payer.publicKey })
+        .rpc();
+      const ticketTree = pda(Buffer.from("ticket_tree"), voiceRoom.toBuffer());
+
+      const buyer = await fundedKeypair();
+      await program.methods
+        .buyCompressedTicket()
+        .accounts({
+          voiceRoom,
+          treeConfig,
+          merkleTree,
+          escrow: null,
+          priceFeed: null,
+          solUsdPrice: null,
+          protocolTreasury: null,
+          buyer: buyer.publicKey,
+        })
+        .signers([buyer])
+        .rpc();
+      expect((await program.account.ticketTree.fetch(ticketTree)).minted.toNumber()).to.equal(1);
+      expect((await program.account.voiceRoom.fetch(voiceRoom)).ticketsSold).to.equal(1);
+
+      // The event hasn't been cancelled and its host still has time to show up
+      await expectError(
+        program.methods
+          .claimCompressedRefund({
+            root: Array(32).fill(0),
+            dataHash: Array(32).fill(0),
+            creatorHash: Array(32).fill(0),
+            nonce: new anchor.BN(0),
+            delegate: buyer.publicKey,
+          })
+          .accounts({ voiceRoom, merkleTree, escrow: null, holder: buyer.publicKey })
+          .signers([buyer])
+          .rpc(),
+        "RefundNotAvailable"
+      );
+    });
+  });
+});
@@ -0,0 +1,219 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import {
+  TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
+  createMint,
+  createAccount,
+  mintTo,
+  getAccount,
+} from "@solana/spl-token";
+import { VoiceChatManager } from "../target/types/voice_chat_manager";
+import { expect } from "chai";
+
+describe("Token-gated voice rooms", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.VoiceChatManager as Program<VoiceChatManager>;
+  const payer = (provider.wallet as anchor.Wallet).payer;
+
+  // Every state-mutating instruction reads the program-wide ProtocolConfig
+  before(async () => {
+    const [protocolConfig] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("protocol_config")],
+      program.programId
+    );
+    if (!(await provider.connection.getAccountInfo(protocolConfig))) {
+      await program.methods.initializeProtocolConfig(0).accounts({ admin: payer.publicKey }).rpc();
+    }
+  });
+
+  const fundedKeypair = async () => {
+    const keypair = anchor.web3.Keypair.generate();
+    const signature = await provider.connection.requestAirdrop(
+      keypair.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(signature, "confirmed");
+    return keypair;
+  };
+
+  // Gates must behave identically for legacy SPL Token and Token-2022 mints
+  for (const [label, tokenProgram] of [
+    ["SPL Token", TOKEN_PROGRAM_ID],
+    ["Token-2022", TOKEN_2022_PROGRAM_ID],
+  ] as const) {
+    it(`admits holders and rejects non-holders (${label})`, async () => {
+      const roomId = `gate-${label === "SPL Token" ? "spl" : "t22"}-${Date.now()}`;
+      const [voiceRoom] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("voice_room"), Buffer.from(roomId)],
+        program.programId
+      );
+
+      const mint = await createMint(
+        provider.connection,
+        payer,
+        payer.publicKey,
+        null,
+        0,
+        undefined,
+        undefined,
+        tokenProgram
+      );
+
+      await program.methods.initializeVoiceRoom(roomId, null).accounts({ host: payer.publicKey }).rpc();
+      await program.methods
+        .setTokenGate(mint, new anchor.BN(5))
+        .accounts({ voiceRoom, host: payer.publicKey })
+        .rpc();
+
+      // Holder with enough balance is admitted
+      const holder = await fundedKeypair();
+      const holderTokenAccount = await createAccount(
+        provider.connection,
+        payer,
+        mint,
+        holder.publicKey,
+        undefined,
+        undefined,
+        tokenProgram
+      );
+      await mintTo(provider.connection, payer, mint, holderTokenAccount, payer, 5, [], undefined, tokenProgram);
+
+      await program.methods
+        .joinVoiceRoom(0, null)
+        .accounts({
+          voiceRoom,
+          participant: holder.publicKey,
+          gateTokenAccount: holderTokenAccount,
+          nftTokenAccount: null,
+          nftMetadata: null,
+        })
+        .signers([holder])
+        .rpc();
+
+      const room = await program.account.voiceRoom.fetch(voiceRoom);
+      expect(room.participantCount).to.equal(2);
+
+      // Holder below the minimum balance is rejected
+      const outsider = await fundedKeypair();
+      const outsiderTokenAccount = await createAccount(
+        provider.connection,
+        payer,
+        mint,
+        outsider.publicKey,
+        undefined,
+        undefined,
+        tokenProgram
+      );
+      await mintTo(provider.connection, payer, mint, outsiderTokenAccount, payer, 1, [], undefined, tokenProgram);
+
+      try {
+        await program.methods
+          .joinVoiceRoom(0, null)
+          .accounts({
+            voiceRoom,
+            participant: outsider.publicKey,
+            gateTokenAccount: outsiderTokenAccount,
+            nftTokenAccount: null,
+            nftMetadata: null,
+          })
+          .signers([outsider])
+          .rpc();
+        expect.fail("join should have been rejected");
+      } catch (error) {
+        expect(error.error.errorCode.code).to.equal("InsufficientGateBalance");
+      }
+    });
+
+    it(`collects lamport and token entry fees into escrow (${label})`, async () => {
+      const roomId = `paid-${label === "SPL Token" ? "spl" : "t22"}-${Date.now()}`;
+      const [voiceRoom] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("voice_room"), Buffer.from(roomId)],
+        program.programId
+      );
+      const [escrow] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("escrow"), voiceRoom.toBuffer()],
+        program.programId
+      );
+      const [escrowVault] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("escrow_vault"), voiceRoom.toBuffer()],
+        program.programId
+      );
+
+      const mint = await createMint(
+        provider.connection,
+        payer,
+        payer.publicKey,
+        null,
+        0,
+        undefined,
+        undefined,
+        tokenProgram
+      );
+      const lamportFee = 10_000_000;
+      const tokenFee = 10;
+
+      await program.methods.initializeVoiceRoom(roomId, null).accounts({ host: payer.publicKey }).rpc();
+      await program.methods
+        .setEntryFee(new anchor.BN(lamportFee), mint, new anchor.BN(tokenFee), new anchor.BN(0))
+        .accounts({ voiceRoom, host: payer.publicKey })
+        .rpc();
+      await program.methods
+        .initializeEscrowVault()
+        .accounts({ voiceRoom, feeMint: mint, host: payer.publicKey, tokenProgram })
+        .rpc();
+
+      const joiner = await fundedKeypair();
+      const joinerTokenAccount = await createAccount(
+        provider.connection,
+        payer,
+        mint,
+        joiner.publicKey,
+        undefined,
+        undefined,
+        tokenProgram
+      );
+      await mintTo(provider.connection, payer, mint, joinerTokenAccount, payer, tokenFee, [], undefined, tokenProgram);
+
+      const joinerBefore = await provider.connection.getBalance(joiner.publicKey);
+      const escrowBefore = await provider.connection.getBalance(escrow);
+      await program.methods
+        .joinVoiceRoom(0, null)
+        .accounts({
+          voiceRoom,
+          participant: joiner.publicKey,
+          gateTokenAccount: null,
+          nftTokenAccount: null,
+          nftMetadata: null,
+          escrow,
+          feePayerTokenAccount: joinerTokenAccount,
+          escrowVault,
+          feeMint: mint,
+          tokenProgram,
+        })
+        .signers([joiner])
+        .rpc();
+
+      // The suite's ProtocolConfig charges no protocol fee, so the whole fee lands in escrow.
+      // The provider wallet pays the transaction fee; the joiner only pays the fee and the
+      // rent of their participant record.
+      const participantRent = await provider.connection.getMinimumBalanceForRentExemption(
+        8 + 32 + 32 + 8 + 8 + 1 + 1 + 2 + 4 + 4 + 1 + 1
+      );
+      expect(await provider.connection.getBalance(escrow)).to.equal(escrowBefore + lamportFee);
+      expect(await provider.connection.getBalance(joiner.publicKey)).to.equal(
+        joinerBefore - lamportFee - participantRent
+      );
+      const roomEscrow = await program.account.roomEscrow.fetch(escrow);
+      expect(roomEscrow.lamportsCollected.toNumber()).to.equal(lamportFee);
+      expect(roomEscrow.tokensCollected.toNumber()).to.equal(tokenFee);
+
+      const vault = await getAccount(provider.connection, escrowVault, undefined, tokenProgram);
+      expect(Number(vault.amount)).to.equal(tokenFee);
+      const joinerTokens = await getAccount(provider.connection, joinerTokenAccount, undefined, tokenProgram);
+      expect(Number(joinerTokens.amount)).to.equal(0);
+    });
+  }
+});
@@ -12,6 +12,38 @@ describe("Voice Chat System", () => {
   const voiceChatProgram = anchor.workspace.VoiceChatManager as Program<VoiceChatManager>;
   const authority = provider.wallet.publicKey;
 
+  // Every state-mutating voice_chat_manager instruction reads the program-wide ProtocolConfig
+  before(async () => {
+    const [protocolConfig] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("protocol_config")],
+      voiceChatProgram.programId
+    );
+    if (!(await provider.connection.getAccountInfo(protocolConfig))) {
+      await voiceChatProgram.methods.initializeProtocolConfig(0).accounts({ admin: authority }).rpc();
+    }
+  });
+
+  // Open page of a room's message index (64 frames per page)
+  const messageIndexPage = async (voiceRoom: anchor.web3.PublicKey) => {
+    const room = await voiceChatProgram.account.voiceRoom.fetch(voiceRoom);
+    const page = room.indexedMessages.divn(64);
+    const [pda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("message_index"), voiceRoom.toBuffer(), page.toArrayLike(Buffer, "le", 8)],
+      voiceChatProgram.programId
+    );
+    return pda;
+  };
+
+  // Messages are indexed by the room's message_count
+  const nextVoiceMessage = async (voiceRoom: anchor.web3.PublicKey) => {
+    const room = await voiceChatProgram.account.voiceRoom.fetch(voiceRoom);
+    const [pda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("voice_message"), voiceRoom.toBuffer(), room.messageCount.toArrayLike(Buffer, "le", 8)],
+      voiceChatProgram.programId
+    );
+    return pda;
+  };
+
   // Function that can be called from a button click
   const callVoiceChatSystem = async () => {
     console.log("🚀 Button clicked! Initializing Voice Chat System...");
@@ -78,6 +110,12 @@ describe("Voice Chat System", () => {
             console.log(`❌ Error with storage PDA ${i}:`, fetchError.message);
           }
         }
+        
+        // Room sends write through voice_chat_manager's storage_writer, which needs delegation
+        await storageProgram.methods
+          .delegate(true)
+          .accounts({ storagePda: storagePDA, authority: authority })
+          .rpc();
       }
       
       results.totalStorage = results.storagePDAs.length * 30;
@@ -94,7 +132,7 @@ describe("Voice Chat System", () => {
       
       try {
         const tx = await voiceChatProgram.methods
-          .initializeVoiceRoom(roomId)
+          .initializeVoiceRoom(roomId, null)
           .accounts({
             voiceRoom: voiceRoom,
             host: authority,
@@ -135,19 +173,18 @@ describe("Voice Chat System", () => {
         const targetPdaIndex = 0;
         const sequenceNumber = Math.floor(Math.random() * 1000000);
         
-        const [voiceMessage] = anchor.web3.PublicKey.findProgramAddressSync(
-          [Buffer.from("voice_message"), authority.toBuffer(), Buffer.from(sequenceNumber.toString())],
-          voiceChatProgram.programId
-        );
+        const voiceMessage = await nextVoiceMessage(voiceRoom);
         
         try {
           const tx = await voiceChatProgram.methods
-            .sendVoiceData(Array.from(sampleVoiceData), targetPdaIndex, sequenceNumber)
+            .sendVoiceData(Array.from(sampleVoiceData), targetPdaIndex, sequenceNumber, new anchor.BN(0), new anchor.BN(Date.now()), 20, false)
             .accounts({
               voiceRoom: voiceRoom,
               storagePda: new anchor.web3.PublicKey(results.storagePDAs[targetPdaIndex].address),
               voiceMessage: voiceMessage,
+              messageIndexPage: await messageIndexPage(voiceRoom),
               sender: authority,
+              authority: authority,
               systemProgram: anchor.web3.SystemProgram.programId,
             })
             .rpc();
@@ -254,6 +291,64 @@ describe("Voice Chat System", () => {
     expect(createdCount).to.be.greaterThan(0);
   });
 
+  it("Batch creates storage PDAs in one transaction", async () => {
+    // A fresh authority so none of its storage PDAs exist yet
+    const batchAuthority = anchor.web3.Keypair.generate();
+    const sig = await provider.connection.requestAirdrop(batchAuthority.publicKey, 2 * anchor.web3.LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(sig);
+    
+    const indices = [0, 1, 2];
+    const storagePDAs = indices.map((i) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("storage"), batchAuthority.publicKey.toBuffer(), Buffer.from([i])],
+        storageProgram.programId
+      )[0]
+    );
+    
+    await storageProgram.methods
+      .createStoragePdas(Buffer.from(indices))
+      .accounts({ authority: batchAuthority.publicKey })
+      .remainingAccounts(storagePDAs.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true })))
+      .signers([batchAuthority])
+      .rpc();
+    
+    for (const [i, storagePDA] of storagePDAs.entries()) {
+      const account = await storageProgram.account.storagePda.fetch(storagePDA);
+      expect(account.index).to.equal(indices[i]);
+      expect(account.authority.toString()).to.equal(batchAuthority.publicKey.toString());
+      expect(account.isActive).to.be.true;
+      expect(account.dataLength).to.equal(0);
+    }
+    console.log(`✅ Batch created ${indices.length} storage PDAs`);
+  });
+
+  it("Snapshots a storage chunk and closes the snapshot", async () => {
+    const [storagePDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("storage"), authority.toBuffer(), Buffer.from([0])],
+      storageProgram.programId
+    );
+    const [snapshot] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("storage_snapshot"), storagePDA.toBuffer(), authority.toBuffer()],
+      storageProgram.programId
+    );
+    
+    await storageProgram.methods
+      .snapshotStorage(0)
+      .accounts({ authority, payer: authority })
+      .rpc();
+    
+    const source = await storageProgram.account.storagePda.fetch(storagePDA);
+    const taken = await storageProgram.account.storageSnapshot.fetch(snapshot);
+    expect(taken.source.toString()).to.equal(storagePDA.toString());
+    expect(taken.dataLength).to.equal(source.dataLength);
+    
+    await storageProgram.methods
+      .closeStorageSnapshot()
+      .accounts({ snapshot, payer: authority })
+      .rpc();
+    expect(await provider.connection.getAccountInfo(snapshot)).to.be.null;
+  });
+
   it("Creates voice room and manages participants", async () => {
     console.log("🏠 Testing voice room management...");
     
@@ -266,7 +361,7 @@ describe("Voice Chat System", () => {
     try {
       // Create voice room
       const tx = await voiceChatProgram.methods
-        .initializeVoiceRoom(roomId)
+        .initializeVoiceRoom(roomId, null)
         .accounts({
           voiceRoom: voiceRoom,
           host: authority,
@@ -278,14 +373,14 @@ describe("Voice Chat System", () => {
       
       // Verify room
       const room = await voiceChatProgram.account.voiceRoom.fetch(voiceRoom);
-      expect(room.roomId).to.equal(roomId);
+      expect(Buffer.from(room.roomId).toString("utf8").replace(/\0+$/, "")).to.equal(roomId);
       expect(room.host.toString()).to.equal(authority.toString());
       expect(room.participantCount).to.equal(1);
       expect(room.isActive).to.be.true;
       
       // Test joining room
       const joinTx = await voiceChatProgram.methods
-        .joinVoiceRoom()
+        .joinVoiceRoom(0, null)
         .accounts({
           voiceRoom: voiceRoom,
           participant: authority,
@@ -315,7 +410,7 @@ describe("Voice Chat System", () => {
     try {
       // Create voice room
       await voiceChatProgram.methods
-        .initializeVoiceRoom(roomId)
+        .initializeVoiceRoom(roomId, null)
         .accounts({
           voiceRoom: voiceRoom,
           host: authority,
@@ -333,18 +428,22 @@ describe("Voice Chat System", () => {
         storageProgram.programId
       );
       
-      const [voiceMessage] = anchor.web3.PublicKey.findProgramAddressSync(
-        [Buffer.from("voice_message"), authority.toBuffer(), Buffer.from(sequenceNumber.toString())],
-        voiceChatProgram.programId
-      );
+      await storageProgram.methods
+        .delegate(true)
+        .accounts({ storagePda: storagePDA, authority: authority })
+        .rpc();
+      
+      const voiceMessage = await nextVoiceMessage(voiceRoom);
       
       const tx = await voiceChatProgram.methods
-        .sendVoiceData(Array.from(voiceData), targetPdaIndex, sequenceNumber)
+        .sendVoiceData(Array.from(voiceData), targetPdaIndex, sequenceNumber, new anchor.BN(0), new anchor.BN(Date.now()), 20, false)
         .accounts({
           voiceRoom: voiceRoom,
           storagePda: storagePDA,
           voiceMessage: voiceMessage,
+          messageIndexPage: await messageIndexPage(voiceRoom),
           sender: authority,
+          authority: authority,
           systemProgram: anchor.web3.SystemProgram.programId,
         })
         .rpc();
@@ -40,6 +40,7 @@ describe("voicechat", () => {
               pdaAccount: pdaAddress,
               authority: authority,
               systemProgram: anchor.web3.SystemProgram.programId,
+              pdaRegistry: null,
             })
             .rpc();
           
@@ -87,7 +88,7 @@ describe("voicechat", () => {
         
         try {
           const updateTx = await program.methods
-            .updatePdaData(Array.from(sampleData))
+            .updatePdaData(Array.from(sampleData), 0, false)
             .accounts({
               pdaAccount: new anchor.web3.PublicKey(firstPDA.address),
               authority: authority,
@@ -175,6 +176,7 @@ describe("voicechat", () => {
           pdaAccount: pdaAddress,
           authority: authority,
           systemProgram: anchor.web3.SystemProgram.programId,
+          pdaRegistry: null,
         })
         .rpc();
       
@@ -204,7 +206,7 @@ describe("voicechat", () => {
     console.log("Updating PDA 0 with test data...");
     
     const tx = await program.methods
-      .updatePdaData(Array.from(testData))
+      .updatePdaData(Array.from(testData), 0, false)
       .accounts({
         pdaAccount: pdaAddress,
         authority: authority,
@@ -219,4 +221,141 @@ describe("voicechat", () => {
     
     console.log(`PDA 0 updated successfully: dataLength=${account.dataLength}`);
   });
+
+  it("Patches a region of PDA data without clobbering the tail", async () => {
+    const authority = provider.wallet.publicKey;
+    const [pdaAddress] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pda"), authority.toBuffer(), Buffer.from([0])],
+      program.programId
+    );
+    
+    await program.methods
+      .updatePdaData(Array.from(Buffer.from("0123456789")), 0, false)
+      .accounts({ pdaAccount: pdaAddress, authority })
+      .rpc();
+    await program.methods
+      .updatePdaData(Array.from(Buffer.from("ab")), 4, true)
+      .accounts({ pdaAccount: pdaAddress, authority })
+      .rpc();
+    
+    let account = await program.account.pdaAccount.fetch(pdaAddress);
+    expect(account.dataLength).to.equal(10);
+    
+    // Without preserve_tail the data ends at the patched region
+    await program.methods
+      .updatePdaData(Array.from(Buffer.from("cd")), 2, false)
+      .accounts({ pdaAccount: pdaAddress, authority })
+      .rpc();
+    account = await program.account.pdaAccount.fetch(pdaAddress);
+    expect(account.dataLength).to.equal(4);
+  });
+
+  it("Deactivates and closes a PDA account", async () => {
+    const authority = provider.wallet.publicKey;
+    const indexBuffer = Buffer.alloc(2);
+    indexBuffer.writeUInt16LE(9, 0);
+    const [pdaAddress] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pda"), authority.toBuffer(), indexBuffer],
+      program.programId
+    );
+    
+    await program.methods
+      .createPdaAccount(9, Buffer.from("temporary data"))
+      .accounts({ pdaAccount: pdaAddress, authority, pdaRegistry: null })
+      .rpc();
+    const sizeBefore = (await provider.connection.getAccountInfo(pdaAddress)).data.length;
+    
+    await program.methods
+      .deactivatePdaAccount()
+      .accounts({ pdaAccount: pdaAddress, authority })
+      .rpc();
+    const deactivated = await provider.connection.getAccountInfo(pdaAddress);
+    expect(deactivated.data.length).to.be.lessThan(sizeBefore);
+    expect((await program.account.pdaAccount.fetch(pdaAddress)).dataLength).to.equal(0);
+    
+    await program.methods
+      .closePdaAccount()
+      .accounts({ pdaAccount: pdaAddress, authority })
+      .rpc();
+    expect(await provider.connection.getAccountInfo(pdaAddress)).to.be.null;
+  });
+
+  it("Provisions PDA indices beyond 10 through a registry", async () => {
+    const owner = anchor.web3.Keypair.generate();
+    const sig = await provider.connection.requestAirdrop(owner.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(sig);
+    
+    const indexBuffer = Buffer.alloc(2);
+    indexBuffer.writeUInt16LE(15, 0);
+    const [pdaAddress] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pda"), owner.publicKey.toBuffer(), indexBuffer],
+      program.programId
+    );
+    const [pdaRegistry] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pda_registry"), owner.publicKey.toBuffer()],
+      program.programId
+    );
+    
+    try {
+      await program.methods
+        .createPdaAccount(15, Buffer.from([]))
+        .accounts({ pdaAccount: pdaAddress, authority: owner.publicKey, pdaRegistry: null })
+        .signers([owner])
+        .rpc();
+      expect.fail("index 15 should be rejected without a registry");
+    } catch (error) {
+      expect(error.message).to.include("InvalidPDAIndex");
+    }
+    
+    await program.methods
+      .initializePdaRegistry(20)
+      .accounts({ authority: owner.publicKey })
+      .signers([owner])
+      .rpc();
+    await program.methods
+      .createPdaAccount(15, Buffer.from([]))
+      .accounts({ pdaAccount: pdaAddress, authority: owner.publicKey, pdaRegistry })
+      .signers([owner])
+      .rpc();
+    
+    const account = await program.account.pdaAccount.fetch(pdaAddress);
+    expect(account.index).to.equal(15);
+  });
+
+  it("Grows a PDA account from its prepaid balance without the authority signing", async () => {
+    const owner = anchor.web3.Keypair.generate();
+    const sig = await provider.connection.requestAirdrop(owner.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(sig);
+    
+    const indexBuffer = Buffer.alloc(2);
+    indexBuffer.writeUInt16LE(0, 0);
+    const [pdaAddress] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pda"), owner.publicKey.toBuffer(), indexBuffer],
+      program.programId
+    );
+    await program.methods
+      .createPdaAccount(0, Buffer.from([]))
+      .accounts({ pdaAccount: pdaAddress, authority: owner.publicKey, pdaRegistry: null })
+      .signers([owner])
+      .rpc();
+    
+    // The owner prepays with a plain transfer to the PDA
+    const sizeBefore = (await provider.connection.getAccountInfo(pdaAddress)).data.length;
+    const prepaid = await provider.connection.getMinimumBalanceForRentExemption(sizeBefore + 10240);
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({ fromPubkey: owner.publicKey, toPubkey: pdaAddress, lamports: prepaid })
+      ),
+      [owner]
+    );
+    
+    // Cranked from the provider wallet; the owner doesn't sign
+    await program.methods
+      .reallocatePdaAccountPrepaid(new anchor.BN(sizeBefore + 10240))
+      .accounts({ pdaAccount: pdaAddress, authority: owner.publicKey })
+      .rpc();
+    
+    const grown = await provider.connection.getAccountInfo(pdaAddress);
+    expect(grown.data.length).to.equal(sizeBefore + 10240);
+  });
 });